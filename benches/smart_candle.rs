@@ -14,10 +14,7 @@ use criterion::{
     criterion_group,
     criterion_main,
 };
-use lfest::{
-    load_trades_from_csv,
-    prelude::*,
-};
+use lfest::prelude::*;
 
 type DecimalT = i64;
 const DECIMALS: u8 = 1;
@@ -53,9 +50,18 @@ fn criterion_benchmark(c: &mut Criterion) {
             config,
         );
 
-    let trades = load_trades_from_csv("./data/Bitmex_XBTUSD_1M.csv");
     const COUNT: usize = 1_000_000;
-    assert_eq!(trades.len(), COUNT);
+    let market_config = SyntheticMarketConfig::builder()
+        .initial_price(QuoteCurrency::new(30_000, 0))
+        .mean_trade_quantity(QuoteCurrency::new(100, 0))
+        .price_filter(price_filter.clone())
+        .build();
+    let trades = Vec::from_iter(
+        SyntheticMarket::new(market_config, 0)
+            .expect("is valid config")
+            .trades()
+            .take(COUNT),
+    );
 
     let candles = Vec::from_iter(trades.chunks(1_000).map(|chunk| {
         let bba = Bba {
@@ -19,10 +19,7 @@ use criterion::{
     criterion_group,
     criterion_main,
 };
-use lfest::{
-    load_trades_from_csv,
-    prelude::*,
-};
+use lfest::prelude::*;
 
 const DECIMALS: u8 = 5;
 
@@ -52,17 +49,18 @@ fn update_state<I, const D: u8, BaseOrQuote, U>(
 
 fn criterion_benchmark(c: &mut Criterion) {
     let starting_balance = BaseCurrency::new(1, 0);
+    let price_filter = PriceFilter::new(
+        None,
+        None,
+        QuoteCurrency::new(5, 1),
+        Decimal::TWO,
+        Decimal::try_from_scaled(5, 1).unwrap(),
+    )
+    .expect("is valid filter");
     let contract_spec = ContractSpecification::new(
         leverage!(1),
         Decimal::try_from_scaled(5, 1).unwrap(),
-        PriceFilter::new(
-            None,
-            None,
-            QuoteCurrency::new(5, 1),
-            Decimal::TWO,
-            Decimal::try_from_scaled(5, 1).unwrap(),
-        )
-        .expect("is valid filter"),
+        price_filter.clone(),
         QuantityFilter::new(None, None, QuoteCurrency::one()).expect("is valid filter"),
         Fee::from(Decimal::try_from_scaled(2, 0).unwrap()),
         Fee::from(Decimal::try_from_scaled(6, 0).unwrap()),
@@ -77,9 +75,18 @@ fn criterion_benchmark(c: &mut Criterion) {
     .unwrap();
     let mut exchange = Exchange::new(config);
 
-    let trades = load_trades_from_csv("./data/Bitmex_XBTUSD_1M.csv");
     const COUNT: usize = 1_000_000;
-    assert_eq!(trades.len(), COUNT);
+    let market_config = SyntheticMarketConfig::builder()
+        .initial_price(QuoteCurrency::new(30_000, 0))
+        .mean_trade_quantity(QuoteCurrency::new(100, 0))
+        .price_filter(price_filter)
+        .build();
+    let trades = Vec::from_iter(
+        SyntheticMarket::new(market_config, 0)
+            .expect("is valid config")
+            .trades()
+            .take(COUNT),
+    );
 
     let mut group = c.benchmark_group("Exchange");
     group.throughput(criterion::Throughput::Elements(COUNT as u64));
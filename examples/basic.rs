@@ -1,4 +1,4 @@
-//! Example usage of Exchange using external trade data.
+//! Example usage of Exchange using synthetic trade data.
 //! A randomly acting agent places market buy / sell orders every 100 candles
 
 #![allow(
@@ -12,10 +12,7 @@ use std::{
 };
 
 use const_decimal::Decimal;
-use lfest::{
-    load_trades_from_csv,
-    prelude::*,
-};
+use lfest::prelude::*;
 use rand::{
     RngExt,
     rng,
@@ -28,17 +25,18 @@ fn main() {
     let t0 = Instant::now();
 
     let starting_balance = BaseCurrency::new(10, 0);
+    let price_filter = PriceFilter::new(
+        None,
+        None,
+        QuoteCurrency::new(1, 1),
+        Decimal::try_from_scaled(2, 0).unwrap(),
+        Decimal::zero(),
+    )
+    .expect("is valid price filter");
     let contract_spec = ContractSpecification::new(
         leverage!(1),
         Decimal::try_from_scaled(5, 1).unwrap(),
-        PriceFilter::new(
-            None,
-            None,
-            QuoteCurrency::new(1, 1),
-            Decimal::try_from_scaled(2, 0).unwrap(),
-            Decimal::zero(),
-        )
-        .expect("is valid price filter"),
+        price_filter.clone(),
         QuantityFilter::default(),
         Fee::from(Decimal::try_from_scaled(2, 4).unwrap()),
         Fee::from(Decimal::try_from_scaled(6, 4).unwrap()),
@@ -54,12 +52,14 @@ fn main() {
     let mut exchange =
         Exchange::<i64, DECIMALS, QuoteCurrency<i64, DECIMALS>, NoUserOrderId>::new(config);
 
-    // load trades from csv file
-    let prices = Vec::from_iter(
-        load_trades_from_csv::<i64, DECIMALS>("./data/Bitmex_XBTUSD_1M.csv")
-            .iter()
-            .map(|t| t.price),
-    );
+    // generate a reproducible stream of trades
+    let market_config = SyntheticMarketConfig::builder()
+        .initial_price(QuoteCurrency::<i64, DECIMALS>::new(30_000, 0))
+        .mean_trade_quantity(QuoteCurrency::new(100, 0))
+        .price_filter(price_filter)
+        .build();
+    let mut market = SyntheticMarket::new(market_config, 0).expect("is valid market config");
+    let prices = Vec::from_iter(market.trades().take(1_000_000).map(|t| t.price));
 
     // use random action every 100 trades to buy or sell
     let mut rng = rng();
@@ -97,7 +97,7 @@ fn main() {
         }
     }
     println!(
-        "time to simulate 1 million historical trades: {}micros",
+        "time to simulate 1 million synthetic trades: {}micros",
         t0.elapsed().as_micros()
     );
 }
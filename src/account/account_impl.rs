@@ -21,6 +21,7 @@ use crate::{
         MarginCurrency,
         MaxNumberOfActiveOrders,
         Mon,
+        NotEnoughAvailableBalance,
         OrderId,
         OrderIdNotFound,
         Pending,
@@ -95,6 +96,21 @@ where
         }
     }
 
+    /// Set the initial margin requirement, e.g. after the venue changed its margin requirements.
+    #[inline]
+    pub(crate) fn set_init_margin_req(&mut self, init_margin_req: Decimal<I, D>) {
+        assert2::debug_assert!(init_margin_req > Decimal::zero());
+        assert2::debug_assert!(init_margin_req <= Decimal::ONE);
+        self.init_margin_req = init_margin_req;
+    }
+
+    /// Set the maker fee rate used for reserving fees of resting limit orders,
+    /// e.g. after the account moved into another fee tier.
+    #[inline]
+    pub(crate) fn set_maker_fee(&mut self, maker_fee: Decimal<I, D>) {
+        self.maker_fee = maker_fee;
+    }
+
     /// The maker fees reserved for the resting limit orders, so that any of their fills
     /// can always be paid for. A negative (rebate) maker fee reserves nothing.
     #[inline(always)]
@@ -192,6 +208,7 @@ where
     }
 
     /// Change the account position, modifying its balances.
+    /// Returns the profit and loss realized by the fill, excluding the `fee`.
     /// This method is usually called by `Exchange`, but exposed for advanced use cases.
     #[inline(always)]
     pub fn change_position(
@@ -200,13 +217,35 @@ where
         fill_price: QuoteCurrency<I, D>,
         side: Side,
         fee: BaseOrQuote::PairedCurrency,
-    ) {
+    ) -> BaseOrQuote::PairedCurrency {
         assert2::debug_assert!(filled_qty > BaseOrQuote::zero());
         assert2::debug_assert!(fill_price > QuoteCurrency::zero());
 
-        self.position
+        let realized_pnl = self
+            .position
             .change(filled_qty, fill_price, side, &mut self.balances);
         self.balances.account_for_fee(fee);
+        realized_pnl
+    }
+
+    /// Deposit `amount` of collateral into the balances.
+    #[inline(always)]
+    pub(crate) fn deposit(&mut self, amount: BaseOrQuote::PairedCurrency) {
+        self.balances.deposit(amount);
+    }
+
+    /// Withdraw `amount` of collateral from the balances,
+    /// as long as it does not exceed the `available_balance`.
+    #[inline(always)]
+    pub(crate) fn withdraw(
+        &mut self,
+        amount: BaseOrQuote::PairedCurrency,
+    ) -> Result<(), NotEnoughAvailableBalance> {
+        if amount > self.available_balance() {
+            return Err(NotEnoughAvailableBalance);
+        }
+        self.balances.withdraw(amount);
+        Ok(())
     }
 
     /// Try to insert a new limit order.
@@ -220,11 +259,13 @@ where
 
     // TODO: this API in princible only need to know `id` (and maybe `side`)
     /// Fill an existing limit order and change the position accordingly; reduces order margin.
+    /// Returns the order if the fill completed it, along with the profit and loss realized by the fill.
     ///
     /// # Panics:
     /// panics if the order id was not found.
     #[inline(always)]
     #[must_use]
+    #[allow(clippy::type_complexity, reason = "Destructured right away")]
     pub fn fill_best(
         &mut self,
         side: Side,
@@ -232,10 +273,15 @@ where
         limit_price: QuoteCurrency<I, D>,
         fee: BaseOrQuote::PairedCurrency,
         ts_ns: TimestampNs,
-    ) -> Option<LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Filled<I, D, BaseOrQuote>>> {
-        self.change_position(filled_quantity, limit_price, side, fee);
-        self.active_limit_orders
-            .fill_best(side, filled_quantity, ts_ns)
+    ) -> (
+        Option<LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Filled<I, D, BaseOrQuote>>>,
+        BaseOrQuote::PairedCurrency,
+    ) {
+        let realized_pnl = self.change_position(filled_quantity, limit_price, side, fee);
+        let filled_order = self
+            .active_limit_orders
+            .fill_best(side, filled_quantity, ts_ns);
+        (filled_order, realized_pnl)
     }
 
     /// Remove a limit order.
@@ -135,6 +135,16 @@ where
         self.iter().map(|order| order.user_order_id())
     }
 
+    /// The summed remaining quantity of the active orders on `side`,
+    /// which is the position change if all of them were filled.
+    #[inline(always)]
+    pub(crate) fn remaining_quantity(&self, side: Side) -> BaseOrQuote {
+        match side {
+            Buy => self.bids.remaining_quantity_sum(),
+            Sell => self.asks.remaining_quantity_sum(),
+        }
+    }
+
     /// Try to insert a new `LimitOrder` into the order book.
     /// Returns an error if the maximum capacity is reached.
     #[inline(always)]
@@ -89,6 +89,22 @@ where
         self.apply_to_equity(pnl);
     }
 
+    /// Add the deposited `amount` to the equity.
+    #[inline(always)]
+    pub(crate) fn deposit(&mut self, amount: BaseOrQuote) {
+        assert2::debug_assert!(amount > BaseOrQuote::zero());
+        self.equity += amount;
+    }
+
+    /// Remove the withdrawn `amount` from the equity, which must cover it.
+    #[inline(always)]
+    pub(crate) fn withdraw(&mut self, amount: BaseOrQuote) {
+        assert2::debug_assert!(amount > BaseOrQuote::zero());
+        assert2::debug_assert!(amount <= self.equity);
+        self.equity -= amount;
+        self.debug_assert_state();
+    }
+
     /// Apply a signed equity change from a realized pnl or fee.
     ///
     /// A change which would push the equity below zero bankrupts the account:
@@ -170,6 +186,15 @@ mod test {
         assert_eq!(balances.total_fees_paid(), QuoteCurrency::new(101, 0));
     }
 
+    #[test]
+    fn balances_deposit_and_withdraw() {
+        let mut balances = Balances::new(QuoteCurrency::<i64, 5>::new(100, 0));
+        balances.deposit(QuoteCurrency::new(50, 0));
+        balances.withdraw(QuoteCurrency::new(120, 0));
+        assert_eq!(balances.equity(), QuoteCurrency::new(30, 0));
+        assert!(balances.total_fees_paid().is_zero());
+    }
+
     #[test]
     fn balances_display() {
         let balances = Balances::builder()
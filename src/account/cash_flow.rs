@@ -0,0 +1,13 @@
+use crate::types::TimestampNs;
+
+/// A deposit or withdrawal of collateral during the simulation.
+///
+/// Generics:
+/// - `M`: The margin currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CashFlow<M> {
+    /// The simulated time of the deposit or withdrawal.
+    pub timestamp_ns: TimestampNs,
+    /// The signed amount, positive for a deposit and negative for a withdrawal.
+    pub amount: M,
+}
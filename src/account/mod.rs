@@ -2,6 +2,7 @@ mod account_impl;
 mod active_limit_orders;
 mod balances;
 mod bids_asks;
+mod cash_flow;
 mod position;
 mod sorted_orders;
 
@@ -9,6 +10,7 @@ pub use account_impl::Account;
 pub use active_limit_orders::ActiveLimitOrders;
 pub use balances::Balances;
 pub use bids_asks::*;
+pub use cash_flow::CashFlow;
 pub use position::{
     Position,
     PositionSide,
@@ -109,6 +109,7 @@ where
     }
 
     /// Change a position while doing proper accounting and balance transfers.
+    /// Returns the profit and loss realized by the fill, which is applied to the `balances`.
     #[inline]
     #[allow(
         clippy::too_many_lines,
@@ -120,7 +121,7 @@ where
         fill_price: QuoteCurrency<I, D>,
         side: Side,
         balances: &mut Balances<I, D, BaseOrQuote::PairedCurrency>,
-    ) {
+    ) -> BaseOrQuote::PairedCurrency {
         use Side::*;
 
         tracing::trace!(
@@ -131,6 +132,7 @@ where
             "The filled_qty must be greater than zero"
         );
 
+        let mut realized_pnl = BaseOrQuote::PairedCurrency::zero();
         // TODO: simplify.
         use std::cmp::Ordering::*;
         match self.quantity.cmp(&Zero::zero()) {
@@ -159,28 +161,28 @@ where
                     Less => {
                         self.quantity -= filled_qty;
                         assert2::debug_assert!(self.quantity > Zero::zero());
-                        balances.apply_pnl(BaseOrQuote::PairedCurrency::pnl(
+                        realized_pnl = BaseOrQuote::PairedCurrency::pnl(
                             self.entry_price,
                             fill_price,
                             filled_qty,
-                        ));
+                        );
                     }
                     Equal => {
-                        balances.apply_pnl(BaseOrQuote::PairedCurrency::pnl(
+                        realized_pnl = BaseOrQuote::PairedCurrency::pnl(
                             self.entry_price,
                             fill_price,
                             filled_qty,
-                        ));
+                        );
                         self.quantity -= filled_qty;
                         debug_assert_eq!(self.quantity, Zero::zero());
                         self.entry_price = Zero::zero();
                     }
                     Greater => {
-                        balances.apply_pnl(BaseOrQuote::PairedCurrency::pnl(
+                        realized_pnl = BaseOrQuote::PairedCurrency::pnl(
                             self.entry_price,
                             fill_price,
                             self.quantity,
-                        ));
+                        );
                         self.quantity -= filled_qty;
                         assert2::debug_assert!(self.quantity < Zero::zero());
                         self.entry_price = fill_price;
@@ -191,30 +193,30 @@ where
             Less => match side {
                 Buy => match filled_qty.cmp(&self.quantity().abs()) {
                     Less => {
-                        balances.apply_pnl(-BaseOrQuote::PairedCurrency::pnl(
+                        realized_pnl = -BaseOrQuote::PairedCurrency::pnl(
                             self.entry_price,
                             fill_price,
                             filled_qty,
-                        ));
+                        );
                         self.quantity += filled_qty;
                         assert2::debug_assert!(self.quantity < Zero::zero());
                     }
                     Equal => {
-                        balances.apply_pnl(-BaseOrQuote::PairedCurrency::pnl(
+                        realized_pnl = -BaseOrQuote::PairedCurrency::pnl(
                             self.entry_price,
                             fill_price,
                             filled_qty,
-                        ));
+                        );
                         self.quantity += filled_qty;
                         debug_assert_eq!(self.quantity, Zero::zero());
                         self.entry_price = Zero::zero();
                     }
                     Greater => {
-                        balances.apply_pnl(-BaseOrQuote::PairedCurrency::pnl(
+                        realized_pnl = -BaseOrQuote::PairedCurrency::pnl(
                             self.entry_price,
                             fill_price,
                             self.quantity.abs(),
-                        ));
+                        );
                         self.quantity += filled_qty;
                         assert2::debug_assert!(self.quantity > Zero::zero());
                         self.entry_price = fill_price;
@@ -233,6 +235,10 @@ where
         }
         let position_is_valid = !self.quantity.is_zero() || self.entry_price.is_zero();
         debug_assert!(position_is_valid);
+        if !realized_pnl.is_zero() {
+            balances.apply_pnl(realized_pnl);
+        }
+        realized_pnl
     }
 }
 
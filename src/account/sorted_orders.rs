@@ -46,6 +46,9 @@ where
     orders: Vec<LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>>,
     #[getset(get_copy = "pub(crate)")]
     notional_sum: BaseOrQuote::PairedCurrency,
+    /// The summed remaining quantity of the orders.
+    #[getset(get_copy = "pub(crate)")]
+    remaining_quantity_sum: BaseOrQuote,
     _side: PhantomData<SideT>,
 }
 
@@ -64,6 +67,7 @@ where
         Self {
             orders,
             notional_sum: self.notional_sum,
+            remaining_quantity_sum: self.remaining_quantity_sum,
             _side: PhantomData,
         }
     }
@@ -84,6 +88,7 @@ where
         Self {
             orders: Vec::with_capacity(cap.get().into()),
             notional_sum: Zero::zero(),
+            remaining_quantity_sum: Zero::zero(),
             _side: PhantomData,
         }
     }
@@ -131,6 +136,7 @@ where
                 );
                 self.notional_sum += notional_delta;
                 assert2::debug_assert!(self.notional_sum >= Zero::zero());
+                self.remaining_quantity_sum -= filled_quantity;
 
                 order.remaining_quantity().is_zero()
             })
@@ -161,6 +167,7 @@ where
                 let order = self.orders.remove(idx);
                 self.notional_sum -= order.notional();
                 assert2::debug_assert!(self.notional_sum >= Zero::zero());
+                self.remaining_quantity_sum -= order.remaining_quantity();
                 order
             })
     }
@@ -178,6 +185,7 @@ where
                 let order = self.orders.remove(idx);
                 self.notional_sum -= order.notional();
                 assert2::debug_assert!(self.notional_sum >= Zero::zero());
+                self.remaining_quantity_sum -= order.remaining_quantity();
                 order
             })
     }
@@ -201,6 +209,7 @@ where
             ));
         }
         self.notional_sum += order.notional();
+        self.remaining_quantity_sum += order.remaining_quantity();
 
         use std::cmp::Ordering::*;
         let idx = self
@@ -307,6 +316,7 @@ mod tests {
             2.into()
         );
         assert_eq!(bids.notional_sum, QuoteCurrency::new(199, 0));
+        assert_eq!(bids.remaining_quantity_sum, BaseCurrency::new(2, 0));
         assert_eq!(bids.orders.len(), 2);
         assert_eq!(bids.len(), 2);
 
@@ -531,5 +541,12 @@ mod tests {
             let pending_0 = order_0.into_pending(meta);
             bids.try_insert(pending_0.clone()).unwrap();
         }
+        assert_eq!(
+            bids.remaining_quantity_sum,
+            bids.orders()
+                .iter()
+                .fold(BaseCurrency::zero(), |acc, order| acc
+                    + order.remaining_quantity())
+        );
     }
 }
@@ -0,0 +1,541 @@
+//! Tracks the performance of an account over the course of a simulation.
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::{
+    EXPECT_CAPACITY,
+    account::Position,
+    types::{
+        ConfigError,
+        Currency,
+        MarginCurrency,
+        Mon,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+    utils::min,
+};
+
+/// The number of nanoseconds in a year of 365 days, used to annualize the risk adjusted returns.
+const NANOS_PER_YEAR: i64 = 365 * 24 * 60 * 60 * 1_000_000_000;
+
+/// The currency in which the `AccountTracker` measures equity, fees and profit and loss.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EquityDenomination {
+    /// The margin currency of the account,
+    /// which is the `QuoteCurrency` for linear futures and the `BaseCurrency` for inverse futures.
+    #[default]
+    Margin,
+    /// The `QuoteCurrency`, converting the amounts of inverse futures at the mid price of an equity sample
+    /// or the price of a fill.
+    Quote,
+}
+
+/// A point of the equity curve recorded by the `AccountTracker`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquitySample {
+    /// The timestamp of the market update at which the equity was sampled.
+    pub timestamp_ns: TimestampNs,
+    /// The equity including the unrealized profit and loss, measured in the `EquityDenomination` of the tracker.
+    pub equity: f64,
+    /// The net deposits minus withdrawals since the previous sample, measured in the `EquityDenomination` of the tracker.
+    pub cash_flow: f64,
+}
+
+/// Records the equity curve and the fills of an account to compute standard trading metrics.
+/// Set it with `Exchange::set_account_tracker`, after which `update_state` samples the equity
+/// and every settled fill is recorded.
+///
+/// The equity is sampled by the first market update of each `sample_interval`,
+/// so the returns of the equity curve are the returns per `sample_interval` if the market data has no gaps.
+/// Wins and losses are counted per fill realizing profit or loss, i.e. per fill reducing the position.
+///
+/// Deposits and withdrawals through `Exchange::deposit` and `Exchange::withdraw` are not counted as returns:
+/// the return metrics are time-weighted, treating the cash flows since the previous sample
+/// as if they happened right after it.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct AccountTracker<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// The interval at which the equity is sampled.
+    #[getset(get_copy = "pub")]
+    sample_interval: TimestampNs,
+
+    /// The currency in which equity, fees and profit and loss are measured.
+    #[getset(get_copy = "pub")]
+    denomination: EquityDenomination,
+
+    equity_curve: Vec<EquitySample>,
+
+    next_sample_ts_ns: TimestampNs,
+
+    /// The cash flows since the last equity sample.
+    pending_cash_flow: f64,
+
+    /// The total deposits minus withdrawals.
+    #[getset(get_copy = "pub")]
+    net_cash_flow: f64,
+
+    /// The total notional value of all fills.
+    #[getset(get_copy = "pub")]
+    traded_notional: f64,
+
+    /// The total fees paid, negative if more maker rebates were received than fees paid.
+    #[getset(get_copy = "pub")]
+    fees_paid: f64,
+
+    /// The total realized profit and loss, excluding fees.
+    #[getset(get_copy = "pub")]
+    realized_pnl: f64,
+
+    /// The sum of the realized profits of the winning fills.
+    #[getset(get_copy = "pub")]
+    gross_profit: f64,
+
+    /// The sum of the realized losses of the losing fills, as a positive number.
+    #[getset(get_copy = "pub")]
+    gross_loss: f64,
+
+    /// The number of fills.
+    #[getset(get_copy = "pub")]
+    num_fills: u64,
+
+    /// The number of fills realizing a profit.
+    #[getset(get_copy = "pub")]
+    num_winning_fills: u64,
+
+    /// The number of fills realizing a loss.
+    #[getset(get_copy = "pub")]
+    num_losing_fills: u64,
+
+    _position: std::marker::PhantomData<Position<I, D, BaseOrQuote>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> AccountTracker<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// Create a new `AccountTracker` sampling the equity every `sample_interval`,
+    /// which must be positive.
+    pub fn new(
+        sample_interval: TimestampNs,
+        denomination: EquityDenomination,
+    ) -> Result<Self, ConfigError> {
+        if sample_interval <= TimestampNs::from(0) {
+            return Err(ConfigError::InvalidAccountTracker);
+        }
+        Ok(Self {
+            sample_interval,
+            denomination,
+            equity_curve: Vec::with_capacity(1024),
+            next_sample_ts_ns: TimestampNs::from(i64::MIN),
+            pending_cash_flow: 0.0,
+            net_cash_flow: 0.0,
+            traded_notional: 0.0,
+            fees_paid: 0.0,
+            realized_pnl: 0.0,
+            gross_profit: 0.0,
+            gross_loss: 0.0,
+            num_fills: 0,
+            num_winning_fills: 0,
+            num_losing_fills: 0,
+            _position: std::marker::PhantomData,
+        })
+    }
+
+    /// The sampled equity curve in chronological order.
+    #[inline]
+    pub fn equity_curve(&self) -> &[EquitySample] {
+        &self.equity_curve
+    }
+
+    /// Convert a margin `amount` into the `EquityDenomination` of the tracker at `price`.
+    fn denominate(&self, amount: BaseOrQuote::PairedCurrency, price: QuoteCurrency<I, D>) -> f64 {
+        match self.denomination {
+            EquityDenomination::Margin => amount.into(),
+            EquityDenomination::Quote => amount.quote_value(price).into(),
+        }
+    }
+
+    /// Sample the `equity`, including the unrealized profit and loss, if a new `sample_interval` started.
+    /// Samples without a valid `mid_price` are skipped.
+    pub(crate) fn sample_equity(
+        &mut self,
+        ts_ns: TimestampNs,
+        equity: BaseOrQuote::PairedCurrency,
+        mid_price: QuoteCurrency<I, D>,
+    ) {
+        if ts_ns < self.next_sample_ts_ns || mid_price <= QuoteCurrency::zero() {
+            return;
+        }
+        let interval = self.sample_interval.get();
+        self.next_sample_ts_ns =
+            TimestampNs::from((ts_ns.get().div_euclid(interval) + 1) * interval);
+        let equity = self.denominate(equity, mid_price);
+        // The equity curve grows with the duration of the simulation.
+        self.equity_curve.reserve(1);
+        self.equity_curve
+            .push_within_capacity(EquitySample {
+                timestamp_ns: ts_ns,
+                equity,
+                cash_flow: std::mem::take(&mut self.pending_cash_flow),
+            })
+            .expect(EXPECT_CAPACITY);
+    }
+
+    /// Record a deposit (positive `amount`) or withdrawal (negative `amount`) at `price`.
+    pub(crate) fn record_cash_flow(
+        &mut self,
+        amount: BaseOrQuote::PairedCurrency,
+        price: QuoteCurrency<I, D>,
+    ) {
+        let amount = self.denominate(amount, price);
+        self.pending_cash_flow += amount;
+        self.net_cash_flow += amount;
+    }
+
+    /// Record a fill of `quantity` at `price` paying `fee`,
+    /// where `position` is the position before the fill is settled.
+    pub(crate) fn record_fill(
+        &mut self,
+        position: &Position<I, D, BaseOrQuote>,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+        fee: BaseOrQuote::PairedCurrency,
+    ) {
+        assert2::debug_assert!(quantity > BaseOrQuote::zero());
+        let notional = BaseOrQuote::PairedCurrency::convert_from(quantity, price);
+        self.traded_notional += self.denominate(notional, price);
+        self.fees_paid += self.denominate(fee, price);
+        self.num_fills += 1;
+
+        let position_qty = position.quantity();
+        let reduces_position = match side {
+            Side::Buy => position_qty < BaseOrQuote::zero(),
+            Side::Sell => position_qty > BaseOrQuote::zero(),
+        };
+        if !reduces_position {
+            return;
+        }
+        let closed_qty = min(quantity, position_qty.abs());
+        let closed_qty = if position_qty < BaseOrQuote::zero() {
+            -closed_qty
+        } else {
+            closed_qty
+        };
+        let pnl = BaseOrQuote::PairedCurrency::pnl(position.entry_price(), price, closed_qty);
+        let pnl = self.denominate(pnl, price);
+        self.realized_pnl += pnl;
+        if pnl > 0.0 {
+            self.gross_profit += pnl;
+            self.num_winning_fills += 1;
+        } else if pnl < 0.0 {
+            self.gross_loss -= pnl;
+            self.num_losing_fills += 1;
+        }
+    }
+
+    /// The equity curve scaled such that the cash flows neither add to nor subtract from it,
+    /// which equals the `equity_curve` as long as there are no cash flows.
+    fn adjusted_equity_curve(&self) -> impl Iterator<Item = EquitySample> + '_ {
+        let mut scale = 1.0;
+        let mut prev_equity = None;
+        self.equity_curve.iter().map(move |sample| {
+            if let Some(prev) = prev_equity
+                && sample.cash_flow != 0.0
+            {
+                let base: f64 = prev + sample.cash_flow;
+                if base > 0.0 {
+                    scale *= prev / base;
+                }
+            }
+            prev_equity = Some(sample.equity);
+            EquitySample {
+                equity: sample.equity * scale,
+                ..*sample
+            }
+        })
+    }
+
+    /// The returns of the equity curve between consecutive samples, excluding the cash flows.
+    fn returns(&self) -> impl Iterator<Item = f64> + '_ {
+        self.adjusted_equity_curve()
+            .scan(None, |prev: &mut Option<f64>, sample| {
+                Some(
+                    prev.replace(sample.equity)
+                        .map(|prev| sample.equity / prev - 1.0),
+                )
+            })
+            .flatten()
+    }
+
+    /// The number of `sample_interval`s in a year of 365 days.
+    fn periods_per_year(&self) -> f64 {
+        NANOS_PER_YEAR as f64 / self.sample_interval.get() as f64
+    }
+
+    /// The mean of the returns and the number of returns, `None` without returns.
+    fn mean_return(&self) -> Option<(f64, f64)> {
+        let n = self.equity_curve.len().checked_sub(1).filter(|n| *n > 0)? as f64;
+        Some((self.returns().sum::<f64>() / n, n))
+    }
+
+    /// The return of the last equity sample relative to the first one, excluding cash flows, e.g. 0.1 for a 10% gain.
+    /// `None` with less than two samples.
+    pub fn total_return(&self) -> Option<f64> {
+        if self.equity_curve.len() < 2 {
+            return None;
+        }
+        let first = self.equity_curve.first()?.equity;
+        let last = self.adjusted_equity_curve().last()?.equity;
+        Some(last / first - 1.0)
+    }
+
+    /// The annualized Sharpe ratio of the returns per `sample_interval`, assuming a risk free rate of zero.
+    /// `None` with less than two returns or returns without variance.
+    pub fn sharpe_ratio(&self) -> Option<f64> {
+        let (mean, n) = self.mean_return()?;
+        if n < 2.0 {
+            return None;
+        }
+        let variance = self.returns().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let std_dev = variance.sqrt();
+        if std_dev == 0.0 {
+            return None;
+        }
+        Some(mean / std_dev * self.periods_per_year().sqrt())
+    }
+
+    /// The annualized Sortino ratio of the returns per `sample_interval`,
+    /// which only penalizes the deviation of negative returns.
+    /// `None` without returns or without negative returns.
+    pub fn sortino_ratio(&self) -> Option<f64> {
+        let (mean, n) = self.mean_return()?;
+        let downside_variance = self.returns().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n;
+        let downside_deviation = downside_variance.sqrt();
+        if downside_deviation == 0.0 {
+            return None;
+        }
+        Some(mean / downside_deviation * self.periods_per_year().sqrt())
+    }
+
+    /// The largest decline of the equity from a previous peak, as a fraction of the peak, e.g. 0.2 for 20%.
+    pub fn max_drawdown(&self) -> f64 {
+        let mut peak = f64::MIN;
+        let mut max_drawdown = 0.0_f64;
+        for sample in self.adjusted_equity_curve() {
+            peak = peak.max(sample.equity);
+            if peak > 0.0 {
+                max_drawdown = max_drawdown.max((peak - sample.equity) / peak);
+            }
+        }
+        max_drawdown
+    }
+
+    /// The longest time the equity spent below a previous peak,
+    /// counting until the last sample if the equity did not recover.
+    pub fn max_drawdown_duration(&self) -> TimestampNs {
+        let Some(first) = self.equity_curve.first() else {
+            return TimestampNs::from(0);
+        };
+        let mut peak = *first;
+        let mut max_duration = TimestampNs::from(0);
+        for sample in self.adjusted_equity_curve() {
+            if sample.equity >= peak.equity {
+                peak = sample;
+            }
+            max_duration = max_duration.max(sample.timestamp_ns - peak.timestamp_ns);
+        }
+        max_duration
+    }
+
+    /// The fraction of fills realizing a profit among the fills realizing a profit or loss.
+    /// `None` if no fill realized a profit or loss.
+    pub fn win_rate(&self) -> Option<f64> {
+        let decided = self.num_winning_fills + self.num_losing_fills;
+        if decided == 0 {
+            return None;
+        }
+        Some(self.num_winning_fills as f64 / decided as f64)
+    }
+
+    /// The gross profit divided by the gross loss. `None` without realized losses.
+    pub fn profit_factor(&self) -> Option<f64> {
+        if self.gross_loss == 0.0 {
+            return None;
+        }
+        Some(self.gross_profit / self.gross_loss)
+    }
+
+    /// The traded notional as a multiple of the average sampled equity. `None` without equity samples.
+    pub fn turnover(&self) -> Option<f64> {
+        if self.equity_curve.is_empty() {
+            return None;
+        }
+        let avg_equity = self.equity_curve.iter().map(|s| s.equity).sum::<f64>()
+            / self.equity_curve.len() as f64;
+        if avg_equity <= 0.0 {
+            return None;
+        }
+        Some(self.traded_notional / avg_equity)
+    }
+
+    /// The fees paid as a fraction of the absolute realized profit and loss,
+    /// e.g. 0.5 if half the realized profit went to fees. `None` without realized profit and loss.
+    pub fn fees_to_pnl_ratio(&self) -> Option<f64> {
+        if self.realized_pnl == 0.0 {
+            return None;
+        }
+        Some(self.fees_paid / self.realized_pnl.abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    fn tracker(denomination: EquityDenomination) -> AccountTracker<i64, 5, QuoteCurrency<i64, 5>> {
+        AccountTracker::new(10.into(), denomination).unwrap()
+    }
+
+    #[test]
+    fn account_tracker_new() {
+        assert_eq!(
+            AccountTracker::<i64, 5, BaseCurrency<i64, 5>>::new(
+                0.into(),
+                EquityDenomination::Margin
+            )
+            .map(|_| ()),
+            Err(ConfigError::InvalidAccountTracker)
+        );
+    }
+
+    #[test]
+    fn account_tracker_sample_clock() {
+        let mut tracker = AccountTracker::<i64, 5, BaseCurrency<i64, 5>>::new(
+            10.into(),
+            EquityDenomination::Margin,
+        )
+        .unwrap();
+        let price = QuoteCurrency::new(100, 0);
+        for (ts, equity) in [(3, 100), (9, 90), (10, 110), (25, 99), (28, 120), (31, 121)] {
+            tracker.sample_equity(ts.into(), QuoteCurrency::new(equity, 0), price);
+        }
+        // Samples without a price are skipped.
+        tracker.sample_equity(45.into(), QuoteCurrency::new(1, 0), QuoteCurrency::zero());
+        let curve: Vec<(i64, f64)> = tracker
+            .equity_curve()
+            .iter()
+            .map(|s| (s.timestamp_ns.get(), s.equity))
+            .collect();
+        assert_eq!(
+            curve,
+            vec![(3, 100.0), (10, 110.0), (25, 99.0), (31, 121.0)]
+        );
+
+        assert!((tracker.total_return().unwrap() - 0.21).abs() < 1e-12);
+        assert!((tracker.max_drawdown() - 0.1).abs() < 1e-12);
+        assert_eq!(tracker.max_drawdown_duration(), 15.into());
+        assert!(tracker.sharpe_ratio().unwrap() > 0.0);
+        assert!(tracker.sortino_ratio().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn account_tracker_excludes_cash_flows() {
+        let mut tracker = tracker(EquityDenomination::Margin);
+        let price = QuoteCurrency::new(100, 0);
+        tracker.sample_equity(0.into(), QuoteCurrency::new(100, 0), price);
+        tracker.sample_equity(10.into(), QuoteCurrency::new(110, 0), price);
+        // Withdraw the profit, then lose 10% before depositing 100.
+        tracker.record_cash_flow(QuoteCurrency::new(-10, 0), price);
+        tracker.sample_equity(20.into(), QuoteCurrency::new(90, 0), price);
+        tracker.record_cash_flow(QuoteCurrency::new(100, 0), price);
+        tracker.sample_equity(30.into(), QuoteCurrency::new(190, 0), price);
+
+        assert_eq!(tracker.net_cash_flow(), 90.0);
+        assert_eq!(tracker.equity_curve()[2].cash_flow, -10.0);
+        assert_eq!(tracker.equity_curve()[3].cash_flow, 100.0);
+        assert!((tracker.total_return().unwrap() - (1.1 * 0.9 - 1.0)).abs() < 1e-12);
+        assert!((tracker.max_drawdown() - 0.1).abs() < 1e-12);
+        assert_eq!(tracker.max_drawdown_duration(), 20.into());
+    }
+
+    #[test]
+    fn account_tracker_inverse_in_quote_terms() {
+        let mut margin = tracker(EquityDenomination::Margin);
+        let mut quote = tracker(EquityDenomination::Quote);
+        for tracker in [&mut margin, &mut quote] {
+            tracker.sample_equity(
+                0.into(),
+                BaseCurrency::new(1, 0),
+                QuoteCurrency::new(100, 0),
+            );
+            tracker.sample_equity(
+                10.into(),
+                BaseCurrency::new(1, 0),
+                QuoteCurrency::new(110, 0),
+            );
+        }
+        assert_eq!(margin.total_return(), Some(0.0));
+        assert!((quote.total_return().unwrap() - 0.1).abs() < 1e-12);
+        assert_eq!(quote.equity_curve()[1].equity, 110.0);
+    }
+
+    #[test]
+    fn account_tracker_record_fill() {
+        let mut tracker = AccountTracker::<i64, 5, BaseCurrency<i64, 5>>::new(
+            10.into(),
+            EquityDenomination::Margin,
+        )
+        .unwrap();
+        let fee = QuoteCurrency::new(1, 0);
+        let neutral = Position::default();
+        tracker.record_fill(
+            &neutral,
+            Side::Buy,
+            BaseCurrency::new(2, 0),
+            QuoteCurrency::new(100, 0),
+            fee,
+        );
+        assert_eq!(tracker.num_fills(), 1);
+        assert_eq!(tracker.realized_pnl(), 0.0);
+        assert_eq!(tracker.win_rate(), None);
+
+        // Reversing a long position only realizes the pnl of the closed quantity.
+        let long = Position::new(BaseCurrency::new(2, 0), QuoteCurrency::new(100, 0)).unwrap();
+        tracker.record_fill(
+            &long,
+            Side::Sell,
+            BaseCurrency::new(3, 0),
+            QuoteCurrency::new(110, 0),
+            fee,
+        );
+        assert_eq!(tracker.realized_pnl(), 20.0);
+
+        let short = Position::new(BaseCurrency::new(-1, 0), QuoteCurrency::new(110, 0)).unwrap();
+        tracker.record_fill(
+            &short,
+            Side::Buy,
+            BaseCurrency::new(1, 0),
+            QuoteCurrency::new(115, 0),
+            fee,
+        );
+        assert_eq!(tracker.realized_pnl(), 15.0);
+        assert_eq!(tracker.gross_profit(), 20.0);
+        assert_eq!(tracker.gross_loss(), 5.0);
+        assert_eq!(tracker.profit_factor(), Some(4.0));
+        assert_eq!(tracker.win_rate(), Some(0.5));
+        assert_eq!(tracker.fees_paid(), 3.0);
+        assert_eq!(tracker.traded_notional(), 200.0 + 330.0 + 115.0);
+        assert_eq!(tracker.fees_to_pnl_ratio(), Some(0.2));
+    }
+}
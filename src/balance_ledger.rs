@@ -0,0 +1,326 @@
+//! An append-only record of every change to the `Balances` of an account.
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::{
+    EXPECT_CAPACITY,
+    account::Balances,
+    types::{
+        MarginCurrency,
+        Mon,
+        OrderId,
+        TimestampNs,
+    },
+};
+
+/// What caused a change of the balances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LedgerCause {
+    /// The profit and loss realized by a fill reducing the position.
+    RealizedPnl,
+    /// The fee of a passive limit order fill, negative for a rebate.
+    MakerFee,
+    /// The fee of an aggressive fill.
+    TakerFee,
+    /// The profit and loss realized by force-closing the position.
+    Liquidation,
+    /// The fee of force-closing the position.
+    LiquidationFee,
+    /// The loss exceeding the equity which the venue absorbed, crediting the equity back to zero.
+    BadDebt,
+    /// Collateral deposited into the account.
+    Deposit,
+    /// Collateral withdrawn from the account.
+    Withdrawal,
+}
+
+impl LedgerCause {
+    /// Whether the entry is a fee and counts towards `Balances::total_fees_paid`.
+    #[inline]
+    pub fn is_fee(&self) -> bool {
+        matches!(
+            self,
+            LedgerCause::MakerFee | LedgerCause::TakerFee | LedgerCause::LiquidationFee
+        )
+    }
+}
+
+/// How the account took part in a fill, which decides the causes of its ledger entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FillKind {
+    /// A passive limit order fill.
+    Maker,
+    /// An aggressive market order fill.
+    Taker,
+    /// Force-closing the position.
+    Liquidation,
+}
+
+impl FillKind {
+    fn pnl_cause(self) -> LedgerCause {
+        match self {
+            FillKind::Maker | FillKind::Taker => LedgerCause::RealizedPnl,
+            FillKind::Liquidation => LedgerCause::Liquidation,
+        }
+    }
+
+    fn fee_cause(self) -> LedgerCause {
+        match self {
+            FillKind::Maker => LedgerCause::MakerFee,
+            FillKind::Taker => LedgerCause::TakerFee,
+            FillKind::Liquidation => LedgerCause::LiquidationFee,
+        }
+    }
+}
+
+/// A single change of the balances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerEntry<M> {
+    /// The simulated time of the change.
+    pub timestamp_ns: TimestampNs,
+    /// The order whose fill caused the change, `None` for a liquidation, deposit or withdrawal.
+    pub order_id: Option<OrderId>,
+    /// What caused the change.
+    pub cause: LedgerCause,
+    /// The signed change of the equity, e.g. a paid fee is negative.
+    pub amount: M,
+}
+
+/// An append-only, double-entry record of every change to the `Balances` of an account,
+/// to audit where its equity came from.
+///
+/// Each `LedgerEntry` posts its `amount` to the equity and the opposite amount to the account of its `LedgerCause`,
+/// so the equity always equals the opening equity plus the sum of `BalanceLedger::total` over all causes.
+/// The entries reconcile exactly to the `Balances` of the account, see `BalanceLedger::reconciles`.
+///
+/// Set it with `Exchange::set_balance_ledger`, which opens it with the balances at that time.
+/// Changes made through `Account::change_position` directly bypass the ledger.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct BalanceLedger<I, const D: u8, M>
+where
+    I: Mon<D>,
+    M: MarginCurrency<I, D>,
+{
+    /// The equity when the ledger was opened.
+    #[getset(get_copy = "pub")]
+    opening_equity: M,
+
+    /// The total fees paid when the ledger was opened.
+    #[getset(get_copy = "pub")]
+    opening_fees_paid: M,
+
+    /// The bad debt when the ledger was opened.
+    #[getset(get_copy = "pub")]
+    opening_bad_debt: M,
+
+    /// The equity after the last entry.
+    #[getset(get_copy = "pub")]
+    equity: M,
+
+    /// The total fees paid after the last entry.
+    #[getset(get_copy = "pub")]
+    total_fees_paid: M,
+
+    /// The bad debt after the last entry.
+    #[getset(get_copy = "pub")]
+    bad_debt: M,
+
+    entries: Vec<LedgerEntry<M>>,
+}
+
+impl<I, const D: u8, M> Default for BalanceLedger<I, D, M>
+where
+    I: Mon<D>,
+    M: MarginCurrency<I, D>,
+{
+    fn default() -> Self {
+        Self {
+            opening_equity: M::zero(),
+            opening_fees_paid: M::zero(),
+            opening_bad_debt: M::zero(),
+            equity: M::zero(),
+            total_fees_paid: M::zero(),
+            bad_debt: M::zero(),
+            entries: Vec::with_capacity(1024),
+        }
+    }
+}
+
+impl<I, const D: u8, M> BalanceLedger<I, D, M>
+where
+    I: Mon<D>,
+    M: MarginCurrency<I, D>,
+{
+    /// All entries in the order they were recorded.
+    #[inline]
+    pub fn entries(&self) -> &[LedgerEntry<M>] {
+        &self.entries
+    }
+
+    /// The sum of the amounts of all entries with `cause`.
+    pub fn total(&self, cause: LedgerCause) -> M {
+        self.entries
+            .iter()
+            .filter(|entry| entry.cause == cause)
+            .fold(M::zero(), |acc, entry| acc + entry.amount)
+    }
+
+    /// Whether the ledger accounts for every change of `balances` since it was opened.
+    pub fn reconciles(&self, balances: &Balances<I, D, M>) -> bool {
+        self.equity == balances.equity()
+            && self.total_fees_paid == balances.total_fees_paid()
+            && self.bad_debt == balances.bad_debt()
+    }
+
+    /// Start recording the changes of `balances`.
+    pub(crate) fn open(&mut self, balances: &Balances<I, D, M>) {
+        self.opening_equity = balances.equity();
+        self.opening_fees_paid = balances.total_fees_paid();
+        self.opening_bad_debt = balances.bad_debt();
+        self.equity = self.opening_equity;
+        self.total_fees_paid = self.opening_fees_paid;
+        self.bad_debt = self.opening_bad_debt;
+        self.entries.clear();
+    }
+
+    /// Record the realized profit and loss and the fee of a fill.
+    pub(crate) fn record_fill(
+        &mut self,
+        kind: FillKind,
+        realized_pnl: M,
+        fee: M,
+        order_id: Option<OrderId>,
+        ts_ns: TimestampNs,
+    ) {
+        self.append(kind.pnl_cause(), realized_pnl, order_id, ts_ns);
+        self.append(kind.fee_cause(), -fee, order_id, ts_ns);
+    }
+
+    /// Record a deposit (positive `amount`) or withdrawal (negative `amount`).
+    pub(crate) fn record_cash_flow(&mut self, amount: M, ts_ns: TimestampNs) {
+        let cause = if amount > M::zero() {
+            LedgerCause::Deposit
+        } else {
+            LedgerCause::Withdrawal
+        };
+        self.append(cause, amount, None, ts_ns);
+    }
+
+    /// Record the bad debt absorbed by the venue since the last entry, given the `bad_debt` of the `Balances`.
+    pub(crate) fn record_bad_debt(
+        &mut self,
+        bad_debt: M,
+        order_id: Option<OrderId>,
+        ts_ns: TimestampNs,
+    ) {
+        assert2::debug_assert!(bad_debt >= self.bad_debt);
+        let absorbed = bad_debt - self.bad_debt;
+        self.append(LedgerCause::BadDebt, absorbed, order_id, ts_ns);
+    }
+
+    fn append(
+        &mut self,
+        cause: LedgerCause,
+        amount: M,
+        order_id: Option<OrderId>,
+        ts_ns: TimestampNs,
+    ) {
+        if amount.is_zero() {
+            return;
+        }
+        // Unlike `Balances::apply_to_equity` the equity is not floored at zero,
+        // it only matches again once the resulting `BadDebt` entry is recorded.
+        self.equity += amount;
+        if cause.is_fee() {
+            self.total_fees_paid -= amount;
+        } else if cause == LedgerCause::BadDebt {
+            self.bad_debt += amount;
+        }
+        // The ledger grows with the number of fills.
+        self.entries.reserve(1);
+        self.entries
+            .push_within_capacity(LedgerEntry {
+                timestamp_ns: ts_ns,
+                order_id,
+                cause,
+                amount,
+            })
+            .expect(EXPECT_CAPACITY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::QuoteCurrency;
+
+    #[test]
+    fn balance_ledger_reconciles() {
+        let mut balances = Balances::new(QuoteCurrency::<i64, 5>::new(100, 0));
+        let mut ledger = BalanceLedger::default();
+        ledger.open(&balances);
+        assert!(ledger.reconciles(&balances));
+
+        let pnl = QuoteCurrency::new(10, 0);
+        let fee = QuoteCurrency::new(1, 0);
+        balances.apply_pnl(pnl);
+        balances.account_for_fee(fee);
+        ledger.record_fill(FillKind::Maker, pnl, fee, Some(0.into()), 1.into());
+        ledger.record_bad_debt(balances.bad_debt(), Some(0.into()), 1.into());
+        assert!(ledger.reconciles(&balances));
+        assert_eq!(ledger.entries().len(), 2);
+        assert_eq!(ledger.total(LedgerCause::RealizedPnl), pnl);
+        assert_eq!(ledger.total(LedgerCause::MakerFee), -fee);
+
+        // A liquidation realizing more than the equity bankrupts the account.
+        let pnl = QuoteCurrency::new(-120, 0);
+        let fee = QuoteCurrency::new(2, 0);
+        balances.apply_pnl(pnl);
+        balances.account_for_fee(fee);
+        ledger.record_fill(FillKind::Liquidation, pnl, fee, None, 2.into());
+        assert!(!ledger.reconciles(&balances));
+        ledger.record_bad_debt(balances.bad_debt(), None, 2.into());
+        assert!(ledger.reconciles(&balances));
+        assert!(ledger.equity().is_zero());
+        assert_eq!(
+            ledger.total(LedgerCause::BadDebt),
+            QuoteCurrency::new(13, 0)
+        );
+        assert_eq!(
+            ledger.entries().last(),
+            Some(&LedgerEntry {
+                timestamp_ns: 2.into(),
+                order_id: None,
+                cause: LedgerCause::BadDebt,
+                amount: QuoteCurrency::new(13, 0),
+            })
+        );
+
+        balances.deposit(QuoteCurrency::new(50, 0));
+        ledger.record_cash_flow(QuoteCurrency::new(50, 0), 3.into());
+        balances.withdraw(QuoteCurrency::new(20, 0));
+        ledger.record_cash_flow(QuoteCurrency::new(-20, 0), 4.into());
+        assert!(ledger.reconciles(&balances));
+        assert_eq!(
+            ledger.total(LedgerCause::Withdrawal),
+            QuoteCurrency::new(-20, 0)
+        );
+
+        let causes = [
+            LedgerCause::RealizedPnl,
+            LedgerCause::MakerFee,
+            LedgerCause::Liquidation,
+            LedgerCause::LiquidationFee,
+            LedgerCause::BadDebt,
+            LedgerCause::Deposit,
+            LedgerCause::Withdrawal,
+        ];
+        let total = causes
+            .into_iter()
+            .fold(ledger.opening_equity(), |acc, cause| {
+                acc + ledger.total(cause)
+            });
+        assert_eq!(total, balances.equity());
+    }
+}
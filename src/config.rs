@@ -3,6 +3,7 @@ use std::num::NonZeroU16;
 use getset::{
     CopyGetters,
     Getters,
+    MutGetters,
 };
 
 use crate::{
@@ -21,7 +22,7 @@ use crate::{
 /// - `I`: The numeric data type of currencies.
 /// - `D`: The constant decimal precision of the currencies.
 /// - `BaseOrQuote`: Either `BaseCurrency` or `QuoteCurrency` depending on the futures type.
-#[derive(Debug, Clone, Getters, CopyGetters)]
+#[derive(Debug, Clone, Getters, CopyGetters, MutGetters)]
 pub struct Config<I, const D: u8, BaseOrQuote>
 where
     I: Mon<D>,
@@ -41,7 +42,7 @@ where
     max_num_open_orders: NonZeroU16,
 
     /// The contract specification.
-    #[getset(get = "pub")]
+    #[getset(get = "pub", get_mut = "pub(crate)")]
     contract_spec: ContractSpecification<I, D, BaseOrQuote::PairedCurrency>,
 
     /// The submission rate limits for orders.
@@ -18,13 +18,16 @@ use crate::{
         Currency,
         Maker,
         Mon,
+        NotionalFilter,
         PriceFilter,
         QuantityFilter,
         Taker,
     },
     types::{
         Fee,
+        FeeSchedule,
         Leverage,
+        TimestampNs,
     },
 };
 
@@ -66,13 +69,34 @@ where
     #[getset(get = "pub")]
     quantity_filter: QuantityFilter<I, D, BaseOrQuote>,
 
+    /// Order notional value rules, unrestricted by default.
+    #[getset(get = "pub", set = "pub")]
+    notional_filter: NotionalFilter<I, D, BaseOrQuote>,
+
     /// The maker fee as parts per 100_000
+    /// If a `fee_schedule` is set, this is the maker fee of the currently active tier.
     #[getset(get_copy = "pub")]
     fee_maker: Fee<I, D, Maker>,
 
     /// The taker fee as parts per 100_000
+    /// If a `fee_schedule` is set, this is the taker fee of the currently active tier.
     #[getset(get_copy = "pub")]
     fee_taker: Fee<I, D, Taker>,
+
+    /// The optional volume-tiered fee schedule, which overrides `fee_maker` and `fee_taker`
+    /// based on the rolling traded notional of the account.
+    #[getset(get = "pub")]
+    fee_schedule: Option<FeeSchedule<I, D, BaseOrQuote::PairedCurrency>>,
+
+    /// The optional maximum absolute position quantity.
+    /// Resting limit orders count as if they were filled.
+    #[getset(get_copy = "pub")]
+    max_position_quantity: Option<BaseOrQuote>,
+
+    /// The optional maximum notional value of the position.
+    /// Resting limit orders count as if they were filled.
+    #[getset(get_copy = "pub")]
+    max_position_notional: Option<BaseOrQuote::PairedCurrency>,
 }
 
 impl<I, const D: u8, BaseOrQuote> ContractSpecification<I, D, BaseOrQuote>
@@ -112,10 +136,56 @@ where
             mark_method: MarkMethod::default(),
             price_filter,
             quantity_filter,
+            notional_filter: NotionalFilter::default(),
             fee_maker,
             fee_taker,
+            fee_schedule: None,
+            max_position_quantity: None,
+            max_position_notional: None,
         })
     }
+
+    /// Set the volume-tiered fee schedule.
+    /// The account starts out in the first tier, whose fees become the active `fee_maker` and `fee_taker`.
+    /// `None` removes the schedule, keeping the currently active fees.
+    pub fn set_fee_schedule(
+        &mut self,
+        fee_schedule: Option<FeeSchedule<I, D, BaseOrQuote::PairedCurrency>>,
+    ) {
+        if let Some(schedule) = fee_schedule.as_ref() {
+            let first_tier = schedule.tier(0);
+            self.set_active_fees(first_tier.fee_maker(), first_tier.fee_taker());
+        }
+        self.fee_schedule = fee_schedule;
+    }
+
+    /// Set the position limits, which are unrestricted by default.
+    /// Both limits must be positive if set.
+    pub fn set_position_limits(
+        &mut self,
+        max_position_quantity: Option<BaseOrQuote>,
+        max_position_notional: Option<BaseOrQuote::PairedCurrency>,
+    ) -> Result<(), ConfigError> {
+        if max_position_quantity.is_some_and(|max| max <= Zero::zero())
+            || max_position_notional.is_some_and(|max| max <= Zero::zero())
+        {
+            return Err(ConfigError::InvalidPositionLimits);
+        }
+        self.max_position_quantity = max_position_quantity;
+        self.max_position_notional = max_position_notional;
+        Ok(())
+    }
+
+    /// Set the currently active fees, e.g. when the account changed its fee tier.
+    #[inline]
+    pub(crate) fn set_active_fees(
+        &mut self,
+        fee_maker: Fee<I, D, Maker>,
+        fee_taker: Fee<I, D, Taker>,
+    ) {
+        self.fee_maker = fee_maker;
+        self.fee_taker = fee_taker;
+    }
 }
 
 impl<I, const D: u8, BaseOrQuote> Default for ContractSpecification<I, D, BaseOrQuote>
@@ -142,6 +212,68 @@ where
     }
 }
 
+/// A schedule of `ContractSpecification` changes over time, e.g. when a venue changes its tick size,
+/// fees or margin requirements.
+/// The `Exchange` applies each change once the simulated time reaches its timestamp.
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies
+/// - `BaseOrQuote`: Either `BaseCurrency` or `QuoteCurrency` depending on the futures type.
+#[derive(Debug, Clone, Getters)]
+pub struct ContractSpecificationSchedule<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The contract specifications along with the timestamp from which on they apply,
+    /// sorted by ascending timestamp.
+    #[getset(get = "pub")]
+    changes: Vec<(TimestampNs, ContractSpecification<I, D, BaseOrQuote>)>,
+}
+
+impl<I, const D: u8, BaseOrQuote> ContractSpecificationSchedule<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a new schedule from changes with strictly increasing timestamps.
+    pub fn new(
+        changes: Vec<(TimestampNs, ContractSpecification<I, D, BaseOrQuote>)>,
+    ) -> Result<Self, ConfigError> {
+        if changes.windows(2).any(|w| w[1].0 <= w[0].0) {
+            return Err(ConfigError::InvalidContractSpecificationSchedule);
+        }
+        Ok(Self { changes })
+    }
+
+    /// The `PriceFilter` of the latest change from index `next_change` on, which is due at `ts_ns`, if any.
+    pub(crate) fn due_price_filter(
+        &self,
+        next_change: usize,
+        ts_ns: TimestampNs,
+    ) -> Option<&PriceFilter<I, D>> {
+        self.changes
+            .iter()
+            .skip(next_change)
+            .take_while(|(change_ts, _)| *change_ts <= ts_ns)
+            .last()
+            .map(|(_, contract_spec)| contract_spec.price_filter())
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> Default for ContractSpecificationSchedule<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn default() -> Self {
+        Self {
+            changes: Vec::with_capacity(0),
+        }
+    }
+}
+
 // TODO: actually switch between the methods.
 /// Which price to use in `mark-to-market` calculations
 #[derive(Debug, Clone, Copy, Default)]
@@ -16,10 +16,39 @@ use crate::{
     account::{
         Account,
         Balances,
+        CashFlow,
+    },
+    account_tracker::AccountTracker,
+    balance_ledger::{
+        BalanceLedger,
+        FillKind,
     },
     config::Config,
+    contract_specification::{
+        ContractSpecification,
+        ContractSpecificationSchedule,
+    },
+    fee_tier_tracker::FeeTierTracker,
+    fill_model::{
+        FillModel,
+        fill_with_model,
+    },
+    lot_tracker::LotTracker,
+    market_impact::MarketImpactModel,
     market_state::MarketState,
+    markout::MarkoutAnalytics,
+    order_filters::PriceFilter,
+    order_latency::{
+        AdmitAll,
+        ArrivalGate,
+        LatencyModel,
+        OrderAction,
+        OrderActionEvent,
+        OrderPipeline,
+        RequestId,
+    },
     order_rate_limiter::OrderRateLimiter,
+    pnl_attribution::PnlAttribution,
     prelude::{
         Currency,
         MarketUpdate,
@@ -31,29 +60,42 @@ use crate::{
         IsolatedMarginRiskEngine,
         RiskEngine,
     },
+    trackers::{
+        TrackedFill,
+        Trackers,
+    },
+    trade_journal::TradeJournal,
     types::{
         AmendLimitOrderError,
         CancelBy,
         CancelLimitOrderError,
+        CashFlowError,
         ExchangeOrderMeta,
+        FeeTierChange,
         Filled,
         LimitOrder,
         LimitOrderEvent,
         LimitOrderFill,
         MarginCurrency,
         MarketOrder,
+        MarketUpdateError,
         MaxNumberOfActiveOrders,
         NewOrder,
+        NotEnoughAvailableBalance,
         OrderId,
         Pending,
         RiskError,
+        Side,
         Side::*,
         Solvency,
+        StrategyTag,
         SubmitLimitOrderError,
         SubmitMarketOrderError,
         TimestampNs,
+        UpdateStateError,
         UserOrderId,
     },
+    utils::max,
 };
 
 /// The resting limit orders which the venue force-cancelled to keep the account's
@@ -79,6 +121,9 @@ where
     pub forced_cancels: ForcedCancels<I, D, BaseOrQuote, UserOrderIdT>,
     /// The solvency of the account after settlement and collateral reconciliation.
     pub solvency: Solvency,
+    /// The change of the active fee tier caused by the traded notional of this fill.
+    /// Always `None` without a `FeeSchedule` in the `ContractSpecification`.
+    pub fee_tier_change: Option<FeeTierChange<I, D, BaseOrQuote::PairedCurrency>>,
 }
 
 /// The main leveraged futures exchange for simulated trading
@@ -109,7 +154,7 @@ where
     /// The limit order events (fills and forced cancellations) of the most recent
     /// [`Exchange::update_state`] call, in occurrence order.
     ///
-    /// This getter matters after `update_state` returned `Err(RiskError::Liquidate)`:
+    /// This getter matters after `update_state` returned a `RiskError::Liquidate`:
     /// the error return cannot hand out the events, yet a liquidation force-cancels
     /// every resting order and those cancellations are recorded here.
     // Buffer kept to avoid allocations in hot-paths.
@@ -124,7 +169,53 @@ where
     /// during the current `update_state` call.
     liquidated_during_fills: bool,
 
+    /// Whether the `MatchingEngine` closes a liquidated position against its `OrderBook`,
+    /// leaving only the cancellation of the resting orders to `force_liquidate`.
+    liquidated_by_matching_engine: bool,
+
     order_rate_limiter: OrderRateLimiter,
+
+    /// Tracks the rolling traded notional which selects the active tier of the `FeeSchedule`.
+    #[getset(get = "pub")]
+    fee_tier_tracker: FeeTierTracker<I, D, BaseOrQuote::PairedCurrency>,
+
+    /// The fee tier changes of the most recent [`Exchange::update_state`] call, in occurrence order.
+    /// Tier changes caused by market orders are reported in their `MarketOrderSettlement`.
+    // Buffer kept to avoid allocations in hot-paths.
+    #[getset(get = "pub")]
+    fee_tier_changes: Vec<FeeTierChange<I, D, BaseOrQuote::PairedCurrency>>,
+
+    /// The scheduled changes of the contract specification.
+    #[getset(get = "pub")]
+    contract_spec_schedule: ContractSpecificationSchedule<I, D, BaseOrQuote>,
+
+    /// The index of the next change in `contract_spec_schedule` to apply.
+    next_contract_spec_change: usize,
+
+    /// The order actions in flight to the venue.
+    order_pipeline: OrderPipeline<I, D, BaseOrQuote, UserOrderIdT>,
+
+    /// The outcomes of the order actions whose responses arrived back at the strategy
+    /// during the most recent [`Exchange::update_state`] call, in the order they arrived back.
+    // Buffer kept to avoid allocations in hot-paths.
+    #[getset(get = "pub")]
+    order_action_events: Vec<OrderActionEvent<I, D, BaseOrQuote, UserOrderIdT>>,
+
+    /// Whether `update_state` rejects market updates violating the `PriceFilter` or going back in time.
+    #[getset(get_copy = "pub")]
+    strict_market_data: bool,
+
+    /// Decides how resting limit orders get filled, if `None` the `MarketUpdate` decides.
+    fill_model: Option<Box<dyn FillModel<I, D, BaseOrQuote>>>,
+
+    /// Shifts the bid and ask after our own market orders, if `None` they don't move the market.
+    market_impact_model: Option<MarketImpactModel<I, D, BaseOrQuote>>,
+
+    /// The trackers observing the account.
+    trackers: Trackers<I, D, BaseOrQuote>,
+
+    /// The deposits and withdrawals in chronological order.
+    cash_flows: Vec<CashFlow<BaseOrQuote::PairedCurrency>>,
 }
 
 impl<I, const D: u8, BaseOrQuote, UserOrderIdT> Exchange<I, D, BaseOrQuote, UserOrderIdT>
@@ -154,13 +245,314 @@ where
             account: Account::new(balances, max_active_orders, init_margin_req, maker_fee),
             // Bids and asks each have a capacity of `max_active_orders`, so one update
             // can emit at most `2 * max_active_orders` fills plus as many forced cancels.
-            limit_order_events: Vec::with_capacity(usize::from(max_active_orders.get()) * 4),
+            // Orders arriving after a contract specification change cancelled all resting ones
+            // double the number of orders an update can see.
+            limit_order_events: Vec::with_capacity(usize::from(max_active_orders.get()) * 6),
             forced_cancel_scratch: Vec::with_capacity(usize::from(max_active_orders.get()) * 2),
             liquidated_during_fills: false,
+            liquidated_by_matching_engine: false,
             order_rate_limiter,
+            fee_tier_tracker: FeeTierTracker::default(),
+            // Each fill changes the tier at most once, plus once each for a contract specification
+            // change and for notional rolling out of the window.
+            fee_tier_changes: Vec::with_capacity(usize::from(max_active_orders.get()) * 2 + 2),
+            contract_spec_schedule: ContractSpecificationSchedule::default(),
+            next_contract_spec_change: 0,
+            order_pipeline: OrderPipeline::default(),
+            order_action_events: Vec::with_capacity(usize::from(max_active_orders.get()) * 2),
+            strict_market_data: false,
+            fill_model: None,
+            market_impact_model: None,
+            trackers: Trackers::default(),
+            cash_flows: Vec::with_capacity(16),
         }
     }
 
+    /// Set the schedule of `ContractSpecification` changes, replacing any previous schedule.
+    /// Each change is applied by `update_state` once a market update reaches its timestamp,
+    /// which includes changes scheduled before the current time.
+    pub fn set_contract_spec_schedule(
+        &mut self,
+        schedule: ContractSpecificationSchedule<I, D, BaseOrQuote>,
+    ) {
+        self.contract_spec_schedule = schedule;
+        self.next_contract_spec_change = 0;
+    }
+
+    /// Reject market updates which violate the `PriceFilter`, e.g. crossed books, zero or off-tick prices,
+    /// or which are older than the current time.
+    /// In strict mode `update_state` returns an error for them before changing any state,
+    /// otherwise they are only checked in debug builds.
+    /// Use a `MarketDataValidation` to repair recorded market data beforehand.
+    pub fn set_strict_market_data(&mut self, strict: bool) {
+        self.strict_market_data = strict;
+    }
+
+    /// The `FillModel` deciding how resting limit orders get filled.
+    /// `None` if the fill logic of the `MarketUpdate` itself is used, see `MarketUpdate::limit_order_filled`.
+    #[inline]
+    pub fn fill_model(&self) -> Option<&dyn FillModel<I, D, BaseOrQuote>> {
+        self.fill_model.as_deref()
+    }
+
+    /// Set the `FillModel` deciding how resting limit orders get filled,
+    /// replacing the fill logic of the `MarketUpdate` itself.
+    pub fn set_fill_model<F>(&mut self, fill_model: F)
+    where
+        F: FillModel<I, D, BaseOrQuote> + 'static,
+    {
+        self.fill_model = Some(Box::new(fill_model));
+    }
+
+    /// The `MarketImpactModel` shifting the bid and ask after our own market orders.
+    /// `None` if our orders don't move the market.
+    #[inline]
+    pub fn market_impact_model(&self) -> Option<&MarketImpactModel<I, D, BaseOrQuote>> {
+        self.market_impact_model.as_ref()
+    }
+
+    /// Set the `MarketImpactModel` shifting the bid and ask after our own market orders.
+    pub fn set_market_impact_model(
+        &mut self,
+        market_impact_model: MarketImpactModel<I, D, BaseOrQuote>,
+    ) {
+        self.market_impact_model = Some(market_impact_model);
+        self.update_price_impact();
+    }
+
+    /// The `AccountTracker` recording the equity curve and fills of the account.
+    /// `None` if the performance of the account is not tracked.
+    #[inline]
+    pub fn account_tracker(&self) -> Option<&AccountTracker<I, D, BaseOrQuote>> {
+        self.trackers.account_tracker.as_ref()
+    }
+
+    /// Set the `AccountTracker` recording the equity curve and fills of the account from now on.
+    pub fn set_account_tracker(&mut self, account_tracker: AccountTracker<I, D, BaseOrQuote>) {
+        self.trackers.account_tracker = Some(account_tracker);
+    }
+
+    /// The `TradeJournal` grouping the fills of the account into round trips.
+    /// `None` if no journal is kept.
+    #[inline]
+    pub fn trade_journal(&self) -> Option<&TradeJournal<I, D, BaseOrQuote>> {
+        self.trackers.trade_journal.as_ref()
+    }
+
+    /// Set the `TradeJournal` grouping the fills of the account into round trips from now on.
+    pub fn set_trade_journal(&mut self, trade_journal: TradeJournal<I, D, BaseOrQuote>) {
+        self.trackers.trade_journal = Some(trade_journal);
+    }
+
+    /// The `MarkoutAnalytics` sampling the markouts after our limit order fills.
+    /// `None` if markouts are not measured.
+    #[inline]
+    pub fn markout_analytics(&self) -> Option<&MarkoutAnalytics<I, D, BaseOrQuote>> {
+        self.trackers.markout_analytics.as_ref()
+    }
+
+    /// Set the `MarkoutAnalytics` sampling the markouts after our limit order fills from now on.
+    pub fn set_markout_analytics(
+        &mut self,
+        markout_analytics: MarkoutAnalytics<I, D, BaseOrQuote>,
+    ) {
+        self.trackers.markout_analytics = Some(markout_analytics);
+    }
+
+    /// The `BalanceLedger` recording every change of the balances.
+    /// `None` if the changes are not recorded.
+    #[inline]
+    pub fn balance_ledger(&self) -> Option<&BalanceLedger<I, D, BaseOrQuote::PairedCurrency>> {
+        self.trackers.balance_ledger.as_ref()
+    }
+
+    /// Set the `BalanceLedger` recording every change of the balances from now on,
+    /// opening it with the current balances.
+    pub fn set_balance_ledger(
+        &mut self,
+        mut balance_ledger: BalanceLedger<I, D, BaseOrQuote::PairedCurrency>,
+    ) {
+        balance_ledger.open(self.account.balances());
+        self.trackers.balance_ledger = Some(balance_ledger);
+    }
+
+    /// The `PnlAttribution` attributing the fills to the sub-strategies placing the orders.
+    /// `None` if the fills are not attributed.
+    #[inline]
+    pub fn pnl_attribution(&self) -> Option<&PnlAttribution<I, D, BaseOrQuote>> {
+        self.trackers.pnl_attribution.as_ref()
+    }
+
+    /// Set the `PnlAttribution` attributing the fills to the sub-strategies placing the orders from now on,
+    /// opening it with the current position.
+    pub fn set_pnl_attribution(&mut self, mut pnl_attribution: PnlAttribution<I, D, BaseOrQuote>) {
+        pnl_attribution.open(self.account.position());
+        self.trackers.pnl_attribution = Some(pnl_attribution);
+    }
+
+    /// The `LotTracker` realizing the profit and loss of the fills lot by lot.
+    /// `None` if no lots are tracked.
+    #[inline]
+    pub fn lot_tracker(&self) -> Option<&LotTracker<I, D, BaseOrQuote>> {
+        self.trackers.lot_tracker.as_ref()
+    }
+
+    /// Set the `LotTracker` realizing the profit and loss of the fills lot by lot from now on,
+    /// opening it with the current position.
+    pub fn set_lot_tracker(&mut self, mut lot_tracker: LotTracker<I, D, BaseOrQuote>) {
+        lot_tracker.open(self.account.position(), self.market_state.current_ts_ns());
+        self.trackers.lot_tracker = Some(lot_tracker);
+    }
+
+    /// The deposits and withdrawals in chronological order.
+    #[inline]
+    pub fn cash_flows(&self) -> &[CashFlow<BaseOrQuote::PairedCurrency>] {
+        &self.cash_flows
+    }
+
+    /// Deposit `amount` of collateral into the account at the current time, e.g. a capital allocation.
+    pub fn deposit(&mut self, amount: BaseOrQuote::PairedCurrency) -> Result<(), CashFlowError> {
+        if amount <= Zero::zero() {
+            return Err(CashFlowError::AmountLTEZero);
+        }
+        self.account.deposit(amount);
+        self.record_cash_flow(amount);
+        Ok(())
+    }
+
+    /// Withdraw `amount` of collateral from the account at the current time, e.g. a profit sweep.
+    /// The withdrawal is limited by `Account::available_balance`,
+    /// so it is rejected if the equity would no longer cover the margin of the position and orders.
+    pub fn withdraw(&mut self, amount: BaseOrQuote::PairedCurrency) -> Result<(), CashFlowError> {
+        if amount <= Zero::zero() {
+            return Err(CashFlowError::AmountLTEZero);
+        }
+        self.account.withdraw(amount)?;
+        self.record_cash_flow(-amount);
+        Ok(())
+    }
+
+    /// Record a deposit (positive `amount`) or withdrawal (negative `amount`)
+    /// in the cash flows and the trackers.
+    fn record_cash_flow(&mut self, amount: BaseOrQuote::PairedCurrency) {
+        let ts_ns = self.market_state.current_ts_ns();
+        debug!("cash flow of {amount} at {ts_ns}");
+        // The cash flows grow with the number of deposits and withdrawals.
+        self.cash_flows.reserve(1);
+        self.cash_flows
+            .push_within_capacity(CashFlow {
+                timestamp_ns: ts_ns,
+                amount,
+            })
+            .expect(EXPECT_CAPACITY);
+        self.trackers
+            .cash_flow(amount, ts_ns, self.market_state.mid_price());
+    }
+
+    /// The latency model of the order actions sent through the `send_*` methods.
+    #[inline]
+    pub fn latency_model(&self) -> &LatencyModel {
+        self.order_pipeline.latency_model()
+    }
+
+    /// Set the latency model of the order actions sent through the `send_*` methods.
+    /// Order actions already in flight keep their arrival timestamp.
+    pub fn set_latency_model(&mut self, latency_model: LatencyModel) {
+        self.order_pipeline.set_latency_model(latency_model);
+    }
+
+    /// The number of order actions in flight to the venue.
+    #[inline]
+    pub fn num_in_flight_order_actions(&self) -> usize {
+        self.order_pipeline.len()
+    }
+
+    /// The latency model of the responses to the order actions, on their way back from the venue.
+    #[inline]
+    pub fn response_latency_model(&self) -> &LatencyModel {
+        self.order_pipeline.response_latency_model()
+    }
+
+    /// Set the latency model of the responses to the order actions, on their way back from the venue.
+    /// The venue applies an order action on its arrival, but its outcome is only reported
+    /// in [`Exchange::order_action_events`] once the response arrives back.
+    /// Responses already in flight keep their timestamp.
+    pub fn set_response_latency_model(&mut self, latency_model: LatencyModel) {
+        self.order_pipeline
+            .set_response_latency_model(latency_model);
+    }
+
+    /// The number of responses to order actions in flight back from the venue.
+    #[inline]
+    pub fn num_in_flight_order_responses(&self) -> usize {
+        self.order_pipeline.num_responses()
+    }
+
+    /// Send a limit order to the venue, where it arrives after the latency sampled from the `LatencyModel`.
+    /// Its outcome is reported in [`Exchange::order_action_events`] by the `update_state` call
+    /// reaching the timestamp its response arrives back, see [`Exchange::set_response_latency_model`].
+    pub fn send_limit_order(
+        &mut self,
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> RequestId {
+        self.order_pipeline.send(
+            self.market_state.current_ts_ns(),
+            OrderAction::SubmitLimitOrder(order),
+        )
+    }
+
+    /// Send a market order to the venue, where it arrives after the latency sampled from the `LatencyModel`
+    /// and fills at the then current bid or ask.
+    /// Its outcome is reported in [`Exchange::order_action_events`] by the `update_state` call
+    /// reaching the timestamp its response arrives back, see [`Exchange::set_response_latency_model`].
+    pub fn send_market_order(
+        &mut self,
+        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> RequestId {
+        self.order_pipeline.send(
+            self.market_state.current_ts_ns(),
+            OrderAction::SubmitMarketOrder(order),
+        )
+    }
+
+    /// Send a cancel request to the venue, where it arrives after the latency sampled from the `LatencyModel`.
+    /// If the order was filled or cancelled in the meantime, the outcome is `OrderActionEvent::TooLateToCancel`.
+    pub fn send_cancel_limit_order(&mut self, cancel_by: CancelBy<UserOrderIdT>) -> RequestId {
+        let order_was_known = match cancel_by {
+            CancelBy::OrderId(order_id) => order_id < self.next_order_id,
+            CancelBy::UserOrderId(user_order_id) => {
+                self.account
+                    .active_limit_orders()
+                    .user_order_ids()
+                    .any(|id| id == user_order_id)
+                    || self.order_pipeline.creates_order_with(user_order_id)
+            }
+        };
+        self.order_pipeline.send(
+            self.market_state.current_ts_ns(),
+            OrderAction::CancelLimitOrder {
+                cancel_by,
+                order_was_known,
+            },
+        )
+    }
+
+    /// Send an amend request to the venue, where it arrives after the latency sampled from the `LatencyModel`.
+    /// See [`Exchange::amend_limit_order`] for how the amendment is applied.
+    pub fn send_amend_limit_order(
+        &mut self,
+        existing_order_id: OrderId,
+        new_order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> RequestId {
+        self.order_pipeline.send(
+            self.market_state.current_ts_ns(),
+            OrderAction::AmendLimitOrder {
+                existing_order_id,
+                new_order,
+            },
+        )
+    }
+
     /// Update the exchange state with new information
     /// Returns a reference to the event vector for performance reasons.
     ///
@@ -171,22 +563,93 @@ where
     /// If Ok, the limit order events of this update in occurrence order: partial and full
     /// fills as well as resting orders the venue force-cancelled to keep the account's
     /// required collateral covered (margin call).
-    /// `Err(RiskError::Liquidate)` means the position was force-closed, either because
+    /// `Err(UpdateStateError::Risk(RiskError::Liquidate))` means the position was force-closed, either because
     /// the market crossed its liquidation price or because a fill left the equity below
     /// the maintenance margin; the accompanying forced cancellations are then available
     /// through [`Exchange::limit_order_events`].
+    /// In strict mode, see `set_strict_market_data`, an invalid `market_update` is rejected
+    /// with an `UpdateStateError::MarketUpdate`, leaving the state unchanged.
     pub fn update_state<U>(
         &mut self,
         market_update: &U,
-    ) -> Result<&Vec<LimitOrderEvent<I, D, BaseOrQuote, UserOrderIdT>>, RiskError>
+    ) -> Result<&Vec<LimitOrderEvent<I, D, BaseOrQuote, UserOrderIdT>>, UpdateStateError>
     where
         U: MarketUpdate<I, D, BaseOrQuote>,
+    {
+        self.update_state_with_gate(market_update, &mut AdmitAll)
+    }
+
+    /// Like [`Exchange::update_state`], but the order actions arriving at the venue
+    /// are subject to the `gate` before they are processed.
+    pub(crate) fn update_state_with_gate<U, G>(
+        &mut self,
+        market_update: &U,
+        gate: &mut G,
+    ) -> Result<&Vec<LimitOrderEvent<I, D, BaseOrQuote, UserOrderIdT>>, UpdateStateError>
+    where
+        U: MarketUpdate<I, D, BaseOrQuote>,
+        G: ArrivalGate<I, D>,
+    {
+        let result = self.process_market_update(market_update, gate);
+        // A rejected market update did not change the state.
+        if !matches!(result, Err(UpdateStateError::MarketUpdate(_))) {
+            self.trackers
+                .market_update(&self.account, &self.market_state);
+        }
+        result.map(|()| &self.limit_order_events)
+    }
+
+    /// Apply the `market_update` to the exchange state, see [`Exchange::update_state`].
+    fn process_market_update<U, G>(
+        &mut self,
+        market_update: &U,
+        gate: &mut G,
+    ) -> Result<(), UpdateStateError>
+    where
+        U: MarketUpdate<I, D, BaseOrQuote>,
+        G: ArrivalGate<I, D>,
     {
         self.limit_order_events.clear();
+        self.fee_tier_changes.clear();
+        self.order_action_events.clear();
         self.liquidated_during_fills = false;
 
-        self.market_state
-            .update_state(market_update, self.config.contract_spec().price_filter());
+        let ts_ns = market_update.timestamp_exchange_ns();
+        if self.strict_market_data {
+            self.check_market_update(market_update)?;
+        }
+        let mut liquidated = false;
+        if let Some(change_ts) = self
+            .contract_spec_schedule
+            .changes()
+            .get(self.next_contract_spec_change)
+            .map(|(change_ts, _)| *change_ts)
+            && change_ts <= ts_ns
+        {
+            core::hint::cold_path();
+            // Order actions arriving before a contract specification change are processed under the previous one.
+            liquidated |= self.apply_arrived_order_actions(change_ts - TimestampNs::from(1), gate);
+            // The collateral is reconciled against the new specification at the quote of this update,
+            // which the order actions arriving after the change observe as well.
+            let price_filter = self
+                .contract_spec_schedule
+                .due_price_filter(self.next_contract_spec_change, ts_ns)
+                .unwrap_or(self.config.contract_spec().price_filter());
+            self.market_state.update_state(market_update, price_filter);
+            self.update_price_impact();
+            liquidated |= self.apply_contract_spec_changes(ts_ns);
+            liquidated |= self.apply_arrived_order_actions(ts_ns, gate);
+        } else {
+            liquidated |= self.apply_arrived_order_actions(ts_ns, gate);
+            self.market_state
+                .update_state(market_update, self.config.contract_spec().price_filter());
+            self.update_price_impact();
+        }
+        self.deliver_order_action_responses(ts_ns);
+        if liquidated {
+            core::hint::cold_path();
+            return Err(RiskError::Liquidate.into());
+        }
 
         if let Err(e) = <IsolatedMarginRiskEngine<I, D, BaseOrQuote> as RiskEngine<
             I,
@@ -201,15 +664,29 @@ where
             core::hint::cold_path();
             self.force_liquidate();
             self.drain_forced_cancels_into_events();
-            return Err(e);
+            return Err(e.into());
         };
 
-        self.check_active_orders(market_update.clone());
+        self.fill_active_orders(market_update.clone());
         if self.liquidated_during_fills {
             core::hint::cold_path();
-            return Err(RiskError::Liquidate);
+            return Err(RiskError::Liquidate.into());
+        }
+
+        // Traded notional may have rolled out of the window of the fee schedule.
+        if let Some(change) = self.update_fee_tier() {
+            self.fee_tier_changes
+                .push_within_capacity(change)
+                .expect(EXPECT_CAPACITY);
+            // A higher maker fee increases the fees reserved for resting limit orders.
+            let solvency = self.reconcile_margin(self.account.balances().bad_debt());
+            self.drain_forced_cancels_into_events();
+            if matches!(solvency, Solvency::Liquidated | Solvency::Bankrupt) {
+                core::hint::cold_path();
+                return Err(RiskError::Liquidate.into());
+            }
         }
-        Ok(&self.limit_order_events)
+        Ok(())
     }
 
     /// Set the best bid and ask, alternatively a `Bba` `MarketUpdate` can be passed into `update_state`
@@ -220,10 +697,65 @@ where
         self.market_state.set_ask(ask);
     }
 
+    /// Apply a quote published by the `MatchingEngine` and check the maintenance margin against it.
+    /// Unlike [`Exchange::update_state`], the limit order events are not cleared,
+    /// so they accumulate over the current action of the `MatchingEngine`.
+    ///
+    /// # Returns:
+    /// `true` if the quote liquidated the position.
+    pub(crate) fn apply_quote(
+        &mut self,
+        bid: QuoteCurrency<I, D>,
+        ask: QuoteCurrency<I, D>,
+        last_trade_price: Option<QuoteCurrency<I, D>>,
+        ts_ns: TimestampNs,
+    ) -> bool {
+        assert2::debug_assert!(bid < ask);
+        self.market_state.set_bid(bid);
+        self.market_state.set_ask(ask);
+        if let Some(price) = last_trade_price {
+            self.market_state.set_last_trade_price(price);
+        }
+        self.market_state.set_current_ts_ns(ts_ns);
+
+        if <IsolatedMarginRiskEngine<I, D, BaseOrQuote> as RiskEngine<
+            I,
+            D,
+            BaseOrQuote,
+            UserOrderIdT,
+        >>::check_maintenance_margin(
+            &self.risk_engine,
+            &self.market_state,
+            self.account.position(),
+        )
+        .is_err()
+        {
+            core::hint::cold_path();
+            self.force_liquidate();
+            self.drain_forced_cancels_into_events();
+            return true;
+        }
+        false
+    }
+
+    /// Leave closing a liquidated position to the `MatchingEngine`, which trades it against the
+    /// resting orders of the other participants instead of closing it at the quote.
+    pub(crate) fn set_liquidated_by_matching_engine(&mut self) {
+        self.liquidated_by_matching_engine = true;
+    }
+
+    /// Clear the limit order events and fee tier changes before an action of the `MatchingEngine`.
+    pub(crate) fn clear_events(&mut self) {
+        self.limit_order_events.clear();
+        self.fee_tier_changes.clear();
+    }
+
     /// Force-close the position like a real venue's liquidation engine:
     /// first cancel every resting limit order of the account (buffering them in the
     /// forced-cancel scratch), then close the position with an internal fill at the
     /// current bid or ask.
+    /// Within a `MatchingEngine`, the position stays open until the engine closes it
+    /// against the `OrderBook`, see `settle_liquidation_fill`.
     ///
     /// This deliberately bypasses the order rate limiter and every admission check,
     /// because a forced liquidation must never fail. A realized loss exceeding the
@@ -256,6 +788,9 @@ where
                 .push_within_capacity(cancelled)
                 .expect(EXPECT_CAPACITY);
         }
+        if self.liquidated_by_matching_engine {
+            return;
+        }
 
         let position_qty = self.account.position().quantity();
         let (side, fill_price) = if position_qty.is_negative() {
@@ -263,11 +798,36 @@ where
         } else {
             (Sell, self.market_state.bid())
         };
-        let quantity = position_qty.abs();
+        self.settle_liquidation_fill(side, position_qty.abs(), fill_price);
+    }
+
+    /// Settle a fill of `quantity` at `fill_price` which closes a liquidated position.
+    pub(crate) fn settle_liquidation_fill(
+        &mut self,
+        side: Side,
+        quantity: BaseOrQuote,
+        fill_price: QuoteCurrency<I, D>,
+    ) {
         let notional = BaseOrQuote::PairedCurrency::convert_from(quantity, fill_price);
         let fee = notional * *self.config.contract_spec().fee_taker().as_ref();
-        self.account
+        let fill = TrackedFill {
+            kind: FillKind::Liquidation,
+            order: None,
+            side,
+            quantity,
+            price: fill_price,
+            fee,
+            order_age: None,
+            ts_ns: self.market_state.current_ts_ns(),
+        };
+        self.trackers.fill(fill, self.account.position());
+        let realized_pnl = self
+            .account
             .change_position(quantity, fill_price, side, fee);
+        self.trackers
+            .settlement(fill, realized_pnl, self.account.balances());
+        self.record_traded_notional(notional);
+        self.record_price_impact(side, quantity, fill_price);
         info!("balances after liquidation: {}", self.account.balances());
     }
 
@@ -337,6 +897,274 @@ where
         }
     }
 
+    /// Make sure the `market_update` satisfies the `PriceFilter` and does not go back in time.
+    fn check_market_update<U>(&self, market_update: &U) -> Result<(), MarketUpdateError>
+    where
+        U: MarketUpdate<I, D, BaseOrQuote>,
+    {
+        let current_ts_ns = self.market_state.current_ts_ns();
+        let update_ts_ns = market_update.timestamp_exchange_ns();
+        // A contract specification change due at the update may change the tick size.
+        market_update
+            .validate_market_update(self.price_filter_at(update_ts_ns))
+            .map_err(MarketUpdateError::InvalidPrice)?;
+        if update_ts_ns < current_ts_ns {
+            return Err(MarketUpdateError::OutOfOrder {
+                current_ts_ns,
+                update_ts_ns,
+            });
+        }
+        Ok(())
+    }
+
+    /// The `PriceFilter` in effect at `ts_ns`, including the scheduled contract specification changes
+    /// which are due but not applied yet.
+    fn price_filter_at(&self, ts_ns: TimestampNs) -> &PriceFilter<I, D> {
+        self.contract_spec_schedule
+            .due_price_filter(self.next_contract_spec_change, ts_ns)
+            .unwrap_or(self.config.contract_spec().price_filter())
+    }
+
+    /// Apply the scheduled contract specification changes up to and including `ts_ns`.
+    /// Resting limit orders violating the new `PriceFilter`, `QuantityFilter` or `NotionalFilter` are cancelled,
+    /// after which the account collateral is reconciled against the new margin requirements and fees.
+    ///
+    /// # Returns:
+    /// `true` if the reconciliation liquidated the position.
+    fn apply_contract_spec_changes(&mut self, ts_ns: TimestampNs) -> bool {
+        let mut applied = false;
+        while let Some((change_ts, contract_spec)) = self
+            .contract_spec_schedule
+            .changes()
+            .get(self.next_contract_spec_change)
+            && *change_ts <= ts_ns
+        {
+            core::hint::cold_path();
+            let contract_spec = contract_spec.clone();
+            self.next_contract_spec_change += 1;
+            self.replace_contract_spec(contract_spec);
+            applied = true;
+        }
+        if !applied {
+            return false;
+        }
+
+        // The active tier of a new fee schedule is selected from the tracked traded notional.
+        self.fee_tier_tracker.reset_active_tier();
+        if let Some(change) = self.update_fee_tier() {
+            self.fee_tier_changes
+                .push_within_capacity(change)
+                .expect(EXPECT_CAPACITY);
+        }
+
+        loop {
+            let contract_spec = self.config.contract_spec();
+            let Some(order_id) = self
+                .account
+                .active_limit_orders()
+                .iter()
+                .find(|order| {
+                    contract_spec
+                        .price_filter()
+                        .validate_resting_price(order.limit_price())
+                        .is_err()
+                        || contract_spec
+                            .quantity_filter()
+                            .validate_order_quantity(order.remaining_quantity())
+                            .is_err()
+                        || contract_spec
+                            .notional_filter()
+                            .validate_order_notional(
+                                order.remaining_quantity(),
+                                order.limit_price(),
+                            )
+                            .is_err()
+                })
+                .map(|order| order.id())
+            else {
+                break;
+            };
+            let cancelled = self
+                .account
+                .cancel_limit_order(CancelBy::OrderId(order_id))
+                .expect("the id belongs to an active order");
+            debug!(
+                "cancelled limit order {} violating the new contract specification",
+                cancelled.id()
+            );
+            self.limit_order_events
+                .push_within_capacity(LimitOrderEvent::ContractSpecCancel(cancelled))
+                .expect(EXPECT_CAPACITY);
+        }
+
+        let solvency = self.reconcile_margin(self.account.balances().bad_debt());
+        self.drain_forced_cancels_into_events();
+        matches!(solvency, Solvency::Liquidated | Solvency::Bankrupt)
+    }
+
+    /// Apply the in-flight order actions which arrived at the venue up to and including `ts_ns`,
+    /// against the market state the venue observed at their arrival.
+    ///
+    /// # Returns:
+    /// `true` if the settlement of a market order liquidated the position.
+    fn apply_arrived_order_actions<G>(&mut self, ts_ns: TimestampNs, gate: &mut G) -> bool
+    where
+        G: ArrivalGate<I, D>,
+    {
+        if !self.order_pipeline.has_arrived(ts_ns) {
+            return false;
+        }
+
+        let mut liquidated = false;
+        while let Some((arrival_ts_ns, request_id, action)) = self.order_pipeline.pop_arrived(ts_ns)
+        {
+            if arrival_ts_ns > self.market_state.current_ts_ns() {
+                self.market_state.set_current_ts_ns(arrival_ts_ns);
+            }
+            let event = match action {
+                OrderAction::SubmitLimitOrder(order) => {
+                    let (side, price) = (order.side(), order.limit_price());
+                    let result = if gate.admit(side, price) {
+                        self.submit_limit_order(order)
+                    } else {
+                        Err(SubmitLimitOrderError::SelfTradePrevented)
+                    };
+                    match result {
+                        Ok(order) => {
+                            gate.accepted(side, price);
+                            OrderActionEvent::LimitOrderAccepted { request_id, order }
+                        }
+                        Err(error) => OrderActionEvent::LimitOrderRejected { request_id, error },
+                    }
+                }
+                OrderAction::SubmitMarketOrder(order) => {
+                    let side = order.side();
+                    let fill_price = match side {
+                        Buy => self.market_state.ask(),
+                        Sell => self.market_state.bid(),
+                    };
+                    let result = if gate.admit(side, fill_price) {
+                        self.submit_market_order(order)
+                    } else {
+                        Err(SubmitMarketOrderError::SelfTradePrevented)
+                    };
+                    match result {
+                        Ok(settlement) => {
+                            gate.accepted(side, fill_price);
+                            liquidated |= matches!(
+                                settlement.solvency,
+                                Solvency::Liquidated | Solvency::Bankrupt
+                            );
+                            OrderActionEvent::MarketOrderFilled {
+                                request_id,
+                                settlement,
+                            }
+                        }
+                        Err(error) => OrderActionEvent::MarketOrderRejected { request_id, error },
+                    }
+                }
+                OrderAction::CancelLimitOrder {
+                    cancel_by,
+                    order_was_known,
+                } => match self.cancel_limit_order(cancel_by) {
+                    Ok(order) => OrderActionEvent::Cancelled { request_id, order },
+                    Err(CancelLimitOrderError::OrderIdNotFound(_)) if order_was_known => {
+                        OrderActionEvent::TooLateToCancel {
+                            request_id,
+                            cancel_by,
+                        }
+                    }
+                    Err(error) => OrderActionEvent::CancelRejected { request_id, error },
+                },
+                OrderAction::AmendLimitOrder {
+                    existing_order_id,
+                    new_order,
+                } => {
+                    let (side, price) = (new_order.side(), new_order.limit_price());
+                    let result = if gate.admit(side, price) {
+                        self.amend_limit_order(existing_order_id, new_order)
+                    } else {
+                        Err(SubmitLimitOrderError::SelfTradePrevented.into())
+                    };
+                    match result {
+                        Ok(order) => {
+                            gate.accepted(side, price);
+                            OrderActionEvent::Amended { request_id, order }
+                        }
+                        Err(error) => OrderActionEvent::AmendRejected { request_id, error },
+                    }
+                }
+            };
+            debug!("order action {request_id} arrived at {arrival_ts_ns}: {event:?}");
+            self.order_pipeline.respond(arrival_ts_ns, event);
+        }
+        liquidated
+    }
+
+    /// Report the outcomes of the order actions whose responses arrived back at the strategy
+    /// up to and including `ts_ns` in `order_action_events`.
+    fn deliver_order_action_responses(&mut self, ts_ns: TimestampNs) {
+        // Sized in `Exchange::new`, so this only allocates if more responses are in flight.
+        self.order_action_events
+            .reserve(self.order_pipeline.num_responses());
+        while let Some(event) = self.order_pipeline.pop_response(ts_ns) {
+            self.order_action_events
+                .push_within_capacity(event)
+                .expect(EXPECT_CAPACITY);
+        }
+    }
+
+    /// Replace the active contract specification of the config, the risk engine and the account.
+    fn replace_contract_spec(&mut self, contract_spec: ContractSpecification<I, D, BaseOrQuote>) {
+        info!(
+            "applying contract specification change with price filter {} at {}",
+            contract_spec.price_filter(),
+            self.market_state.current_ts_ns()
+        );
+        *self.config.contract_spec_mut() = contract_spec;
+        self.risk_engine
+            .set_contract_spec(self.config.contract_spec().clone());
+        self.account
+            .set_init_margin_req(self.config.contract_spec().init_margin_req());
+        self.account
+            .set_maker_fee(*self.config.contract_spec().fee_maker().as_ref());
+    }
+
+    /// Count the notional of a fill towards the rolling traded notional of the fee schedule.
+    #[inline]
+    fn record_traded_notional(&mut self, notional: BaseOrQuote::PairedCurrency) {
+        // Without a schedule nothing would ever expire the recorded notional.
+        if self.config.contract_spec().fee_schedule().is_some() {
+            self.fee_tier_tracker
+                .record_fill(self.market_state.current_ts_ns(), notional);
+        }
+    }
+
+    /// Select the active tier of the fee schedule, if the `ContractSpecification` has one.
+    /// A tier change is applied to the active fees of the contract specification, the risk engine
+    /// and the fee reserve of the account.
+    ///
+    /// # Returns:
+    /// The change of the active fee tier, if any.
+    fn update_fee_tier(&mut self) -> Option<FeeTierChange<I, D, BaseOrQuote::PairedCurrency>> {
+        let schedule = self.config.contract_spec().fee_schedule().as_ref()?;
+        let change = self
+            .fee_tier_tracker
+            .update(schedule, self.market_state.current_ts_ns())?;
+        info!(
+            "fee tier changed from {} to {} at a rolling traded notional of {}",
+            change.previous_tier, change.new_tier, change.rolling_notional
+        );
+        self.config
+            .contract_spec_mut()
+            .set_active_fees(change.fee_maker, change.fee_taker);
+        self.risk_engine
+            .set_contract_spec(self.config.contract_spec().clone());
+        self.account.set_maker_fee(*change.fee_maker.as_ref());
+
+        Some(change)
+    }
+
     /// Route the forced cancellations of a reconciliation into the event stream of
     /// [`Exchange::update_state`], preserving their order.
     fn drain_forced_cancels_into_events(&mut self) {
@@ -369,6 +1197,60 @@ where
         order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
     ) -> Result<MarketOrderSettlement<I, D, BaseOrQuote, UserOrderIdT>, SubmitMarketOrderError>
     {
+        assert2::debug_assert!(self.market_state.ask() > QuoteCurrency::zero());
+        assert2::debug_assert!(self.market_state.bid() > QuoteCurrency::zero());
+        let fill_price = match order.side() {
+            Buy => self.market_state.ask(),
+            Sell => self.market_state.bid(),
+        };
+        let order = self.admit_market_order(order, fill_price)?;
+
+        let filled_order = order.into_filled(fill_price, self.market_state.current_timestamp_ns());
+        let bad_debt_before = self.account.balances().bad_debt();
+        self.settle_taker_fill(
+            filled_order.state().meta().id(),
+            filled_order.strategy_tag(),
+            filled_order.side(),
+            filled_order.quantity(),
+            fill_price,
+        );
+        let (forced_cancels, solvency, fee_tier_change) =
+            self.finish_taker_settlement(bad_debt_before);
+
+        Ok(MarketOrderSettlement {
+            filled_order,
+            forced_cancels,
+            solvency,
+            fee_tier_change,
+        })
+    }
+
+    /// Run the admission checks of a `MarketOrder` which is expected to fill at `fill_price`,
+    /// assigning it an `OrderId` if it passes.
+    pub(crate) fn admit_market_order(
+        &mut self,
+        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+        fill_price: QuoteCurrency<I, D>,
+    ) -> Result<
+        MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        SubmitMarketOrderError,
+    > {
+        let order = self.admit_market_order_without_margin_check(order, fill_price)?;
+        self.risk_engine
+            .check_market_order(&self.account, &order, fill_price)?;
+        Ok(order)
+    }
+
+    /// Like `admit_market_order`, but leaves the margin check to the caller,
+    /// which checks each fill separately with `check_taker_fill`.
+    pub(crate) fn admit_market_order_without_margin_check(
+        &mut self,
+        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+        fill_price: QuoteCurrency<I, D>,
+    ) -> Result<
+        MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        SubmitMarketOrderError,
+    > {
         self.order_rate_limiter
             .aquire(self.market_state.current_ts_ns())?;
         // Basic checks
@@ -376,6 +1258,10 @@ where
             .contract_spec()
             .quantity_filter()
             .validate_order_quantity(order.quantity())?;
+        self.config
+            .contract_spec()
+            .notional_filter()
+            .validate_order_notional(order.quantity(), fill_price)?;
 
         let meta = ExchangeOrderMeta::new(
             self.next_order_id(),
@@ -383,42 +1269,80 @@ where
         );
         let order = order.into_pending(meta);
 
-        assert2::debug_assert!(self.market_state.ask() > QuoteCurrency::zero());
-        assert2::debug_assert!(self.market_state.bid() > QuoteCurrency::zero());
-        let fill_price = match order.side() {
-            Buy => self.market_state.ask(),
-            Sell => self.market_state.bid(),
-        };
-        self.risk_engine
-            .check_market_order(&self.account, &order, fill_price)?;
+        self.risk_engine.check_position_limits(
+            &self.account,
+            order.side(),
+            order.quantity(),
+            fill_price,
+        )?;
 
-        let filled_order = order.into_filled(fill_price, self.market_state.current_timestamp_ns());
-        let (forced_cancels, solvency) = self.settle_filled_market_order(filled_order.clone());
+        Ok(order)
+    }
 
-        Ok(MarketOrderSettlement {
-            filled_order,
-            forced_cancels,
-            solvency,
-        })
+    /// Check that the account can afford the next fill of `quantity` at `fill_price` of the admitted `order`,
+    /// given the fills of it which are already settled.
+    pub(crate) fn check_taker_fill(
+        &self,
+        order: &MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        quantity: BaseOrQuote,
+        fill_price: QuoteCurrency<I, D>,
+    ) -> Result<(), NotEnoughAvailableBalance> {
+        let fill =
+            MarketOrder::new_with_user_order_id(order.side(), quantity, order.user_order_id())
+                .expect("The fill quantity is positive")
+                .into_pending(order.state().meta().clone());
+        self.risk_engine
+            .check_market_order(&self.account, &fill, fill_price)
     }
 
-    /// Settle an immediately filled market order and reconcile the account collateral,
-    /// returning the forced cancellations and the resulting solvency.
-    fn settle_filled_market_order(
+    /// Settle an aggressive fill of `quantity` at `fill_price` into the account.
+    /// The collateral is reconciled once all fills of the order are settled, see `finish_taker_settlement`.
+    pub(crate) fn settle_taker_fill(
         &mut self,
-        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Filled<I, D, BaseOrQuote>>,
-    ) -> (ForcedCancels<I, D, BaseOrQuote, UserOrderIdT>, Solvency) {
-        let filled_qty = order.quantity();
-        assert2::debug_assert!(filled_qty > BaseOrQuote::zero());
-        let fill_price = order.state().avg_fill_price();
+        order_id: OrderId,
+        strategy_tag: StrategyTag,
+        side: Side,
+        quantity: BaseOrQuote,
+        fill_price: QuoteCurrency<I, D>,
+    ) {
+        assert2::debug_assert!(quantity > BaseOrQuote::zero());
         assert2::debug_assert!(fill_price > QuoteCurrency::zero());
 
-        let notional = BaseOrQuote::PairedCurrency::convert_from(filled_qty, fill_price);
+        let notional = BaseOrQuote::PairedCurrency::convert_from(quantity, fill_price);
         let fee = notional * *self.config.contract_spec().fee_taker().as_ref();
 
-        let bad_debt_before = self.account.balances().bad_debt();
-        self.account
-            .change_position(filled_qty, fill_price, order.side(), fee);
+        let fill = TrackedFill {
+            kind: FillKind::Taker,
+            order: Some((order_id, strategy_tag)),
+            side,
+            quantity,
+            price: fill_price,
+            fee,
+            order_age: None,
+            ts_ns: self.market_state.current_ts_ns(),
+        };
+        self.trackers.fill(fill, self.account.position());
+        let realized_pnl = self
+            .account
+            .change_position(quantity, fill_price, side, fee);
+        self.trackers
+            .settlement(fill, realized_pnl, self.account.balances());
+        self.record_traded_notional(notional);
+        self.record_price_impact(side, quantity, fill_price);
+    }
+
+    /// Reconcile the account collateral after the aggressive fills of an order were settled,
+    /// returning the forced cancellations, the resulting solvency and the fee tier change.
+    #[allow(clippy::type_complexity, reason = "Destructured right away")]
+    pub(crate) fn finish_taker_settlement(
+        &mut self,
+        bad_debt_before: BaseOrQuote::PairedCurrency,
+    ) -> (
+        ForcedCancels<I, D, BaseOrQuote, UserOrderIdT>,
+        Solvency,
+        Option<FeeTierChange<I, D, BaseOrQuote::PairedCurrency>>,
+    ) {
+        let fee_tier_change = self.update_fee_tier();
 
         // A position-reducing fill settles without a prior risk check; the venue
         // reconciles any collateral shortfall instead of rejecting the reduction.
@@ -431,7 +1355,50 @@ where
                 .push_within_capacity(order)
                 .expect(EXPECT_CAPACITY);
         }
-        (forced_cancels, solvency)
+        (forced_cancels, solvency, fee_tier_change)
+    }
+
+    /// Record the impact of our own aggressive fill and shift the bid and ask accordingly.
+    fn record_price_impact(
+        &mut self,
+        side: Side,
+        quantity: BaseOrQuote,
+        fill_price: QuoteCurrency<I, D>,
+    ) {
+        let Some(model) = self.market_impact_model.as_mut() else {
+            return;
+        };
+        model.record_fill(
+            side,
+            quantity,
+            fill_price,
+            self.market_state.current_ts_ns(),
+        );
+        self.update_price_impact();
+    }
+
+    /// Decay the price impact of our own market orders to the current timestamp.
+    fn update_price_impact(&mut self) {
+        let Some(model) = self.market_impact_model.as_ref() else {
+            return;
+        };
+        let recorded_bid = self.market_state.recorded_bid();
+        let tick_size = self
+            .config
+            .contract_spec()
+            .price_filter()
+            .tick_size_at(recorded_bid);
+        // Buying pressure can at most double the ask.
+        let mut price_impact = model.price_impact(
+            self.market_state.current_ts_ns(),
+            tick_size,
+            self.market_state.recorded_ask(),
+        );
+        // Selling pressure can never push the bid to zero.
+        if recorded_bid > QuoteCurrency::zero() {
+            price_impact = max(price_impact, tick_size - recorded_bid);
+        }
+        self.market_state.set_price_impact(price_impact);
     }
 
     #[inline(always)]
@@ -466,7 +1433,15 @@ where
         self.config
             .contract_spec()
             .price_filter()
-            .validate_limit_price(order.limit_price(), self.market_state.mid_price())?;
+            .validate_limit_price(
+                order.limit_price(),
+                self.market_state
+                    .reference_price(self.config.contract_spec().price_filter().reference_price()),
+            )?;
+        self.config
+            .contract_spec()
+            .notional_filter()
+            .validate_order_notional(order.remaining_quantity(), order.limit_price())?;
 
         let meta = ExchangeOrderMeta::new(
             self.next_order_id(),
@@ -474,6 +1449,12 @@ where
         );
         let order = order.into_pending(meta);
 
+        self.risk_engine.check_position_limits(
+            &self.account,
+            order.side(),
+            order.remaining_quantity(),
+            order.limit_price(),
+        )?;
         self.risk_engine.check_limit_order(&self.account, &order)?;
 
         // If a limit order is marketable, it will take liquidity from the book at the `limit_price` price level and pay the taker fee,
@@ -550,6 +1531,21 @@ where
         }
 
         new_order.set_remaining_quantity(new_leaves_qty);
+        // Reject before the existing order is cancelled, so it keeps resting.
+        self.config
+            .contract_spec()
+            .notional_filter()
+            .validate_order_notional(new_leaves_qty, new_order.limit_price())
+            .map_err(SubmitLimitOrderError::from)?;
+        // The existing order still counts as resting, so replacing it changes the worst case by `qty_delta`.
+        self.risk_engine
+            .check_position_limits(
+                &self.account,
+                new_order.side(),
+                qty_delta,
+                new_order.limit_price(),
+            )
+            .map_err(SubmitLimitOrderError::from)?;
 
         self.cancel_limit_order_no_rate_limit(CancelBy::OrderId(existing_order_id))
             .expect("Can always cancel the order here");
@@ -593,7 +1589,7 @@ where
     }
 
     #[allow(clippy::complexity, reason = "How is this hard to read?")]
-    fn cancel_limit_order_no_rate_limit(
+    pub(crate) fn cancel_limit_order_no_rate_limit(
         &mut self,
         cancel_by: CancelBy<UserOrderIdT>,
     ) -> Result<
@@ -613,77 +1609,29 @@ where
 
     /// Checks for the execution of active limit orders in the account.
     /// NOTE: only public for benchmarking purposes.
-    pub fn check_active_orders<U>(&mut self, mut market_update: U)
+    pub fn check_active_orders<U>(&mut self, market_update: U)
     where
         U: MarketUpdate<I, D, BaseOrQuote>,
     {
         // Clear any potential order events from the previous iteration.
         self.limit_order_events.clear();
+        self.fill_active_orders(market_update);
+    }
 
+    /// Fill the active limit orders of the account with `market_update`,
+    /// appending the resulting events to the ones already emitted in this update.
+    fn fill_active_orders<U>(&mut self, mut market_update: U)
+    where
+        U: MarketUpdate<I, D, BaseOrQuote>,
+    {
         if !U::CAN_FILL_LIMIT_ORDERS {
             return;
         }
 
-        if market_update.can_fill_bids() {
-            // peek at the best bid order.
-            while let Some(order) = self.account.active_limit_orders().best_bid() {
-                if let Some((filled_qty, exhausted)) = market_update.limit_order_filled(order) {
-                    let bad_debt_before = self.account.balances().bad_debt();
-                    let limit_order_update = self.fill_limit_order(
-                        order.clone(),
-                        filled_qty,
-                        market_update.timestamp_exchange_ns(),
-                    );
-                    self.limit_order_events
-                        .push_within_capacity(LimitOrderEvent::Fill(limit_order_update))
-                        .expect(EXPECT_CAPACITY);
-                    // A fill which reduced the position settles without a prior risk
-                    // check; the venue reconciles any collateral shortfall it caused.
-                    let solvency = self.reconcile_margin(bad_debt_before);
-                    self.drain_forced_cancels_into_events();
-                    if matches!(solvency, Solvency::Liquidated | Solvency::Bankrupt) {
-                        core::hint::cold_path();
-                        self.liquidated_during_fills = true;
-                        return;
-                    }
-                    if exhausted {
-                        return;
-                    }
-                } else {
-                    // We can be sure that no other bid can be filled if this one could not be filled.
-                    break;
-                }
-            }
-        }
-
-        if market_update.can_fill_asks() {
-            while let Some(order) = self.account.active_limit_orders().best_ask() {
-                if let Some((filled_qty, exhausted)) = market_update.limit_order_filled(order) {
-                    let bad_debt_before = self.account.balances().bad_debt();
-                    let limit_order_update = self.fill_limit_order(
-                        order.clone(),
-                        filled_qty,
-                        market_update.timestamp_exchange_ns(),
-                    );
-                    self.limit_order_events
-                        .push_within_capacity(LimitOrderEvent::Fill(limit_order_update))
-                        .expect(EXPECT_CAPACITY);
-                    // A fill which reduced the position settles without a prior risk
-                    // check; the venue reconciles any collateral shortfall it caused.
-                    let solvency = self.reconcile_margin(bad_debt_before);
-                    self.drain_forced_cancels_into_events();
-                    if matches!(solvency, Solvency::Liquidated | Solvency::Bankrupt) {
-                        core::hint::cold_path();
-                        self.liquidated_during_fills = true;
-                        return;
-                    }
-                    if exhausted {
-                        return;
-                    }
-                } else {
-                    // We can be sure that no other ask can be filled if this one could not be filled.
-                    break;
-                }
+        let first_side = market_update.first_filled_side();
+        for side in [first_side, first_side.inverted()] {
+            if self.fill_side(side, &mut market_update) {
+                return;
             }
         }
 
@@ -695,6 +1643,108 @@ where
         self.account.balances().debug_assert_state();
     }
 
+    /// Fill the active limit orders of `side` with `market_update`, best priced first.
+    ///
+    /// # Returns:
+    /// `true` if the fills stop the processing of the `market_update`,
+    /// either because its liquidity is exhausted or because the position got liquidated.
+    fn fill_side<U>(&mut self, side: Side, market_update: &mut U) -> bool
+    where
+        U: MarketUpdate<I, D, BaseOrQuote>,
+    {
+        let can_fill = match side {
+            Buy => market_update.can_fill_bids(),
+            Sell => market_update.can_fill_asks(),
+        };
+        if !can_fill {
+            return false;
+        }
+
+        let mut consumed_volume = BaseOrQuote::zero();
+        loop {
+            // peek at the best order.
+            let best_order = match side {
+                Buy => self.account.active_limit_orders().best_bid(),
+                Sell => self.account.active_limit_orders().best_ask(),
+            };
+            let Some(order) = best_order else {
+                return false;
+            };
+            let order_id = order.id();
+            let remaining_quantity = order.remaining_quantity();
+            let fill = match self.fill_model.as_deref_mut() {
+                Some(fill_model) => fill_with_model(
+                    fill_model,
+                    &*market_update,
+                    order,
+                    &mut consumed_volume,
+                    self.config.contract_spec().quantity_filter().tick_size(),
+                ),
+                None => market_update.limit_order_filled(order),
+            };
+            let Some((filled_qty, exhausted)) = fill else {
+                // We can be sure that no other order of this side can be filled if this one could not be filled.
+                return false;
+            };
+
+            if self.fill_best_limit_order(
+                side,
+                order_id,
+                filled_qty,
+                market_update.timestamp_exchange_ns(),
+            ) {
+                core::hint::cold_path();
+                self.liquidated_during_fills = true;
+                return true;
+            }
+            if exhausted {
+                return true;
+            }
+            if filled_qty < remaining_quantity {
+                // A partly filled order stays at the front of the queue,
+                // so it is filled at most once per market update and no worse order can fill.
+                return false;
+            }
+        }
+    }
+
+    /// Fill the best resting limit order of `side`, which has `order_id`, by `filled_quantity`,
+    /// appending the fill and any resulting forced cancellations to the limit order events.
+    ///
+    /// # Returns:
+    /// `true` if the fill liquidated or bankrupted the account.
+    ///
+    /// # Panics:
+    /// If the order with `order_id` is not the best active order of `side`.
+    pub(crate) fn fill_best_limit_order(
+        &mut self,
+        side: Side,
+        order_id: OrderId,
+        filled_quantity: BaseOrQuote,
+        ts_ns: TimestampNs,
+    ) -> bool {
+        let order = match side {
+            Buy => self.account.active_limit_orders().best_bid(),
+            Sell => self.account.active_limit_orders().best_ask(),
+        }
+        .cloned()
+        .expect("Has an active order on this side");
+        assert2::assert!(
+            order.id() == order_id,
+            "The price-time priority of the order book and the account agree"
+        );
+        let bad_debt_before = self.account.balances().bad_debt();
+        let limit_order_update = self.fill_limit_order(order, filled_quantity, ts_ns);
+        self.limit_order_events
+            .push_within_capacity(LimitOrderEvent::Fill(limit_order_update))
+            .expect(EXPECT_CAPACITY);
+        // A fill which reduced the position settles without a prior risk
+        // check; the venue reconciles any collateral shortfall it caused.
+        let solvency = self.reconcile_margin(bad_debt_before);
+        self.drain_forced_cancels_into_events();
+        matches!(solvency, Solvency::Liquidated | Solvency::Bankrupt)
+    }
+
     fn fill_limit_order(
         &mut self,
         // TODO: refactor this as technically ownership does not make sense here as we should reference the `ActiveLimitOrders` one.
@@ -719,10 +1769,30 @@ where
         let notional = BaseOrQuote::PairedCurrency::convert_from(filled_quantity, limit_price);
         let fee = notional * *self.config().contract_spec().fee_maker().as_ref();
 
-        match self
-            .account
-            .fill_best(side, filled_quantity, limit_price, fee, ts_ns)
-        {
+        let fill = TrackedFill {
+            kind: FillKind::Maker,
+            order: Some((order.id(), order.strategy_tag())),
+            side,
+            quantity: filled_quantity,
+            price: limit_price,
+            fee,
+            order_age: Some(ts_ns - order.state().meta().ts_exchange_received()),
+            ts_ns,
+        };
+        self.trackers.fill(fill, self.account.position());
+        let (order_after_fill, realized_pnl) =
+            self.account
+                .fill_best(side, filled_quantity, limit_price, fee, ts_ns);
+        self.trackers
+            .settlement(fill, realized_pnl, self.account.balances());
+        self.record_traded_notional(notional);
+        if let Some(change) = self.update_fee_tier() {
+            self.fee_tier_changes
+                .push_within_capacity(change)
+                .expect(EXPECT_CAPACITY);
+        }
+
+        match order_after_fill {
             Some(order_after_fill) => LimitOrderFill::FullyFilled {
                 filled_quantity,
                 fee,
@@ -18,3 +18,6 @@ pub const EXPECT_CONTRACT_SPEC: &str = "Is a valid `ContractSpecification`";
 
 /// Expect message when conversion from a primitive is gonna work.
 pub const EXPECT_CONVERSION: &str = "Can convert from a primitive";
+
+/// Expect message when the `AccountTracker` is correct.
+pub const EXPECT_ACCOUNT_TRACKER: &str = "Is a valid `AccountTracker`";
@@ -0,0 +1,192 @@
+//! Tracks the rolling traded notional which selects the active tier of a `FeeSchedule`.
+
+use std::collections::VecDeque;
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::types::{
+    Currency,
+    FeeSchedule,
+    FeeTierChange,
+    Mon,
+    TimestampNs,
+};
+
+/// Keeps the traded notional of the account over the rolling window of a [`FeeSchedule`]
+/// and the resulting active fee tier.
+/// Fills are aggregated into buckets of one second, bounding the memory usage for high-turnover strategies.
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies.
+/// - `NotionalT`: The currency in which the traded notional is measured, which is the margin currency.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct FeeTierTracker<I, const D: u8, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// The traded notional aggregated into buckets starting at the contained timestamp.
+    buckets: VecDeque<(TimestampNs, NotionalT)>,
+
+    /// The traded notional within the rolling window.
+    #[getset(get_copy = "pub")]
+    rolling_notional: NotionalT,
+
+    /// The index of the currently active tier in the `FeeSchedule`.
+    #[getset(get_copy = "pub")]
+    active_tier: usize,
+
+    _i: std::marker::PhantomData<I>,
+}
+
+impl<I, const D: u8, NotionalT> Default for FeeTierTracker<I, D, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    fn default() -> Self {
+        Self {
+            buckets: VecDeque::with_capacity(64),
+            rolling_notional: NotionalT::zero(),
+            active_tier: 0,
+            _i: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<I, const D: u8, NotionalT> FeeTierTracker<I, D, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// Record the notional value of a fill, which counts towards the rolling traded notional.
+    pub(crate) fn record_fill(&mut self, ts_ns: TimestampNs, notional: NotionalT) {
+        assert2::debug_assert!(notional >= NotionalT::zero());
+        let bucket_start = ts_ns.floor_to_nearest_second();
+        let same_bucket = self
+            .buckets
+            .back()
+            .is_some_and(|(start, _)| *start == bucket_start);
+        if same_bucket {
+            self.buckets.back_mut().expect("Checked above").1 += notional;
+        } else {
+            self.buckets.push_back((bucket_start, notional));
+        }
+        self.rolling_notional += notional;
+    }
+
+    /// Fall back to the first tier, e.g. because the `FeeSchedule` was replaced.
+    /// The next `update` selects the active tier of the new schedule.
+    #[inline]
+    pub(crate) fn reset_active_tier(&mut self) {
+        self.active_tier = 0;
+    }
+
+    /// Expire the notional which fell out of the rolling window and select the active tier.
+    ///
+    /// # Returns:
+    /// The change of the active tier, if any.
+    pub(crate) fn update(
+        &mut self,
+        schedule: &FeeSchedule<I, D, NotionalT>,
+        ts_ns: TimestampNs,
+    ) -> Option<FeeTierChange<I, D, NotionalT>> {
+        while let Some((start, notional)) = self.buckets.front().copied() {
+            if start + schedule.rolling_window_ns() > ts_ns {
+                break;
+            }
+            self.rolling_notional -= notional;
+            self.buckets.pop_front();
+        }
+        if self.buckets.is_empty() {
+            // Avoid accumulating rounding residue.
+            self.rolling_notional = NotionalT::zero();
+        }
+
+        let new_tier = schedule.tier_index(self.rolling_notional);
+        if new_tier == self.active_tier {
+            return None;
+        }
+        let previous_tier = self.active_tier;
+        self.active_tier = new_tier;
+        let tier = schedule.tier(new_tier);
+
+        Some(FeeTierChange {
+            timestamp_ns: ts_ns,
+            previous_tier,
+            new_tier,
+            rolling_notional: self.rolling_notional,
+            fee_maker: tier.fee_maker(),
+            fee_taker: tier.fee_taker(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use const_decimal::Decimal;
+
+    use super::*;
+    use crate::types::{
+        Fee,
+        FeeTier,
+        NANOS_PER_SECOND,
+        QuoteCurrency,
+    };
+
+    #[test]
+    fn fee_tier_tracker() {
+        let schedule = FeeSchedule::new(
+            vec![
+                FeeTier::new(
+                    QuoteCurrency::<i64, 5>::new(0, 0),
+                    Fee::from(Decimal::try_from_scaled(2, 4).unwrap()),
+                    Fee::from(Decimal::try_from_scaled(6, 4).unwrap()),
+                ),
+                FeeTier::new(
+                    QuoteCurrency::new(1000, 0),
+                    Fee::from(Decimal::try_from_scaled(1, 4).unwrap()),
+                    Fee::from(Decimal::try_from_scaled(5, 4).unwrap()),
+                ),
+            ],
+            (10 * NANOS_PER_SECOND).into(),
+        )
+        .unwrap();
+        let mut tracker = FeeTierTracker::default();
+        assert_eq!(tracker.update(&schedule, 0.into()), None);
+
+        tracker.record_fill(0.into(), QuoteCurrency::new(600, 0));
+        tracker.record_fill(1.into(), QuoteCurrency::new(300, 0));
+        assert_eq!(tracker.buckets.len(), 1);
+        assert_eq!(tracker.update(&schedule, 1.into()), None);
+        assert_eq!(tracker.rolling_notional(), QuoteCurrency::new(900, 0));
+
+        let ts: TimestampNs = (5 * NANOS_PER_SECOND).into();
+        tracker.record_fill(ts, QuoteCurrency::new(100, 0));
+        assert_eq!(
+            tracker.update(&schedule, ts),
+            Some(FeeTierChange {
+                timestamp_ns: ts,
+                previous_tier: 0,
+                new_tier: 1,
+                rolling_notional: QuoteCurrency::new(1000, 0),
+                fee_maker: Fee::from(Decimal::try_from_scaled(1, 4).unwrap()),
+                fee_taker: Fee::from(Decimal::try_from_scaled(5, 4).unwrap()),
+            })
+        );
+        assert_eq!(tracker.active_tier(), 1);
+
+        // The first bucket rolls out of the window.
+        let ts: TimestampNs = (10 * NANOS_PER_SECOND).into();
+        let change = tracker.update(&schedule, ts).unwrap();
+        assert_eq!(change.previous_tier, 1);
+        assert_eq!(change.new_tier, 0);
+        assert_eq!(tracker.rolling_notional(), QuoteCurrency::new(100, 0));
+
+        let ts: TimestampNs = (15 * NANOS_PER_SECOND).into();
+        assert_eq!(tracker.update(&schedule, ts), None);
+        assert_eq!(tracker.rolling_notional(), QuoteCurrency::zero());
+    }
+}
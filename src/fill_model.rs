@@ -0,0 +1,462 @@
+//! Decouples the assumption of how resting limit orders get filled from the `MarketUpdate` types,
+//! so the sensitivity of a strategy to its fill assumptions can be measured.
+
+use const_decimal::Decimal;
+use getset::CopyGetters;
+
+use crate::{
+    market_update::{
+        MarketUpdate,
+        PriceContact,
+    },
+    rng::SplitMix64,
+    types::{
+        ConfigError,
+        Currency,
+        LimitOrder,
+        Mon,
+        Pending,
+        UserOrderId,
+    },
+    utils::min,
+};
+
+/// Decides how much of a resting limit order gets filled once the market reached its limit price.
+/// Set with `Exchange::set_fill_model`. Without one, the `Exchange` uses `MarketUpdate::limit_order_filled`.
+pub trait FillModel<I, const D: u8, BaseOrQuote>: std::fmt::Debug
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The filled quantity of a resting order with `remaining_quantity`, given how the market reached its limit price.
+    /// The volumes of `contact` exclude the volume already consumed by better priced orders of the same side.
+    /// A partial fill must be a multiple of the `quantity_step` of the `QuantityFilter`.
+    ///
+    /// # Returns:
+    /// `None` if the order is not filled, otherwise a quantity in the range (0, `remaining_quantity`].
+    fn fill_quantity(
+        &mut self,
+        remaining_quantity: BaseOrQuote,
+        contact: PriceContact<BaseOrQuote>,
+        quantity_step: BaseOrQuote,
+    ) -> Option<BaseOrQuote>;
+
+    /// Clone the fill model into a `Box`, which keeps the `Exchange` clonable.
+    fn clone_box(&self) -> Box<dyn FillModel<I, D, BaseOrQuote>>;
+}
+
+impl<I, const D: u8, BaseOrQuote> Clone for Box<dyn FillModel<I, D, BaseOrQuote>>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Fill up to `volume` rounded down to the `quantity_step`,
+/// where `None` means the market update carries no volume information.
+#[inline]
+fn fill_up_to<I, const D: u8, BaseOrQuote>(
+    remaining_quantity: BaseOrQuote,
+    volume: Option<BaseOrQuote>,
+    quantity_step: BaseOrQuote,
+) -> Option<BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    let quantity = volume.map_or(remaining_quantity, |volume| {
+        let volume =
+            BaseOrQuote::from(volume.as_ref().quantize_round_to_zero(*quantity_step.as_ref()));
+        min(volume, remaining_quantity)
+    });
+    (quantity > BaseOrQuote::zero()).then_some(quantity)
+}
+
+/// Fills only if the market traded strictly through the limit price,
+/// assuming the worst possible queue position. Matches the behaviour of the `MarketUpdate` types.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TradeThrough;
+
+impl<I, const D: u8, BaseOrQuote> FillModel<I, D, BaseOrQuote> for TradeThrough
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fill_quantity(
+        &mut self,
+        remaining_quantity: BaseOrQuote,
+        contact: PriceContact<BaseOrQuote>,
+        quantity_step: BaseOrQuote,
+    ) -> Option<BaseOrQuote> {
+        if !contact.traded_through {
+            return None;
+        }
+        fill_up_to(remaining_quantity, contact.volume_through, quantity_step)
+    }
+
+    fn clone_box(&self) -> Box<dyn FillModel<I, D, BaseOrQuote>> {
+        Box::new(*self)
+    }
+}
+
+/// Fills as soon as the market touches the limit price, assuming the best possible queue position.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TouchFill;
+
+impl<I, const D: u8, BaseOrQuote> FillModel<I, D, BaseOrQuote> for TouchFill
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fill_quantity(
+        &mut self,
+        remaining_quantity: BaseOrQuote,
+        contact: PriceContact<BaseOrQuote>,
+        quantity_step: BaseOrQuote,
+    ) -> Option<BaseOrQuote> {
+        fill_up_to(remaining_quantity, contact.volume_touching, quantity_step)
+    }
+
+    fn clone_box(&self) -> Box<dyn FillModel<I, D, BaseOrQuote>> {
+        Box::new(*self)
+    }
+}
+
+/// Fills with the volume at the limit price with a `probability`, approximating an unknown queue position.
+/// Otherwise it only fills with the volume traded through the limit price, like `TradeThrough`.
+/// Seeded, so a backtest is reproducible.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct ProbabilisticTouchFill {
+    /// The probability of filling with the volume at the limit price.
+    #[getset(get_copy = "pub")]
+    probability: f64,
+
+    rng: SplitMix64,
+}
+
+impl ProbabilisticTouchFill {
+    /// Create a new instance, where the `probability` must be in the range [0, 1].
+    pub fn new(probability: f64, seed: u64) -> Result<Self, ConfigError> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(ConfigError::InvalidFillModel);
+        }
+        Ok(Self {
+            probability,
+            rng: SplitMix64::new(seed),
+        })
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> FillModel<I, D, BaseOrQuote> for ProbabilisticTouchFill
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fill_quantity(
+        &mut self,
+        remaining_quantity: BaseOrQuote,
+        contact: PriceContact<BaseOrQuote>,
+        quantity_step: BaseOrQuote,
+    ) -> Option<BaseOrQuote> {
+        if self.rng.next_f64() < self.probability {
+            fill_up_to(remaining_quantity, contact.volume_touching, quantity_step)
+        } else if contact.traded_through {
+            fill_up_to(remaining_quantity, contact.volume_through, quantity_step)
+        } else {
+            None
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn FillModel<I, D, BaseOrQuote>> {
+        Box::new(self.clone())
+    }
+}
+
+/// Fills only if the market traded strictly through the limit price,
+/// with at most the `participation_rate` of the volume traded through it.
+/// Market updates without volume information (e.g `Candle`) fill the remaining quantity.
+#[derive(Debug, Clone, Copy, CopyGetters)]
+pub struct VolumeCappedFill<I, const D: u8>
+where
+    I: Mon<D>,
+{
+    /// The fraction of the traded volume the order can capture.
+    #[getset(get_copy = "pub")]
+    participation_rate: Decimal<I, D>,
+}
+
+impl<I, const D: u8> VolumeCappedFill<I, D>
+where
+    I: Mon<D>,
+{
+    /// Create a new instance, where the `participation_rate` must be in the range (0, 1].
+    pub fn new(participation_rate: Decimal<I, D>) -> Result<Self, ConfigError> {
+        if participation_rate <= Decimal::ZERO || participation_rate > Decimal::ONE {
+            return Err(ConfigError::InvalidFillModel);
+        }
+        Ok(Self { participation_rate })
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> FillModel<I, D, BaseOrQuote> for VolumeCappedFill<I, D>
+where
+    I: Mon<D> + 'static,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fill_quantity(
+        &mut self,
+        remaining_quantity: BaseOrQuote,
+        contact: PriceContact<BaseOrQuote>,
+        quantity_step: BaseOrQuote,
+    ) -> Option<BaseOrQuote> {
+        if !contact.traded_through {
+            return None;
+        }
+        let cap = contact
+            .volume_through
+            .map(|volume| volume * self.participation_rate);
+        fill_up_to(remaining_quantity, cap, quantity_step)
+    }
+
+    fn clone_box(&self) -> Box<dyn FillModel<I, D, BaseOrQuote>> {
+        Box::new(*self)
+    }
+}
+
+/// Fill the resting `order` according to the `fill_model`,
+/// where `consumed_volume` tracks the volume of the market update used up by the previous fills of the same side.
+///
+/// # Returns:
+/// If filled, the filled quantity and whether the market update has no volume left to fill further orders.
+pub(crate) fn fill_with_model<I, const D: u8, BaseOrQuote, U, UserOrderIdT>(
+    fill_model: &mut dyn FillModel<I, D, BaseOrQuote>,
+    market_update: &U,
+    order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    consumed_volume: &mut BaseOrQuote,
+    quantity_step: BaseOrQuote,
+) -> Option<(BaseOrQuote, bool)>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    U: MarketUpdate<I, D, BaseOrQuote>,
+    UserOrderIdT: UserOrderId,
+{
+    let contact = market_update.price_contact(order.side(), order.limit_price())?;
+    let unconsumed = |volume: BaseOrQuote| {
+        let volume = volume - *consumed_volume;
+        if volume > BaseOrQuote::zero() {
+            volume
+        } else {
+            BaseOrQuote::zero()
+        }
+    };
+    let contact = PriceContact {
+        traded_through: contact.traded_through,
+        volume_through: contact.volume_through.map(unconsumed),
+        volume_touching: contact.volume_touching.map(unconsumed),
+    };
+
+    let filled_quantity =
+        fill_model.fill_quantity(order.remaining_quantity(), contact, quantity_step)?;
+    assert2::debug_assert!(filled_quantity > BaseOrQuote::zero());
+    assert2::debug_assert!(filled_quantity <= order.remaining_quantity());
+    *consumed_volume += filled_quantity;
+
+    let exhausted = contact
+        .volume_touching
+        .is_some_and(|volume| filled_quantity >= volume);
+    Some((filled_quantity, exhausted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    type Model = dyn FillModel<i64, 2, BaseCurrency<i64, 2>>;
+
+    fn step() -> BaseCurrency<i64, 2> {
+        BaseCurrency::new(1, 2)
+    }
+
+    fn contact(
+        traded_through: bool,
+        volume_through: Option<i64>,
+        volume_touching: Option<i64>,
+    ) -> PriceContact<BaseCurrency<i64, 2>> {
+        PriceContact {
+            traded_through,
+            volume_through: volume_through.map(|v| BaseCurrency::new(v, 0)),
+            volume_touching: volume_touching.map(|v| BaseCurrency::new(v, 0)),
+        }
+    }
+
+    #[test]
+    fn fill_model_trade_through() {
+        let mut model: Box<Model> = Box::new(TradeThrough);
+        let remaining = BaseCurrency::new(5, 0);
+        assert_eq!(
+            model.fill_quantity(remaining, contact(false, Some(0), Some(10)), step()),
+            None
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, Some(3), Some(10)), step()),
+            Some(BaseCurrency::new(3, 0))
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, None, None), step()),
+            Some(remaining)
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, Some(0), Some(0)), step()),
+            None
+        );
+    }
+
+    #[test]
+    fn fill_model_touch() {
+        let mut model: Box<Model> = Box::new(TouchFill);
+        let remaining = BaseCurrency::new(5, 0);
+        assert_eq!(
+            model.fill_quantity(remaining, contact(false, Some(0), Some(10)), step()),
+            Some(remaining)
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(false, None, None), step()),
+            Some(remaining)
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, Some(1), Some(2)), step()),
+            Some(BaseCurrency::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn fill_model_probabilistic_touch() {
+        assert_eq!(
+            ProbabilisticTouchFill::new(1.1, 0).unwrap_err(),
+            ConfigError::InvalidFillModel
+        );
+        assert_eq!(
+            ProbabilisticTouchFill::new(-0.1, 0).unwrap_err(),
+            ConfigError::InvalidFillModel
+        );
+
+        let remaining = BaseCurrency::new(5, 0);
+        let mut never: Box<Model> = Box::new(ProbabilisticTouchFill::new(0.0, 0).unwrap());
+        let mut always: Box<Model> = Box::new(ProbabilisticTouchFill::new(1.0, 0).unwrap());
+        for _ in 0..10 {
+            assert_eq!(
+                never.fill_quantity(remaining, contact(false, Some(0), Some(10)), step()),
+                None
+            );
+            assert_eq!(
+                never.fill_quantity(remaining, contact(true, Some(2), Some(10)), step()),
+                Some(BaseCurrency::new(2, 0))
+            );
+            assert_eq!(
+                always.fill_quantity(remaining, contact(false, Some(0), Some(10)), step()),
+                Some(remaining)
+            );
+        }
+
+        let mut model: Box<Model> = Box::new(ProbabilisticTouchFill::new(0.5, 42).unwrap());
+        let num_fills = (0..1000)
+            .filter(|_| {
+                model
+                    .fill_quantity(remaining, contact(false, Some(0), Some(10)), step())
+                    .is_some()
+            })
+            .count();
+        assert!((400..600).contains(&num_fills));
+
+        // Identical seeds yield identical fills.
+        let mut a: Box<Model> = Box::new(ProbabilisticTouchFill::new(0.5, 7).unwrap());
+        let mut b = a.clone();
+        for _ in 0..100 {
+            assert_eq!(
+                a.fill_quantity(remaining, contact(false, None, None), step()),
+                b.fill_quantity(remaining, contact(false, None, None), step())
+            );
+        }
+    }
+
+    #[test]
+    fn fill_model_volume_capped() {
+        assert_eq!(
+            VolumeCappedFill::<i64, 2>::new(Decimal::ZERO).unwrap_err(),
+            ConfigError::InvalidFillModel
+        );
+        assert_eq!(
+            VolumeCappedFill::<i64, 2>::new(Decimal::try_from_scaled(11, 1).unwrap()).unwrap_err(),
+            ConfigError::InvalidFillModel
+        );
+
+        let mut model: Box<Model> =
+            Box::new(VolumeCappedFill::new(Decimal::try_from_scaled(25, 2).unwrap()).unwrap());
+        let remaining = BaseCurrency::new(5, 0);
+        assert_eq!(
+            model.fill_quantity(remaining, contact(false, Some(0), Some(10)), step()),
+            None
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, Some(8), Some(10)), step()),
+            Some(BaseCurrency::new(2, 0))
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, Some(100), Some(100)), step()),
+            Some(remaining)
+        );
+        assert_eq!(
+            model.fill_quantity(remaining, contact(true, None, None), step()),
+            Some(remaining)
+        );
+    }
+
+    #[test]
+    fn fill_model_volume_capped_rounds_down_to_step() {
+        let mut model: Box<Model> =
+            Box::new(VolumeCappedFill::new(Decimal::try_from_scaled(3, 1).unwrap()).unwrap());
+        let remaining = BaseCurrency::new(5, 0);
+        // 30% of 5 is 1.5, which is not a multiple of a step of 1.
+        assert_eq!(
+            model.fill_quantity(
+                remaining,
+                contact(true, Some(5), Some(5)),
+                BaseCurrency::new(1, 0)
+            ),
+            Some(BaseCurrency::new(1, 0))
+        );
+        assert_eq!(
+            model.fill_quantity(
+                remaining,
+                contact(true, Some(5), Some(5)),
+                BaseCurrency::new(1, 1)
+            ),
+            Some(BaseCurrency::new(15, 1))
+        );
+        // Less than a step of volume does not fill.
+        assert_eq!(
+            model.fill_quantity(
+                remaining,
+                contact(true, Some(3), Some(3)),
+                BaseCurrency::new(1, 0)
+            ),
+            None
+        );
+
+        let mut model: Box<Model> = Box::new(TradeThrough);
+        let volume = PriceContact {
+            traded_through: true,
+            volume_through: Some(BaseCurrency::new(37, 2)),
+            volume_touching: Some(BaseCurrency::new(37, 2)),
+        };
+        assert_eq!(
+            model.fill_quantity(remaining, volume, BaseCurrency::new(1, 1)),
+            Some(BaseCurrency::new(3, 1))
+        );
+    }
+}
@@ -4,21 +4,37 @@
 //! lfest - leveraged futures exchange for simulated trading
 
 mod account;
+mod account_tracker;
+mod balance_ledger;
 mod config;
 mod contract_specification;
 mod exchange;
 mod expect_messages;
+mod fee_tier_tracker;
+mod fill_model;
 mod load_trades_from_csv;
+mod lot_tracker;
+mod market_data;
+mod market_impact;
 mod market_state;
 mod market_update;
+mod markout;
+mod matching_engine;
 mod mock_exchange;
+mod multi_account_exchange;
 mod order_filters;
+mod order_latency;
 pub mod order_rate_limiter;
+mod pnl_attribution;
 mod risk_engine;
+mod rng;
+mod synthetic;
 #[cfg(test)]
 mod tests;
+mod trackers;
 #[cfg(feature = "trade_aggregation")]
 mod trade_aggregation;
+mod trade_journal;
 mod types;
 mod utils;
 
@@ -44,6 +60,16 @@ pub mod prelude {
 
     pub use crate::{
         account::*,
+        account_tracker::{
+            AccountTracker,
+            EquityDenomination,
+            EquitySample,
+        },
+        balance_ledger::{
+            BalanceLedger,
+            LedgerCause,
+            LedgerEntry,
+        },
         config::Config,
         contract_specification::*,
         exchange::{
@@ -51,12 +77,57 @@ pub mod prelude {
             ForcedCancels,
             MarketOrderSettlement,
         },
+        fee_tier_tracker::FeeTierTracker,
+        fill_model::{
+            FillModel,
+            ProbabilisticTouchFill,
+            TouchFill,
+            TradeThrough,
+            VolumeCappedFill,
+        },
         leverage,
+        lot_tracker::{
+            ClosedLot,
+            CostBasisMethod,
+            Lot,
+            LotTracker,
+        },
+        market_data::*,
+        market_impact::MarketImpactModel,
         market_state::MarketState,
         market_update::*,
+        markout::{
+            MarkoutAnalytics,
+            MarkoutStats,
+        },
+        matching_engine::*,
+        multi_account_exchange::{
+            FirmId,
+            MultiAccountExchange,
+            SelfTradePrevention,
+        },
         order_filters::{
+            NotionalFilter,
             PriceFilter,
+            PriceReference,
             QuantityFilter,
+            TickRounding,
+            TickSizeTable,
+        },
+        order_latency::{
+            LatencyDistribution,
+            LatencyModel,
+            OrderActionEvent,
+            RequestId,
+        },
+        pnl_attribution::{
+            PnlAttribution,
+            TagAttribution,
+        },
+        synthetic::*,
+        trade_journal::{
+            RoundTrip,
+            TradeJournal,
         },
         types::*,
         utils::{
@@ -0,0 +1,296 @@
+//! Tracks the position as individual lots to realize profit and loss under a chosen cost-basis method.
+
+use std::collections::VecDeque;
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::{
+    EXPECT_CAPACITY,
+    account::Position,
+    types::{
+        Currency,
+        MarginCurrency,
+        Mon,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+    utils::min,
+};
+
+/// Which open lots a fill reducing the position closes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CostBasisMethod {
+    /// First in, first out: the oldest lot is closed first.
+    Fifo,
+    /// Last in, first out: the most recent lot is closed first.
+    Lifo,
+}
+
+/// An open lot, opened by a single fill increasing the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lot<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The timestamp of the fill opening the lot.
+    pub entry_ts_ns: TimestampNs,
+    /// The remaining quantity of the lot, negative if short.
+    pub quantity: BaseOrQuote,
+    /// The price of the fill opening the lot.
+    pub entry_price: QuoteCurrency<I, D>,
+}
+
+/// The part of a `Lot` closed by a single fill reducing the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosedLot<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The timestamp of the fill opening the lot.
+    pub entry_ts_ns: TimestampNs,
+    /// The timestamp of the fill closing it.
+    pub exit_ts_ns: TimestampNs,
+    /// The closed quantity, negative if the lot was short.
+    pub quantity: BaseOrQuote,
+    /// The price of the fill opening the lot.
+    pub entry_price: QuoteCurrency<I, D>,
+    /// The price of the fill closing it.
+    pub exit_price: QuoteCurrency<I, D>,
+    /// The realized profit and loss, excluding fees.
+    pub realized_pnl: BaseOrQuote::PairedCurrency,
+}
+
+/// Tracks the position as individual lots and realizes the profit and loss of each fill reducing it
+/// under a `CostBasisMethod`, e.g. for tax and audit reporting.
+///
+/// The `Position` of the account realizes profit and loss on the average cost basis,
+/// which the `LotTracker` leaves unchanged, so the margin accounting does not depend on the method.
+/// The totals of both generally differ, even once the position is flat again:
+/// the average entry price of the `Position` is rounded to the decimal precision `D`,
+/// and the profit and loss of inverse futures is not linear in the entry price it averages.
+/// Set it with `Exchange::set_lot_tracker`, after which every fill, including liquidations, is recorded.
+/// A position which is already open when the tracker is set becomes a single lot at its entry price.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct LotTracker<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Which open lots a reducing fill closes first.
+    #[getset(get_copy = "pub")]
+    method: CostBasisMethod,
+
+    /// The sum of the realized profit and loss of all closed lots.
+    #[getset(get_copy = "pub")]
+    realized_pnl: BaseOrQuote::PairedCurrency,
+
+    /// The open lots from oldest to most recent, all on the same side of the position.
+    open_lots: VecDeque<Lot<I, D, BaseOrQuote>>,
+
+    closed_lots: Vec<ClosedLot<I, D, BaseOrQuote>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> LotTracker<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// Create a new instance closing lots according to `method`.
+    pub fn new(method: CostBasisMethod) -> Self {
+        Self {
+            method,
+            realized_pnl: Zero::zero(),
+            open_lots: VecDeque::with_capacity(16),
+            closed_lots: Vec::with_capacity(64),
+        }
+    }
+
+    /// The open lots from oldest to most recent.
+    #[inline]
+    pub fn open_lots(&self) -> &VecDeque<Lot<I, D, BaseOrQuote>> {
+        &self.open_lots
+    }
+
+    /// The closed lots in the order they were closed.
+    /// A fill closing several lots appends one `ClosedLot` per lot.
+    #[inline]
+    pub fn closed_lots(&self) -> &[ClosedLot<I, D, BaseOrQuote>] {
+        &self.closed_lots
+    }
+
+    /// The total quantity of the open lots, negative if short, which equals the quantity of the `Position`.
+    pub fn open_quantity(&self) -> BaseOrQuote {
+        self.open_lots
+            .iter()
+            .fold(BaseOrQuote::zero(), |acc, lot| acc + lot.quantity)
+    }
+
+    /// Start tracking the lots of `position`, which becomes a single lot if it is open.
+    pub(crate) fn open(&mut self, position: &Position<I, D, BaseOrQuote>, ts_ns: TimestampNs) {
+        self.open_lots.clear();
+        if !position.quantity().is_zero() {
+            self.open_lots.push_back(Lot {
+                entry_ts_ns: ts_ns,
+                quantity: position.quantity(),
+                entry_price: position.entry_price(),
+            });
+        }
+    }
+
+    /// Record a fill of `quantity` at `price`, closing the open lots chosen by the `CostBasisMethod`
+    /// and opening a new lot with any remaining quantity.
+    pub(crate) fn record_fill(
+        &mut self,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+        ts_ns: TimestampNs,
+    ) {
+        assert2::debug_assert!(quantity > BaseOrQuote::zero());
+        let mut remaining = quantity;
+        while !remaining.is_zero() {
+            let lot = match self.method {
+                CostBasisMethod::Fifo => self.open_lots.front_mut(),
+                CostBasisMethod::Lifo => self.open_lots.back_mut(),
+            };
+            let Some(lot) = lot else {
+                break;
+            };
+            let is_long = lot.quantity > BaseOrQuote::zero();
+            if is_long == (side == Side::Buy) {
+                // The fill increases the position.
+                break;
+            }
+            let closed = min(remaining, lot.quantity.abs());
+            let (closed_quantity, realized_pnl) = if is_long {
+                (
+                    closed,
+                    BaseOrQuote::PairedCurrency::pnl(lot.entry_price, price, closed),
+                )
+            } else {
+                (
+                    -closed,
+                    -BaseOrQuote::PairedCurrency::pnl(lot.entry_price, price, closed),
+                )
+            };
+            lot.quantity -= closed_quantity;
+            remaining -= closed;
+            self.realized_pnl += realized_pnl;
+            // Grows with the number of fills reducing the position.
+            self.closed_lots.reserve(1);
+            self.closed_lots
+                .push_within_capacity(ClosedLot {
+                    entry_ts_ns: lot.entry_ts_ns,
+                    exit_ts_ns: ts_ns,
+                    quantity: closed_quantity,
+                    entry_price: lot.entry_price,
+                    exit_price: price,
+                    realized_pnl,
+                })
+                .expect(EXPECT_CAPACITY);
+            if lot.quantity.is_zero() {
+                match self.method {
+                    CostBasisMethod::Fifo => self.open_lots.pop_front(),
+                    CostBasisMethod::Lifo => self.open_lots.pop_back(),
+                };
+            }
+        }
+        if !remaining.is_zero() {
+            let quantity = match side {
+                Side::Buy => remaining,
+                Side::Sell => -remaining,
+            };
+            self.open_lots.push_back(Lot {
+                entry_ts_ns: ts_ns,
+                quantity,
+                entry_price: price,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_matrix;
+
+    use super::*;
+    use crate::{
+        account::Balances,
+        types::BaseCurrency,
+    };
+
+    fn fills() -> [(Side, i64, i64); 4] {
+        [
+            (Side::Buy, 1, 100),
+            (Side::Buy, 1, 110),
+            (Side::Sell, 1, 120),
+            (Side::Sell, 2, 130),
+        ]
+    }
+
+    #[test_matrix([CostBasisMethod::Fifo, CostBasisMethod::Lifo])]
+    fn lot_tracker_matches_average_cost_once_closed(method: CostBasisMethod) {
+        // The entry prices of these linear fills average exactly, so the rounding of the `Position` does not show.
+        let mut tracker = LotTracker::<i64, 5, BaseCurrency<i64, 5>>::new(method);
+        let mut position = Position::default();
+        let mut balances = Balances::new(QuoteCurrency::new(10_000, 0));
+        let mut realized_pnl = QuoteCurrency::zero();
+        for (i, (side, qty, price)) in fills().into_iter().enumerate() {
+            let qty = BaseCurrency::new(qty, 0);
+            let price = QuoteCurrency::new(price, 0);
+            tracker.record_fill(side, qty, price, i64::try_from(i).unwrap().into());
+            realized_pnl += position.change(qty, price, side, &mut balances);
+            assert_eq!(tracker.open_quantity(), position.quantity());
+        }
+        // The last sell reversed the position into a short lot.
+        assert_eq!(
+            tracker.open_lots().iter().copied().collect::<Vec<_>>(),
+            vec![Lot {
+                entry_ts_ns: 3.into(),
+                quantity: BaseCurrency::new(-1, 0),
+                entry_price: QuoteCurrency::new(130, 0),
+            }]
+        );
+        assert_eq!(tracker.realized_pnl(), realized_pnl);
+        assert_eq!(tracker.realized_pnl(), QuoteCurrency::new(40, 0));
+    }
+
+    #[test]
+    fn lot_tracker_fifo_and_lifo() {
+        let mut fifo = LotTracker::<i64, 5, BaseCurrency<i64, 5>>::new(CostBasisMethod::Fifo);
+        let mut lifo = LotTracker::new(CostBasisMethod::Lifo);
+        for (i, (side, qty, price)) in fills().into_iter().take(3).enumerate() {
+            let ts = i64::try_from(i).unwrap().into();
+            let qty = BaseCurrency::new(qty, 0);
+            let price = QuoteCurrency::new(price, 0);
+            fifo.record_fill(side, qty, price, ts);
+            lifo.record_fill(side, qty, price, ts);
+        }
+
+        assert_eq!(fifo.realized_pnl(), QuoteCurrency::new(20, 0));
+        assert_eq!(
+            fifo.closed_lots(),
+            &[ClosedLot {
+                entry_ts_ns: 0.into(),
+                exit_ts_ns: 2.into(),
+                quantity: BaseCurrency::new(1, 0),
+                entry_price: QuoteCurrency::new(100, 0),
+                exit_price: QuoteCurrency::new(120, 0),
+                realized_pnl: QuoteCurrency::new(20, 0),
+            }]
+        );
+        assert_eq!(fifo.open_lots()[0].entry_price, QuoteCurrency::new(110, 0));
+
+        assert_eq!(lifo.realized_pnl(), QuoteCurrency::new(10, 0));
+        assert_eq!(
+            lifo.closed_lots()[0].entry_price,
+            QuoteCurrency::new(110, 0)
+        );
+        assert_eq!(lifo.open_lots()[0].entry_price, QuoteCurrency::new(100, 0));
+    }
+}
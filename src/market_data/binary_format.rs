@@ -0,0 +1,474 @@
+//! A compact, fixed-width binary encoding of market updates.
+//!
+//! A file starts with a 16 byte header, followed by records of a single type:
+//!
+//! | bytes  | content                                                        |
+//! |--------|----------------------------------------------------------------|
+//! | 0..4   | the magic bytes `LFMD`                                         |
+//! | 4      | the format version                                             |
+//! | 5      | the record type, see `BinaryRecordKind`                        |
+//! | 6      | the decimal precision `D`                                      |
+//! | 7      | the width of the integer type `I` in bytes                     |
+//! | 8      | the denomination of trade quantities, `0` if there are none    |
+//! | 9..16  | reserved, zeroed                                               |
+//!
+//! All integers are little endian. Prices and quantities are stored as the scaled
+//! integer of their `Decimal`, so no precision is lost and decoding needs no parsing.
+
+use const_decimal::Decimal;
+
+use crate::{
+    market_update::{
+        Bba,
+        Candle,
+        Trade,
+    },
+    types::{
+        BaseCurrency,
+        Currency,
+        MarketDataError,
+        Mon,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+};
+
+pub(crate) const MAGIC: [u8; 4] = *b"LFMD";
+pub(crate) const VERSION: u8 = 1;
+pub(crate) const HEADER_LEN: usize = 16;
+const TIMESTAMP_WIDTH: usize = size_of::<i64>();
+
+/// An integer type which can be stored in the binary market data format.
+pub trait BinaryInteger: Copy {
+    /// The number of bytes of the little endian representation.
+    const WIDTH: usize;
+
+    /// Write the little endian bytes into `buf`, which has a length of `WIDTH`.
+    fn write_le(self, buf: &mut [u8]);
+
+    /// Read the integer from the little endian bytes in `buf`, which has a length of `WIDTH`.
+    fn read_le(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_binary_integer {
+    ($($int:ty),*) => {
+        $(
+            impl BinaryInteger for $int {
+                const WIDTH: usize = size_of::<$int>();
+
+                #[inline(always)]
+                fn write_le(self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline(always)]
+                fn read_le(buf: &[u8]) -> Self {
+                    Self::from_le_bytes(buf.try_into().expect("The record width is fixed"))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_integer!(i32, i64, i128);
+
+/// The currency in which the quantity of a `Trade` is denoted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Denomination {
+    /// Denoted in the `BaseCurrency`, e.g. for linear contracts.
+    Base = 1,
+    /// Denoted in the `QuoteCurrency`, e.g. for inverse contracts.
+    Quote = 2,
+}
+
+/// A currency with a known `Denomination`, so it can be recorded in the header.
+pub trait Denominated {
+    /// The denomination of the currency.
+    const DENOMINATION: Denomination;
+}
+
+impl<I: Mon<D>, const D: u8> Denominated for BaseCurrency<I, D> {
+    const DENOMINATION: Denomination = Denomination::Base;
+}
+
+impl<I: Mon<D>, const D: u8> Denominated for QuoteCurrency<I, D> {
+    const DENOMINATION: Denomination = Denomination::Quote;
+}
+
+/// The type of the records in a binary market data file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BinaryRecordKind {
+    /// `Trade` records.
+    Trade = 1,
+    /// `Bba` records.
+    Bba = 2,
+    /// `Candle` records.
+    Candle = 3,
+}
+
+/// A market update with a fixed-width binary encoding.
+pub trait BinaryRecord: Sized {
+    /// The type of the records.
+    const KIND: BinaryRecordKind;
+
+    /// The decimal precision of the prices and quantities.
+    const DECIMALS: u8;
+
+    /// The width of the integers of the prices and quantities in bytes.
+    const INTEGER_WIDTH: usize;
+
+    /// The denomination of the quantity, if there is one.
+    const DENOMINATION: Option<Denomination>;
+
+    /// The number of bytes of a single record.
+    const WIDTH: usize;
+
+    /// Write the record into `buf`, which has a length of `WIDTH`.
+    fn encode(&self, buf: &mut [u8]);
+
+    /// Read a record from `buf`, which has a length of `WIDTH`, where `record` is its index for the error.
+    /// Errors if a field holds a byte pattern which is not valid for it, e.g. the side of a `Trade`.
+    fn decode(buf: &[u8], record: u64) -> Result<Self, MarketDataError>;
+}
+
+/// Encodes the fields of a record one after another.
+struct Encoder<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    #[inline(always)]
+    fn int<T: BinaryInteger>(&mut self, value: T) {
+        value.write_le(&mut self.buf[self.pos..self.pos + T::WIDTH]);
+        self.pos += T::WIDTH;
+    }
+
+    #[inline(always)]
+    fn byte(&mut self, value: u8) {
+        self.buf[self.pos] = value;
+        self.pos += 1;
+    }
+
+    #[inline(always)]
+    fn timestamp(&mut self, ts: TimestampNs) {
+        self.int(i64::from(ts));
+    }
+
+    #[inline(always)]
+    fn decimal<I, const D: u8>(&mut self, value: Decimal<I, D>)
+    where
+        I: Mon<D> + BinaryInteger,
+    {
+        self.int(value.0);
+    }
+}
+
+/// Decodes the fields of a record one after another.
+struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    #[inline(always)]
+    fn int<T: BinaryInteger>(&mut self) -> T {
+        let value = T::read_le(&self.buf[self.pos..self.pos + T::WIDTH]);
+        self.pos += T::WIDTH;
+        value
+    }
+
+    #[inline(always)]
+    fn byte(&mut self) -> u8 {
+        let value = self.buf[self.pos];
+        self.pos += 1;
+        value
+    }
+
+    #[inline(always)]
+    fn timestamp(&mut self) -> TimestampNs {
+        TimestampNs::from(self.int::<i64>())
+    }
+
+    #[inline(always)]
+    fn decimal<I, const D: u8>(&mut self) -> Decimal<I, D>
+    where
+        I: Mon<D> + BinaryInteger,
+    {
+        Decimal(self.int())
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> BinaryRecord for Trade<I, D, BaseOrQuote>
+where
+    I: Mon<D> + BinaryInteger,
+    BaseOrQuote: Currency<I, D> + Denominated,
+{
+    const KIND: BinaryRecordKind = BinaryRecordKind::Trade;
+    const DECIMALS: u8 = D;
+    const INTEGER_WIDTH: usize = I::WIDTH;
+    const DENOMINATION: Option<Denomination> = Some(BaseOrQuote::DENOMINATION);
+    // The side is a single byte.
+    const WIDTH: usize = TIMESTAMP_WIDTH + 2 * I::WIDTH + 1;
+
+    #[inline]
+    fn encode(&self, buf: &mut [u8]) {
+        let mut enc = Encoder::new(buf);
+        enc.timestamp(self.timestamp_exchange_ns);
+        enc.decimal(*self.price.as_ref());
+        enc.decimal(*self.quantity.as_ref());
+        enc.byte(match self.side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        });
+    }
+
+    #[inline]
+    fn decode(buf: &[u8], record: u64) -> Result<Self, MarketDataError> {
+        let mut dec = Decoder::new(buf);
+        let timestamp_exchange_ns = dec.timestamp();
+        let price = QuoteCurrency::from(dec.decimal());
+        let quantity = BaseOrQuote::from(dec.decimal());
+        let side = match dec.byte() {
+            0 => Side::Buy,
+            1 => Side::Sell,
+            found => {
+                return Err(MarketDataError::InvalidBinaryRecord {
+                    record,
+                    field: "side",
+                    found,
+                });
+            }
+        };
+        Ok(Self {
+            timestamp_exchange_ns,
+            price,
+            quantity,
+            side,
+        })
+    }
+}
+
+impl<I, const D: u8> BinaryRecord for Bba<I, D>
+where
+    I: Mon<D> + BinaryInteger,
+{
+    const KIND: BinaryRecordKind = BinaryRecordKind::Bba;
+    const DECIMALS: u8 = D;
+    const INTEGER_WIDTH: usize = I::WIDTH;
+    const DENOMINATION: Option<Denomination> = None;
+    const WIDTH: usize = TIMESTAMP_WIDTH + 2 * I::WIDTH;
+
+    #[inline]
+    fn encode(&self, buf: &mut [u8]) {
+        let mut enc = Encoder::new(buf);
+        enc.timestamp(self.timestamp_exchange_ns);
+        enc.decimal(*self.bid.as_ref());
+        enc.decimal(*self.ask.as_ref());
+    }
+
+    #[inline]
+    fn decode(buf: &[u8], _record: u64) -> Result<Self, MarketDataError> {
+        let mut dec = Decoder::new(buf);
+        Ok(Self {
+            timestamp_exchange_ns: dec.timestamp(),
+            bid: QuoteCurrency::from(dec.decimal()),
+            ask: QuoteCurrency::from(dec.decimal()),
+        })
+    }
+}
+
+impl<I, const D: u8> BinaryRecord for Candle<I, D>
+where
+    I: Mon<D> + BinaryInteger,
+{
+    const KIND: BinaryRecordKind = BinaryRecordKind::Candle;
+    const DECIMALS: u8 = D;
+    const INTEGER_WIDTH: usize = I::WIDTH;
+    const DENOMINATION: Option<Denomination> = None;
+    const WIDTH: usize = TIMESTAMP_WIDTH + 4 * I::WIDTH;
+
+    #[inline]
+    fn encode(&self, buf: &mut [u8]) {
+        let mut enc = Encoder::new(buf);
+        enc.timestamp(self.timestamp_exchange_ns());
+        enc.decimal(*self.bid().as_ref());
+        enc.decimal(*self.ask().as_ref());
+        enc.decimal(*self.low().as_ref());
+        enc.decimal(*self.high().as_ref());
+    }
+
+    #[inline]
+    fn decode(buf: &[u8], _record: u64) -> Result<Self, MarketDataError> {
+        let mut dec = Decoder::new(buf);
+        // The candle was validated by its constructor before it was written.
+        Ok(Candle::builder()
+            .timestamp_exchange_ns(dec.timestamp())
+            .bid(QuoteCurrency::from(dec.decimal()))
+            .ask(QuoteCurrency::from(dec.decimal()))
+            .low(QuoteCurrency::from(dec.decimal()))
+            .high(QuoteCurrency::from(dec.decimal()))
+            .build())
+    }
+}
+
+/// The header of a binary market data file containing records of type `T`.
+pub(crate) fn encode_header<T: BinaryRecord>() -> [u8; HEADER_LEN] {
+    let mut header = [0; HEADER_LEN];
+    header[..4].copy_from_slice(&MAGIC);
+    header[4] = VERSION;
+    header[5] = T::KIND as u8;
+    header[6] = T::DECIMALS;
+    header[7] = u8::try_from(T::INTEGER_WIDTH).expect("Integers are at most 16 bytes wide");
+    header[8] = T::DENOMINATION.map_or(0, |denomination| denomination as u8);
+    header
+}
+
+/// Make sure the `header` describes records of type `T`.
+pub(crate) fn validate_header<T: BinaryRecord>(header: &[u8]) -> Result<(), MarketDataError> {
+    if header.len() < HEADER_LEN || header[..4] != MAGIC {
+        return Err(MarketDataError::NotBinaryMarketData);
+    }
+    if header[4] != VERSION {
+        return Err(MarketDataError::UnsupportedBinaryVersion(header[4]));
+    }
+    let expected = encode_header::<T>();
+    for (idx, field) in [
+        (5, "record type"),
+        (6, "decimals"),
+        (7, "integer width"),
+        (8, "denomination"),
+    ] {
+        if header[idx] != expected[idx] {
+            return Err(MarketDataError::BinaryHeaderMismatch {
+                field,
+                expected: expected[idx],
+                found: header[idx],
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DECIMALS;
+
+    fn roundtrip<T: BinaryRecord + std::fmt::Debug + PartialEq>(record: T) {
+        let mut buf = vec![0; T::WIDTH];
+        record.encode(&mut buf);
+        assert_eq!(T::decode(&buf, 0), Ok(record));
+    }
+
+    #[test]
+    fn binary_record_width() {
+        assert_eq!(
+            <Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>> as BinaryRecord>::WIDTH,
+            25
+        );
+        assert_eq!(<Bba<i32, DECIMALS> as BinaryRecord>::WIDTH, 16);
+        assert_eq!(<Candle<i128, DECIMALS> as BinaryRecord>::WIDTH, 72);
+    }
+
+    #[test]
+    fn binary_record_roundtrip() {
+        roundtrip(Trade {
+            timestamp_exchange_ns: 1_700_000_000_000_000_000.into(),
+            price: QuoteCurrency::<i64, DECIMALS>::new(300001, 1),
+            quantity: BaseCurrency::new(2, 3),
+            side: Side::Sell,
+        });
+        roundtrip(Trade {
+            timestamp_exchange_ns: (-1).into(),
+            price: QuoteCurrency::<i32, DECIMALS>::new(100, 0),
+            quantity: QuoteCurrency::new(10, 0),
+            side: Side::Buy,
+        });
+        roundtrip(Bba {
+            bid: QuoteCurrency::<i128, DECIMALS>::new(100, 0),
+            ask: QuoteCurrency::new(1005, 1),
+            timestamp_exchange_ns: 5.into(),
+        });
+        roundtrip(
+            Candle::<i64, DECIMALS>::builder()
+                .bid(QuoteCurrency::new(100, 0))
+                .ask(QuoteCurrency::new(101, 0))
+                .low(QuoteCurrency::new(99, 0))
+                .high(QuoteCurrency::new(102, 0))
+                .timestamp_exchange_ns(6.into())
+                .build(),
+        );
+    }
+
+    #[test]
+    fn binary_trade_invalid_side() {
+        type BaseTrade = Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>;
+        let mut buf = vec![0; BaseTrade::WIDTH];
+        buf[BaseTrade::WIDTH - 1] = 2;
+        assert_eq!(
+            BaseTrade::decode(&buf, 7),
+            Err(MarketDataError::InvalidBinaryRecord {
+                record: 7,
+                field: "side",
+                found: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn binary_header() {
+        type QuoteTrade = Trade<i64, DECIMALS, QuoteCurrency<i64, DECIMALS>>;
+        let header = encode_header::<QuoteTrade>();
+        assert_eq!(header, [
+            b'L', b'F', b'M', b'D', 1, 1, 5, 8, 2, 0, 0, 0, 0, 0, 0, 0
+        ]);
+        assert_eq!(validate_header::<QuoteTrade>(&header), Ok(()));
+
+        assert_eq!(
+            validate_header::<Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>>(&header),
+            Err(MarketDataError::BinaryHeaderMismatch {
+                field: "denomination",
+                expected: 1,
+                found: 2,
+            })
+        );
+        assert_eq!(
+            validate_header::<Trade<i64, 2, QuoteCurrency<i64, 2>>>(&header),
+            Err(MarketDataError::BinaryHeaderMismatch {
+                field: "decimals",
+                expected: 2,
+                found: 5,
+            })
+        );
+        assert_eq!(
+            validate_header::<Bba<i64, DECIMALS>>(&header),
+            Err(MarketDataError::BinaryHeaderMismatch {
+                field: "record type",
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            validate_header::<QuoteTrade>(&header[..8]),
+            Err(MarketDataError::NotBinaryMarketData)
+        );
+        let mut future = header;
+        future[4] = 2;
+        assert_eq!(
+            validate_header::<QuoteTrade>(&future),
+            Err(MarketDataError::UnsupportedBinaryVersion(2))
+        );
+    }
+}
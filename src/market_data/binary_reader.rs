@@ -0,0 +1,280 @@
+//! Replays binary market data from memory, e.g. from a memory-mapped file.
+
+use super::binary_format::{
+    BinaryRecord,
+    HEADER_LEN,
+    validate_header,
+};
+use crate::types::MarketDataError;
+
+/// A memory-mapped file in the binary market data format, containing records of type `T`.
+/// Requires the `mmap` feature.
+///
+/// Opening the file only validates its header against `T` and its length,
+/// the fields of the records are validated as they are decoded, e.g. the side of a `Trade`.
+/// The operating system pages in the records as they are read and keeps them cached,
+/// so replaying the same file many times is cheap.
+/// Records are decoded straight from the mapped bytes without any parsing or allocation.
+///
+/// The file must not be modified or truncated while it is mapped.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct BinaryMarketData<T>
+where
+    T: BinaryRecord,
+{
+    mmap: memmap2::Mmap,
+    _record: std::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "mmap")]
+impl<T> BinaryMarketData<T>
+where
+    T: BinaryRecord,
+{
+    /// Map the file at `path` into memory.
+    /// Errors if its header does not match `T` or it ends with a truncated record.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, MarketDataError> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: `Mmap::map` is unsafe because the mapped bytes change if another process modifies
+        // the file, breaking the immutability of the `&[u8]` handed out below,
+        // and reading them faults if the file gets truncated.
+        // The mapping is read only and the file is documented to not be modified or truncated while mapped,
+        // which the caller has to uphold. The bytes are never trusted beyond that,
+        // every record is validated as it is decoded.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        BinaryRecords::<T>::from_bytes(&mmap)?;
+        Ok(Self {
+            mmap,
+            _record: std::marker::PhantomData,
+        })
+    }
+
+    /// The number of records.
+    #[inline]
+    pub fn len(&self) -> usize {
+        (self.mmap.len() - HEADER_LEN) / T::WIDTH
+    }
+
+    /// Whether the file contains no records.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The record at `idx`, if there is one.
+    /// Errors if the record is invalid.
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<Result<T, MarketDataError>> {
+        let start = HEADER_LEN + idx.checked_mul(T::WIDTH)?;
+        self.mmap
+            .get(start..start.checked_add(T::WIDTH)?)
+            .map(|buf| T::decode(buf, idx as u64))
+    }
+
+    /// Iterate over all records in the order they were written,
+    /// e.g. to pass them into `Exchange::update_state`.
+    #[inline]
+    pub fn iter(&self) -> BinaryRecords<'_, T> {
+        BinaryRecords::new(&self.mmap[HEADER_LEN..])
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<'a, T> IntoIterator for &'a BinaryMarketData<T>
+where
+    T: BinaryRecord,
+{
+    type Item = Result<T, MarketDataError>;
+    type IntoIter = BinaryRecords<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator decoding the records of type `T` from binary market data,
+/// yielding an error for each invalid record.
+#[derive(Debug, Clone)]
+pub struct BinaryRecords<'a, T>
+where
+    T: BinaryRecord,
+{
+    chunks: std::slice::ChunksExact<'a, u8>,
+    /// The index of the record `chunks` yields next from the front.
+    front: u64,
+    _record: std::marker::PhantomData<T>,
+}
+
+impl<'a, T> BinaryRecords<'a, T>
+where
+    T: BinaryRecord,
+{
+    /// Iterate over the records of binary market data in memory, including its header.
+    /// Errors if the header does not match `T` or the data ends with a truncated record.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, MarketDataError> {
+        validate_header::<T>(bytes)?;
+        let records = &bytes[HEADER_LEN..];
+        let trailing = records.len() % T::WIDTH;
+        if trailing != 0 {
+            return Err(MarketDataError::TruncatedBinaryRecord { bytes: trailing });
+        }
+        Ok(Self::new(records))
+    }
+
+    fn new(records: &'a [u8]) -> Self {
+        Self {
+            chunks: records.chunks_exact(T::WIDTH),
+            front: 0,
+            _record: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> Iterator for BinaryRecords<'_, T>
+where
+    T: BinaryRecord,
+{
+    type Item = Result<T, MarketDataError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let buf = self.chunks.next()?;
+        let record = self.front;
+        self.front += 1;
+        Some(T::decode(buf, record))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let buf = self.chunks.nth(n);
+        let record = self.front + n as u64;
+        self.front = record + 1;
+        buf.map(|buf| T::decode(buf, record))
+    }
+}
+
+impl<T> DoubleEndedIterator for BinaryRecords<'_, T>
+where
+    T: BinaryRecord,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let buf = self.chunks.next_back()?;
+        // The remaining records are the ones between the front and the one just taken from the back.
+        Some(T::decode(buf, self.front + self.chunks.len() as u64))
+    }
+}
+
+impl<T> ExactSizeIterator for BinaryRecords<'_, T> where T: BinaryRecord {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DECIMALS,
+        market_data::BinaryMarketDataWriter,
+        market_update::{
+            Bba,
+            Trade,
+        },
+        types::{
+            BaseCurrency,
+            QuoteCurrency,
+            Side,
+        },
+    };
+
+    type BaseTrade = Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>;
+
+    fn trades() -> Vec<BaseTrade> {
+        (0..10)
+            .map(|i| Trade {
+                timestamp_exchange_ns: i.into(),
+                price: QuoteCurrency::new(100 + i, 0),
+                quantity: BaseCurrency::new(i + 1, 2),
+                side: if i % 2 == 0 { Side::Buy } else { Side::Sell },
+            })
+            .collect()
+    }
+
+    fn encoded(trades: &[BaseTrade]) -> Vec<u8> {
+        let mut writer = BinaryMarketDataWriter::new(Vec::with_capacity(256)).unwrap();
+        writer.write_all(trades).unwrap();
+        assert_eq!(writer.records(), trades.len() as u64);
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn binary_records_from_bytes() {
+        let trades = trades();
+        let bytes = encoded(&trades);
+        assert_eq!(bytes.len(), HEADER_LEN + 10 * BaseTrade::WIDTH);
+
+        let records = BinaryRecords::<BaseTrade>::from_bytes(&bytes).unwrap();
+        assert_eq!(records.len(), 10);
+        assert_eq!(
+            records.clone().collect::<Result<Vec<_>, _>>(),
+            Ok(trades.clone())
+        );
+        assert_eq!(records.clone().rev().next(), Some(Ok(trades[9])));
+        assert_eq!(records.clone().nth(4), Some(Ok(trades[4])));
+
+        assert_eq!(
+            BinaryRecords::<BaseTrade>::from_bytes(&bytes[..bytes.len() - 3]).map(|_| ()),
+            Err(MarketDataError::TruncatedBinaryRecord {
+                bytes: BaseTrade::WIDTH - 3
+            })
+        );
+        assert!(BinaryRecords::<Bba<i64, DECIMALS>>::from_bytes(&bytes).is_err());
+
+        // An invalid record is only reported once it is decoded, from either end.
+        let mut corrupted = bytes.clone();
+        corrupted[HEADER_LEN + 4 * BaseTrade::WIDTH - 1] = 7;
+        let invalid = Err(MarketDataError::InvalidBinaryRecord {
+            record: 3,
+            field: "side",
+            found: 7,
+        });
+        let mut records = BinaryRecords::<BaseTrade>::from_bytes(&corrupted).unwrap();
+        assert_eq!(records.nth(2), Some(Ok(trades[2])));
+        assert_eq!(records.next(), Some(invalid.clone()));
+        assert_eq!(records.next(), Some(Ok(trades[4])));
+        let mut records = BinaryRecords::<BaseTrade>::from_bytes(&corrupted).unwrap();
+        assert_eq!(records.by_ref().rev().nth(5), Some(Ok(trades[4])));
+        assert_eq!(records.next_back(), Some(invalid));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn binary_market_data_mmap() {
+        let path = std::env::temp_dir().join("lfest_binary_market_data_mmap.bin");
+        let trades = trades();
+        let mut writer = BinaryMarketDataWriter::create(&path).unwrap();
+        writer.write_all(&trades).unwrap();
+        writer.finish().unwrap();
+
+        let data = BinaryMarketData::<BaseTrade>::open(&path).unwrap();
+        assert_eq!(data.len(), 10);
+        assert!(!data.is_empty());
+        assert_eq!(data.get(3), Some(Ok(trades[3])));
+        assert_eq!(data.get(10), None);
+        // Replaying the same data multiple times.
+        for _ in 0..2 {
+            assert_eq!(
+                data.iter().collect::<Result<Vec<_>, _>>(),
+                Ok(trades.clone())
+            );
+        }
+        assert!(
+            BinaryMarketData::<Trade<i64, DECIMALS, QuoteCurrency<i64, DECIMALS>>>::open(&path)
+                .is_err()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
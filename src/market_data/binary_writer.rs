@@ -0,0 +1,84 @@
+//! Writes market updates in the binary market data format.
+
+use std::io::Write;
+
+use super::binary_format::{
+    BinaryRecord,
+    encode_header,
+};
+use crate::types::MarketDataError;
+
+/// Writes records of type `T`, e.g. `Trade`s read once from a csv file,
+/// so they can be replayed many times with `BinaryRecords` or a `BinaryMarketData` reader.
+#[derive(Debug)]
+pub struct BinaryMarketDataWriter<W, T>
+where
+    W: Write,
+    T: BinaryRecord,
+{
+    writer: W,
+    buf: Box<[u8]>,
+    records: u64,
+    _record: std::marker::PhantomData<T>,
+}
+
+impl<T> BinaryMarketDataWriter<std::io::BufWriter<std::fs::File>, T>
+where
+    T: BinaryRecord,
+{
+    /// Create the file at `path`, truncating it if it exists.
+    pub fn create(path: impl AsRef<std::path::Path>) -> Result<Self, MarketDataError> {
+        Self::new(std::io::BufWriter::new(std::fs::File::create(path)?))
+    }
+}
+
+impl<W, T> BinaryMarketDataWriter<W, T>
+where
+    W: Write,
+    T: BinaryRecord,
+{
+    /// Write the header to `writer`, which should be buffered.
+    pub fn new(mut writer: W) -> Result<Self, MarketDataError> {
+        writer.write_all(&encode_header::<T>())?;
+        Ok(Self {
+            writer,
+            buf: vec![0; T::WIDTH].into_boxed_slice(),
+            records: 0,
+            _record: std::marker::PhantomData,
+        })
+    }
+
+    /// Append a single record.
+    /// Records are replayed in the order they are written, so they should be sorted by time.
+    pub fn write(&mut self, record: &T) -> Result<(), MarketDataError> {
+        record.encode(&mut self.buf);
+        self.writer.write_all(&self.buf)?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Append all `records`, e.g. from a `CsvTradeReader` after handling its errors.
+    pub fn write_all<'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a T>,
+    ) -> Result<(), MarketDataError>
+    where
+        T: 'a,
+    {
+        records
+            .into_iter()
+            .try_for_each(|record| self.write(record))
+    }
+
+    /// The number of records written so far.
+    #[inline]
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Flush the written records and return the inner writer.
+    pub fn finish(mut self) -> Result<W, MarketDataError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
@@ -0,0 +1,361 @@
+//! The building blocks shared by the csv readers of market data.
+
+use crate::types::{
+    MarketDataError,
+    TimestampNs,
+};
+
+/// Refers to a column of a csv file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// The column with this name in the header.
+    Name(String),
+    /// The column at this zero based index.
+    Index(usize),
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<usize> for CsvColumn {
+    fn from(idx: usize) -> Self {
+        Self::Index(idx)
+    }
+}
+
+/// The unit of the timestamps in the market data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampUnit {
+    /// Seconds since the unix epoch.
+    Seconds,
+    /// Milliseconds since the unix epoch.
+    #[default]
+    Milliseconds,
+    /// Microseconds since the unix epoch.
+    Microseconds,
+    /// Nanoseconds since the unix epoch.
+    Nanoseconds,
+}
+
+impl TimestampUnit {
+    /// The number of nanoseconds in one unit.
+    #[inline]
+    pub fn nanos_per_unit(self) -> i64 {
+        match self {
+            Self::Seconds => 1_000_000_000,
+            Self::Milliseconds => 1_000_000,
+            Self::Microseconds => 1_000,
+            Self::Nanoseconds => 1,
+        }
+    }
+
+    /// Convert a `timestamp` of this unit into a `TimestampNs`, if it does not overflow.
+    #[inline]
+    pub fn to_timestamp_ns(self, timestamp: i64) -> Option<TimestampNs> {
+        timestamp
+            .checked_mul(self.nanos_per_unit())
+            .map(TimestampNs::from)
+    }
+}
+
+/// How the timestamps are written in the market data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// A number of `TimestampUnit` since the unix epoch, optionally with a fractional part, e.g. `1585180700.0647`.
+    /// Digits beyond nanosecond precision are truncated.
+    Unix(TimestampUnit),
+    /// A UTC date time like `2014-11-22T03:33:14.372478Z`.
+    /// The date and time may also be separated by a space or a `D` as in the BitMEX dumps,
+    /// and the trailing `Z` is optional.
+    DateTime,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self::Unix(TimestampUnit::default())
+    }
+}
+
+impl From<TimestampUnit> for TimestampFormat {
+    fn from(unit: TimestampUnit) -> Self {
+        Self::Unix(unit)
+    }
+}
+
+impl TimestampFormat {
+    /// Parse a `timestamp` of this format, if it is valid and does not overflow.
+    pub fn parse(self, timestamp: &str) -> Option<TimestampNs> {
+        match self {
+            Self::Unix(unit) => parse_unix(timestamp, unit),
+            Self::DateTime => parse_date_time(timestamp),
+        }
+    }
+}
+
+fn parse_unix(timestamp: &str, unit: TimestampUnit) -> Option<TimestampNs> {
+    let Some((integer, fraction)) = timestamp.split_once('.') else {
+        return unit.to_timestamp_ns(timestamp.parse().ok()?);
+    };
+    if integer.starts_with('-') {
+        return None;
+    }
+    let nanos = i64::from(parse_fraction_nanos(fraction)?);
+    let nanos = (i128::from(unit.nanos_per_unit()) * i128::from(nanos) / 1_000_000_000) as i64;
+    unit.to_timestamp_ns(integer.parse().ok()?)?
+        .get()
+        .checked_add(nanos)
+        .map(TimestampNs::from)
+}
+
+/// Parse the digits after the decimal point as nanoseconds, truncating digits beyond nanosecond precision.
+fn parse_fraction_nanos(fraction: &str) -> Option<u32> {
+    if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits = &fraction[..fraction.len().min(9)];
+    let scale = 10_u32.pow(9 - digits.len() as u32);
+    Some(digits.parse::<u32>().ok()? * scale)
+}
+
+fn parse_date_time(timestamp: &str) -> Option<TimestampNs> {
+    let timestamp = timestamp.strip_suffix('Z').unwrap_or(timestamp);
+    let bytes = timestamp.as_bytes();
+    if !timestamp.is_ascii()
+        || bytes.len() < 19
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b'D' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = &timestamp[range];
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())
+            .flatten()
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+    let nanos = match &timestamp[19..] {
+        "" => 0,
+        rest => parse_fraction_nanos(rest.strip_prefix('.')?)?,
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second;
+    seconds
+        .checked_mul(1_000_000_000)?
+        .checked_add(i64::from(nanos))
+        .map(TimestampNs::from)
+}
+
+/// The days since the unix epoch of a date in the proleptic gregorian calendar (Howard Hinnant).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Looks up the index of a `CsvColumn`.
+pub(crate) struct ColumnResolver {
+    headers: Option<csv::StringRecord>,
+}
+
+impl ColumnResolver {
+    /// Read the header of `reader`, if it has one.
+    pub(crate) fn new<R: std::io::Read>(
+        reader: &mut csv::Reader<R>,
+        has_headers: bool,
+    ) -> Result<Self, MarketDataError> {
+        let headers = if has_headers {
+            Some(reader.headers().map_err(csv_error)?.clone())
+        } else {
+            None
+        };
+        Ok(Self { headers })
+    }
+
+    /// The index of `column`. Errors if it is not part of the header.
+    pub(crate) fn resolve(&self, column: &CsvColumn) -> Result<usize, MarketDataError> {
+        match (column, &self.headers) {
+            (CsvColumn::Name(name), Some(headers)) => headers
+                .iter()
+                .position(|header| header.trim() == name.as_str())
+                .ok_or_else(|| MarketDataError::MissingColumn(name.clone())),
+            (CsvColumn::Name(name), None) => Err(MarketDataError::MissingColumn(name.clone())),
+            (CsvColumn::Index(idx), Some(headers)) if *idx >= headers.len() => {
+                Err(MarketDataError::MissingColumn(idx.to_string()))
+            }
+            (CsvColumn::Index(idx), _) => Ok(*idx),
+        }
+    }
+}
+
+/// Only keeps the records of one symbol, for files containing several.
+#[derive(Debug, Clone)]
+pub(crate) struct SymbolFilter {
+    idx: usize,
+    symbol: String,
+}
+
+impl SymbolFilter {
+    /// Resolve the filter, if both a `column` and a `symbol` are given.
+    pub(crate) fn new(
+        resolver: &ColumnResolver,
+        column: Option<&CsvColumn>,
+        symbol: Option<&String>,
+    ) -> Result<Option<Self>, MarketDataError> {
+        let (Some(column), Some(symbol)) = (column, symbol) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            idx: resolver.resolve(column)?,
+            symbol: symbol.clone(),
+        }))
+    }
+
+    /// Whether the `record` belongs to the symbol.
+    #[inline]
+    pub(crate) fn matches(&self, record: &csv::StringRecord) -> bool {
+        record
+            .get(self.idx)
+            .is_some_and(|symbol| symbol.trim() == self.symbol)
+    }
+}
+
+/// Access to the fields of a single csv record, producing errors with its line number.
+pub(crate) struct Fields<'a> {
+    record: &'a csv::StringRecord,
+    pub(crate) line: u64,
+}
+
+impl<'a> Fields<'a> {
+    pub(crate) fn new(record: &'a csv::StringRecord) -> Self {
+        Self {
+            record,
+            line: record.position().map_or(0, |pos| pos.line()),
+        }
+    }
+
+    /// The trimmed field at `idx`.
+    #[inline]
+    pub(crate) fn get(&self, idx: usize) -> Result<&'a str, MarketDataError> {
+        self.record
+            .get(idx)
+            .map(str::trim)
+            .ok_or(MarketDataError::MissingField {
+                line: self.line,
+                column: idx,
+            })
+    }
+
+    /// The error of a `value` of `field` which cannot be parsed.
+    #[inline]
+    pub(crate) fn invalid(&self, field: &'static str, value: &str) -> MarketDataError {
+        MarketDataError::InvalidField {
+            line: self.line,
+            field,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Open the file at `path`, decompressing it on the fly if its extension is `gz`.
+pub(crate) fn open_path(
+    path: &std::path::Path,
+) -> Result<Box<dyn std::io::Read + Send>, MarketDataError> {
+    let file = std::fs::File::open(path)?;
+    let is_gzip = path.extension().is_some_and(|ext| ext == "gz");
+    if !is_gzip {
+        return Ok(Box::new(file));
+    }
+    #[cfg(feature = "gzip")]
+    {
+        Ok(Box::new(flate2::read::MultiGzDecoder::new(
+            std::io::BufReader::new(file),
+        )))
+    }
+    #[cfg(not(feature = "gzip"))]
+    {
+        Err(MarketDataError::GzipUnsupported)
+    }
+}
+
+/// Convert an error of the `csv` crate, keeping the line number if there is one.
+pub(crate) fn csv_error(err: csv::Error) -> MarketDataError {
+    match err.position() {
+        Some(pos) => MarketDataError::InvalidRecord {
+            line: pos.line(),
+            message: err.to_string(),
+        },
+        None => MarketDataError::Io(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_format_unix() {
+        let ms = TimestampFormat::Unix(TimestampUnit::Milliseconds);
+        assert_eq!(ms.parse("1"), Some(1_000_000.into()));
+        assert_eq!(ms.parse("-1"), Some((-1_000_000).into()));
+        assert_eq!(ms.parse("1.5"), Some(1_500_000.into()));
+        assert_eq!(ms.parse("abc"), None);
+        assert_eq!(ms.parse("1."), None);
+        assert_eq!(ms.parse("-1.5"), None);
+        assert_eq!(ms.parse("9223372036854775807"), None);
+
+        let s = TimestampFormat::Unix(TimestampUnit::Seconds);
+        assert_eq!(
+            s.parse("1585180700.0647"),
+            Some(1_585_180_700_064_700_000.into())
+        );
+        assert_eq!(s.parse("1.0000000019"), Some(1_000_000_001.into()));
+
+        let ns = TimestampFormat::Unix(TimestampUnit::Nanoseconds);
+        assert_eq!(ns.parse("5.9"), Some(5.into()));
+    }
+
+    #[test]
+    fn timestamp_format_date_time() {
+        let f = TimestampFormat::DateTime;
+        assert_eq!(f.parse("1970-01-01T00:00:00Z"), Some(0.into()));
+        assert_eq!(
+            f.parse("2014-11-22D03:33:14.372478000"),
+            Some(1_416_627_194_372_478_000.into())
+        );
+        assert_eq!(
+            f.parse("2020-02-29 12:00:00.5"),
+            Some(1_582_977_600_500_000_000.into())
+        );
+        assert_eq!(
+            f.parse("1969-12-31T23:59:59"),
+            Some((-1_000_000_000).into())
+        );
+        assert_eq!(f.parse("2020-13-01T00:00:00"), None);
+        assert_eq!(f.parse("2020-01-01X00:00:00"), None);
+        assert_eq!(f.parse("2020-01-01T00:00:00."), None);
+        assert_eq!(f.parse("2020-01-01"), None);
+        assert_eq!(f.parse("2020-01-01T00:00:0é"), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! Streams `Bba`s from csv files of arbitrary size with bounded memory.
+
+use const_decimal::Decimal;
+use getset::{
+    CopyGetters,
+    Getters,
+};
+use num_traits::Zero;
+use typed_builder::TypedBuilder;
+
+use super::csv_format::{
+    ColumnResolver,
+    CsvColumn,
+    Fields,
+    SymbolFilter,
+    TimestampFormat,
+    csv_error,
+    open_path,
+};
+use crate::{
+    market_update::Bba,
+    types::{
+        MarketDataError,
+        Mon,
+        QuoteCurrency,
+    },
+};
+
+/// Describes the layout of a csv file with best bid and ask quotes.
+/// The default has a header of `timestamp`, `bid_price` and `ask_price` and millisecond timestamps.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder, Getters, CopyGetters)]
+pub struct CsvBbaFormat {
+    /// Whether the first row is a header.
+    #[builder(default = true)]
+    #[getset(get_copy = "pub")]
+    has_headers: bool,
+
+    /// The field delimiter.
+    #[builder(default = b',')]
+    #[getset(get_copy = "pub")]
+    delimiter: u8,
+
+    /// The column of the timestamp.
+    #[builder(default = CsvColumn::from("timestamp"), setter(into))]
+    #[getset(get = "pub")]
+    timestamp_column: CsvColumn,
+
+    /// The column of the best bid price.
+    #[builder(default = CsvColumn::from("bid_price"), setter(into))]
+    #[getset(get = "pub")]
+    bid_column: CsvColumn,
+
+    /// The column of the best ask price.
+    #[builder(default = CsvColumn::from("ask_price"), setter(into))]
+    #[getset(get = "pub")]
+    ask_column: CsvColumn,
+
+    /// How the timestamps are written, e.g. a `TimestampUnit`.
+    #[builder(default, setter(into))]
+    #[getset(get_copy = "pub")]
+    timestamp_format: TimestampFormat,
+
+    /// The column of the symbol, for files containing the quotes of several symbols.
+    #[builder(default, setter(strip_option, into))]
+    #[getset(get = "pub")]
+    symbol_column: Option<CsvColumn>,
+
+    /// Only the quotes of this symbol are read if there is a `symbol_column`.
+    #[builder(default, setter(strip_option, into))]
+    #[getset(get = "pub")]
+    symbol: Option<String>,
+}
+
+impl Default for CsvBbaFormat {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl CsvBbaFormat {
+    /// Only read the quotes of `symbol`, given the format has a `symbol_column`.
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+}
+
+/// An iterator over the `Bba`s of a csv file, reading one record at a time.
+///
+/// A record which cannot be parsed yields an error with its line number,
+/// after which the iteration continues with the next record.
+/// The iteration ends after an I/O error, as the reader cannot recover from it.
+/// Quotes are not checked against a `PriceFilter`, so a crossed quote is passed on as is.
+#[derive(Debug)]
+pub struct CsvBbaReader<R, I, const D: u8>
+where
+    I: Mon<D>,
+{
+    reader: csv::Reader<R>,
+    record: csv::StringRecord,
+    timestamp_idx: usize,
+    bid_idx: usize,
+    ask_idx: usize,
+    symbol_filter: Option<SymbolFilter>,
+    timestamp_format: TimestampFormat,
+    finished: bool,
+    _bba: std::marker::PhantomData<Bba<I, D>>,
+}
+
+impl<I, const D: u8> CsvBbaReader<Box<dyn std::io::Read + Send>, I, D>
+where
+    I: Mon<D>,
+{
+    /// Open the csv file at `path`.
+    /// Files with a `gz` extension are decompressed on the fly, which requires the `gzip` feature.
+    pub fn from_path(
+        path: impl AsRef<std::path::Path>,
+        format: &CsvBbaFormat,
+    ) -> Result<Self, MarketDataError> {
+        Self::from_reader(open_path(path.as_ref())?, format)
+    }
+}
+
+impl<R, I, const D: u8> CsvBbaReader<R, I, D>
+where
+    R: std::io::Read,
+    I: Mon<D>,
+{
+    /// Read the csv data from `reader`, which is buffered internally.
+    /// Errors if a column of the `format` is not part of the header.
+    pub fn from_reader(reader: R, format: &CsvBbaFormat) -> Result<Self, MarketDataError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(format.has_headers)
+            .delimiter(format.delimiter)
+            .from_reader(reader);
+        let resolver = ColumnResolver::new(&mut reader, format.has_headers)?;
+
+        Ok(Self {
+            timestamp_idx: resolver.resolve(&format.timestamp_column)?,
+            bid_idx: resolver.resolve(&format.bid_column)?,
+            ask_idx: resolver.resolve(&format.ask_column)?,
+            symbol_filter: SymbolFilter::new(
+                &resolver,
+                format.symbol_column.as_ref(),
+                format.symbol.as_ref(),
+            )?,
+            timestamp_format: format.timestamp_format,
+            reader,
+            record: csv::StringRecord::new(),
+            finished: false,
+            _bba: std::marker::PhantomData,
+        })
+    }
+
+    /// Convert the current record into a `Bba`.
+    fn parse_record(&self) -> Result<Bba<I, D>, MarketDataError> {
+        let fields = Fields::new(&self.record);
+
+        let timestamp = fields.get(self.timestamp_idx)?;
+        let timestamp_exchange_ns = self
+            .timestamp_format
+            .parse(timestamp)
+            .ok_or_else(|| fields.invalid("timestamp", timestamp))?;
+
+        let price =
+            |idx: usize, field: &'static str| -> Result<QuoteCurrency<I, D>, MarketDataError> {
+                let value = fields.get(idx)?;
+                value
+                    .parse::<Decimal<I, D>>()
+                    .ok()
+                    .map(QuoteCurrency::from)
+                    .filter(|price| *price > QuoteCurrency::zero())
+                    .ok_or_else(|| fields.invalid(field, value))
+            };
+
+        Ok(Bba {
+            bid: price(self.bid_idx, "bid")?,
+            ask: price(self.ask_idx, "ask")?,
+            timestamp_exchange_ns,
+        })
+    }
+}
+
+impl<R, I, const D: u8> Iterator for CsvBbaReader<R, I, D>
+where
+    R: std::io::Read,
+    I: Mon<D>,
+{
+    type Item = Result<Bba<I, D>, MarketDataError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            match self.reader.read_record(&mut self.record) {
+                Ok(true) => {
+                    if let Some(filter) = &self.symbol_filter
+                        && !filter.matches(&self.record)
+                    {
+                        continue;
+                    }
+                    return Some(self.parse_record());
+                }
+                Ok(false) => self.finished = true,
+                Err(err) => {
+                    self.finished = err.is_io_error();
+                    return Some(Err(csv_error(err)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DECIMALS;
+
+    #[test]
+    fn csv_bba_reader() {
+        let data = "timestamp,bid_price,ask_price\n1,100,101\n2,,101\n3,100.5,101\n";
+        let quotes: Vec<_> = CsvBbaReader::<_, i64, DECIMALS>::from_reader(
+            data.as_bytes(),
+            &CsvBbaFormat::default(),
+        )
+        .unwrap()
+        .collect();
+        assert_eq!(quotes, vec![
+            Ok(Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 1_000_000.into(),
+            }),
+            Err(MarketDataError::InvalidField {
+                line: 3,
+                field: "bid",
+                value: String::new(),
+            }),
+            Ok(Bba {
+                bid: QuoteCurrency::new(1005, 1),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 3_000_000.into(),
+            }),
+        ]);
+    }
+
+    #[test]
+    fn csv_bba_reader_symbol_filter() {
+        let data = "timestamp,symbol,bid_price,ask_price\n1,A,100,101\n2,B,50,51\n3,A,100,102\n";
+        let format = CsvBbaFormat::builder()
+            .symbol_column("symbol")
+            .symbol("A")
+            .build();
+        let quotes: Vec<_> =
+            CsvBbaReader::<_, i64, DECIMALS>::from_reader(data.as_bytes(), &format)
+                .unwrap()
+                .map(|bba| bba.unwrap().timestamp_exchange_ns)
+                .collect();
+        assert_eq!(quotes, vec![1_000_000.into(), 3_000_000.into()]);
+    }
+}
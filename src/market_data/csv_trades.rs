@@ -0,0 +1,456 @@
+//! Streams `Trade`s from csv files of arbitrary size with bounded memory.
+
+use std::marker::PhantomData;
+
+use const_decimal::Decimal;
+use getset::{
+    CopyGetters,
+    Getters,
+};
+use num_traits::{
+    Signed,
+    Zero,
+};
+use typed_builder::TypedBuilder;
+
+use super::csv_format::{
+    ColumnResolver,
+    CsvColumn,
+    Fields,
+    SymbolFilter,
+    TimestampFormat,
+    csv_error,
+    open_path,
+};
+use crate::{
+    market_update::Trade,
+    types::{
+        Currency,
+        MarketDataError,
+        Mon,
+        QuoteCurrency,
+        Side,
+    },
+};
+
+/// How the taker side of a trade is encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SideEncoding {
+    /// A positive quantity denotes a buy and a negative quantity a sell, e.g. Bitmex.
+    #[default]
+    SignedQuantity,
+    /// A separate column denotes the side, matched case-insensitively against `buy` and `sell`.
+    /// Quantities must be positive.
+    /// E.g. the `is_buyer_maker` column of Binance, with `buy` being "false" and `sell` being "true".
+    Column {
+        /// The column of the side.
+        column: CsvColumn,
+        /// The value denoting a taker buy.
+        buy: String,
+        /// The value denoting a taker sell.
+        sell: String,
+    },
+}
+
+/// Describes the layout of a csv file with taker trades.
+/// The default matches the format of `load_trades_from_csv`,
+/// with a header of `timestamp`, `price` and a signed `size` and millisecond timestamps.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder, Getters, CopyGetters)]
+pub struct CsvTradeFormat {
+    /// Whether the first row is a header.
+    #[builder(default = true)]
+    #[getset(get_copy = "pub")]
+    has_headers: bool,
+
+    /// The field delimiter.
+    #[builder(default = b',')]
+    #[getset(get_copy = "pub")]
+    delimiter: u8,
+
+    /// The column of the timestamp.
+    #[builder(default = CsvColumn::from("timestamp"), setter(into))]
+    #[getset(get = "pub")]
+    timestamp_column: CsvColumn,
+
+    /// The column of the price.
+    #[builder(default = CsvColumn::from("price"), setter(into))]
+    #[getset(get = "pub")]
+    price_column: CsvColumn,
+
+    /// The column of the quantity.
+    #[builder(default = CsvColumn::from("size"), setter(into))]
+    #[getset(get = "pub")]
+    quantity_column: CsvColumn,
+
+    /// How the timestamps are written, e.g. a `TimestampUnit`.
+    #[builder(default, setter(into))]
+    #[getset(get_copy = "pub")]
+    timestamp_format: TimestampFormat,
+
+    /// How the taker side is encoded.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    side_encoding: SideEncoding,
+
+    /// The column of the symbol, for files containing the trades of several symbols.
+    #[builder(default, setter(strip_option, into))]
+    #[getset(get = "pub")]
+    symbol_column: Option<CsvColumn>,
+
+    /// Only the trades of this symbol are read if there is a `symbol_column`.
+    #[builder(default, setter(strip_option, into))]
+    #[getset(get = "pub")]
+    symbol: Option<String>,
+}
+
+impl Default for CsvTradeFormat {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl CsvTradeFormat {
+    /// Only read the trades of `symbol`, given the format has a `symbol_column`.
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+}
+
+/// The resolved column of the side, if it is not encoded in the quantity.
+#[derive(Debug, Clone)]
+struct SideColumn {
+    idx: usize,
+    buy: String,
+    sell: String,
+}
+
+/// An iterator over the `Trade`s of a csv file, reading one record at a time.
+///
+/// A record which cannot be parsed yields an error with its line number,
+/// after which the iteration continues with the next record.
+/// The iteration ends after an I/O error, as the reader cannot recover from it.
+#[derive(Debug)]
+pub struct CsvTradeReader<R, I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    reader: csv::Reader<R>,
+    record: csv::StringRecord,
+    timestamp_idx: usize,
+    price_idx: usize,
+    quantity_idx: usize,
+    side_column: Option<SideColumn>,
+    symbol_filter: Option<SymbolFilter>,
+    timestamp_format: TimestampFormat,
+    finished: bool,
+    _trade: PhantomData<Trade<I, D, BaseOrQuote>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> CsvTradeReader<Box<dyn std::io::Read + Send>, I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Open the csv file at `path`.
+    /// Files with a `gz` extension are decompressed on the fly, which requires the `gzip` feature.
+    pub fn from_path(
+        path: impl AsRef<std::path::Path>,
+        format: &CsvTradeFormat,
+    ) -> Result<Self, MarketDataError> {
+        Self::from_reader(open_path(path.as_ref())?, format)
+    }
+}
+
+impl<R, I, const D: u8, BaseOrQuote> CsvTradeReader<R, I, D, BaseOrQuote>
+where
+    R: std::io::Read,
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Read the csv data from `reader`, which is buffered internally.
+    /// Errors if a column of the `format` is not part of the header.
+    pub fn from_reader(reader: R, format: &CsvTradeFormat) -> Result<Self, MarketDataError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(format.has_headers)
+            .delimiter(format.delimiter)
+            .from_reader(reader);
+        let resolver = ColumnResolver::new(&mut reader, format.has_headers)?;
+
+        let side_column = match &format.side_encoding {
+            SideEncoding::SignedQuantity => None,
+            SideEncoding::Column { column, buy, sell } => Some(SideColumn {
+                idx: resolver.resolve(column)?,
+                buy: buy.clone(),
+                sell: sell.clone(),
+            }),
+        };
+        Ok(Self {
+            timestamp_idx: resolver.resolve(&format.timestamp_column)?,
+            price_idx: resolver.resolve(&format.price_column)?,
+            quantity_idx: resolver.resolve(&format.quantity_column)?,
+            side_column,
+            symbol_filter: SymbolFilter::new(
+                &resolver,
+                format.symbol_column.as_ref(),
+                format.symbol.as_ref(),
+            )?,
+            timestamp_format: format.timestamp_format,
+            reader,
+            record: csv::StringRecord::new(),
+            finished: false,
+            _trade: PhantomData,
+        })
+    }
+
+    /// Convert the current record into a `Trade`.
+    fn parse_record(&self) -> Result<Trade<I, D, BaseOrQuote>, MarketDataError> {
+        let fields = Fields::new(&self.record);
+
+        let timestamp = fields.get(self.timestamp_idx)?;
+        let timestamp_exchange_ns = self
+            .timestamp_format
+            .parse(timestamp)
+            .ok_or_else(|| fields.invalid("timestamp", timestamp))?;
+
+        let price = fields.get(self.price_idx)?;
+        let price = price
+            .parse::<Decimal<I, D>>()
+            .ok()
+            .map(QuoteCurrency::from)
+            .filter(|price| *price > QuoteCurrency::zero())
+            .ok_or_else(|| fields.invalid("price", price))?;
+
+        let quantity_field = fields.get(self.quantity_idx)?;
+        let quantity = BaseOrQuote::from(
+            quantity_field
+                .parse::<Decimal<I, D>>()
+                .map_err(|_| fields.invalid("quantity", quantity_field))?,
+        );
+        if quantity.is_zero() {
+            return Err(MarketDataError::ZeroQuantity { line: fields.line });
+        }
+
+        let side = match &self.side_column {
+            None => Side::from_taker_quantity(quantity),
+            Some(_) if quantity.is_negative() => {
+                return Err(fields.invalid("quantity", quantity_field));
+            }
+            Some(side_column) => {
+                let side = fields.get(side_column.idx)?;
+                if side.eq_ignore_ascii_case(&side_column.buy) {
+                    Side::Buy
+                } else if side.eq_ignore_ascii_case(&side_column.sell) {
+                    Side::Sell
+                } else {
+                    return Err(fields.invalid("side", side));
+                }
+            }
+        };
+
+        Ok(Trade {
+            timestamp_exchange_ns,
+            price,
+            quantity: quantity.abs(),
+            side,
+        })
+    }
+}
+
+impl<R, I, const D: u8, BaseOrQuote> Iterator for CsvTradeReader<R, I, D, BaseOrQuote>
+where
+    R: std::io::Read,
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    type Item = Result<Trade<I, D, BaseOrQuote>, MarketDataError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            match self.reader.read_record(&mut self.record) {
+                Ok(true) => {
+                    if let Some(filter) = &self.symbol_filter
+                        && !filter.matches(&self.record)
+                    {
+                        continue;
+                    }
+                    return Some(self.parse_record());
+                }
+                Ok(false) => self.finished = true,
+                Err(err) => {
+                    self.finished = err.is_io_error();
+                    return Some(Err(csv_error(err)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DECIMALS,
+        market_data::TimestampUnit,
+        types::BaseCurrency,
+    };
+
+    type QuoteTrade = Trade<i64, DECIMALS, QuoteCurrency<i64, DECIMALS>>;
+
+    fn read<BaseOrQuote: Currency<i64, DECIMALS>>(
+        data: &str,
+        format: &CsvTradeFormat,
+    ) -> Vec<Result<Trade<i64, DECIMALS, BaseOrQuote>, MarketDataError>> {
+        CsvTradeReader::from_reader(data.as_bytes(), format)
+            .unwrap()
+            .collect()
+    }
+
+    #[test]
+    fn csv_trade_reader_default_format() {
+        let data = "timestamp,price,size\n1,100.5,10\n2,100.0,-5\n";
+        let trades = read::<QuoteCurrency<i64, DECIMALS>>(data, &CsvTradeFormat::default());
+        assert_eq!(trades, vec![
+            Ok(QuoteTrade {
+                timestamp_exchange_ns: 1_000_000.into(),
+                price: QuoteCurrency::new(1005, 1),
+                quantity: QuoteCurrency::new(10, 0),
+                side: Side::Buy,
+            }),
+            Ok(QuoteTrade {
+                timestamp_exchange_ns: 2_000_000.into(),
+                price: QuoteCurrency::new(100, 0),
+                quantity: QuoteCurrency::new(5, 0),
+                side: Side::Sell,
+            }),
+        ]);
+    }
+
+    #[test]
+    fn csv_trade_reader_continues_after_bad_rows() {
+        let data = "timestamp,price,size\n1,100,1\nabc,100,1\n3,-1,1\n4,100,0\n5,100\n6,101,2\n";
+        let trades = read::<QuoteCurrency<i64, DECIMALS>>(data, &CsvTradeFormat::default());
+        assert_eq!(trades.len(), 6);
+        assert!(trades[0].is_ok());
+        assert_eq!(
+            trades[1],
+            Err(MarketDataError::InvalidField {
+                line: 3,
+                field: "timestamp",
+                value: "abc".to_string()
+            })
+        );
+        assert_eq!(
+            trades[2],
+            Err(MarketDataError::InvalidField {
+                line: 4,
+                field: "price",
+                value: "-1".to_string()
+            })
+        );
+        assert_eq!(trades[3], Err(MarketDataError::ZeroQuantity { line: 5 }));
+        assert!(matches!(
+            trades[4],
+            Err(MarketDataError::InvalidRecord { line: 6, .. })
+        ));
+        assert_eq!(
+            trades[5].as_ref().unwrap().price,
+            QuoteCurrency::new(101, 0)
+        );
+    }
+
+    #[test]
+    fn csv_trade_reader_custom_format() {
+        // Binance style aggregated trades.
+        let data = "id;qty;px;time;is_buyer_maker\n0;0.5;100;1700000000000000;false\n1;0.25;99;1700000000000001;TRUE\n2;1;99;1700000000000002;maybe\n";
+        let format = CsvTradeFormat::builder()
+            .delimiter(b';')
+            .timestamp_column("time")
+            .price_column(2_usize)
+            .quantity_column("qty")
+            .timestamp_format(TimestampUnit::Microseconds)
+            .side_encoding(SideEncoding::Column {
+                column: "is_buyer_maker".into(),
+                buy: "false".to_string(),
+                sell: "true".to_string(),
+            })
+            .build();
+        let trades = read::<BaseCurrency<i64, DECIMALS>>(data, &format);
+        assert_eq!(trades, vec![
+            Ok(Trade {
+                timestamp_exchange_ns: 1_700_000_000_000_000_000.into(),
+                price: QuoteCurrency::new(100, 0),
+                quantity: BaseCurrency::new(5, 1),
+                side: Side::Buy,
+            }),
+            Ok(Trade {
+                timestamp_exchange_ns: 1_700_000_000_000_001_000.into(),
+                price: QuoteCurrency::new(99, 0),
+                quantity: BaseCurrency::new(25, 2),
+                side: Side::Sell,
+            }),
+            Err(MarketDataError::InvalidField {
+                line: 4,
+                field: "side",
+                value: "maybe".to_string()
+            }),
+        ]);
+    }
+
+    #[test]
+    fn csv_trade_reader_without_headers() {
+        let data = "1,100,1\n";
+        let format = CsvTradeFormat::builder()
+            .has_headers(false)
+            .timestamp_column(0_usize)
+            .price_column(1_usize)
+            .quantity_column(2_usize)
+            .timestamp_format(TimestampUnit::Seconds)
+            .build();
+        let trades = read::<QuoteCurrency<i64, DECIMALS>>(data, &format);
+        assert_eq!(
+            trades[0].as_ref().unwrap().timestamp_exchange_ns,
+            1_000_000_000.into()
+        );
+
+        // Named columns need a header.
+        let format = CsvTradeFormat::builder().has_headers(false).build();
+        assert_eq!(
+            CsvTradeReader::<_, i64, DECIMALS, QuoteCurrency<i64, DECIMALS>>::from_reader(
+                data.as_bytes(),
+                &format
+            )
+            .map(|_| ()),
+            Err(MarketDataError::MissingColumn("timestamp".to_string()))
+        );
+    }
+
+    #[test]
+    fn csv_trade_reader_missing_column() {
+        let data = "timestamp,price,quantity\n1,100,1\n";
+        assert_eq!(
+            CsvTradeReader::<_, i64, DECIMALS, QuoteCurrency<i64, DECIMALS>>::from_reader(
+                data.as_bytes(),
+                &CsvTradeFormat::default()
+            )
+            .map(|_| ()),
+            Err(MarketDataError::MissingColumn("size".to_string()))
+        );
+    }
+
+    #[test]
+    fn csv_trade_reader_timestamp_overflow() {
+        let data = "timestamp,price,size\n9223372036854775807,100,1\n";
+        let trades = read::<QuoteCurrency<i64, DECIMALS>>(data, &CsvTradeFormat::default());
+        assert_eq!(
+            trades[0],
+            Err(MarketDataError::InvalidField {
+                line: 2,
+                field: "timestamp",
+                value: "9223372036854775807".to_string()
+            })
+        );
+    }
+}
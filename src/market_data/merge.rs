@@ -0,0 +1,215 @@
+//! Combines several time sorted streams of market data into one.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+};
+
+use crate::{
+    EXPECT_CAPACITY,
+    market_update::{
+        MarketEvent,
+        MarketUpdate,
+    },
+    types::{
+        Currency,
+        Mon,
+        TimestampNs,
+    },
+};
+
+type EventStream<'a, I, const D: u8, BaseOrQuote> =
+    Box<dyn Iterator<Item = MarketEvent<I, D, BaseOrQuote>> + 'a>;
+
+/// A k-way merge of streams which are each sorted by `timestamp_exchange_ns`,
+/// yielding the `MarketEvent`s of all streams in time order.
+///
+/// Events with the same timestamp are yielded in the order in which their streams were added,
+/// e.g. adding the mark prices before the quotes and the quotes before the trades
+/// means the `MarketState` is up to date by the time a trade fills an order.
+/// Within a stream the order of events with equal timestamps is preserved.
+pub struct MarketEventMerge<'a, I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    streams: Vec<EventStream<'a, I, D, BaseOrQuote>>,
+    // The next event of each stream, which is `None` once the stream is exhausted.
+    heads: Vec<Option<MarketEvent<I, D, BaseOrQuote>>>,
+    // The timestamp of each pending head along with the index of its stream, smallest first.
+    queue: BinaryHeap<Reverse<(TimestampNs, usize)>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> std::fmt::Debug for MarketEventMerge<'_, I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarketEventMerge")
+            .field("streams", &self.streams.len())
+            .field("heads", &self.heads)
+            .finish()
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> Default for MarketEventMerge<'_, I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, I, const D: u8, BaseOrQuote> MarketEventMerge<'a, I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a merge without any streams.
+    pub fn new() -> Self {
+        Self {
+            streams: Vec::with_capacity(4),
+            heads: Vec::with_capacity(4),
+            queue: BinaryHeap::with_capacity(4),
+        }
+    }
+
+    /// Add a stream of any market update type convertible into a `MarketEvent`,
+    /// which must be sorted by `timestamp_exchange_ns`.
+    /// Takes precedence over the streams added later if timestamps are equal.
+    pub fn with_stream<S, U>(mut self, stream: S) -> Self
+    where
+        S: IntoIterator<Item = U>,
+        S::IntoIter: 'a,
+        U: Into<MarketEvent<I, D, BaseOrQuote>>,
+    {
+        let idx = self.streams.len();
+        let mut stream: EventStream<'a, I, D, BaseOrQuote> =
+            Box::new(stream.into_iter().map(Into::into));
+        let head = stream.next();
+        if let Some(event) = &head {
+            self.queue
+                .push(Reverse((event.timestamp_exchange_ns(), idx)));
+        }
+        // Adding streams happens once during setup and not in the hot path.
+        self.streams.reserve(1);
+        self.streams
+            .push_within_capacity(stream)
+            .expect(EXPECT_CAPACITY);
+        self.heads.reserve(1);
+        self.heads
+            .push_within_capacity(head)
+            .expect(EXPECT_CAPACITY);
+        self
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> Iterator for MarketEventMerge<'_, I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    type Item = MarketEvent<I, D, BaseOrQuote>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((ts, idx)) = self.queue.pop()?;
+        let next = self.streams[idx].next();
+        if let Some(event) = &next {
+            let next_ts = event.timestamp_exchange_ns();
+            debug_assert!(
+                next_ts >= ts,
+                "Stream {idx} must be sorted by `timestamp_exchange_ns`"
+            );
+            self.queue.push(Reverse((next_ts, idx)));
+        }
+        std::mem::replace(&mut self.heads[idx], next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.len(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DECIMALS,
+        market_update::{
+            Bba,
+            MarkPrice,
+            Trade,
+        },
+        mock_bba,
+        types::{
+            BaseCurrency,
+            QuoteCurrency,
+            Side,
+        },
+    };
+
+    type Event = MarketEvent<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>;
+
+    fn trade(ts: i64, quantity: i64) -> Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>> {
+        Trade {
+            timestamp_exchange_ns: ts.into(),
+            price: QuoteCurrency::new(100, 0),
+            quantity: BaseCurrency::new(quantity, 0),
+            side: Side::Sell,
+        }
+    }
+
+    fn mark_price(ts: i64) -> MarkPrice<i64, DECIMALS> {
+        MarkPrice {
+            price: QuoteCurrency::new(100, 0),
+            timestamp_exchange_ns: ts.into(),
+        }
+    }
+
+    #[test]
+    fn market_event_merge_time_order() {
+        let merged: Vec<Event> = MarketEventMerge::new()
+            .with_stream([mock_bba(100, 1), mock_bba(100, 4), mock_bba(100, 6)])
+            .with_stream([trade(2, 1), trade(3, 1), trade(7, 1)])
+            .with_stream([mark_price(5)])
+            .collect();
+        assert_eq!(merged, vec![
+            Event::from(mock_bba(100, 1)),
+            Event::from(trade(2, 1)),
+            Event::from(trade(3, 1)),
+            Event::from(mock_bba(100, 4)),
+            Event::from(mark_price(5)),
+            Event::from(mock_bba(100, 6)),
+            Event::from(trade(7, 1)),
+        ]);
+    }
+
+    #[test]
+    fn market_event_merge_ties() {
+        let merged: Vec<Event> = MarketEventMerge::new()
+            .with_stream([mark_price(1), mark_price(2)])
+            .with_stream(Vec::<Bba<i64, DECIMALS>>::with_capacity(0))
+            .with_stream([trade(1, 1), trade(1, 2), trade(2, 3)])
+            .with_stream([mock_bba(100, 1), mock_bba(100, 2)])
+            .collect();
+        assert_eq!(merged, vec![
+            Event::from(mark_price(1)),
+            Event::from(trade(1, 1)),
+            Event::from(trade(1, 2)),
+            Event::from(mock_bba(100, 1)),
+            Event::from(mark_price(2)),
+            Event::from(trade(2, 3)),
+            Event::from(mock_bba(100, 2)),
+        ]);
+    }
+
+    #[test]
+    fn market_event_merge_empty() {
+        let mut merge = MarketEventMerge::<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>::default();
+        assert_eq!(merge.size_hint(), (0, None));
+        assert_eq!(merge.next(), None);
+    }
+}
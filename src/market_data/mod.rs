@@ -0,0 +1,46 @@
+//! Loading of recorded market data.
+
+mod binary_format;
+mod binary_reader;
+mod binary_writer;
+mod csv_format;
+mod csv_quotes;
+mod csv_trades;
+mod merge;
+mod validation;
+mod venues;
+
+pub use binary_format::{
+    BinaryInteger,
+    BinaryRecord,
+    BinaryRecordKind,
+    Denominated,
+    Denomination,
+};
+#[cfg(feature = "mmap")]
+pub use binary_reader::BinaryMarketData;
+pub use binary_reader::BinaryRecords;
+pub use binary_writer::BinaryMarketDataWriter;
+pub use csv_format::{
+    CsvColumn,
+    TimestampFormat,
+    TimestampUnit,
+};
+pub use csv_quotes::{
+    CsvBbaFormat,
+    CsvBbaReader,
+};
+pub use csv_trades::{
+    CsvTradeFormat,
+    CsvTradeReader,
+    SideEncoding,
+};
+pub use merge::MarketEventMerge;
+pub use validation::{
+    IssueAction,
+    IssueCount,
+    MarketDataIssue,
+    MarketDataValidation,
+    ValidatedMarketData,
+    ValidationReport,
+};
@@ -0,0 +1,530 @@
+//! Checks recorded market data for problems and repairs them before they reach the `Exchange`.
+
+use std::collections::VecDeque;
+
+use getset::CopyGetters;
+use typed_builder::TypedBuilder;
+
+use crate::{
+    market_update::MarketUpdate,
+    order_filters::PriceFilter,
+    types::{
+        Currency,
+        Mon,
+        PriceFilterError,
+        TimestampNs,
+    },
+};
+
+/// A problem of a market update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, std::hash::Hash)]
+pub enum MarketDataIssue {
+    /// A price is zero, negative or outside the bounds of the `PriceFilter`,
+    /// or a candle is inconsistent, e.g. its close lies above its high.
+    InvalidPrice,
+    /// A price is not a multiple of the tick size.
+    OffTick,
+    /// The bid is at or above the ask.
+    CrossedBook,
+    /// The timestamp is older than the one of a preceding market update.
+    OutOfOrder,
+}
+
+impl MarketDataIssue {
+    fn from_price_filter_error(err: &PriceFilterError) -> Self {
+        match err {
+            PriceFilterError::PriceStepSize { .. } => Self::OffTick,
+            PriceFilterError::InvalidBidAskSpread => Self::CrossedBook,
+            PriceFilterError::PriceTooLow
+            | PriceFilterError::PriceTooHigh
+            | PriceFilterError::LimitPriceBelowMultiple
+            | PriceFilterError::LimitPriceAboveMultiple => Self::InvalidPrice,
+        }
+    }
+}
+
+/// How a `MarketDataIssue` is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueAction {
+    /// Only count the issue and pass the market update on as is.
+    Report,
+    /// Count the issue and drop the market update.
+    Drop,
+    /// Count the issue and repair the market update, dropping it if it cannot be repaired.
+    /// Off-tick prices are snapped onto the tick grid, see `MarketUpdate::snap_to_tick`,
+    /// and out of order updates are re-ordered within the `reorder_window_ns`.
+    /// Invalid prices and crossed books cannot be repaired.
+    Repair,
+}
+
+/// How often an issue occurred and how it was handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct IssueCount {
+    /// The number of market updates with the issue.
+    detected: u64,
+    /// The number of market updates which were repaired.
+    repaired: u64,
+    /// The number of market updates which were dropped.
+    dropped: u64,
+}
+
+/// A summary of the problems found by a `MarketDataValidation`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct ValidationReport {
+    /// The number of market updates read from the source.
+    updates: u64,
+    /// The number of market updates passed on.
+    emitted: u64,
+    /// See `MarketDataIssue::InvalidPrice`.
+    invalid_price: IssueCount,
+    /// See `MarketDataIssue::OffTick`.
+    off_tick: IssueCount,
+    /// See `MarketDataIssue::CrossedBook`.
+    crossed_book: IssueCount,
+    /// See `MarketDataIssue::OutOfOrder`.
+    out_of_order: IssueCount,
+    /// The timestamp and kind of the first issue, to start investigating the source.
+    first_issue: Option<(TimestampNs, MarketDataIssue)>,
+}
+
+impl ValidationReport {
+    /// How often `issue` occurred and how it was handled.
+    pub fn issue(&self, issue: MarketDataIssue) -> IssueCount {
+        match issue {
+            MarketDataIssue::InvalidPrice => self.invalid_price,
+            MarketDataIssue::OffTick => self.off_tick,
+            MarketDataIssue::CrossedBook => self.crossed_book,
+            MarketDataIssue::OutOfOrder => self.out_of_order,
+        }
+    }
+
+    /// `true` if no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.first_issue.is_none()
+    }
+
+    fn count_mut(&mut self, issue: MarketDataIssue) -> &mut IssueCount {
+        match issue {
+            MarketDataIssue::InvalidPrice => &mut self.invalid_price,
+            MarketDataIssue::OffTick => &mut self.off_tick,
+            MarketDataIssue::CrossedBook => &mut self.crossed_book,
+            MarketDataIssue::OutOfOrder => &mut self.out_of_order,
+        }
+    }
+
+    fn detected(&mut self, issue: MarketDataIssue, ts_ns: TimestampNs) {
+        self.count_mut(issue).detected += 1;
+        self.first_issue.get_or_insert((ts_ns, issue));
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "updates: {}, emitted: {}", self.updates, self.emitted)?;
+        for (name, count) in [
+            ("invalid price", self.invalid_price),
+            ("off tick", self.off_tick),
+            ("crossed book", self.crossed_book),
+            ("out of order", self.out_of_order),
+        ] {
+            write!(
+                f,
+                ", {name}: {} (repaired: {}, dropped: {})",
+                count.detected, count.repaired, count.dropped
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks market updates against a `PriceFilter` and for monotonic timestamps,
+/// as the `Exchange` only does so in debug builds or in strict mode.
+///
+/// # Example:
+/// ```rust
+/// use lfest::prelude::*;
+///
+/// let bba = |ts: i64, bid: i64, ask: i64| Bba {
+///     bid: QuoteCurrency::<i64, 2>::new(bid, 0),
+///     ask: QuoteCurrency::new(ask, 0),
+///     timestamp_exchange_ns: ts.into(),
+/// };
+/// // The second quote is crossed.
+/// let bbas = [bba(1, 100, 101), bba(2, 101, 100)];
+/// let validation = MarketDataValidation::builder()
+///     .price_filter(PriceFilter::default())
+///     .build();
+/// let mut validated = validation.validate::<_, _, BaseCurrency<i64, 2>>(bbas);
+/// assert_eq!(validated.by_ref().count(), 1);
+/// assert_eq!(validated.report().crossed_book().dropped(), 1);
+/// ```
+#[derive(Debug, Clone, TypedBuilder, CopyGetters)]
+pub struct MarketDataValidation<I, const D: u8>
+where
+    I: Mon<D>,
+{
+    /// The pricing rules the market updates must satisfy.
+    price_filter: PriceFilter<I, D>,
+
+    /// How to handle `MarketDataIssue::InvalidPrice`.
+    #[builder(default = IssueAction::Drop)]
+    #[getset(get_copy = "pub")]
+    invalid_price: IssueAction,
+
+    /// How to handle `MarketDataIssue::OffTick`.
+    #[builder(default = IssueAction::Repair)]
+    #[getset(get_copy = "pub")]
+    off_tick: IssueAction,
+
+    /// How to handle `MarketDataIssue::CrossedBook`.
+    #[builder(default = IssueAction::Drop)]
+    #[getset(get_copy = "pub")]
+    crossed_book: IssueAction,
+
+    /// How to handle `MarketDataIssue::OutOfOrder`.
+    #[builder(default = IssueAction::Drop)]
+    #[getset(get_copy = "pub")]
+    out_of_order: IssueAction,
+
+    /// When repairing out of order updates, how long an update is held back
+    /// so that late updates up to this much older can be sorted in before it.
+    #[builder(default = TimestampNs::from(0))]
+    #[getset(get_copy = "pub")]
+    reorder_window_ns: TimestampNs,
+}
+
+impl<I, const D: u8> MarketDataValidation<I, D>
+where
+    I: Mon<D>,
+{
+    /// The pricing rules the market updates must satisfy.
+    #[inline]
+    pub fn price_filter(&self) -> &PriceFilter<I, D> {
+        &self.price_filter
+    }
+
+    /// Validate the market `updates`, which yields the market updates to pass on to the `Exchange`
+    /// and summarizes the issues in its `ValidatedMarketData::report`.
+    pub fn validate<S, U, BaseOrQuote>(
+        self,
+        updates: S,
+    ) -> ValidatedMarketData<S::IntoIter, I, D, BaseOrQuote>
+    where
+        S: IntoIterator<Item = U>,
+        U: MarketUpdate<I, D, BaseOrQuote>,
+        BaseOrQuote: Currency<I, D>,
+    {
+        ValidatedMarketData {
+            validation: self,
+            updates: updates.into_iter(),
+            pending: VecDeque::with_capacity(16),
+            newest_ts_ns: None,
+            last_emitted_ts_ns: None,
+            report: ValidationReport::default(),
+            _currency: std::marker::PhantomData,
+        }
+    }
+}
+
+/// An iterator over validated and repaired market updates, see `MarketDataValidation`.
+#[derive(Debug)]
+pub struct ValidatedMarketData<S, I, const D: u8, BaseOrQuote>
+where
+    S: Iterator,
+    I: Mon<D>,
+{
+    validation: MarketDataValidation<I, D>,
+    updates: S,
+    // The market updates held back for re-ordering, sorted by timestamp.
+    pending: VecDeque<S::Item>,
+    newest_ts_ns: Option<TimestampNs>,
+    last_emitted_ts_ns: Option<TimestampNs>,
+    report: ValidationReport,
+    _currency: std::marker::PhantomData<BaseOrQuote>,
+}
+
+impl<S, I, const D: u8, BaseOrQuote> ValidatedMarketData<S, I, D, BaseOrQuote>
+where
+    S: Iterator,
+    S::Item: MarketUpdate<I, D, BaseOrQuote>,
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The summary of the issues found so far.
+    #[inline]
+    pub fn report(&self) -> &ValidationReport {
+        &self.report
+    }
+
+    /// Check the prices of the `update`, returning it if it is passed on.
+    fn check_prices(&mut self, mut update: S::Item) -> Option<S::Item> {
+        let ts_ns = update.timestamp_exchange_ns();
+        let mut snapped = false;
+        loop {
+            let Err(err) = update.validate_market_update(&self.validation.price_filter) else {
+                return Some(update);
+            };
+            let issue = MarketDataIssue::from_price_filter_error(&err);
+            if snapped && issue == MarketDataIssue::OffTick {
+                // Snapping did not help, so the market update cannot be repaired.
+                self.report.off_tick.repaired -= 1;
+                self.report.off_tick.dropped += 1;
+                return None;
+            }
+            self.report.detected(issue, ts_ns);
+            let action = match issue {
+                MarketDataIssue::InvalidPrice => self.validation.invalid_price,
+                MarketDataIssue::OffTick => self.validation.off_tick,
+                MarketDataIssue::CrossedBook => self.validation.crossed_book,
+                MarketDataIssue::OutOfOrder => unreachable!("Not a price issue"),
+            };
+            match action {
+                IssueAction::Report => return Some(update),
+                IssueAction::Repair if issue == MarketDataIssue::OffTick => {
+                    update.snap_to_tick(&self.validation.price_filter);
+                    self.report.off_tick.repaired += 1;
+                    snapped = true;
+                }
+                IssueAction::Drop | IssueAction::Repair => {
+                    self.report.count_mut(issue).dropped += 1;
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Queue the `update` to be passed on, unless it is out of order and dropped.
+    fn enqueue(&mut self, update: S::Item) {
+        let ts_ns = update.timestamp_exchange_ns();
+        if self.last_emitted_ts_ns.is_some_and(|last| ts_ns < last) {
+            self.report.detected(MarketDataIssue::OutOfOrder, ts_ns);
+            if self.validation.out_of_order != IssueAction::Report {
+                // Updates older than an already emitted one cannot be sorted in anymore.
+                self.report.out_of_order.dropped += 1;
+                return;
+            }
+        } else if self.validation.out_of_order == IssueAction::Repair {
+            let idx = self
+                .pending
+                .partition_point(|pending| pending.timestamp_exchange_ns() <= ts_ns);
+            if idx < self.pending.len() {
+                self.report.detected(MarketDataIssue::OutOfOrder, ts_ns);
+                self.report.out_of_order.repaired += 1;
+            }
+            self.pending.insert(idx, update);
+            self.newest_ts_ns = self.newest_ts_ns.max(Some(ts_ns));
+            return;
+        }
+        self.pending.push_back(update);
+        self.newest_ts_ns = self.newest_ts_ns.max(Some(ts_ns));
+    }
+
+    /// The oldest pending update, if it no longer has to wait for late updates.
+    fn pop_ready(&mut self, exhausted: bool) -> Option<S::Item> {
+        let oldest_ts_ns = self.pending.front()?.timestamp_exchange_ns();
+        let ready = exhausted
+            || self.validation.out_of_order != IssueAction::Repair
+            || self
+                .newest_ts_ns
+                .is_some_and(|newest| oldest_ts_ns + self.validation.reorder_window_ns <= newest);
+        if !ready {
+            return None;
+        }
+        self.report.emitted += 1;
+        self.last_emitted_ts_ns = self.last_emitted_ts_ns.max(Some(oldest_ts_ns));
+        self.pending.pop_front()
+    }
+}
+
+impl<S, I, const D: u8, BaseOrQuote> Iterator for ValidatedMarketData<S, I, D, BaseOrQuote>
+where
+    S: Iterator,
+    S::Item: MarketUpdate<I, D, BaseOrQuote>,
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(update) = self.pop_ready(false) {
+                return Some(update);
+            }
+            let Some(update) = self.updates.next() else {
+                return self.pop_ready(true);
+            };
+            self.report.updates += 1;
+            if let Some(update) = self.check_prices(update) {
+                self.enqueue(update);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use const_decimal::Decimal;
+    use num_traits::Zero;
+
+    use super::*;
+    use crate::{
+        DECIMALS,
+        market_update::{
+            Bba,
+            Trade,
+        },
+        mock_bba,
+        types::{
+            BaseCurrency,
+            QuoteCurrency,
+            Side,
+        },
+    };
+
+    type BaseTrade = Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>;
+
+    fn price_filter() -> PriceFilter<i64, DECIMALS> {
+        PriceFilter::new(
+            None,
+            None,
+            QuoteCurrency::new(5, 1),
+            Decimal::TWO,
+            Decimal::zero(),
+        )
+        .unwrap()
+    }
+
+    fn trade(ts: i64, price: QuoteCurrency<i64, DECIMALS>) -> BaseTrade {
+        Trade {
+            timestamp_exchange_ns: ts.into(),
+            price,
+            quantity: BaseCurrency::new(1, 0),
+            side: Side::Buy,
+        }
+    }
+
+    #[test]
+    fn validation_repairs_prices() {
+        let trades = [
+            trade(1, QuoteCurrency::new(100, 0)),
+            trade(2, QuoteCurrency::zero()),
+            trade(3, QuoteCurrency::new(10026, 2)),
+            trade(4, QuoteCurrency::new(101, 0)),
+        ];
+        let mut validated = MarketDataValidation::builder()
+            .price_filter(price_filter())
+            .build()
+            .validate(trades);
+        assert_eq!(validated.by_ref().collect::<Vec<_>>(), vec![
+            trade(1, QuoteCurrency::new(100, 0)),
+            trade(3, QuoteCurrency::new(1005, 1)),
+            trade(4, QuoteCurrency::new(101, 0)),
+        ]);
+
+        let report = validated.report();
+        assert_eq!(report.updates(), 4);
+        assert_eq!(report.emitted(), 3);
+        assert_eq!(report.invalid_price(), IssueCount {
+            detected: 1,
+            repaired: 0,
+            dropped: 1,
+        });
+        assert_eq!(report.off_tick(), IssueCount {
+            detected: 1,
+            repaired: 1,
+            dropped: 0,
+        });
+        assert_eq!(
+            report.first_issue(),
+            Some((2.into(), MarketDataIssue::InvalidPrice))
+        );
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.to_string(),
+            "updates: 4, emitted: 3, invalid price: 1 (repaired: 0, dropped: 1), off tick: 1 (repaired: 1, dropped: 0), crossed book: 0 (repaired: 0, dropped: 0), out of order: 0 (repaired: 0, dropped: 0)"
+        );
+    }
+
+    #[test]
+    fn validation_report_only() {
+        let bbas = [
+            mock_bba(100, 1),
+            Bba {
+                bid: QuoteCurrency::new(101, 0),
+                ask: QuoteCurrency::new(100, 0),
+                timestamp_exchange_ns: 2.into(),
+            },
+            mock_bba(100, 1),
+        ];
+        let mut validated = MarketDataValidation::builder()
+            .price_filter(price_filter())
+            .crossed_book(IssueAction::Report)
+            .out_of_order(IssueAction::Report)
+            .build()
+            .validate::<_, _, BaseCurrency<i64, DECIMALS>>(bbas);
+        assert_eq!(validated.by_ref().count(), 3);
+        let report = validated.report();
+        assert_eq!(report.crossed_book().detected(), 1);
+        assert_eq!(report.crossed_book().dropped(), 0);
+        assert_eq!(report.out_of_order().detected(), 1);
+        assert_eq!(report.out_of_order().dropped(), 0);
+    }
+
+    #[test]
+    fn validation_drops_out_of_order() {
+        let bbas = [
+            mock_bba(100, 1),
+            mock_bba(100, 3),
+            mock_bba(100, 2),
+            mock_bba(100, 4),
+        ];
+        let mut validated = MarketDataValidation::builder()
+            .price_filter(price_filter())
+            .build()
+            .validate::<_, _, BaseCurrency<i64, DECIMALS>>(bbas);
+        assert_eq!(
+            validated
+                .by_ref()
+                .map(|bba| bba.timestamp_exchange_ns)
+                .collect::<Vec<_>>(),
+            vec![1.into(), 3.into(), 4.into()]
+        );
+        assert_eq!(validated.report().out_of_order(), IssueCount {
+            detected: 1,
+            repaired: 0,
+            dropped: 1,
+        });
+    }
+
+    #[test]
+    fn validation_reorders_within_window() {
+        let bbas = [
+            mock_bba(100, 10),
+            mock_bba(100, 30),
+            mock_bba(100, 20),
+            mock_bba(100, 50),
+            mock_bba(100, 5),
+            mock_bba(100, 60),
+        ];
+        let mut validated = MarketDataValidation::builder()
+            .price_filter(price_filter())
+            .out_of_order(IssueAction::Repair)
+            .reorder_window_ns(15.into())
+            .build()
+            .validate::<_, _, BaseCurrency<i64, DECIMALS>>(bbas);
+        assert_eq!(
+            validated
+                .by_ref()
+                .map(|bba| bba.timestamp_exchange_ns)
+                .collect::<Vec<_>>(),
+            vec![10.into(), 20.into(), 30.into(), 50.into(), 60.into()]
+        );
+        assert_eq!(validated.report().out_of_order(), IssueCount {
+            detected: 2,
+            repaired: 1,
+            dropped: 1,
+        });
+    }
+}
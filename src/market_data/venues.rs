@@ -0,0 +1,245 @@
+//! The historical data formats of common venues and data vendors.
+//! Whether the quantity is denoted in the base or quote currency depends on the contract,
+//! e.g. inverse contracts are usually quoted in contracts worth one unit of the quote currency.
+
+use super::{
+    csv_format::{
+        CsvColumn,
+        TimestampFormat,
+        TimestampUnit,
+    },
+    csv_quotes::CsvBbaFormat,
+    csv_trades::{
+        CsvTradeFormat,
+        SideEncoding,
+    },
+};
+
+/// The side encoding of the venues writing `Buy` and `Sell` into a `side` column.
+fn side_column(buy: &str, sell: &str) -> SideEncoding {
+    SideEncoding::Column {
+        column: CsvColumn::from("side"),
+        buy: buy.to_string(),
+        sell: sell.to_string(),
+    }
+}
+
+impl CsvTradeFormat {
+    /// The aggregated trades of Binance (`aggTrades` on data.binance.vision), with the columns
+    /// `agg_trade_id,price,quantity,first_trade_id,last_trade_id,transact_time,is_buyer_maker`.
+    /// Older files have no header.
+    /// The timestamps of the futures files are in milliseconds and of recent spot files in microseconds.
+    pub fn binance_agg_trades(has_headers: bool, timestamp_unit: TimestampUnit) -> Self {
+        Self::builder()
+            .has_headers(has_headers)
+            .timestamp_column(5_usize)
+            .price_column(1_usize)
+            .quantity_column(2_usize)
+            .timestamp_format(timestamp_unit)
+            .side_encoding(SideEncoding::Column {
+                column: CsvColumn::from(6_usize),
+                buy: "false".to_string(),
+                // The buyer being the maker means the taker sold.
+                sell: "true".to_string(),
+            })
+            .build()
+    }
+
+    /// The public trades of Bybit (public.bybit.com/trading), with the columns
+    /// `timestamp,symbol,side,size,price,...` and the timestamps in fractional seconds.
+    pub fn bybit_trades() -> Self {
+        Self::builder()
+            .timestamp_format(TimestampUnit::Seconds)
+            .side_encoding(side_column("Buy", "Sell"))
+            .symbol_column("symbol")
+            .build()
+    }
+
+    /// The trade dumps of BitMEX (public.bitmex.com/data/trade), with the columns
+    /// `timestamp,symbol,side,size,price,...` and timestamps like `2014-11-22D03:33:14.372478000`.
+    /// Every file contains all symbols, so select one with `with_symbol`.
+    pub fn bitmex_trades() -> Self {
+        Self::builder()
+            .timestamp_format(TimestampFormat::DateTime)
+            .side_encoding(side_column("Buy", "Sell"))
+            .symbol_column("symbol")
+            .build()
+    }
+
+    /// The `trades` csv files of Tardis, with the columns
+    /// `exchange,symbol,timestamp,local_timestamp,id,side,price,amount` and microsecond timestamps.
+    /// Trades with an `unknown` side yield an error.
+    pub fn tardis_trades() -> Self {
+        Self::builder()
+            .quantity_column("amount")
+            .timestamp_format(TimestampUnit::Microseconds)
+            .side_encoding(side_column("buy", "sell"))
+            .symbol_column("symbol")
+            .build()
+    }
+}
+
+impl CsvBbaFormat {
+    /// The `quotes` csv files of Tardis, with the columns
+    /// `exchange,symbol,timestamp,local_timestamp,ask_amount,ask_price,bid_price,bid_amount` and microsecond timestamps.
+    pub fn tardis_quotes() -> Self {
+        Self::builder()
+            .timestamp_format(TimestampUnit::Microseconds)
+            .symbol_column("symbol")
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DECIMALS,
+        market_data::{
+            CsvBbaReader,
+            CsvTradeReader,
+        },
+        market_update::{
+            Bba,
+            Trade,
+        },
+        types::{
+            BaseCurrency,
+            Currency,
+            MarketDataError,
+            QuoteCurrency,
+            Side,
+        },
+    };
+
+    type BaseTrade = Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>;
+    type QuoteTrade = Trade<i64, DECIMALS, QuoteCurrency<i64, DECIMALS>>;
+
+    fn trades<BaseOrQuote: Currency<i64, DECIMALS>>(
+        data: &str,
+        format: &CsvTradeFormat,
+    ) -> Vec<Result<Trade<i64, DECIMALS, BaseOrQuote>, MarketDataError>> {
+        CsvTradeReader::from_reader(data.as_bytes(), format)
+            .unwrap()
+            .collect()
+    }
+
+    #[test]
+    fn venue_binance_agg_trades() {
+        let data = "agg_trade_id,price,quantity,first_trade_id,last_trade_id,transact_time,is_buyer_maker\n1,30000.1,0.002,1,1,1700000000000,true\n2,30000.2,0.5,2,3,1700000000001,false\n";
+        assert_eq!(
+            trades::<BaseCurrency<i64, DECIMALS>>(
+                data,
+                &CsvTradeFormat::binance_agg_trades(true, TimestampUnit::Milliseconds)
+            ),
+            vec![
+                Ok(BaseTrade {
+                    timestamp_exchange_ns: 1_700_000_000_000_000_000.into(),
+                    price: QuoteCurrency::new(300001, 1),
+                    quantity: BaseCurrency::new(2, 3),
+                    side: Side::Sell,
+                }),
+                Ok(BaseTrade {
+                    timestamp_exchange_ns: 1_700_000_000_001_000_000.into(),
+                    price: QuoteCurrency::new(300002, 1),
+                    quantity: BaseCurrency::new(5, 1),
+                    side: Side::Buy,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn venue_bybit_trades() {
+        let data = "timestamp,symbol,side,size,price,tickDirection,trdMatchID,grossValue,homeNotional,foreignNotional\n1585180700.0647,BTCUSD,Sell,100,6698.5,ZeroMinusTick,a8d8a1c8-42b5-5b1b-9a4b-1a7f3c1a0f1b,1492871.53,0.014928715,100\n";
+        assert_eq!(
+            trades::<QuoteCurrency<i64, DECIMALS>>(data, &CsvTradeFormat::bybit_trades()),
+            vec![Ok(QuoteTrade {
+                timestamp_exchange_ns: 1_585_180_700_064_700_000.into(),
+                price: QuoteCurrency::new(66985, 1),
+                quantity: QuoteCurrency::new(100, 0),
+                side: Side::Sell,
+            })]
+        );
+    }
+
+    #[test]
+    fn venue_bitmex_trades() {
+        let data = "timestamp,symbol,side,size,price,tickDirection,trdMatchID,grossValue,homeNotional,foreignNotional\n2014-11-22D03:33:14.372478000,XBTZ14,Buy,1,350.5,PlusTick,6f1f7ee2-0d7c-4a4b-9e0c-3c7b1f1e7a1b,285306,0.00285306,1\n2014-11-22D03:33:15.000000000,XBTUSD,Sell,20,351,MinusTick,00000000-0000-0000-0000-000000000000,5698000,0.05698,20\n";
+        let format = CsvTradeFormat::bitmex_trades().with_symbol("XBTUSD");
+        assert_eq!(trades::<QuoteCurrency<i64, DECIMALS>>(data, &format), vec![
+            Ok(QuoteTrade {
+                timestamp_exchange_ns: 1_416_627_195_000_000_000.into(),
+                price: QuoteCurrency::new(351, 0),
+                quantity: QuoteCurrency::new(20, 0),
+                side: Side::Sell,
+            })
+        ]);
+    }
+
+    #[test]
+    fn venue_tardis_trades() {
+        let data = "exchange,symbol,timestamp,local_timestamp,id,side,price,amount\nbinance-futures,BTCUSDT,1585699202957000,1585699203089980,1,buy,6399.99,0.5\nbinance-futures,BTCUSDT,1585699202958000,1585699203089990,2,unknown,6399.99,0.5\n";
+        assert_eq!(
+            trades::<BaseCurrency<i64, DECIMALS>>(data, &CsvTradeFormat::tardis_trades()),
+            vec![
+                Ok(BaseTrade {
+                    timestamp_exchange_ns: 1_585_699_202_957_000_000.into(),
+                    price: QuoteCurrency::new(639999, 2),
+                    quantity: BaseCurrency::new(5, 1),
+                    side: Side::Buy,
+                }),
+                Err(MarketDataError::InvalidField {
+                    line: 3,
+                    field: "side",
+                    value: "unknown".to_string(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn venue_tardis_quotes() {
+        let data = "exchange,symbol,timestamp,local_timestamp,ask_amount,ask_price,bid_price,bid_amount\nbinance-futures,BTCUSDT,1585699200245000,1585699200355897,0.001,6399.5,6399.49,1.2\n";
+        let quotes: Vec<_> = CsvBbaReader::<_, i64, DECIMALS>::from_reader(
+            data.as_bytes(),
+            &CsvBbaFormat::tardis_quotes(),
+        )
+        .unwrap()
+        .collect();
+        assert_eq!(quotes, vec![Ok(Bba {
+            bid: QuoteCurrency::new(639949, 2),
+            ask: QuoteCurrency::new(63995, 1),
+            timestamp_exchange_ns: 1_585_699_200_245_000_000.into(),
+        })]);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn venue_gzip_file() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join("lfest_venue_gzip_file.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            Default::default(),
+        );
+        encoder
+            .write_all(b"exchange,symbol,timestamp,local_timestamp,id,side,price,amount\nbybit,BTCUSD,1,1,1,sell,100,10\n")
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let trades: Vec<QuoteTrade> =
+            CsvTradeReader::from_path(&path, &CsvTradeFormat::tardis_trades())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(trades, vec![QuoteTrade {
+            timestamp_exchange_ns: 1_000.into(),
+            price: QuoteCurrency::new(100, 0),
+            quantity: QuoteCurrency::new(10, 0),
+            side: Side::Sell,
+        }]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,326 @@
+//! Models how our own aggressive orders move the market,
+//! which the recorded market data cannot reflect.
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::{
+    types::{
+        ConfigError,
+        Currency,
+        Mon,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+    utils::decimal_from_f64,
+};
+
+/// The price impact of our own market orders, shifting the bid and ask the `Exchange` uses
+/// for subsequent market order fills, limit order checks and liquidation checks.
+///
+/// A fill of `quantity` at `price` moves the price by `coefficient * price * (quantity / reference_quantity)^exponent`,
+/// upwards for buys and downwards for sells. An `exponent` of 0.5 yields the square-root law.
+/// The temporary part decays exponentially with the `half_life`, while the permanent part persists.
+/// The impact is capped at the recorded ask in either direction, and selling pressure never pushes the bid to zero.
+/// Each part is also capped at the price of the most recent fill, so a misconfigured model can't overflow the currency.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct MarketImpactModel<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The temporary impact of a fill of `reference_quantity`, as a fraction of the fill price.
+    #[getset(get_copy = "pub")]
+    temporary_coefficient: f64,
+
+    /// The permanent impact of a fill of `reference_quantity`, as a fraction of the fill price.
+    #[getset(get_copy = "pub")]
+    permanent_coefficient: f64,
+
+    /// The exponent of the impact in the filled quantity.
+    #[getset(get_copy = "pub")]
+    exponent: f64,
+
+    /// The quantity the impact is normalized by, e.g the typical daily volume.
+    #[getset(get_copy = "pub")]
+    reference_quantity: BaseOrQuote,
+
+    /// The time after which the temporary impact has decayed to half its value.
+    #[getset(get_copy = "pub")]
+    half_life: TimestampNs,
+
+    /// The temporary price impact at `last_fill_ts_ns`.
+    temporary_impact: QuoteCurrency<I, D>,
+
+    /// The accumulated permanent price impact.
+    permanent_impact: QuoteCurrency<I, D>,
+
+    last_fill_ts_ns: TimestampNs,
+
+    _quote: std::marker::PhantomData<QuoteCurrency<I, D>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> MarketImpactModel<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a new `MarketImpactModel`.
+    /// The coefficients must be finite and not negative, the `exponent` finite and positive,
+    /// and the `reference_quantity` and `half_life` positive.
+    pub fn new(
+        temporary_coefficient: f64,
+        permanent_coefficient: f64,
+        exponent: f64,
+        reference_quantity: BaseOrQuote,
+        half_life: TimestampNs,
+    ) -> Result<Self, ConfigError> {
+        let valid_coefficient = |c: f64| c.is_finite() && c >= 0.0;
+        if !valid_coefficient(temporary_coefficient)
+            || !valid_coefficient(permanent_coefficient)
+            || !exponent.is_finite()
+            || exponent <= 0.0
+            || reference_quantity <= BaseOrQuote::zero()
+            || half_life <= TimestampNs::from(0)
+        {
+            return Err(ConfigError::InvalidMarketImpactModel);
+        }
+
+        Ok(Self {
+            temporary_coefficient,
+            permanent_coefficient,
+            exponent,
+            reference_quantity,
+            half_life,
+            temporary_impact: QuoteCurrency::zero(),
+            permanent_impact: QuoteCurrency::zero(),
+            last_fill_ts_ns: TimestampNs::from(0),
+            _quote: std::marker::PhantomData,
+        })
+    }
+
+    /// The temporary impact decayed from the most recent fill up to `ts_ns`.
+    /// Decaying from the fill each time keeps the rounding to the currency precision from accumulating.
+    fn temporary_impact_at(&self, ts_ns: TimestampNs) -> QuoteCurrency<I, D> {
+        if ts_ns <= self.last_fill_ts_ns {
+            return self.temporary_impact;
+        }
+        let elapsed = (ts_ns - self.last_fill_ts_ns).get() as f64;
+        let impact: f64 = self.temporary_impact.into();
+        // The decayed impact is smaller than the representable `temporary_impact`.
+        bounded_impact(
+            impact * 0.5_f64.powf(elapsed / self.half_life.get() as f64),
+            self.temporary_impact.abs(),
+        )
+    }
+
+    /// Record the fill of our own market order.
+    pub(crate) fn record_fill(
+        &mut self,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+        ts_ns: TimestampNs,
+    ) {
+        assert2::debug_assert!(quantity > BaseOrQuote::zero());
+        assert2::debug_assert!(price > QuoteCurrency::zero());
+        self.temporary_impact = self.temporary_impact_at(ts_ns);
+        self.last_fill_ts_ns = self.last_fill_ts_ns.max(ts_ns);
+
+        let quantity: f64 = quantity.into();
+        let reference_quantity: f64 = self.reference_quantity.into();
+        let price_f64: f64 = price.into();
+        let sign = match side {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        };
+        let scale = sign * price_f64 * (quantity / reference_quantity).powf(self.exponent);
+        self.temporary_impact = (self.temporary_impact
+            + bounded_impact(self.temporary_coefficient * scale, price))
+        .clamp(-price, price);
+        self.permanent_impact = (self.permanent_impact
+            + bounded_impact(self.permanent_coefficient * scale, price))
+        .clamp(-price, price);
+    }
+
+    /// The price impact at `ts_ns`, clamped to `max_impact` in either direction
+    /// and rounded towards zero to a multiple of `tick_size`.
+    pub(crate) fn price_impact(
+        &self,
+        ts_ns: TimestampNs,
+        tick_size: QuoteCurrency<I, D>,
+        max_impact: QuoteCurrency<I, D>,
+    ) -> QuoteCurrency<I, D> {
+        assert2::debug_assert!(max_impact >= QuoteCurrency::zero());
+        (self.temporary_impact_at(ts_ns) + self.permanent_impact)
+            .clamp(-max_impact, max_impact)
+            .quantize_round_to_zero(tick_size)
+    }
+}
+
+/// Convert an `impact` computed in `f64` into the currency, capped at `bound` in the direction of its sign.
+/// A `NaN` impact moves nothing.
+fn bounded_impact<I, const D: u8>(impact: f64, bound: QuoteCurrency<I, D>) -> QuoteCurrency<I, D>
+where
+    I: Mon<D>,
+{
+    if impact.is_nan() {
+        return QuoteCurrency::zero();
+    }
+    let max: f64 = bound.into();
+    let capped = if impact.is_sign_negative() { -bound } else { bound };
+    decimal_from_f64(impact.clamp(-max, max)).map_or(capped, QuoteCurrency::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    fn model(
+        temporary_coefficient: f64,
+        permanent_coefficient: f64,
+    ) -> MarketImpactModel<i64, 2, BaseCurrency<i64, 2>> {
+        MarketImpactModel::new(
+            temporary_coefficient,
+            permanent_coefficient,
+            0.5,
+            BaseCurrency::new(100, 0),
+            1_000.into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn market_impact_model_new() {
+        let new = |temporary: f64, exponent: f64, reference: i64, half_life: i64| {
+            MarketImpactModel::<i64, 2, BaseCurrency<i64, 2>>::new(
+                temporary,
+                0.0,
+                exponent,
+                BaseCurrency::new(reference, 0),
+                half_life.into(),
+            )
+            .map(|_| ())
+        };
+        assert_eq!(new(0.1, 0.5, 100, 1), Ok(()));
+        let err = Err(ConfigError::InvalidMarketImpactModel);
+        assert_eq!(new(-0.1, 0.5, 100, 1), err);
+        assert_eq!(new(f64::NAN, 0.5, 100, 1), err);
+        assert_eq!(new(0.1, 0.0, 100, 1), err);
+        assert_eq!(new(0.1, 0.5, 0, 1), err);
+        assert_eq!(new(0.1, 0.5, 100, 0), err);
+    }
+
+    #[test]
+    fn market_impact_model_square_root_law() {
+        let tick = QuoteCurrency::new(1, 2);
+        let max = QuoteCurrency::new(100, 0);
+        let mut model = model(0.01, 0.0);
+        // A quarter of the reference quantity moves the price by half the coefficient.
+        model.record_fill(
+            Side::Buy,
+            BaseCurrency::new(25, 0),
+            QuoteCurrency::new(100, 0),
+            0.into(),
+        );
+        assert_eq!(
+            model.price_impact(0.into(), tick, max),
+            QuoteCurrency::new(5, 1)
+        );
+
+        // Temporary impact decays with the half life.
+        assert_eq!(
+            model.price_impact(1_000.into(), tick, max),
+            QuoteCurrency::new(25, 2)
+        );
+        assert_eq!(
+            model.price_impact(3_000.into(), tick, max),
+            QuoteCurrency::new(6, 2)
+        );
+    }
+
+    #[test]
+    fn market_impact_model_permanent() {
+        let tick = QuoteCurrency::new(1, 2);
+        let max = QuoteCurrency::new(100, 0);
+        let mut model = model(0.0, 0.01);
+        model.record_fill(
+            Side::Sell,
+            BaseCurrency::new(100, 0),
+            QuoteCurrency::new(100, 0),
+            0.into(),
+        );
+        assert_eq!(
+            model.price_impact(0.into(), tick, max),
+            QuoteCurrency::new(-1, 0)
+        );
+        assert_eq!(
+            model.price_impact(1_000_000.into(), tick, max),
+            QuoteCurrency::new(-1, 0)
+        );
+
+        // An opposing fill offsets the impact.
+        model.record_fill(
+            Side::Buy,
+            BaseCurrency::new(100, 0),
+            QuoteCurrency::new(100, 0),
+            1_000_000.into(),
+        );
+        assert_eq!(
+            model.price_impact(1_000_000.into(), tick, max),
+            QuoteCurrency::new(0, 0)
+        );
+    }
+
+    #[test]
+    fn market_impact_model_clamped() {
+        let tick = QuoteCurrency::new(1, 2);
+        let mut model = model(1_000.0, 1_000.0);
+        model.record_fill(
+            Side::Buy,
+            BaseCurrency::new(1_000_000, 0),
+            QuoteCurrency::new(100, 0),
+            0.into(),
+        );
+        assert_eq!(
+            model.price_impact(0.into(), tick, QuoteCurrency::new(101, 0)),
+            QuoteCurrency::new(101, 0)
+        );
+
+        // Selling pressure is capped in the other direction.
+        let mut model = model(1_000.0, 1_000.0);
+        model.record_fill(
+            Side::Sell,
+            BaseCurrency::new(10_000_000_000, 0),
+            QuoteCurrency::new(100, 0),
+            0.into(),
+        );
+        assert_eq!(
+            model.price_impact(0.into(), tick, QuoteCurrency::new(101, 0)),
+            QuoteCurrency::new(-101, 0)
+        );
+        // Each part is capped at the fill price, so opposing fills still offset each other.
+        model.record_fill(
+            Side::Buy,
+            BaseCurrency::new(10_000_000_000, 0),
+            QuoteCurrency::new(100, 0),
+            0.into(),
+        );
+        assert_eq!(
+            model.price_impact(0.into(), tick, QuoteCurrency::new(101, 0)),
+            QuoteCurrency::new(0, 0)
+        );
+    }
+
+    #[test]
+    fn market_impact_bounded_impact() {
+        let bound = QuoteCurrency::<i64, 2>::new(100, 0);
+        assert_eq!(bounded_impact(f64::NAN, bound), QuoteCurrency::new(0, 0));
+        assert_eq!(bounded_impact(f64::INFINITY, bound), bound);
+        assert_eq!(bounded_impact(f64::NEG_INFINITY, bound), -bound);
+        assert_eq!(bounded_impact(-1e300, bound), -bound);
+        assert_eq!(bounded_impact(-12.34, bound), QuoteCurrency::new(-1234, 2));
+    }
+}
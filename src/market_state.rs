@@ -4,6 +4,7 @@ use getset::{
     Getters,
     Setters,
 };
+use num_traits::Zero;
 
 use crate::{
     prelude::{
@@ -11,6 +12,7 @@ use crate::{
         MarketUpdate,
         Mon,
         PriceFilter,
+        PriceReference,
         QuoteCurrency,
     },
     types::TimestampNs,
@@ -25,20 +27,28 @@ pub struct MarketState<I, const D: u8>
 where
     I: Mon<D>,
 {
-    /// The current bid
+    /// The current bid as recorded in the market data.
     #[getset(get_copy = "pub", set = "pub(crate)")]
-    bid: QuoteCurrency<I, D>,
+    recorded_bid: QuoteCurrency<I, D>,
 
-    /// The current ask
+    /// The current ask as recorded in the market data.
     #[getset(get_copy = "pub", set = "pub(crate)")]
-    ask: QuoteCurrency<I, D>,
+    recorded_ask: QuoteCurrency<I, D>,
+
+    /// The shift of the bid and ask caused by our own market orders, see `MarketImpactModel`.
+    #[getset(get_copy = "pub", set = "pub(crate)")]
+    price_impact: QuoteCurrency<I, D>,
 
     /// The last trade price.
     #[getset(get_copy = "pub", set = "pub")]
     last_trade_price: QuoteCurrency<I, D>,
 
+    /// The last mark price of the venue.
+    #[getset(get_copy = "pub", set = "pub(crate)")]
+    mark_price: QuoteCurrency<I, D>,
+
     /// The current timestamp in nanoseconds
-    #[getset(get_copy = "pub")]
+    #[getset(get_copy = "pub", set = "pub(crate)")]
     current_ts_ns: TimestampNs,
 
     /// Used for synchronizing orders.
@@ -51,7 +61,10 @@ impl<I: Mon<D>, const D: u8> std::fmt::Display for MarketState<I, D> {
         write!(
             f,
             "MarketState( bid: {}, ask: {}, ts_ns: {}, step: {} )",
-            self.bid, self.ask, self.current_ts_ns, self.step
+            self.bid(),
+            self.ask(),
+            self.current_ts_ns,
+            self.step
         )
     }
 }
@@ -82,10 +95,49 @@ where
         self.step += 1;
     }
 
+    /// The current bid, shifted by the `price_impact` of our own market orders.
+    #[inline(always)]
+    pub fn bid(&self) -> QuoteCurrency<I, D> {
+        self.recorded_bid + self.price_impact
+    }
+
+    /// The current ask, shifted by the `price_impact` of our own market orders.
+    #[inline(always)]
+    pub fn ask(&self) -> QuoteCurrency<I, D> {
+        self.recorded_ask + self.price_impact
+    }
+
+    /// Set the current bid as recorded in the market data.
+    #[inline(always)]
+    pub(crate) fn set_bid(&mut self, bid: QuoteCurrency<I, D>) {
+        self.recorded_bid = bid;
+    }
+
+    /// Set the current ask as recorded in the market data.
+    #[inline(always)]
+    pub(crate) fn set_ask(&mut self, ask: QuoteCurrency<I, D>) {
+        self.recorded_ask = ask;
+    }
+
     /// Get the mid price
     #[inline(always)]
     pub fn mid_price(&self) -> QuoteCurrency<I, D> {
-        (self.bid + self.ask) / Decimal::TWO
+        (self.bid() + self.ask()) / Decimal::TWO
+    }
+
+    /// Get the price selected by `reference`, which anchors the price band of the `PriceFilter`.
+    /// Falls back to the mid price as long as no trade or mark price has been observed.
+    #[inline]
+    pub fn reference_price(&self, reference: PriceReference) -> QuoteCurrency<I, D> {
+        let price = match reference {
+            PriceReference::MidPrice => return self.mid_price(),
+            PriceReference::LastTradePrice => self.last_trade_price,
+            PriceReference::MarkPrice => self.mark_price,
+        };
+        if price.is_zero() {
+            return self.mid_price();
+        }
+        price
     }
 
     /// Get the last observed timestamp in nanoseconts
@@ -104,9 +156,11 @@ where
         step: u64,
     ) -> Self {
         Self {
-            bid,
-            ask,
+            recorded_bid: bid,
+            recorded_ask: ask,
+            price_impact: QuoteCurrency::zero(),
             last_trade_price,
+            mark_price: QuoteCurrency::zero(),
             current_ts_ns,
             step,
         }
@@ -117,7 +171,10 @@ where
 mod test {
     use super::*;
     use crate::{
-        prelude::Bba,
+        prelude::{
+            Bba,
+            MarkPrice,
+        },
         types::BaseCurrency,
     };
 
@@ -144,4 +201,43 @@ mod test {
         );
         assert_eq!(state.mid_price(), QuoteCurrency::new(1005, 1));
     }
+
+    #[test]
+    fn market_state_reference_price() {
+        let mut state = MarketState::<i64, 1>::default();
+        let pf = PriceFilter::default();
+        state.update_state::<_, BaseCurrency<_, 1>>(
+            &Bba {
+                bid: QuoteCurrency::<i64, 1>::new(100, 0),
+                ask: QuoteCurrency::new(102, 0),
+                timestamp_exchange_ns: 1.into(),
+            },
+            &pf,
+        );
+        // Nothing observed yet, so it falls back to the mid price.
+        assert_eq!(
+            state.reference_price(PriceReference::MarkPrice),
+            QuoteCurrency::new(101, 0)
+        );
+        assert_eq!(
+            state.reference_price(PriceReference::LastTradePrice),
+            QuoteCurrency::new(101, 0)
+        );
+
+        state.update_state::<_, BaseCurrency<_, 1>>(
+            &MarkPrice {
+                price: QuoteCurrency::<i64, 1>::new(1015, 1),
+                timestamp_exchange_ns: 2.into(),
+            },
+            &pf,
+        );
+        assert_eq!(
+            state.reference_price(PriceReference::MarkPrice),
+            QuoteCurrency::new(1015, 1)
+        );
+        assert_eq!(
+            state.reference_price(PriceReference::MidPrice),
+            QuoteCurrency::new(101, 0)
+        );
+    }
 }
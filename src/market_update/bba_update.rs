@@ -2,7 +2,10 @@ use const_decimal::Decimal;
 
 use super::MarketUpdate;
 use crate::{
-    market_update::market_update_trait::Exhausted,
+    market_update::market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
     order_filters::{
         enforce_bid_ask_spread,
         enforce_max_price,
@@ -17,9 +20,11 @@ use crate::{
         Pending,
         PriceFilter,
         QuoteCurrency,
+        TickRounding,
     },
     types::{
         PriceFilterError,
+        Side,
         TimestampNs,
         UserOrderId,
     },
@@ -79,6 +84,21 @@ where
         );
     }
 
+    #[inline(always)]
+    fn price_contact(
+        &self,
+        _order_side: Side,
+        _limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        None
+    }
+
+    #[inline]
+    fn snap_to_tick(&mut self, price_filter: &PriceFilter<I, D>) {
+        self.bid = price_filter.snap_to_tick(self.bid, TickRounding::Down);
+        self.ask = price_filter.snap_to_tick(self.ask, TickRounding::Up);
+    }
+
     fn validate_market_update(
         &self,
         price_filter: &PriceFilter<I, D>,
@@ -87,12 +107,21 @@ where
         enforce_min_price(price_filter.min_price(), self.ask)?;
         enforce_max_price(price_filter.max_price(), self.bid)?;
         enforce_max_price(price_filter.max_price(), self.ask)?;
-        enforce_step_size(price_filter.tick_size(), self.bid)?;
-        enforce_step_size(price_filter.tick_size(), self.ask)?;
+        enforce_step_size(price_filter.tick_size_at(self.bid), self.bid)?;
+        enforce_step_size(price_filter.tick_size_at(self.ask), self.ask)?;
         enforce_bid_ask_spread(self.bid, self.ask)?;
         Ok(())
     }
 
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        price_filter.validate_price_band(self.bid, reference_price)?;
+        price_filter.validate_price_band(self.ask, reference_price)
+    }
+
     #[inline(always)]
     fn update_market_state(&self, market_state: &mut MarketState<I, D>) {
         market_state.set_bid(self.bid);
@@ -3,7 +3,10 @@ use typed_builder::TypedBuilder;
 
 use super::MarketUpdate;
 use crate::{
-    market_update::market_update_trait::Exhausted,
+    market_update::market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
     order_filters::{
         enforce_bid_ask_spread,
         enforce_max_price,
@@ -19,6 +22,7 @@ use crate::{
         PriceFilter,
         QuoteCurrency,
         Side,
+        TickRounding,
     },
     types::{
         PriceFilterError,
@@ -161,6 +165,32 @@ where
         }
     }
 
+    #[inline]
+    fn price_contact(
+        &self,
+        order_side: Side,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        let traded_through = match order_side {
+            Side::Buy if self.low <= limit_price => self.low < limit_price,
+            Side::Sell if self.high >= limit_price => self.high > limit_price,
+            _ => return None,
+        };
+        Some(PriceContact {
+            traded_through,
+            volume_through: None,
+            volume_touching: None,
+        })
+    }
+
+    #[inline]
+    fn snap_to_tick(&mut self, price_filter: &PriceFilter<I, D>) {
+        self.bid = price_filter.snap_to_tick(self.bid, TickRounding::Down);
+        self.ask = price_filter.snap_to_tick(self.ask, TickRounding::Up);
+        self.low = price_filter.snap_to_tick(self.low, TickRounding::Down);
+        self.high = price_filter.snap_to_tick(self.high, TickRounding::Up);
+    }
+
     fn validate_market_update(
         &self,
         price_filter: &PriceFilter<I, D>,
@@ -173,15 +203,26 @@ where
         enforce_max_price(price_filter.max_price(), self.ask)?;
         enforce_max_price(price_filter.max_price(), self.low)?;
         enforce_max_price(price_filter.max_price(), self.high)?;
-        enforce_step_size(price_filter.tick_size(), self.bid)?;
-        enforce_step_size(price_filter.tick_size(), self.ask)?;
-        enforce_step_size(price_filter.tick_size(), self.low)?;
-        enforce_step_size(price_filter.tick_size(), self.high)?;
+        enforce_step_size(price_filter.tick_size_at(self.bid), self.bid)?;
+        enforce_step_size(price_filter.tick_size_at(self.ask), self.ask)?;
+        enforce_step_size(price_filter.tick_size_at(self.low), self.low)?;
+        enforce_step_size(price_filter.tick_size_at(self.high), self.high)?;
         enforce_bid_ask_spread(self.bid, self.ask)?;
         enforce_bid_ask_spread(self.low, self.high)?;
         Ok(())
     }
 
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        for price in [self.bid, self.ask, self.low, self.high] {
+            price_filter.validate_price_band(price, reference_price)?;
+        }
+        Ok(())
+    }
+
     #[inline]
     fn update_market_state(&self, market_state: &mut MarketState<I, D>) {
         market_state.set_bid(self.bid);
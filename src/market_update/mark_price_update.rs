@@ -0,0 +1,152 @@
+use super::MarketUpdate;
+use crate::{
+    market_update::market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
+    order_filters::enforce_min_price,
+    prelude::{
+        Currency,
+        LimitOrder,
+        MarketState,
+        Mon,
+        Pending,
+        PriceFilter,
+        QuoteCurrency,
+    },
+    types::{
+        PriceFilterError,
+        Side,
+        TimestampNs,
+        UserOrderId,
+    },
+};
+
+/// An update to the mark price of the venue, which usually tracks an index price.
+/// It anchors the price band of the `PriceFilter` when its `PriceReference` is `MarkPrice`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MarkPrice<I, const D: u8>
+where
+    I: Mon<D>,
+{
+    /// The new mark price.
+    pub price: QuoteCurrency<I, D>,
+    /// The nanosecond timestamp at which this event occurred at the exchange.
+    pub timestamp_exchange_ns: TimestampNs,
+}
+
+impl<I, const D: u8> std::fmt::Display for MarkPrice<I, D>
+where
+    I: Mon<D>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mark_price: {}, ts: {}",
+            self.price, self.timestamp_exchange_ns
+        )
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> MarketUpdate<I, D, BaseOrQuote> for MarkPrice<I, D>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    const CAN_FILL_LIMIT_ORDERS: bool = false;
+
+    fn limit_order_filled<UserOrderIdT: UserOrderId>(
+        &mut self,
+        _limit_order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    ) -> Option<(BaseOrQuote, Exhausted)> {
+        std::hint::cold_path();
+        unreachable!(
+            "This should never be called, because a mark price update can never fill a limit order."
+        );
+    }
+
+    #[inline(always)]
+    fn price_contact(
+        &self,
+        _order_side: Side,
+        _limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        None
+    }
+
+    fn validate_market_update(
+        &self,
+        _price_filter: &PriceFilter<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        // The mark price is derived from other prices, so it does not need to conform to the tick size.
+        enforce_min_price(None, self.price)
+    }
+
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        price_filter.validate_price_band(self.price, reference_price)
+    }
+
+    #[inline(always)]
+    fn update_market_state(&self, market_state: &mut MarketState<I, D>) {
+        market_state.set_mark_price(self.price);
+    }
+
+    #[inline(always)]
+    fn timestamp_exchange_ns(&self) -> TimestampNs {
+        self.timestamp_exchange_ns
+    }
+
+    #[inline(always)]
+    fn can_fill_bids(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn can_fill_asks(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    #[test]
+    fn mark_price_update_display() {
+        let update = MarkPrice {
+            price: QuoteCurrency::<i64, 1>::new(100, 0),
+            timestamp_exchange_ns: 1.into(),
+        };
+        assert_eq!(&update.to_string(), "mark_price: 100.0 Quote, ts: 1");
+    }
+
+    #[test]
+    fn mark_price_update_validate() {
+        let pf = PriceFilter::default();
+        <MarkPrice<i64, 1> as MarketUpdate<i64, 1, BaseCurrency<i64, 1>>>::validate_market_update(
+            &MarkPrice {
+                price: QuoteCurrency::new(1005, 1),
+                timestamp_exchange_ns: 1.into(),
+            },
+            &pf,
+        )
+        .unwrap();
+        assert_eq!(
+            <MarkPrice<i64, 1> as MarketUpdate<i64, 1, BaseCurrency<i64, 1>>>::validate_market_update(
+                &MarkPrice {
+                    price: QuoteCurrency::zero(),
+                    timestamp_exchange_ns: 1.into(),
+                },
+                &pf,
+            ),
+            Err(PriceFilterError::PriceTooLow)
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use super::{
+    Bba,
+    Candle,
+    MarkPrice,
+    MarketUpdate,
+    OhlcvCandle,
+    SmartCandle,
+    Trade,
+    market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
+};
+use crate::{
+    prelude::{
+        Currency,
+        LimitOrder,
+        MarketState,
+        Mon,
+        Pending,
+        PriceFilter,
+        QuoteCurrency,
+    },
+    types::{
+        PriceFilterError,
+        Side,
+        TimestampNs,
+        UserOrderId,
+    },
+};
+
+/// Any of the built-in market updates, so a single `Exchange::update_state` loop
+/// can process quotes, trades, candles and mark prices interleaved in time.
+/// See `MarketEventMerge` for combining several sorted streams into one.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::From)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum MarketEvent<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    Bba(Bba<I, D>),
+    Trade(Trade<I, D, BaseOrQuote>),
+    Candle(Candle<I, D>),
+    OhlcvCandle(OhlcvCandle<I, D, BaseOrQuote>),
+    SmartCandle(SmartCandle<I, D, BaseOrQuote>),
+    MarkPrice(MarkPrice<I, D>),
+}
+
+/// Evaluate `$body` with `$update` bound to the inner market update of `$event`.
+macro_rules! with_update {
+    ($event:expr, $update:ident => $body:expr) => {
+        match $event {
+            MarketEvent::Bba($update) => $body,
+            MarketEvent::Trade($update) => $body,
+            MarketEvent::Candle($update) => $body,
+            MarketEvent::OhlcvCandle($update) => $body,
+            MarketEvent::SmartCandle($update) => $body,
+            MarketEvent::MarkPrice($update) => $body,
+        }
+    };
+}
+
+impl<I, const D: u8, BaseOrQuote> std::fmt::Display for MarketEvent<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        with_update!(self, update => std::fmt::Display::fmt(update, f))
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> MarketUpdate<I, D, BaseOrQuote> for MarketEvent<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    // Whether an event can fill is decided at runtime by `can_fill_bids` and `can_fill_asks`.
+    const CAN_FILL_LIMIT_ORDERS: bool = true;
+
+    #[inline]
+    fn can_fill_bids(&self) -> bool {
+        with_update!(self, update => MarketUpdate::<I, D, BaseOrQuote>::can_fill_bids(update))
+    }
+
+    #[inline]
+    fn can_fill_asks(&self) -> bool {
+        with_update!(self, update => MarketUpdate::<I, D, BaseOrQuote>::can_fill_asks(update))
+    }
+
+    #[inline]
+    fn limit_order_filled<UserOrderIdT: UserOrderId>(
+        &mut self,
+        limit_order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    ) -> Option<(BaseOrQuote, Exhausted)> {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::limit_order_filled(update, limit_order)
+        })
+    }
+
+    #[inline]
+    fn price_contact(
+        &self,
+        order_side: Side,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::price_contact(update, order_side, limit_price)
+        })
+    }
+
+    #[inline]
+    fn first_filled_side(&self) -> Side {
+        with_update!(self, update => MarketUpdate::<I, D, BaseOrQuote>::first_filled_side(update))
+    }
+
+    #[inline]
+    fn snap_to_tick(&mut self, price_filter: &PriceFilter<I, D>) {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::snap_to_tick(update, price_filter)
+        })
+    }
+
+    fn validate_market_update(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::validate_market_update(update, price_filter)
+        })
+    }
+
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::validate_price_band(
+                update,
+                price_filter,
+                reference_price,
+            )
+        })
+    }
+
+    #[inline]
+    fn update_market_state(&self, market_state: &mut MarketState<I, D>) {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::update_market_state(update, market_state)
+        })
+    }
+
+    #[inline]
+    fn timestamp_exchange_ns(&self) -> TimestampNs {
+        with_update!(self, update => {
+            MarketUpdate::<I, D, BaseOrQuote>::timestamp_exchange_ns(update)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DECIMALS,
+        types::BaseCurrency,
+    };
+
+    type Event = MarketEvent<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>;
+
+    #[test]
+    fn market_event_delegates() {
+        let bba = Event::from(Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 5.into(),
+        });
+        assert_eq!(bba.timestamp_exchange_ns(), 5.into());
+        assert!(!bba.can_fill_bids());
+        assert!(!bba.can_fill_asks());
+        assert_eq!(
+            bba.to_string(),
+            "bid: 100.00000 Quote, ask: 101.00000 Quote, ts: 5"
+        );
+
+        let trade = Event::from(Trade {
+            timestamp_exchange_ns: 6.into(),
+            price: QuoteCurrency::new(100, 0),
+            quantity: BaseCurrency::new(1, 0),
+            side: Side::Sell,
+        });
+        assert_eq!(trade.timestamp_exchange_ns(), 6.into());
+        assert!(trade.can_fill_bids());
+        assert!(!trade.can_fill_asks());
+        assert_eq!(
+            trade.price_contact(Side::Buy, QuoteCurrency::new(101, 0)),
+            Some(PriceContact {
+                traded_through: true,
+                volume_through: Some(BaseCurrency::new(1, 0)),
+                volume_touching: Some(BaseCurrency::new(1, 0)),
+            })
+        );
+
+        let mark_price = Event::from(MarkPrice {
+            price: QuoteCurrency::new(99, 0),
+            timestamp_exchange_ns: 7.into(),
+        });
+        let mut market_state = MarketState::default();
+        mark_price.update_market_state(&mut market_state);
+        assert_eq!(market_state.mark_price(), QuoteCurrency::new(99, 0));
+    }
+}
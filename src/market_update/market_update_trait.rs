@@ -6,9 +6,11 @@ use crate::{
         Mon,
         Pending,
         PriceFilter,
+        QuoteCurrency,
     },
     types::{
         PriceFilterError,
+        Side,
         TimestampNs,
         UserOrderId,
     },
@@ -17,6 +19,20 @@ use crate::{
 /// If `true`, the `MarketUpdate` can no longer fill limit orders.
 pub(crate) type Exhausted = bool;
 
+/// How a market update reached the limit price of a resting order,
+/// which a `FillModel` turns into a filled quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceContact<BaseOrQuote> {
+    /// `true` if the market traded strictly through the limit price, `false` if it only touched it.
+    pub traded_through: bool,
+    /// The taker volume traded strictly through the limit price,
+    /// `None` if the market update carries no volume information (e.g `Candle`).
+    pub volume_through: Option<BaseOrQuote>,
+    /// The taker volume traded at or through the limit price,
+    /// `None` if the market update carries no volume information (e.g `Candle`).
+    pub volume_touching: Option<BaseOrQuote>,
+}
+
 /// The interface of what a market update must be able to do.
 pub trait MarketUpdate<I, const D: u8, BaseOrQuote>:
     Clone + std::fmt::Debug + std::fmt::Display
@@ -40,12 +56,42 @@ where
         limit_order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
     ) -> Option<(BaseOrQuote, Exhausted)>;
 
+    /// Describes how the market update reached `limit_price` from the perspective of a resting order on `order_side`,
+    /// independent of any fill assumption. `None` if the price was not reached.
+    /// Unlike `limit_order_filled`, this does not consume any liquidity of the market update.
+    fn price_contact(
+        &self,
+        order_side: Side,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>>;
+
+    /// The side of the resting limit orders the market update reaches first.
+    /// Matters for market updates spanning a period, where the path of the price decides which orders fill first.
+    #[inline(always)]
+    fn first_filled_side(&self) -> Side {
+        Side::Buy
+    }
+
     /// Checks if the market update satisfies the `PriceFilter`.
     fn validate_market_update(
         &self,
         price_filter: &PriceFilter<I, D>,
     ) -> Result<(), PriceFilterError>;
 
+    /// Checks if the prices of the market update lie within the price band of the `PriceFilter`
+    /// around the `reference_price` observed before it, see `PriceFilter::validate_price_band`.
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError>;
+
+    /// Move the prices which are off the tick grid of the `PriceFilter` onto it,
+    /// widening rather than narrowing any spread or range. Used to repair recorded market data.
+    /// The default leaves the market update as is.
+    #[inline(always)]
+    fn snap_to_tick(&mut self, _price_filter: &PriceFilter<I, D>) {}
+
     /// Update the `MarketState` with new information.
     fn update_market_state(&self, market_state: &mut MarketState<I, D>);
 
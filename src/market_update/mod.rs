@@ -1,11 +1,28 @@
 mod bba_update;
 mod candle_update;
+mod mark_price_update;
+mod market_event;
 mod market_update_trait;
+mod ohlcv_candle;
 mod smart_candle;
+mod smart_candle_aggregator;
 mod trade_update;
 
 pub use bba_update::Bba;
 pub use candle_update::Candle;
-pub use market_update_trait::MarketUpdate;
+pub use mark_price_update::MarkPrice;
+pub use market_event::MarketEvent;
+pub use market_update_trait::{
+    MarketUpdate,
+    PriceContact,
+};
+pub use ohlcv_candle::{
+    IntrabarPath,
+    OhlcvCandle,
+};
 pub use smart_candle::SmartCandle;
+pub use smart_candle_aggregator::{
+    BarRule,
+    SmartCandleAggregator,
+};
 pub use trade_update::Trade;
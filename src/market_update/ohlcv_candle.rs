@@ -0,0 +1,482 @@
+use getset::CopyGetters;
+use typed_builder::TypedBuilder;
+
+use super::MarketUpdate;
+use crate::{
+    market_update::market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
+    order_filters::{
+        enforce_bid_ask_spread,
+        enforce_max_price,
+        enforce_min_price,
+        enforce_step_size,
+    },
+    prelude::{
+        Currency,
+        LimitOrder,
+        MarketState,
+        Mon,
+        Pending,
+        PriceFilter,
+        QuoteCurrency,
+        Side,
+        TickRounding,
+    },
+    types::{
+        PriceFilterError,
+        TimestampNs,
+        UserOrderId,
+    },
+    utils::min,
+};
+
+/// The path the price is assumed to take within a candle,
+/// which decides whether resting asks or bids are reached first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IntrabarPath {
+    /// The price moves from the open to the high, then to the low and finally to the close.
+    OpenHighLowClose,
+    /// The price moves from the open to the low, then to the high and finally to the close.
+    OpenLowHighClose,
+    /// The price first moves to the extreme closer to the open.
+    /// If both are equally far away, the low is assumed to come first.
+    #[default]
+    NearestExtremeFirst,
+}
+
+/// A candle with open, high, low, close and volume information.
+/// Unlike `Candle`, it fills resting limit orders only up to the traded taker volume
+/// and reaches the resting bids and asks in the order given by its `IntrabarPath`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, TypedBuilder, CopyGetters)]
+pub struct OhlcvCandle<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The best bid at the time of candle creation
+    #[getset(get_copy = "pub")]
+    bid: QuoteCurrency<I, D>,
+
+    /// The best ask at the time of candle creation
+    #[getset(get_copy = "pub")]
+    ask: QuoteCurrency<I, D>,
+
+    /// The open price of the candle
+    #[getset(get_copy = "pub")]
+    open: QuoteCurrency<I, D>,
+
+    /// The high price of the candle
+    #[getset(get_copy = "pub")]
+    high: QuoteCurrency<I, D>,
+
+    /// The low price of the candle
+    #[getset(get_copy = "pub")]
+    low: QuoteCurrency<I, D>,
+
+    /// The close price of the candle
+    #[getset(get_copy = "pub")]
+    close: QuoteCurrency<I, D>,
+
+    /// The total traded volume of the candle.
+    #[getset(get_copy = "pub")]
+    volume: BaseOrQuote,
+
+    /// The volume of taker buys, which fill resting asks.
+    /// If `None`, the split of the volume is unknown and the total `volume` is shared by both sides.
+    #[getset(get_copy = "pub")]
+    #[builder(default)]
+    taker_buy_volume: Option<BaseOrQuote>,
+
+    /// The assumed path of the price within the candle.
+    #[getset(get_copy = "pub")]
+    #[builder(default)]
+    intrabar_path: IntrabarPath,
+
+    /// The nanosecond timestamp at which this event occurred at the exchange.
+    #[getset(get_copy = "pub")]
+    timestamp_exchange_ns: TimestampNs,
+
+    /// The taker buy volume already used up by fills of resting asks.
+    #[builder(setter(skip), default)]
+    consumed_buy_volume: BaseOrQuote,
+
+    /// The taker sell volume already used up by fills of resting bids.
+    #[builder(setter(skip), default)]
+    consumed_sell_volume: BaseOrQuote,
+}
+
+impl<I, const D: u8, BaseOrQuote> OhlcvCandle<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The volume of taker sells, which fill resting bids.
+    /// If the split of the volume is unknown, the total `volume`.
+    #[inline]
+    pub fn taker_sell_volume(&self) -> BaseOrQuote {
+        self.taker_buy_volume
+            .map_or(self.volume, |buy_volume| self.volume - buy_volume)
+    }
+
+    /// The taker volume not yet used up, which is available to fill resting orders of `order_side`.
+    /// If the split of the volume is unknown, fills of either side consume the same total `volume`.
+    #[inline]
+    fn available_volume(&self, order_side: Side) -> BaseOrQuote {
+        match (self.taker_buy_volume, order_side) {
+            (None, _) => self.volume - self.consumed_buy_volume - self.consumed_sell_volume,
+            (Some(_), Side::Buy) => self.taker_sell_volume() - self.consumed_sell_volume,
+            (Some(buy_volume), Side::Sell) => buy_volume - self.consumed_buy_volume,
+        }
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> std::fmt::Display for OhlcvCandle<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "open: {}, high: {}, low: {}, close: {}, volume: {}",
+            self.open, self.high, self.low, self.close, self.volume
+        )
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> MarketUpdate<I, D, BaseOrQuote> for OhlcvCandle<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    const CAN_FILL_LIMIT_ORDERS: bool = true;
+
+    // Like `Trade`, the limit price must be traded through, as the queue position is unknown.
+    // As the taker buy and sell volumes are tracked separately, the candle never reports being exhausted,
+    // but stops filling the side whose volume is used up, or both sides once an unsplit volume is used up.
+    #[inline]
+    fn limit_order_filled<UserOrderIdT: UserOrderId>(
+        &mut self,
+        order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    ) -> Option<(BaseOrQuote, Exhausted)> {
+        debug_assert!(order.remaining_quantity() > BaseOrQuote::zero());
+
+        let traded_through = match order.side() {
+            Side::Buy => self.low < order.limit_price(),
+            Side::Sell => self.high > order.limit_price(),
+        };
+        if !traded_through {
+            return None;
+        }
+        let available = self.available_volume(order.side());
+        if available <= BaseOrQuote::zero() {
+            return None;
+        }
+
+        let filled_qty = min(available, order.remaining_quantity());
+        match order.side() {
+            Side::Buy => self.consumed_sell_volume += filled_qty,
+            Side::Sell => self.consumed_buy_volume += filled_qty,
+        }
+        Some((filled_qty, false))
+    }
+
+    #[inline]
+    fn price_contact(
+        &self,
+        order_side: Side,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        let traded_through = match order_side {
+            Side::Buy if self.low <= limit_price => self.low < limit_price,
+            Side::Sell if self.high >= limit_price => self.high > limit_price,
+            _ => return None,
+        };
+        // Where within the range the volume traded is unknown, so all of it may have traded through.
+        let volume = self.available_volume(order_side);
+        Some(PriceContact {
+            traded_through,
+            volume_through: Some(if traded_through {
+                volume
+            } else {
+                BaseOrQuote::zero()
+            }),
+            volume_touching: Some(volume),
+        })
+    }
+
+    #[inline]
+    fn first_filled_side(&self) -> Side {
+        let high_first = match self.intrabar_path {
+            IntrabarPath::OpenHighLowClose => true,
+            IntrabarPath::OpenLowHighClose => false,
+            IntrabarPath::NearestExtremeFirst => self.high - self.open < self.open - self.low,
+        };
+        // Reaching the high first fills the resting asks first.
+        if high_first { Side::Sell } else { Side::Buy }
+    }
+
+    #[inline]
+    fn snap_to_tick(&mut self, price_filter: &PriceFilter<I, D>) {
+        self.bid = price_filter.snap_to_tick(self.bid, TickRounding::Down);
+        self.ask = price_filter.snap_to_tick(self.ask, TickRounding::Up);
+        self.low = price_filter.snap_to_tick(self.low, TickRounding::Down);
+        self.high = price_filter.snap_to_tick(self.high, TickRounding::Up);
+        self.open = price_filter.snap_to_tick(self.open, TickRounding::Nearest);
+        self.close = price_filter.snap_to_tick(self.close, TickRounding::Nearest);
+    }
+
+    fn validate_market_update(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        assert2::debug_assert!(self.volume >= BaseOrQuote::zero());
+        assert2::debug_assert!(
+            self.taker_buy_volume
+                .is_none_or(|v| v >= BaseOrQuote::zero() && v <= self.volume)
+        );
+        for price in [
+            self.bid, self.ask, self.open, self.high, self.low, self.close,
+        ] {
+            enforce_min_price(price_filter.min_price(), price)?;
+            enforce_max_price(price_filter.max_price(), price)?;
+            enforce_step_size(price_filter.tick_size_at(price), price)?;
+        }
+        // The open and close lie within the range of the candle.
+        for price in [self.open, self.close, self.high] {
+            enforce_min_price(Some(self.low), price)?;
+        }
+        for price in [self.open, self.close] {
+            enforce_max_price(Some(self.high), price)?;
+        }
+        enforce_bid_ask_spread(self.bid, self.ask)?;
+        Ok(())
+    }
+
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        for price in [
+            self.bid, self.ask, self.open, self.high, self.low, self.close,
+        ] {
+            price_filter.validate_price_band(price, reference_price)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn update_market_state(&self, market_state: &mut MarketState<I, D>) {
+        market_state.set_bid(self.bid);
+        market_state.set_ask(self.ask);
+        market_state.set_last_trade_price(self.close);
+    }
+
+    #[inline(always)]
+    fn timestamp_exchange_ns(&self) -> TimestampNs {
+        self.timestamp_exchange_ns
+    }
+
+    #[inline(always)]
+    fn can_fill_bids(&self) -> bool {
+        self.taker_sell_volume() > BaseOrQuote::zero()
+    }
+
+    #[inline(always)]
+    fn can_fill_asks(&self) -> bool {
+        self.taker_buy_volume.unwrap_or(self.volume) > BaseOrQuote::zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        prelude::NoUserOrderId,
+        types::{
+            BaseCurrency,
+            ExchangeOrderMeta,
+        },
+    };
+
+    fn candle(
+        intrabar_path: IntrabarPath,
+        taker_buy_volume: Option<BaseCurrency<i64, 1>>,
+    ) -> OhlcvCandle<i64, 1, BaseCurrency<i64, 1>> {
+        OhlcvCandle::builder()
+            .bid(QuoteCurrency::new(100, 0))
+            .ask(QuoteCurrency::new(101, 0))
+            .open(QuoteCurrency::new(98, 0))
+            .high(QuoteCurrency::new(105, 0))
+            .low(QuoteCurrency::new(95, 0))
+            .close(QuoteCurrency::new(100, 0))
+            .volume(BaseCurrency::new(10, 0))
+            .taker_buy_volume(taker_buy_volume)
+            .intrabar_path(intrabar_path)
+            .timestamp_exchange_ns(1.into())
+            .build()
+    }
+
+    fn pending_order(
+        side: Side,
+        limit_price: i64,
+        quantity: i64,
+    ) -> LimitOrder<
+        i64,
+        1,
+        BaseCurrency<i64, 1>,
+        NoUserOrderId,
+        Pending<i64, 1, BaseCurrency<i64, 1>>,
+    > {
+        LimitOrder::new(
+            side,
+            QuoteCurrency::new(limit_price, 0),
+            BaseCurrency::new(quantity, 0),
+        )
+        .unwrap()
+        .into_pending(ExchangeOrderMeta::new(0.into(), 1.into()))
+    }
+
+    #[test]
+    fn ohlcv_candle_first_filled_side() {
+        let first_filled_side = |path| {
+            <OhlcvCandle<i64, 1, BaseCurrency<i64, 1>> as MarketUpdate<_, 1, _>>::first_filled_side(
+                &candle(path, None),
+            )
+        };
+        assert_eq!(
+            first_filled_side(IntrabarPath::OpenHighLowClose),
+            Side::Sell
+        );
+        assert_eq!(first_filled_side(IntrabarPath::OpenLowHighClose), Side::Buy);
+        // The open of 98 is closer to the low of 95 than to the high of 105.
+        assert_eq!(
+            first_filled_side(IntrabarPath::NearestExtremeFirst),
+            Side::Buy
+        );
+    }
+
+    #[test]
+    fn ohlcv_candle_volume_capped_fills() {
+        let mut candle = candle(IntrabarPath::default(), Some(BaseCurrency::new(4, 0)));
+        assert_eq!(candle.taker_sell_volume(), BaseCurrency::new(6, 0));
+
+        // Touching the low is not enough.
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 95, 5)),
+            None
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 99, 5)),
+            Some((BaseCurrency::new(5, 0), false))
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 98, 5)),
+            Some((BaseCurrency::new(1, 0), false))
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 97, 5)),
+            None
+        );
+
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Sell, 102, 5)),
+            Some((BaseCurrency::new(4, 0), false))
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Sell, 103, 5)),
+            None
+        );
+    }
+
+    #[test]
+    fn ohlcv_candle_unknown_volume_split() {
+        let mut candle = candle(IntrabarPath::default(), None);
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 99, 20)),
+            Some((BaseCurrency::new(10, 0), false))
+        );
+        // The whole volume was used up by the bid.
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Sell, 102, 20)),
+            None
+        );
+    }
+
+    #[test]
+    fn ohlcv_candle_unknown_volume_split_fills_both_sides() {
+        let mut candle = candle(IntrabarPath::default(), None);
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 99, 6)),
+            Some((BaseCurrency::new(6, 0), false))
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Sell, 102, 20)),
+            Some((BaseCurrency::new(4, 0), false))
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Buy, 98, 1)),
+            None
+        );
+        assert_eq!(
+            candle.limit_order_filled(&pending_order(Side::Sell, 103, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn ohlcv_candle_validate() {
+        let price_filter = PriceFilter::default();
+        let valid = candle(IntrabarPath::default(), None);
+        assert_eq!(
+            <OhlcvCandle<_, 1, _> as MarketUpdate<_, 1, BaseCurrency<_, 1>>>::validate_market_update(
+                &valid,
+                &price_filter,
+            ),
+            Ok(())
+        );
+
+        let mut close_above_high = valid;
+        close_above_high.close = QuoteCurrency::new(106, 0);
+        assert_eq!(
+            <OhlcvCandle<_, 1, _> as MarketUpdate<_, 1, BaseCurrency<_, 1>>>::validate_market_update(
+                &close_above_high,
+                &price_filter,
+            ),
+            Err(PriceFilterError::PriceTooHigh)
+        );
+
+        let mut open_below_low = valid;
+        open_below_low.open = QuoteCurrency::new(94, 0);
+        assert_eq!(
+            <OhlcvCandle<_, 1, _> as MarketUpdate<_, 1, BaseCurrency<_, 1>>>::validate_market_update(
+                &open_below_low,
+                &price_filter,
+            ),
+            Err(PriceFilterError::PriceTooLow)
+        );
+    }
+
+    #[test]
+    fn ohlcv_candle_update_market_state() {
+        let candle = candle(IntrabarPath::default(), None);
+        let mut state = MarketState::<i64, 1>::default();
+        <OhlcvCandle<_, 1, BaseCurrency<_, 1>> as MarketUpdate<_, 1, _>>::update_market_state(
+            &candle, &mut state,
+        );
+        assert_eq!(state.bid(), QuoteCurrency::new(100, 0));
+        assert_eq!(state.ask(), QuoteCurrency::new(101, 0));
+        assert_eq!(state.last_trade_price(), QuoteCurrency::new(100, 0));
+    }
+
+    #[test]
+    fn ohlcv_candle_display() {
+        assert_eq!(
+            &candle(IntrabarPath::default(), None).to_string(),
+            "open: 98.0 Quote, high: 105.0 Quote, low: 95.0 Quote, close: 100.0 Quote, volume: 10.0 Base"
+        );
+    }
+}
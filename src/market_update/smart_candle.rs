@@ -9,7 +9,10 @@ use super::{
     Trade,
 };
 use crate::{
-    market_update::market_update_trait::Exhausted,
+    market_update::market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
     prelude::PriceFilter,
     types::{
         Currency,
@@ -100,66 +103,101 @@ where
             }
         }
 
-        // sort by prices.
-        glidesort::sort_by_key(&mut buys, |t| -t.0); // Reverse is easier here.
-        glidesort::sort_by_key(&mut sells, |t| t.0);
-
-        // aggregate price levels, summing up the quantities.
-        let mut aggregate_buy_volume = Vec::with_capacity(10);
-        if !buys.is_empty() {
-            let mut last_buy_price = buys[0].0;
-            let mut buy_volume_sum = BaseOrQuote::zero();
-            // Largest prices first.
-            for (buy_price, buy_qty) in buys {
-                if buy_price != last_buy_price {
-                    #[allow(
-                        clippy::disallowed_methods,
-                        reason = "Don't know if we have enough capacity"
-                    )]
-                    aggregate_buy_volume.push((last_buy_price, buy_volume_sum));
-                    last_buy_price = buy_price
-                }
-                buy_volume_sum += buy_qty;
-            }
-            #[allow(
-                clippy::disallowed_methods,
-                reason = "Don't know if we have enough capacity"
-            )]
-            aggregate_buy_volume.push((last_buy_price, buy_volume_sum));
+        let mut candle = Self {
+            high,
+            low,
+            aggregate_buy_volume: Vec::with_capacity(10),
+            aggregate_sell_volume: Vec::with_capacity(10),
+            last_timestamp_exchange_ns: taker_trades[taker_trades.len() - 1].timestamp_exchange_ns,
+            bba,
+        };
+        candle.aggregate_flow(&mut buys, &mut sells);
+        candle
+    }
+
+    /// An empty candle with room for `levels` price levels on each side, to be filled by `rebuild`.
+    pub(crate) fn with_capacity(levels: usize) -> Self {
+        Self {
+            high: QuoteCurrency::zero(),
+            low: QuoteCurrency::zero(),
+            aggregate_buy_volume: Vec::with_capacity(levels),
+            aggregate_sell_volume: Vec::with_capacity(levels),
+            bba: Bba {
+                bid: QuoteCurrency::zero(),
+                ask: QuoteCurrency::zero(),
+                timestamp_exchange_ns: TimestampNs::from(0),
+            },
+            last_timestamp_exchange_ns: TimestampNs::from(0),
         }
+    }
 
-        let mut aggregate_sell_volume = Vec::with_capacity(10);
-        if !sells.is_empty() {
-            let mut last_sell_price = sells[0].0;
-            let mut sell_volume_sum = BaseOrQuote::zero();
-            // Smallest prices first
-            for (sell_price, sell_qty) in sells {
-                if sell_price != last_sell_price {
-                    #[allow(
-                        clippy::disallowed_methods,
-                        reason = "Don't know if we have enough capacity"
-                    )]
-                    aggregate_sell_volume.push((last_sell_price, sell_volume_sum));
-                    last_sell_price = sell_price;
-                }
-                sell_volume_sum += sell_qty;
-            }
+    /// Replace the contents with a new candle, reusing the allocated volume levels.
+    /// The `buys` and `sells` are the price and quantity of the taker trades, which get sorted in place.
+    pub(crate) fn rebuild(
+        &mut self,
+        buys: &mut [(QuoteCurrency<I, D>, BaseOrQuote)],
+        sells: &mut [(QuoteCurrency<I, D>, BaseOrQuote)],
+        high: QuoteCurrency<I, D>,
+        low: QuoteCurrency<I, D>,
+        bba: Bba<I, D>,
+        last_timestamp_exchange_ns: TimestampNs,
+    ) {
+        debug_assert!(!buys.is_empty() || !sells.is_empty());
+        self.high = high;
+        self.low = low;
+        self.bba = bba;
+        self.last_timestamp_exchange_ns = last_timestamp_exchange_ns;
+        self.aggregate_flow(buys, sells);
+    }
+
+    /// Sort the taker flow by price and aggregate it into the cumulative volume levels.
+    fn aggregate_flow(
+        &mut self,
+        buys: &mut [(QuoteCurrency<I, D>, BaseOrQuote)],
+        sells: &mut [(QuoteCurrency<I, D>, BaseOrQuote)],
+    ) {
+        // sort by prices.
+        glidesort::sort_by_key(buys, |t| -t.0); // Reverse is easier here.
+        glidesort::sort_by_key(sells, |t| t.0);
+
+        // Largest prices first.
+        aggregate_levels(buys, &mut self.aggregate_buy_volume);
+        // Smallest prices first
+        aggregate_levels(sells, &mut self.aggregate_sell_volume);
+    }
+}
+
+/// Aggregate the taker flow sorted by price into price levels,
+/// each containing the cumulative quantity of the preceding levels and the current one.
+fn aggregate_levels<I, const D: u8, BaseOrQuote>(
+    flow: &[(QuoteCurrency<I, D>, BaseOrQuote)],
+    levels: &mut Vec<(QuoteCurrency<I, D>, BaseOrQuote)>,
+) where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    levels.clear();
+    let Some(&(first_price, _)) = flow.first() else {
+        return;
+    };
+    let mut last_price = first_price;
+    let mut volume_sum = BaseOrQuote::zero();
+    for &(price, qty) in flow {
+        if price != last_price {
             #[allow(
                 clippy::disallowed_methods,
                 reason = "Don't know if we have enough capacity"
             )]
-            aggregate_sell_volume.push((last_sell_price, sell_volume_sum));
-        }
-
-        Self {
-            high,
-            low,
-            aggregate_buy_volume,
-            aggregate_sell_volume,
-            last_timestamp_exchange_ns: taker_trades[taker_trades.len() - 1].timestamp_exchange_ns,
-            bba,
+            levels.push((last_price, volume_sum));
+            last_price = price;
         }
+        volume_sum += qty;
     }
+    #[allow(
+        clippy::disallowed_methods,
+        reason = "Don't know if we have enough capacity"
+    )]
+    levels.push((last_price, volume_sum));
 }
 
 impl<I, const D: u8, BaseOrQuote> std::fmt::Display for SmartCandle<I, D, BaseOrQuote>
@@ -222,6 +260,48 @@ where
         }
     }
 
+    #[inline]
+    fn price_contact(
+        &self,
+        order_side: Side,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        // The aggregate volumes are cumulative, so the level closest to the limit price holds all volume beyond it.
+        let (traded_through, volume_through, volume_touching) = match order_side {
+            Side::Buy => {
+                if self.low > limit_price {
+                    return None;
+                }
+                let volume_at = |through: bool| {
+                    self.aggregate_sell_volume
+                        .iter()
+                        .rev()
+                        .find(|v| v.0 < limit_price || (!through && v.0 == limit_price))
+                        .map_or(BaseOrQuote::zero(), |v| v.1)
+                };
+                (self.low < limit_price, volume_at(true), volume_at(false))
+            }
+            Side::Sell => {
+                if self.high < limit_price {
+                    return None;
+                }
+                let volume_at = |through: bool| {
+                    self.aggregate_buy_volume
+                        .iter()
+                        .rev()
+                        .find(|v| v.0 > limit_price || (!through && v.0 == limit_price))
+                        .map_or(BaseOrQuote::zero(), |v| v.1)
+                };
+                (self.high > limit_price, volume_at(true), volume_at(false))
+            }
+        };
+        Some(PriceContact {
+            traded_through,
+            volume_through: Some(volume_through),
+            volume_touching: Some(volume_touching),
+        })
+    }
+
     #[inline(always)]
     fn validate_market_update(
         &self,
@@ -231,6 +311,17 @@ where
         Ok(())
     }
 
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        for price in [self.bba.bid, self.bba.ask, self.low, self.high] {
+            price_filter.validate_price_band(price, reference_price)?;
+        }
+        Ok(())
+    }
+
     // Basically whatever the user inputs as the best bid and ask.
     #[inline]
     fn update_market_state(&self, market_state: &mut crate::prelude::MarketState<I, D>) {
@@ -0,0 +1,409 @@
+//! Builds `SmartCandle`s incrementally from streams of `Trade`s and `Bba`s.
+
+use num_traits::Zero;
+
+use super::{
+    Bba,
+    MarketEvent,
+    SmartCandle,
+    Trade,
+};
+use crate::{
+    EXPECT_CAPACITY,
+    types::{
+        ConfigError,
+        Currency,
+        Mon,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+};
+
+/// Decides when a bar of trades is complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarRule<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Bars spanning a fixed interval, aligned to multiples of the interval since the unix epoch.
+    /// A bar completes once a trade or quote of a later interval arrives.
+    Time(TimestampNs),
+    /// Bars of at least this traded quantity.
+    Volume(BaseOrQuote),
+    /// Bars of this many trades.
+    TickCount(u64),
+    /// Bars of at least this traded value denoted in the paired currency,
+    /// e.g. the dollar value for trades denoted in the `BaseCurrency`.
+    Notional(BaseOrQuote::PairedCurrency),
+}
+
+impl<I, const D: u8, BaseOrQuote> BarRule<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::Time(interval) => *interval > TimestampNs::from(0),
+            Self::Volume(volume) => *volume > BaseOrQuote::zero(),
+            Self::TickCount(ticks) => *ticks > 0,
+            Self::Notional(notional) => *notional > BaseOrQuote::PairedCurrency::zero(),
+        }
+    }
+}
+
+/// Aggregates `Trade`s into `SmartCandle`s according to a `BarRule`,
+/// so the bar type can be changed without slicing the trades by hand.
+///
+/// Each candle takes the most recent `Bba` at its completion,
+/// so trades arriving before the first `Bba` are skipped.
+/// The candles are built in place, reusing the buffers of the previous candle,
+/// which is why a completed candle is only borrowed until the next update.
+#[derive(Debug, Clone)]
+pub struct SmartCandleAggregator<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    rule: BarRule<I, D, BaseOrQuote>,
+    candle: SmartCandle<I, D, BaseOrQuote>,
+    bba: Option<Bba<I, D>>,
+    // The taker flow of the current bar.
+    buys: Vec<(QuoteCurrency<I, D>, BaseOrQuote)>,
+    sells: Vec<(QuoteCurrency<I, D>, BaseOrQuote)>,
+    high: QuoteCurrency<I, D>,
+    low: QuoteCurrency<I, D>,
+    last_ts_ns: TimestampNs,
+    // The end of the interval of the current bar, only used by `BarRule::Time`.
+    bar_end_ts_ns: TimestampNs,
+    ticks: u64,
+    volume: BaseOrQuote,
+    notional: BaseOrQuote::PairedCurrency,
+}
+
+impl<I, const D: u8, BaseOrQuote> SmartCandleAggregator<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a new aggregator completing bars according to the `rule`.
+    /// Errors if the threshold of the `rule` is not positive.
+    pub fn new(rule: BarRule<I, D, BaseOrQuote>) -> Result<Self, ConfigError> {
+        if !rule.is_valid() {
+            return Err(ConfigError::InvalidBarRule);
+        }
+        Ok(Self {
+            rule,
+            candle: SmartCandle::with_capacity(16),
+            bba: None,
+            buys: Vec::with_capacity(64),
+            sells: Vec::with_capacity(64),
+            high: QuoteCurrency::zero(),
+            low: QuoteCurrency::zero(),
+            last_ts_ns: TimestampNs::from(0),
+            bar_end_ts_ns: TimestampNs::from(0),
+            ticks: 0,
+            volume: BaseOrQuote::zero(),
+            notional: BaseOrQuote::PairedCurrency::zero(),
+        })
+    }
+
+    /// The rule deciding when a bar is complete.
+    #[inline]
+    pub fn rule(&self) -> &BarRule<I, D, BaseOrQuote> {
+        &self.rule
+    }
+
+    /// The number of trades in the current, incomplete bar.
+    #[inline]
+    pub fn pending_trades(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Process a new best bid and ask, which completes a time bar if it belongs to a later interval.
+    pub fn update_bba(&mut self, bba: Bba<I, D>) -> Option<&SmartCandle<I, D, BaseOrQuote>> {
+        let completed = self.ticks > 0
+            && matches!(self.rule, BarRule::Time(_))
+            && bba.timestamp_exchange_ns >= self.bar_end_ts_ns;
+        if completed {
+            self.complete_bar();
+        }
+        self.bba = Some(bba);
+        completed.then_some(&self.candle)
+    }
+
+    /// Process a new taker `trade`, returning the candle it completed, if any.
+    /// With a `BarRule::Time` the trade belongs to the next bar,
+    /// otherwise it is the last trade of the completed bar.
+    pub fn update_trade(
+        &mut self,
+        trade: &Trade<I, D, BaseOrQuote>,
+    ) -> Option<&SmartCandle<I, D, BaseOrQuote>> {
+        if self.bba.is_none() {
+            return None;
+        }
+        debug_assert!(trade.quantity > BaseOrQuote::zero());
+
+        let completed = match self.rule {
+            BarRule::Time(interval) => {
+                let completed = self.ticks > 0 && trade.timestamp_exchange_ns >= self.bar_end_ts_ns;
+                if completed {
+                    self.complete_bar();
+                }
+                self.add_trade(trade);
+                if self.ticks == 1 {
+                    let ts = trade.timestamp_exchange_ns.get();
+                    let interval = interval.get();
+                    self.bar_end_ts_ns =
+                        TimestampNs::from((ts.div_euclid(interval) + 1) * interval);
+                }
+                completed
+            }
+            BarRule::Volume(volume) => {
+                self.add_trade(trade);
+                self.volume >= volume
+            }
+            BarRule::TickCount(ticks) => {
+                self.add_trade(trade);
+                self.ticks >= ticks
+            }
+            BarRule::Notional(notional) => {
+                self.add_trade(trade);
+                self.notional >= notional
+            }
+        };
+        if completed && !matches!(self.rule, BarRule::Time(_)) {
+            self.complete_bar();
+        }
+        completed.then_some(&self.candle)
+    }
+
+    /// Process a `MarketEvent`, e.g. from a `MarketEventMerge` of trade and quote streams.
+    /// Other events than `Trade` and `Bba` are ignored.
+    pub fn update(
+        &mut self,
+        event: &MarketEvent<I, D, BaseOrQuote>,
+    ) -> Option<&SmartCandle<I, D, BaseOrQuote>> {
+        match event {
+            MarketEvent::Bba(bba) => self.update_bba(*bba),
+            MarketEvent::Trade(trade) => self.update_trade(trade),
+            MarketEvent::Candle(_)
+            | MarketEvent::OhlcvCandle(_)
+            | MarketEvent::SmartCandle(_)
+            | MarketEvent::MarkPrice(_) => None,
+        }
+    }
+
+    /// Complete the current bar even though the rule is not satisfied yet, e.g. at the end of the data.
+    pub fn flush(&mut self) -> Option<&SmartCandle<I, D, BaseOrQuote>> {
+        if self.ticks == 0 {
+            return None;
+        }
+        self.complete_bar();
+        Some(&self.candle)
+    }
+
+    fn add_trade(&mut self, trade: &Trade<I, D, BaseOrQuote>) {
+        if self.ticks == 0 {
+            self.high = trade.price;
+            self.low = trade.price;
+        } else {
+            self.high = self.high.max(trade.price);
+            self.low = self.low.min(trade.price);
+        }
+        let flow = match trade.side {
+            Side::Buy => &mut self.buys,
+            Side::Sell => &mut self.sells,
+        };
+        // The buffers grow to the size of the largest bar once and are reused.
+        flow.reserve(1);
+        flow.push_within_capacity((trade.price, trade.quantity))
+            .expect(EXPECT_CAPACITY);
+        self.last_ts_ns = trade.timestamp_exchange_ns;
+        self.ticks += 1;
+        self.volume += trade.quantity;
+        self.notional += BaseOrQuote::PairedCurrency::convert_from(trade.quantity, trade.price);
+    }
+
+    fn complete_bar(&mut self) {
+        debug_assert!(self.ticks > 0);
+        self.candle.rebuild(
+            &mut self.buys,
+            &mut self.sells,
+            self.high,
+            self.low,
+            self.bba
+                .expect("Trades are only added after the first `Bba`"),
+            self.last_ts_ns,
+        );
+        self.buys.clear();
+        self.sells.clear();
+        self.ticks = 0;
+        self.volume = BaseOrQuote::zero();
+        self.notional = BaseOrQuote::PairedCurrency::zero();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use const_decimal::Decimal;
+
+    use super::*;
+    use crate::{
+        mock_bba,
+        prelude::PriceFilter,
+        types::BaseCurrency,
+    };
+
+    fn trades() -> Vec<Trade<i64, 5, BaseCurrency<i64, 5>>> {
+        vec![
+            Trade {
+                timestamp_exchange_ns: 0.into(),
+                price: QuoteCurrency::new(100, 0),
+                quantity: BaseCurrency::new(1, 0),
+                side: Side::Buy,
+            },
+            Trade {
+                timestamp_exchange_ns: 5.into(),
+                price: QuoteCurrency::new(101, 0),
+                quantity: BaseCurrency::new(2, 0),
+                side: Side::Sell,
+            },
+            Trade {
+                timestamp_exchange_ns: 10.into(),
+                price: QuoteCurrency::new(99, 0),
+                quantity: BaseCurrency::new(1, 0),
+                side: Side::Buy,
+            },
+            Trade {
+                timestamp_exchange_ns: 15.into(),
+                price: QuoteCurrency::new(100, 0),
+                quantity: BaseCurrency::new(3, 0),
+                side: Side::Sell,
+            },
+        ]
+    }
+
+    fn expected_candle(
+        trades: &[Trade<i64, 5, BaseCurrency<i64, 5>>],
+    ) -> SmartCandle<i64, 5, BaseCurrency<i64, 5>> {
+        let pf = PriceFilter::new(
+            None,
+            None,
+            QuoteCurrency::new(1, 0),
+            Decimal::TWO,
+            Decimal::try_from_scaled(5, 1).unwrap(),
+        )
+        .unwrap();
+        SmartCandle::new(trades, mock_bba(100, 0), &pf)
+    }
+
+    #[test]
+    fn smart_candle_aggregator_invalid_rule() {
+        assert_eq!(
+            SmartCandleAggregator::<i64, 5, BaseCurrency<i64, 5>>::new(BarRule::TickCount(0))
+                .unwrap_err(),
+            ConfigError::InvalidBarRule
+        );
+        assert!(
+            SmartCandleAggregator::<i64, 5, BaseCurrency<i64, 5>>::new(BarRule::Volume(
+                BaseCurrency::new(-1, 0)
+            ))
+            .is_err()
+        );
+        assert!(
+            SmartCandleAggregator::<i64, 5, BaseCurrency<i64, 5>>::new(BarRule::Time(0.into()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn smart_candle_aggregator_skips_trades_before_bba() {
+        let trades = trades();
+        let mut agg = SmartCandleAggregator::new(BarRule::TickCount(1)).unwrap();
+        assert!(agg.update_trade(&trades[0]).is_none());
+        assert_eq!(agg.pending_trades(), 0);
+        assert!(agg.flush().is_none());
+
+        assert!(agg.update_bba(mock_bba(100, 0)).is_none());
+        assert_eq!(
+            agg.update_trade(&trades[1]),
+            Some(&expected_candle(&trades[1..2]))
+        );
+    }
+
+    #[test]
+    fn smart_candle_aggregator_tick_count() {
+        let trades = trades();
+        let mut agg = SmartCandleAggregator::new(BarRule::TickCount(2)).unwrap();
+        agg.update_bba(mock_bba(100, 0));
+        assert!(agg.update_trade(&trades[0]).is_none());
+        assert_eq!(agg.pending_trades(), 1);
+        assert_eq!(
+            agg.update_trade(&trades[1]),
+            Some(&expected_candle(&trades[..2]))
+        );
+        assert_eq!(agg.pending_trades(), 0);
+        assert!(agg.update_trade(&trades[2]).is_none());
+        assert_eq!(
+            agg.update_trade(&trades[3]),
+            Some(&expected_candle(&trades[2..]))
+        );
+    }
+
+    #[test]
+    fn smart_candle_aggregator_volume() {
+        let trades = trades();
+        let mut agg = SmartCandleAggregator::new(BarRule::Volume(BaseCurrency::new(4, 0))).unwrap();
+        agg.update_bba(mock_bba(100, 0));
+        assert!(agg.update_trade(&trades[0]).is_none());
+        assert!(agg.update_trade(&trades[1]).is_none());
+        assert!(agg.update_trade(&trades[2]).is_some());
+        assert!(agg.update_trade(&trades[3]).is_none());
+        assert_eq!(agg.flush(), Some(&expected_candle(&trades[3..])));
+        assert!(agg.flush().is_none());
+    }
+
+    #[test]
+    fn smart_candle_aggregator_notional() {
+        let trades = trades();
+        let mut agg =
+            SmartCandleAggregator::new(BarRule::Notional(QuoteCurrency::new(300, 0))).unwrap();
+        agg.update_bba(mock_bba(100, 0));
+        assert!(agg.update_trade(&trades[0]).is_none());
+        // 100 + 202 >= 300
+        assert_eq!(
+            agg.update_trade(&trades[1]),
+            Some(&expected_candle(&trades[..2]))
+        );
+        assert!(agg.update_trade(&trades[2]).is_none());
+        assert_eq!(
+            agg.update_trade(&trades[3]),
+            Some(&expected_candle(&trades[2..]))
+        );
+    }
+
+    #[test]
+    fn smart_candle_aggregator_time() {
+        let trades = trades();
+        let mut agg = SmartCandleAggregator::new(BarRule::Time(10.into())).unwrap();
+        assert!(agg.update(&MarketEvent::Bba(mock_bba(100, 0))).is_none());
+        assert!(agg.update(&MarketEvent::Trade(trades[0])).is_none());
+        assert!(agg.update(&MarketEvent::Trade(trades[1])).is_none());
+        // The trade of the next interval completes the bar but is not part of it.
+        assert_eq!(
+            agg.update(&MarketEvent::Trade(trades[2])),
+            Some(&expected_candle(&trades[..2]))
+        );
+        assert_eq!(agg.pending_trades(), 1);
+        assert!(agg.update_trade(&trades[3]).is_none());
+        // A quote of the next interval completes the bar as well, which keeps the preceding `Bba`.
+        assert_eq!(
+            agg.update_bba(mock_bba(100, 20)),
+            Some(&expected_candle(&trades[2..]))
+        );
+        assert!(agg.flush().is_none());
+    }
+}
@@ -3,7 +3,10 @@ use num::Zero;
 
 use super::MarketUpdate;
 use crate::{
-    market_update::market_update_trait::Exhausted,
+    market_update::market_update_trait::{
+        Exhausted,
+        PriceContact,
+    },
     order_filters::{
         enforce_max_price,
         enforce_min_price,
@@ -19,6 +22,7 @@ use crate::{
         QuoteCurrency,
         Side,
         Side::*,
+        TickRounding,
     },
     types::{
         PriceFilterError,
@@ -114,17 +118,51 @@ where
         }
     }
 
+    #[inline]
+    fn price_contact(
+        &self,
+        order_side: Side,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Option<PriceContact<BaseOrQuote>> {
+        let traded_through = match (order_side, self.side) {
+            (Buy, Sell) if self.price <= limit_price => self.price < limit_price,
+            (Sell, Buy) if self.price >= limit_price => self.price > limit_price,
+            _ => return None,
+        };
+        Some(PriceContact {
+            traded_through,
+            volume_through: Some(if traded_through {
+                self.quantity
+            } else {
+                BaseOrQuote::zero()
+            }),
+            volume_touching: Some(self.quantity),
+        })
+    }
+
+    #[inline]
+    fn snap_to_tick(&mut self, price_filter: &PriceFilter<I, D>) {
+        self.price = price_filter.snap_to_tick(self.price, TickRounding::Nearest);
+    }
+
     fn validate_market_update(
         &self,
         price_filter: &PriceFilter<I, D>,
     ) -> Result<(), PriceFilterError> {
-        debug_assert!(self.price > QuoteCurrency::zero());
         enforce_min_price(price_filter.min_price(), self.price)?;
         enforce_max_price(price_filter.max_price(), self.price)?;
-        enforce_step_size(price_filter.tick_size(), self.price)?;
+        enforce_step_size(price_filter.tick_size_at(self.price), self.price)?;
         Ok(())
     }
 
+    fn validate_price_band(
+        &self,
+        price_filter: &PriceFilter<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        price_filter.validate_price_band(self.price, reference_price)
+    }
+
     #[inline(always)]
     fn update_market_state(&self, market_state: &mut MarketState<I, D>) {
         market_state.set_last_trade_price(self.price);
@@ -376,4 +414,39 @@ mod tests {
         assert_eq!(size_of::<Trade<i32, 2, BaseCurrency<i32, 2>>>(), 24);
         assert_eq!(size_of::<Trade<i64, 2, BaseCurrency<i64, 2>>>(), 32);
     }
+
+    #[test]
+    fn trade_update_price_contact() {
+        let trade = Trade {
+            price: QuoteCurrency::<i64, 1>::new(100, 0),
+            quantity: BaseCurrency::new(5, 0),
+            side: Sell,
+            timestamp_exchange_ns: 0.into(),
+        };
+        let price_contact = |side, price| {
+            <Trade<i64, 1, BaseCurrency<i64, 1>> as MarketUpdate<_, 1, _>>::price_contact(
+                &trade,
+                side,
+                QuoteCurrency::new(price, 0),
+            )
+        };
+        assert_eq!(price_contact(Buy, 99), None);
+        assert_eq!(price_contact(Sell, 99), None);
+        assert_eq!(
+            price_contact(Buy, 100),
+            Some(PriceContact {
+                traded_through: false,
+                volume_through: Some(BaseCurrency::zero()),
+                volume_touching: Some(BaseCurrency::new(5, 0)),
+            })
+        );
+        assert_eq!(
+            price_contact(Buy, 101),
+            Some(PriceContact {
+                traded_through: true,
+                volume_through: Some(BaseCurrency::new(5, 0)),
+                volume_touching: Some(BaseCurrency::new(5, 0)),
+            })
+        );
+    }
 }
@@ -0,0 +1,420 @@
+//! Measures how the market moved after our limit orders were filled (adverse selection).
+
+use std::collections::VecDeque;
+
+use num_traits::Zero;
+
+use crate::types::{
+    ConfigError,
+    Currency,
+    Mon,
+    QuoteCurrency,
+    Side,
+    TimestampNs,
+};
+
+/// The aggregated markouts of the fills in one bucket at one horizon.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MarkoutStats {
+    /// The number of fills whose markout was sampled.
+    pub count: u64,
+    /// The sum of the markouts in `QuoteCurrency` per unit of quantity.
+    pub sum_markout: f64,
+    /// The sum of the markouts in basis points of the fill price.
+    pub sum_markout_bps: f64,
+}
+
+impl MarkoutStats {
+    /// The average markout in `QuoteCurrency` per unit of quantity, positive if the mid price moved in our favour.
+    /// `None` without sampled fills.
+    pub fn average(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum_markout / self.count as f64)
+    }
+
+    /// The average markout in basis points of the fill price, positive if the mid price moved in our favour.
+    /// `None` without sampled fills.
+    pub fn average_bps(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum_markout_bps / self.count as f64)
+    }
+
+    fn record(&mut self, markout: f64, markout_bps: f64) {
+        self.count += 1;
+        self.sum_markout += markout;
+        self.sum_markout_bps += markout_bps;
+    }
+
+    fn merge(mut self, other: &Self) -> Self {
+        self.count += other.count;
+        self.sum_markout += other.sum_markout;
+        self.sum_markout_bps += other.sum_markout_bps;
+        self
+    }
+}
+
+/// A limit order fill whose markouts are not sampled at every horizon yet.
+#[derive(Debug, Clone)]
+struct PendingFill<I, const D: u8>
+where
+    I: Mon<D>,
+{
+    ts_ns: TimestampNs,
+    price: QuoteCurrency<I, D>,
+    side: Side,
+    /// The index of the bucket in `MarkoutAnalytics::stats`, excluding the horizon.
+    bucket: usize,
+}
+
+/// Records our limit order fills and samples the mid price of the `MarketState`
+/// at configured horizons after each fill, e.g. 1s, 10s and 60s later.
+/// The markouts are aggregated by side, by the age of the order when it was filled and by the filled quantity.
+/// Set it with `Exchange::set_markout_analytics`, after which every limit order fill is recorded
+/// and sampled by the `update_state` calls reaching its horizons.
+///
+/// A horizon is sampled with the mid price of the first market update at or after the horizon,
+/// so sparse market data shifts the samples to later times.
+/// The markout is the move of the mid price from the fill price in our favour,
+/// i.e. the mid price minus the fill price for buys and the fill price minus the mid price for sells.
+#[derive(Debug, Clone)]
+pub struct MarkoutAnalytics<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    horizons: Vec<TimestampNs>,
+    age_bounds: Vec<TimestampNs>,
+    size_bounds: Vec<BaseOrQuote>,
+    /// Indexed by side, age bucket, size bucket and horizon, in that order of significance.
+    stats: Vec<MarkoutStats>,
+    /// In time order, so the fills reach each horizon in the order they were recorded.
+    pending: VecDeque<PendingFill<I, D>>,
+    /// For each horizon, the index in `pending` of the first fill not sampled at it yet.
+    next_fill: Vec<usize>,
+}
+
+impl<I, const D: u8, BaseOrQuote> MarkoutAnalytics<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a new instance sampling the markouts at the `horizons` after each fill.
+    ///
+    /// The fills are bucketed by the age of their order and by their filled quantity,
+    /// where a fill falls into the bucket given by the number of `age_bounds` (`size_bounds`)
+    /// less than or equal to its age (quantity).
+    /// So the bounds `[1s, 10s]` result in the age buckets `[0, 1s)`, `[1s, 10s)` and `[10s, inf)`.
+    ///
+    /// The `horizons` must not be empty and all values must be positive and strictly increasing.
+    pub fn new(
+        horizons: Vec<TimestampNs>,
+        age_bounds: Vec<TimestampNs>,
+        size_bounds: Vec<BaseOrQuote>,
+    ) -> Result<Self, ConfigError> {
+        let valid = !horizons.is_empty()
+            && horizons[0] > TimestampNs::from(0)
+            && horizons.is_sorted_by(|a, b| a < b)
+            && age_bounds.first().is_none_or(|b| *b > TimestampNs::from(0))
+            && age_bounds.is_sorted_by(|a, b| a < b)
+            && size_bounds.first().is_none_or(|b| *b > BaseOrQuote::zero())
+            && size_bounds.is_sorted_by(|a, b| a < b);
+        if !valid {
+            return Err(ConfigError::InvalidMarkoutAnalytics);
+        }
+
+        let num_stats = 2 * (age_bounds.len() + 1) * (size_bounds.len() + 1) * horizons.len();
+        Ok(Self {
+            next_fill: vec![0; horizons.len()],
+            horizons,
+            age_bounds,
+            size_bounds,
+            stats: vec![MarkoutStats::default(); num_stats],
+            pending: VecDeque::with_capacity(64),
+        })
+    }
+
+    /// The horizons after each fill at which the markout is sampled.
+    #[inline]
+    pub fn horizons(&self) -> &[TimestampNs] {
+        &self.horizons
+    }
+
+    /// The bounds of the order age buckets.
+    #[inline]
+    pub fn age_bounds(&self) -> &[TimestampNs] {
+        &self.age_bounds
+    }
+
+    /// The bounds of the filled quantity buckets.
+    #[inline]
+    pub fn size_bounds(&self) -> &[BaseOrQuote] {
+        &self.size_bounds
+    }
+
+    /// The number of fills which are not sampled at every horizon yet.
+    #[inline]
+    pub fn num_pending_fills(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn num_age_buckets(&self) -> usize {
+        self.age_bounds.len() + 1
+    }
+
+    fn num_size_buckets(&self) -> usize {
+        self.size_bounds.len() + 1
+    }
+
+    /// The index of a bucket in `stats`, excluding the horizon.
+    fn bucket(&self, side: Side, age_bucket: usize, size_bucket: usize) -> usize {
+        let side = match side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        };
+        ((side * self.num_age_buckets() + age_bucket) * self.num_size_buckets() + size_bucket)
+            * self.horizons.len()
+    }
+
+    /// The markouts of the fills of `side` in the given age and size bucket at the horizon with index `horizon`.
+    ///
+    /// # Panics:
+    /// If a bucket or the horizon is out of range.
+    pub fn stats(
+        &self,
+        side: Side,
+        age_bucket: usize,
+        size_bucket: usize,
+        horizon: usize,
+    ) -> MarkoutStats {
+        assert!(age_bucket < self.num_age_buckets());
+        assert!(size_bucket < self.num_size_buckets());
+        assert!(horizon < self.horizons.len());
+        self.stats[self.bucket(side, age_bucket, size_bucket) + horizon]
+    }
+
+    /// The markouts of all fills of `side` at the horizon with index `horizon`.
+    pub fn stats_by_side(&self, side: Side, horizon: usize) -> MarkoutStats {
+        self.aggregate(horizon, |s, _, _| s == side)
+    }
+
+    /// The markouts of all fills in the age bucket `age_bucket` at the horizon with index `horizon`.
+    pub fn stats_by_age(&self, age_bucket: usize, horizon: usize) -> MarkoutStats {
+        assert!(age_bucket < self.num_age_buckets());
+        self.aggregate(horizon, |_, age, _| age == age_bucket)
+    }
+
+    /// The markouts of all fills in the size bucket `size_bucket` at the horizon with index `horizon`.
+    pub fn stats_by_size(&self, size_bucket: usize, horizon: usize) -> MarkoutStats {
+        assert!(size_bucket < self.num_size_buckets());
+        self.aggregate(horizon, |_, _, size| size == size_bucket)
+    }
+
+    fn aggregate<F>(&self, horizon: usize, include: F) -> MarkoutStats
+    where
+        F: Fn(Side, usize, usize) -> bool,
+    {
+        assert!(horizon < self.horizons.len());
+        let mut stats = MarkoutStats::default();
+        for side in [Side::Buy, Side::Sell] {
+            for age_bucket in 0..self.num_age_buckets() {
+                for size_bucket in 0..self.num_size_buckets() {
+                    if include(side, age_bucket, size_bucket) {
+                        stats = stats.merge(
+                            &self.stats[self.bucket(side, age_bucket, size_bucket) + horizon],
+                        );
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    /// Record a limit order fill of `quantity` at `price` at `ts_ns`, where the order is `order_age` old.
+    pub(crate) fn record_fill(
+        &mut self,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+        order_age: TimestampNs,
+        ts_ns: TimestampNs,
+    ) {
+        let age_bucket = self.age_bounds.partition_point(|b| *b <= order_age);
+        let size_bucket = self.size_bounds.partition_point(|b| *b <= quantity);
+        let bucket = self.bucket(side, age_bucket, size_bucket);
+        self.pending.push_back(PendingFill {
+            ts_ns,
+            price,
+            side,
+            bucket,
+        });
+    }
+
+    /// Sample the markouts of the pending fills whose horizons were reached at `ts_ns`.
+    pub(crate) fn update(&mut self, ts_ns: TimestampNs, mid_price: QuoteCurrency<I, D>) {
+        let mid_price: f64 = mid_price.into();
+        for (horizon_idx, horizon) in self.horizons.iter().enumerate() {
+            // The first fill which did not reach the horizon yet is followed by younger fills only.
+            let next_fill = &mut self.next_fill[horizon_idx];
+            while let Some(fill) = self.pending.get(*next_fill)
+                && fill.ts_ns + *horizon <= ts_ns
+            {
+                let fill_price: f64 = fill.price.into();
+                let markout = match fill.side {
+                    Side::Buy => mid_price - fill_price,
+                    Side::Sell => fill_price - mid_price,
+                };
+                self.stats[fill.bucket + horizon_idx]
+                    .record(markout, markout * 10_000.0 / fill_price);
+                *next_fill += 1;
+            }
+        }
+        // The fills sampled at the last horizon are sampled at every horizon.
+        let num_completed = *self.next_fill.last().expect("Horizons are not empty");
+        if num_completed > 0 {
+            self.pending.drain(..num_completed);
+            self.next_fill.iter_mut().for_each(|n| *n -= num_completed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    fn analytics() -> MarkoutAnalytics<i64, 5, BaseCurrency<i64, 5>> {
+        MarkoutAnalytics::new(
+            vec![10.into(), 100.into()],
+            vec![5.into()],
+            vec![BaseCurrency::new(2, 0)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn markout_analytics_new() {
+        let new = |horizons: Vec<i64>, age_bounds: Vec<i64>, size_bounds: Vec<i64>| {
+            MarkoutAnalytics::<i64, 5, BaseCurrency<i64, 5>>::new(
+                horizons.into_iter().map(TimestampNs::from).collect(),
+                age_bounds.into_iter().map(TimestampNs::from).collect(),
+                size_bounds
+                    .into_iter()
+                    .map(|s| BaseCurrency::new(s, 0))
+                    .collect(),
+            )
+            .map(|_| ())
+        };
+        assert_eq!(new(vec![1, 2], vec![], vec![]), Ok(()));
+        let err = Err(ConfigError::InvalidMarkoutAnalytics);
+        assert_eq!(new(vec![], vec![], vec![]), err);
+        assert_eq!(new(vec![0], vec![], vec![]), err);
+        assert_eq!(new(vec![2, 1], vec![], vec![]), err);
+        assert_eq!(new(vec![1], vec![0], vec![]), err);
+        assert_eq!(new(vec![1], vec![], vec![2, 2]), err);
+    }
+
+    #[test]
+    fn markout_analytics_samples_horizons() {
+        let mut analytics = analytics();
+        let qty = BaseCurrency::new(1, 0);
+        analytics.record_fill(
+            Side::Buy,
+            qty,
+            QuoteCurrency::new(100, 0),
+            0.into(),
+            0.into(),
+        );
+        analytics.record_fill(
+            Side::Sell,
+            qty,
+            QuoteCurrency::new(100, 0),
+            0.into(),
+            5.into(),
+        );
+        assert_eq!(analytics.num_pending_fills(), 2);
+
+        analytics.update(9.into(), QuoteCurrency::new(101, 0));
+        assert_eq!(analytics.stats_by_side(Side::Buy, 0).count, 0);
+
+        // The first update after the horizon samples it.
+        analytics.update(12.into(), QuoteCurrency::new(99, 0));
+        assert_eq!(analytics.stats_by_side(Side::Buy, 0).average(), Some(-1.0));
+        assert_eq!(analytics.stats_by_side(Side::Sell, 0).count, 0);
+
+        analytics.update(15.into(), QuoteCurrency::new(98, 0));
+        assert_eq!(analytics.stats_by_side(Side::Sell, 0).average(), Some(2.0));
+        assert_eq!(
+            analytics.stats_by_side(Side::Sell, 0).average_bps(),
+            Some(200.0)
+        );
+        assert_eq!(analytics.stats_by_age(0, 0).count, 2);
+        assert_eq!(analytics.stats_by_age(0, 0).average(), Some(0.5));
+
+        // A gap samples every horizon that was reached.
+        analytics.update(1_000.into(), QuoteCurrency::new(110, 0));
+        assert_eq!(analytics.num_pending_fills(), 0);
+        assert_eq!(analytics.stats_by_side(Side::Buy, 1).average(), Some(10.0));
+        assert_eq!(
+            analytics.stats_by_side(Side::Sell, 1).average(),
+            Some(-10.0)
+        );
+    }
+
+    #[test]
+    fn markout_analytics_overlapping_horizons() {
+        let mut analytics = analytics();
+        let qty = BaseCurrency::new(1, 0);
+        let price = QuoteCurrency::new(100, 0);
+        analytics.record_fill(Side::Buy, qty, price, 0.into(), 0.into());
+        analytics.update(12.into(), QuoteCurrency::new(101, 0));
+        analytics.record_fill(Side::Buy, qty, price, 0.into(), 50.into());
+
+        // The younger fill reaches the first horizon while the older one waits for the second.
+        analytics.update(65.into(), QuoteCurrency::new(103, 0));
+        assert_eq!(analytics.stats_by_side(Side::Buy, 0).average(), Some(2.0));
+        assert_eq!(analytics.stats_by_side(Side::Buy, 1).count, 0);
+        assert_eq!(analytics.num_pending_fills(), 2);
+
+        analytics.update(100.into(), QuoteCurrency::new(104, 0));
+        assert_eq!(analytics.stats_by_side(Side::Buy, 1).average(), Some(4.0));
+        assert_eq!(analytics.num_pending_fills(), 1);
+
+        analytics.update(150.into(), QuoteCurrency::new(98, 0));
+        assert_eq!(analytics.stats_by_side(Side::Buy, 1).average(), Some(1.0));
+        assert_eq!(analytics.num_pending_fills(), 0);
+    }
+
+    #[test]
+    fn markout_analytics_buckets() {
+        let mut analytics = analytics();
+        let price = QuoteCurrency::new(100, 0);
+        analytics.record_fill(
+            Side::Buy,
+            BaseCurrency::new(1, 0),
+            price,
+            4.into(),
+            0.into(),
+        );
+        analytics.record_fill(
+            Side::Buy,
+            BaseCurrency::new(2, 0),
+            price,
+            5.into(),
+            0.into(),
+        );
+        analytics.record_fill(
+            Side::Buy,
+            BaseCurrency::new(3, 0),
+            price,
+            50.into(),
+            0.into(),
+        );
+        analytics.update(10.into(), QuoteCurrency::new(101, 0));
+
+        assert_eq!(analytics.stats(Side::Buy, 0, 0, 0).count, 1);
+        assert_eq!(analytics.stats(Side::Buy, 1, 1, 0).count, 2);
+        assert_eq!(analytics.stats(Side::Sell, 1, 1, 0).count, 0);
+        assert_eq!(analytics.stats_by_age(1, 0).count, 2);
+        assert_eq!(analytics.stats_by_size(1, 0).count, 2);
+        assert_eq!(analytics.stats_by_size(0, 0).average(), Some(1.0));
+        assert_eq!(analytics.stats(Side::Buy, 0, 0, 1).count, 0);
+    }
+}
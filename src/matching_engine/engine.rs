@@ -0,0 +1,669 @@
+use std::collections::{
+    BTreeSet,
+    VecDeque,
+};
+
+use getset::{
+    CopyGetters,
+    Getters,
+};
+use num_traits::Zero;
+use tracing::{
+    debug,
+    warn,
+};
+
+use super::{
+    BookOrder,
+    OrderBook,
+};
+use crate::{
+    EXPECT_CAPACITY,
+    config::Config,
+    exchange::{
+        Exchange,
+        MarketOrderSettlement,
+    },
+    market_update::{
+        Bba,
+        Trade,
+    },
+    order_filters::enforce_bid_ask_spread,
+    types::{
+        AccountId,
+        CancelBy,
+        CancelLimitOrderError,
+        Currency,
+        LimitOrder,
+        LimitOrderEvent,
+        MarginCurrency,
+        MarketOrder,
+        Mon,
+        NewOrder,
+        Pending,
+        PriceFilterError,
+        QuoteCurrency,
+        Side,
+        Solvency,
+        SubmitLimitOrderError,
+        SubmitMarketOrderError,
+        TimestampNs,
+        UserOrderId,
+    },
+    utils::min,
+};
+
+/// Why the `MatchingEngine` cancelled the remainder of a partially filled market order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemainderCancelReason {
+    /// The resting orders of the other participants ran out.
+    InsufficientLiquidity,
+    /// The account can't afford the next fill at the price of the next resting order.
+    NotEnoughAvailableBalance,
+}
+
+/// The outcome of a market order matched by the `MatchingEngine`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchedMarketOrder<I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// The whole order got filled.
+    Filled(MarketOrderSettlement<I, D, BaseOrQuote, UserOrderIdT>),
+    /// The order got filled partially and the rest of it got cancelled.
+    RemainderCancelled {
+        /// The settlement of the filled part, where the filled order carries the filled quantity.
+        settlement: MarketOrderSettlement<I, D, BaseOrQuote, UserOrderIdT>,
+        /// The quantity which did not get filled.
+        cancelled_quantity: BaseOrQuote,
+        /// Why the rest of the order got cancelled.
+        reason: RemainderCancelReason,
+    },
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT> MatchedMarketOrder<I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// The settlement of the filled part of the order.
+    pub fn settlement(&self) -> &MarketOrderSettlement<I, D, BaseOrQuote, UserOrderIdT> {
+        match self {
+            Self::Filled(settlement) | Self::RemainderCancelled { settlement, .. } => settlement,
+        }
+    }
+}
+
+/// A price-time priority matching engine in which several participants trade with each other.
+///
+/// Each participant is an `Exchange` with its own `Account`, risk engine and order rate limiter,
+/// while their resting limit orders share one `OrderBook`.
+/// Market orders match against the resting orders of the other participants, so trades and the quote
+/// emerge from the participants themselves instead of from recorded market data.
+///
+/// After every change of the `OrderBook`, the best bid and ask are published to the participants
+/// holding a position, which checks them for liquidation. The other participants observe the quote
+/// once they act or trade. A liquidated position is closed with a market order against the resting
+/// orders of the other participants, so the positions of all participants always net to zero.
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies.
+/// - `BaseOrQuote`: Either `BaseCurrency` or `QuoteCurrency` depending on the futures type.
+/// - `UserOrderIdT`: The type of user order id to use. Set to `()` if you don't need one.
+#[derive(Debug, Clone, Getters, CopyGetters)]
+pub struct MatchingEngine<I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// The participants, indexed by their `AccountId`.
+    #[getset(get = "pub")]
+    participants: Vec<Exchange<I, D, BaseOrQuote, UserOrderIdT>>,
+
+    /// The version of the published quote each participant observed, indexed by its `AccountId`.
+    synced_quote_versions: Vec<u64>,
+
+    /// Incremented whenever the bid, ask, last trade price or timestamp change.
+    quote_version: u64,
+
+    /// The participants holding a position, which the published quote may liquidate.
+    exposed: BTreeSet<AccountId>,
+
+    /// Scratch buffer to visit the `exposed` participants while their positions change.
+    exposed_scratch: Vec<AccountId>,
+
+    /// The participants whose positions are yet to be closed against the `OrderBook`.
+    pending_liquidations: VecDeque<AccountId>,
+
+    /// The resting limit orders of all participants.
+    #[getset(get = "pub")]
+    order_book: OrderBook<I, D, BaseOrQuote>,
+
+    /// The maximum number of resting orders of all participants.
+    max_num_resting_orders: usize,
+
+    /// The bid published to the participants.
+    /// Keeps its last price while there are no resting buy orders.
+    #[getset(get_copy = "pub")]
+    bid: QuoteCurrency<I, D>,
+
+    /// The ask published to the participants.
+    /// Keeps its last price while there are no resting sell orders.
+    #[getset(get_copy = "pub")]
+    ask: QuoteCurrency<I, D>,
+
+    /// The price of the most recent trade, if any.
+    #[getset(get_copy = "pub")]
+    last_trade_price: Option<QuoteCurrency<I, D>>,
+
+    /// The current timestamp in nanoseconds.
+    #[getset(get_copy = "pub")]
+    current_ts_ns: TimestampNs,
+
+    /// The trades of the most recent action, in execution order.
+    /// The `side` is the one of the aggressing market order or liquidation.
+    #[getset(get = "pub")]
+    trades: Vec<Trade<I, D, BaseOrQuote>>,
+
+    /// The limit order events of the most recent action, in occurrence order:
+    /// the fills of resting orders and the orders the venue force-cancelled.
+    #[getset(get = "pub")]
+    limit_order_events: Vec<(AccountId, LimitOrderEvent<I, D, BaseOrQuote, UserOrderIdT>)>,
+
+    /// The participants whose position got liquidated during the most recent action.
+    #[getset(get = "pub")]
+    liquidations: Vec<AccountId>,
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT> MatchingEngine<I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// Create a new `MatchingEngine` without participants.
+    ///
+    /// # Arguments:
+    /// `opening_quote`: The bid and ask published to the participants until the `OrderBook` quotes both sides,
+    /// which anchors the price band of the `PriceFilter` and the liquidation checks.
+    pub fn new(opening_quote: Bba<I, D>) -> Result<Self, PriceFilterError> {
+        enforce_bid_ask_spread(opening_quote.bid, opening_quote.ask)?;
+        Ok(Self {
+            participants: Vec::with_capacity(4),
+            synced_quote_versions: Vec::with_capacity(4),
+            quote_version: 0,
+            exposed: BTreeSet::default(),
+            exposed_scratch: Vec::with_capacity(4),
+            pending_liquidations: VecDeque::with_capacity(4),
+            order_book: OrderBook::default(),
+            max_num_resting_orders: 0,
+            bid: opening_quote.bid,
+            ask: opening_quote.ask,
+            last_trade_price: None,
+            current_ts_ns: opening_quote.timestamp_exchange_ns,
+            trades: Vec::with_capacity(16),
+            limit_order_events: Vec::with_capacity(16),
+            liquidations: Vec::with_capacity(4),
+        })
+    }
+
+    /// Add a participant trading with its own `Account` under `config`.
+    pub fn add_participant(
+        &mut self,
+        config: Config<I, D, BaseOrQuote::PairedCurrency>,
+    ) -> AccountId {
+        let mut exchange = Exchange::new(config);
+        exchange.set_liquidated_by_matching_engine();
+        let liquidated = exchange.apply_quote(
+            self.bid,
+            self.ask,
+            self.last_trade_price,
+            self.current_ts_ns,
+        );
+        debug_assert!(!liquidated, "A new participant has no position");
+        let max_num_open_orders = usize::from(exchange.config().max_num_open_orders().get());
+        self.order_book.reserve(max_num_open_orders);
+        self.max_num_resting_orders += max_num_open_orders;
+
+        let participant = AccountId::new(self.participants.len());
+        let num_participants = self.participants.len() + 1;
+        // Every fill of an action fills a resting order completely, except for the last fill of the
+        // market order and of each liquidation. Each resting order is force-cancelled at most once.
+        reserve_total(
+            &mut self.trades,
+            self.max_num_resting_orders + num_participants,
+        );
+        reserve_total(
+            &mut self.limit_order_events,
+            2 * self.max_num_resting_orders + num_participants,
+        );
+        reserve_total(&mut self.liquidations, num_participants);
+        reserve_total(&mut self.exposed_scratch, num_participants);
+        self.pending_liquidations
+            .reserve(num_participants.saturating_sub(self.pending_liquidations.len()));
+        self.participants.reserve(1);
+        self.participants
+            .push_within_capacity(exchange)
+            .expect(EXPECT_CAPACITY);
+        self.synced_quote_versions.reserve(1);
+        self.synced_quote_versions
+            .push_within_capacity(self.quote_version)
+            .expect(EXPECT_CAPACITY);
+        participant
+    }
+
+    /// The `Exchange` of `participant`, holding its `Account` and `MarketState`.
+    /// Its `MarketState` lags behind the published quote while it holds no position
+    /// and did not act or trade since the quote changed.
+    ///
+    /// # Panics:
+    /// If the `participant` was not added to this `MatchingEngine`.
+    #[inline]
+    pub fn participant(
+        &self,
+        participant: AccountId,
+    ) -> &Exchange<I, D, BaseOrQuote, UserOrderIdT> {
+        &self.participants[participant.idx()]
+    }
+
+    /// Advance the time to `ts_ns`, publishing the current quote to all participants.
+    pub fn update_timestamp(&mut self, ts_ns: TimestampNs) {
+        assert2::assert!(ts_ns >= self.current_ts_ns, "Time must not go backwards");
+        self.clear_events();
+        self.current_ts_ns = ts_ns;
+        self.publish_quote(true);
+        for idx in 0..self.participants.len() {
+            self.sync_quote(AccountId::new(idx));
+        }
+    }
+
+    /// Submit a passive limit order of `participant` into the `OrderBook`.
+    /// It runs through the same checks as with `Exchange::submit_limit_order`,
+    /// so an order locking or crossing the published quote is rejected.
+    ///
+    /// # Panics:
+    /// If the `participant` was not added to this `MatchingEngine`.
+    pub fn submit_limit_order(
+        &mut self,
+        participant: AccountId,
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> Result<
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        SubmitLimitOrderError,
+    > {
+        self.clear_events();
+        self.sync_quote(participant);
+        let order = self.participants[participant.idx()].submit_limit_order(order)?;
+        self.order_book.insert(
+            order.side(),
+            BookOrder::new(
+                participant,
+                order.id(),
+                order.limit_price(),
+                order.remaining_quantity(),
+            ),
+        );
+        self.publish_quote(false);
+        self.sync_quote(participant);
+        Ok(order)
+    }
+
+    /// Cancel a resting limit order of `participant`.
+    ///
+    /// # Panics:
+    /// If the `participant` was not added to this `MatchingEngine`.
+    #[allow(clippy::complexity, reason = "How is this hard to read?")]
+    pub fn cancel_limit_order(
+        &mut self,
+        participant: AccountId,
+        cancel_by: CancelBy<UserOrderIdT>,
+    ) -> Result<
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        CancelLimitOrderError<UserOrderIdT>,
+    > {
+        self.clear_events();
+        self.sync_quote(participant);
+        let order = self.participants[participant.idx()].cancel_limit_order(cancel_by)?;
+        self.remove_resting_order(participant, &order);
+        self.publish_quote(false);
+        self.sync_quote(participant);
+        Ok(order)
+    }
+
+    /// Submit a market order of `participant`, which sweeps the resting orders of the other participants
+    /// in price-time priority. Resting orders of the `participant` itself are skipped.
+    ///
+    /// The admission checks run against the price of the best resting order, while the margin is checked
+    /// before each fill at the price of the resting order it fills.
+    /// Each fill is settled at the price of the resting order, while the collateral of the
+    /// `participant` is reconciled once the order is done.
+    ///
+    /// # Returns:
+    /// If Ok, whether the order filled completely or its remainder got cancelled, with the settlement
+    /// of the `participant` where the filled order carries the average fill price.
+    /// The resulting trades and the fills of the resting orders are available through
+    /// [`MatchingEngine::trades`] and [`MatchingEngine::limit_order_events`].
+    /// `SubmitMarketOrderError::InsufficientLiquidity` if there are no resting orders of the other participants,
+    /// `SubmitMarketOrderError::NotEnoughAvailableBalance` if the account can't afford the first fill.
+    ///
+    /// # Panics:
+    /// If the `participant` was not added to this `MatchingEngine`.
+    pub fn submit_market_order(
+        &mut self,
+        participant: AccountId,
+        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> Result<MatchedMarketOrder<I, D, BaseOrQuote, UserOrderIdT>, SubmitMarketOrderError> {
+        self.clear_events();
+        self.sync_quote(participant);
+        let side = order.side();
+        let book_side = side.inverted();
+        let Some(best) = self.order_book.best_counterparty(book_side, participant) else {
+            return Err(SubmitMarketOrderError::InsufficientLiquidity);
+        };
+        let order = self.participants[participant.idx()]
+            .admit_market_order_without_margin_check(order, best.limit_price())?;
+
+        let ts_ns = self.current_ts_ns;
+        let bad_debt_before = self.participants[participant.idx()]
+            .account()
+            .balances()
+            .bad_debt();
+        let mut filled_quantity = BaseOrQuote::zero();
+        let mut avg_fill_price = QuoteCurrency::zero();
+        let mut cancel_reason = None;
+        while filled_quantity < order.quantity() {
+            // A liquidated maker loses its resting orders, which may exhaust the liquidity.
+            let Some(resting) = self.order_book.best_counterparty(book_side, participant) else {
+                cancel_reason = Some(RemainderCancelReason::InsufficientLiquidity);
+                break;
+            };
+            let quantity = min(
+                order.quantity() - filled_quantity,
+                resting.remaining_quantity(),
+            );
+            let price = resting.limit_price();
+            if let Err(err) =
+                self.participants[participant.idx()].check_taker_fill(&order, quantity, price)
+            {
+                if filled_quantity.is_zero() {
+                    return Err(err.into());
+                }
+                cancel_reason = Some(RemainderCancelReason::NotEnoughAvailableBalance);
+                break;
+            }
+            debug!(
+                "match {quantity} @ {price} between taker {participant} and maker {}",
+                resting.participant()
+            );
+
+            self.fill_resting_order(book_side, resting, quantity);
+            self.participants[participant.idx()].settle_taker_fill(
+                order.state().meta().id(),
+                order.strategy_tag(),
+                side,
+                quantity,
+                price,
+            );
+            avg_fill_price = if filled_quantity.is_zero() {
+                price
+            } else {
+                QuoteCurrency::new_weighted_price(
+                    avg_fill_price,
+                    *filled_quantity.as_ref(),
+                    price,
+                    *quantity.as_ref(),
+                )
+            };
+            filled_quantity += quantity;
+            self.record_trade(price, quantity, side);
+        }
+        assert2::debug_assert!(filled_quantity > BaseOrQuote::zero());
+
+        let (forced_cancels, solvency, fee_tier_change) =
+            self.participants[participant.idx()].finish_taker_settlement(bad_debt_before);
+        for cancelled in forced_cancels.iter() {
+            self.remove_resting_order(participant, cancelled);
+        }
+        if matches!(solvency, Solvency::Liquidated | Solvency::Bankrupt) {
+            self.queue_liquidation(participant);
+        }
+        self.update_exposure(participant);
+        self.publish_quote(true);
+        self.sync_quote(participant);
+
+        Ok(match cancel_reason {
+            None => MatchedMarketOrder::Filled(MarketOrderSettlement {
+                filled_order: order.into_filled(avg_fill_price, ts_ns),
+                forced_cancels,
+                solvency,
+                fee_tier_change,
+            }),
+            Some(reason) => {
+                warn!(
+                    "cancelled the remainder of a market order of participant {participant}: {reason:?}"
+                );
+                let cancelled_quantity = order.quantity() - filled_quantity;
+                MatchedMarketOrder::RemainderCancelled {
+                    settlement: MarketOrderSettlement {
+                        filled_order: order.into_partially_filled(
+                            avg_fill_price,
+                            filled_quantity,
+                            ts_ns,
+                        ),
+                        forced_cancels,
+                        solvency,
+                        fee_tier_change,
+                    },
+                    cancelled_quantity,
+                    reason,
+                }
+            }
+        })
+    }
+
+    /// Fill the `resting` order of `book_side` by `quantity` at its limit price,
+    /// settling the fill into the account of its maker.
+    fn fill_resting_order(
+        &mut self,
+        book_side: Side,
+        resting: BookOrder<I, D, BaseOrQuote>,
+        quantity: BaseOrQuote,
+    ) {
+        let maker = resting.participant();
+        // Only participants holding a position can get liquidated by the quote and they observed it already,
+        // so the resting order survives the sync.
+        self.sync_quote(maker);
+        self.order_book.fill(maker, resting.order_id(), quantity);
+        let exchange = &mut self.participants[maker.idx()];
+        exchange.clear_events();
+        if exchange.fill_best_limit_order(
+            book_side,
+            resting.order_id(),
+            quantity,
+            self.current_ts_ns,
+        ) {
+            self.queue_liquidation(maker);
+        }
+        self.collect_events(maker);
+        self.update_exposure(maker);
+    }
+
+    /// Close the position of the liquidated `participant` with a market order against the resting orders
+    /// of the other participants. Without enough resting liquidity, the rest of the position stays open
+    /// until a later quote liquidates it again.
+    ///
+    /// # Returns:
+    /// `true` if it traded.
+    fn liquidate(&mut self, participant: AccountId) -> bool {
+        let position_qty = self.participants[participant.idx()]
+            .account()
+            .position()
+            .quantity();
+        let side = if position_qty.is_negative() {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        let book_side = side.inverted();
+        let mut remaining = position_qty.abs();
+        let mut traded = false;
+        while !remaining.is_zero() {
+            let Some(resting) = self.order_book.best_counterparty(book_side, participant) else {
+                warn!(
+                    "not enough resting liquidity to close the position of participant {participant}"
+                );
+                break;
+            };
+            let quantity = min(remaining, resting.remaining_quantity());
+            let price = resting.limit_price();
+            debug!(
+                "liquidate {quantity} @ {price} of participant {participant} against maker {}",
+                resting.participant()
+            );
+
+            self.fill_resting_order(book_side, resting, quantity);
+            self.participants[participant.idx()].settle_liquidation_fill(side, quantity, price);
+            remaining -= quantity;
+            self.record_trade(price, quantity, side);
+            traded = true;
+        }
+        self.update_exposure(participant);
+        traded
+    }
+
+    /// Publish the best bid and ask of the `OrderBook` if they changed or if `changed` is set,
+    /// because the last trade price or the timestamp changed.
+    /// Only the participants holding a position observe the quote right away, as only they can get
+    /// liquidated by it; the others observe it once they act or trade, see `sync_quote`.
+    /// Repeats as long as closing liquidated positions against the `OrderBook` changes the quote.
+    fn publish_quote(&mut self, mut changed: bool) {
+        loop {
+            let bid = self.order_book.best_bid().unwrap_or(self.bid);
+            let ask = self.order_book.best_ask().unwrap_or(self.ask);
+            if changed || bid != self.bid || ask != self.ask {
+                self.bid = bid;
+                self.ask = ask;
+                self.quote_version += 1;
+
+                self.exposed_scratch.clear();
+                for participant in self.exposed.iter() {
+                    self.exposed_scratch
+                        .push_within_capacity(*participant)
+                        .expect(EXPECT_CAPACITY);
+                }
+                for i in 0..self.exposed_scratch.len() {
+                    self.sync_quote(self.exposed_scratch[i]);
+                }
+            }
+
+            let Some(participant) = self.pending_liquidations.pop_front() else {
+                return;
+            };
+            changed = self.liquidate(participant);
+        }
+    }
+
+    /// Apply the published quote to `participant` unless it observed it already,
+    /// queueing its liquidation if the quote violates its maintenance margin.
+    fn sync_quote(&mut self, participant: AccountId) {
+        if self.synced_quote_versions[participant.idx()] == self.quote_version {
+            return;
+        }
+        self.synced_quote_versions[participant.idx()] = self.quote_version;
+        let exchange = &mut self.participants[participant.idx()];
+        exchange.clear_events();
+        if exchange.apply_quote(
+            self.bid,
+            self.ask,
+            self.last_trade_price,
+            self.current_ts_ns,
+        ) {
+            self.queue_liquidation(participant);
+        }
+        self.collect_events(participant);
+    }
+
+    /// Move the limit order events of `participant` into the events of the current action,
+    /// removing the orders the venue cancelled from the `OrderBook`.
+    fn collect_events(&mut self, participant: AccountId) {
+        for event in self.participants[participant.idx()].limit_order_events() {
+            match event {
+                LimitOrderEvent::Fill(_) => {}
+                LimitOrderEvent::ForcedCancel(order)
+                | LimitOrderEvent::ContractSpecCancel(order) => {
+                    let removed = self.order_book.remove(participant, order.id());
+                    assert2::assert!(removed.is_some(), "Every resting order is in the book");
+                }
+            }
+            self.limit_order_events
+                .push_within_capacity((participant, event.clone()))
+                .expect(EXPECT_CAPACITY);
+        }
+    }
+
+    fn remove_resting_order(
+        &mut self,
+        participant: AccountId,
+        order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    ) {
+        let removed = self.order_book.remove(participant, order.id());
+        assert2::assert!(removed.is_some(), "Every resting order is in the book");
+    }
+
+    fn record_trade(&mut self, price: QuoteCurrency<I, D>, quantity: BaseOrQuote, side: Side) {
+        self.trades
+            .push_within_capacity(Trade {
+                timestamp_exchange_ns: self.current_ts_ns,
+                price,
+                quantity,
+                side,
+            })
+            .expect(EXPECT_CAPACITY);
+        self.last_trade_price = Some(price);
+    }
+
+    fn update_exposure(&mut self, participant: AccountId) {
+        if self.participants[participant.idx()]
+            .account()
+            .position()
+            .quantity()
+            .is_zero()
+        {
+            self.exposed.remove(&participant);
+        } else {
+            self.exposed.insert(participant);
+        }
+    }
+
+    fn queue_liquidation(&mut self, participant: AccountId) {
+        if !self.liquidations.contains(&participant) {
+            warn!("participant {participant} got liquidated");
+            self.liquidations
+                .push_within_capacity(participant)
+                .expect(EXPECT_CAPACITY);
+        }
+        if !self.pending_liquidations.contains(&participant) {
+            self.pending_liquidations.push_back(participant);
+        }
+    }
+
+    fn clear_events(&mut self) {
+        self.trades.clear();
+        self.limit_order_events.clear();
+        self.liquidations.clear();
+    }
+}
+
+/// Reserve capacity for `capacity` elements in total, which `push_within_capacity` relies on.
+fn reserve_total<T>(buffer: &mut Vec<T>, capacity: usize) {
+    buffer.reserve(capacity.saturating_sub(buffer.len()));
+}
@@ -0,0 +1,14 @@
+//! A matching engine where several participants trade with each other through a shared order book.
+
+mod engine;
+mod order_book;
+
+pub use engine::{
+    MatchedMarketOrder,
+    MatchingEngine,
+    RemainderCancelReason,
+};
+pub use order_book::{
+    BookOrder,
+    OrderBook,
+};
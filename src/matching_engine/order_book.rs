@@ -0,0 +1,352 @@
+//! A price-time priority limit order book shared by all participants of the `MatchingEngine`.
+
+use std::collections::{
+    BTreeMap,
+    HashMap,
+    VecDeque,
+};
+
+use getset::{
+    CopyGetters,
+    Getters,
+};
+use num_traits::Zero;
+
+use crate::types::{
+    AccountId,
+    Currency,
+    Mon,
+    OrderId,
+    QuoteCurrency,
+    Side,
+};
+
+/// A resting limit order in the `OrderBook`.
+/// The full order lives in the `Account` of its participant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CopyGetters)]
+pub struct BookOrder<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The participant who owns the order.
+    #[getset(get_copy = "pub")]
+    participant: AccountId,
+
+    /// The id assigned by the `Exchange` of the participant.
+    #[getset(get_copy = "pub")]
+    order_id: OrderId,
+
+    /// The price at which the order rests.
+    #[getset(get_copy = "pub")]
+    limit_price: QuoteCurrency<I, D>,
+
+    /// The quantity which has not been filled yet.
+    #[getset(get_copy = "pub")]
+    remaining_quantity: BaseOrQuote,
+}
+
+impl<I, const D: u8, BaseOrQuote> BookOrder<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    pub(crate) fn new(
+        participant: AccountId,
+        order_id: OrderId,
+        limit_price: QuoteCurrency<I, D>,
+        remaining_quantity: BaseOrQuote,
+    ) -> Self {
+        assert2::debug_assert!(limit_price > QuoteCurrency::zero());
+        assert2::debug_assert!(remaining_quantity > BaseOrQuote::zero());
+        Self {
+            participant,
+            order_id,
+            limit_price,
+            remaining_quantity,
+        }
+    }
+}
+
+/// The price levels of one side of the `OrderBook`, each holding its resting orders in time priority.
+type PriceLevels<I, const D: u8, BaseOrQuote> =
+    BTreeMap<QuoteCurrency<I, D>, VecDeque<BookOrder<I, D, BaseOrQuote>>>;
+
+/// The resting limit orders of all participants, in price-time priority.
+/// Each side is a map of price levels, where the oldest order of a level is at its front.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Getters)]
+pub struct OrderBook<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The resting buy orders by price level, the best bid is the last level.
+    #[getset(get = "pub")]
+    bids: PriceLevels<I, D, BaseOrQuote>,
+
+    /// The resting sell orders by price level, the best ask is the first level.
+    #[getset(get = "pub")]
+    asks: PriceLevels<I, D, BaseOrQuote>,
+
+    /// The side and price level of every resting order, by its participant and `OrderId`.
+    index: HashMap<(AccountId, OrderId), (Side, QuoteCurrency<I, D>)>,
+}
+
+impl<I, const D: u8, BaseOrQuote> OrderBook<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Reserve capacity for `additional` resting orders.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.index.reserve(additional);
+    }
+
+    /// The number of resting orders on both sides.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// `true` if there are no resting orders.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The best bid price, if any.
+    #[inline]
+    pub fn best_bid(&self) -> Option<QuoteCurrency<I, D>> {
+        self.bids.last_key_value().map(|(price, _)| *price)
+    }
+
+    /// The best ask price, if any.
+    #[inline]
+    pub fn best_ask(&self) -> Option<QuoteCurrency<I, D>> {
+        self.asks.first_key_value().map(|(price, _)| *price)
+    }
+
+    /// The total resting quantity of `side` at `price`.
+    pub fn quantity_at(&self, side: Side, price: QuoteCurrency<I, D>) -> BaseOrQuote {
+        self.levels(side)
+            .get(&price)
+            .map_or(BaseOrQuote::zero(), |level| {
+                level.iter().fold(BaseOrQuote::zero(), |acc, order| {
+                    acc + order.remaining_quantity
+                })
+            })
+    }
+
+    #[inline]
+    fn levels(&self, side: Side) -> &PriceLevels<I, D, BaseOrQuote> {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    #[inline]
+    fn levels_mut(&mut self, side: Side) -> &mut PriceLevels<I, D, BaseOrQuote> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+
+    /// Insert a new order of `side` behind all orders with the same or a better price.
+    pub(crate) fn insert(&mut self, side: Side, order: BookOrder<I, D, BaseOrQuote>) {
+        let previous = self.index.insert(
+            (order.participant, order.order_id),
+            (side, order.limit_price),
+        );
+        assert2::debug_assert!(previous.is_none(), "An order rests only once");
+        self.levels_mut(side)
+            .entry(order.limit_price)
+            .or_default()
+            .push_back(order);
+    }
+
+    /// Remove the order of `participant` with `order_id`, if it rests in the book.
+    pub(crate) fn remove(
+        &mut self,
+        participant: AccountId,
+        order_id: OrderId,
+    ) -> Option<BookOrder<I, D, BaseOrQuote>> {
+        let (side, price) = self.index.remove(&(participant, order_id))?;
+        let levels = self.levels_mut(side);
+        let level = levels
+            .get_mut(&price)
+            .expect("The index points to an existing price level");
+        let idx = level
+            .iter()
+            .position(|order| order.participant == participant && order.order_id == order_id)
+            .expect("The index points to an order of the price level");
+        let order = level.remove(idx);
+        if level.is_empty() {
+            levels.remove(&price);
+        }
+        order
+    }
+
+    /// The best order of `side` which does not belong to `taker`.
+    pub(crate) fn best_counterparty(
+        &self,
+        side: Side,
+        taker: AccountId,
+    ) -> Option<BookOrder<I, D, BaseOrQuote>> {
+        let first_of_others = |level: &VecDeque<BookOrder<I, D, BaseOrQuote>>| {
+            level
+                .iter()
+                .find(|order| order.participant != taker)
+                .copied()
+        };
+        match side {
+            Side::Buy => self.bids.values().rev().find_map(first_of_others),
+            Side::Sell => self.asks.values().find_map(first_of_others),
+        }
+    }
+
+    /// Fill the resting order of `participant` with `order_id` by `filled_quantity`,
+    /// removing it once fully filled.
+    ///
+    /// # Panics:
+    /// If the order does not rest in the book or `filled_quantity` exceeds its remaining quantity.
+    pub(crate) fn fill(
+        &mut self,
+        participant: AccountId,
+        order_id: OrderId,
+        filled_quantity: BaseOrQuote,
+    ) {
+        let (side, price) = *self
+            .index
+            .get(&(participant, order_id))
+            .expect("Only resting orders get filled");
+        let levels = self.levels_mut(side);
+        let level = levels
+            .get_mut(&price)
+            .expect("The index points to an existing price level");
+        let idx = level
+            .iter()
+            .position(|order| order.participant == participant && order.order_id == order_id)
+            .expect("The index points to an order of the price level");
+        let order = &mut level[idx];
+        assert2::assert!(filled_quantity <= order.remaining_quantity);
+        order.remaining_quantity -= filled_quantity;
+        if order.remaining_quantity.is_zero() {
+            level.remove(idx);
+            if level.is_empty() {
+                levels.remove(&price);
+            }
+            self.index.remove(&(participant, order_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::One;
+
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    fn order(
+        participant: usize,
+        order_id: u64,
+        price: i64,
+    ) -> BookOrder<i64, 5, BaseCurrency<i64, 5>> {
+        BookOrder::new(
+            AccountId::new(participant),
+            order_id.into(),
+            QuoteCurrency::new(price, 0),
+            BaseCurrency::one(),
+        )
+    }
+
+    fn level(
+        book: &OrderBook<i64, 5, BaseCurrency<i64, 5>>,
+        side: Side,
+        price: i64,
+    ) -> Vec<BookOrder<i64, 5, BaseCurrency<i64, 5>>> {
+        book.levels(side)[&QuoteCurrency::new(price, 0)]
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn order_book_price_time_priority() {
+        let mut book = OrderBook::default();
+        book.reserve(7);
+        book.insert(Side::Buy, order(0, 0, 100));
+        book.insert(Side::Buy, order(1, 0, 101));
+        book.insert(Side::Buy, order(2, 0, 100));
+        book.insert(Side::Buy, order(2, 1, 99));
+        assert_eq!(book.best_bid(), Some(QuoteCurrency::new(101, 0)));
+        assert_eq!(book.bids().len(), 3);
+        assert_eq!(level(&book, Side::Buy, 100), vec![
+            order(0, 0, 100),
+            order(2, 0, 100)
+        ]);
+
+        book.insert(Side::Sell, order(0, 1, 103));
+        book.insert(Side::Sell, order(1, 1, 102));
+        book.insert(Side::Sell, order(2, 2, 102));
+        assert_eq!(book.best_ask(), Some(QuoteCurrency::new(102, 0)));
+        assert_eq!(level(&book, Side::Sell, 102), vec![
+            order(1, 1, 102),
+            order(2, 2, 102)
+        ]);
+        assert_eq!(
+            book.quantity_at(Side::Sell, QuoteCurrency::new(102, 0)),
+            BaseCurrency::new(2, 0)
+        );
+        assert_eq!(
+            book.quantity_at(Side::Sell, QuoteCurrency::new(101, 0)),
+            BaseCurrency::zero()
+        );
+        assert_eq!(book.len(), 7);
+    }
+
+    #[test]
+    fn order_book_best_counterparty() {
+        let mut book = OrderBook::default();
+        book.reserve(3);
+        book.insert(Side::Sell, order(0, 0, 102));
+        book.insert(Side::Sell, order(1, 0, 101));
+        book.insert(Side::Sell, order(0, 1, 101));
+        assert_eq!(
+            book.best_counterparty(Side::Sell, AccountId::new(0)),
+            Some(order(1, 0, 101))
+        );
+        assert_eq!(
+            book.best_counterparty(Side::Sell, AccountId::new(1)),
+            Some(order(0, 1, 101))
+        );
+        assert_eq!(book.best_counterparty(Side::Buy, AccountId::new(1)), None);
+    }
+
+    #[test]
+    fn order_book_fill_and_remove() {
+        let mut book = OrderBook::default();
+        book.reserve(2);
+        book.insert(Side::Buy, order(0, 0, 100));
+        book.insert(Side::Buy, order(1, 0, 99));
+
+        book.fill(AccountId::new(0), 0.into(), BaseCurrency::new(5, 1));
+        assert_eq!(
+            level(&book, Side::Buy, 100)[0].remaining_quantity(),
+            BaseCurrency::new(5, 1)
+        );
+        book.fill(AccountId::new(0), 0.into(), BaseCurrency::new(5, 1));
+        assert_eq!(book.best_bid(), Some(QuoteCurrency::new(99, 0)));
+        assert_eq!(book.len(), 1);
+
+        assert_eq!(
+            book.remove(AccountId::new(1), 0.into()),
+            Some(order(1, 0, 99))
+        );
+        assert_eq!(book.remove(AccountId::new(1), 0.into()), None);
+        assert!(book.is_empty());
+        assert!(book.bids().is_empty());
+    }
+}
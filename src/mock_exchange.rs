@@ -5,6 +5,7 @@ use std::num::NonZeroU16;
 use const_decimal::Decimal;
 
 use crate::{
+    EXPECT_ACCOUNT_TRACKER,
     EXPECT_CONFIG,
     EXPECT_CONTRACT_SPEC,
     EXPECT_DECIMAL,
@@ -51,9 +52,19 @@ pub fn mock_exchange_linear() -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIM
     Exchange::new(config)
 }
 
+/// Constructs a best bid and offer one tick wide above `bid`, as used with the mock exchanges.
+pub fn mock_bba(bid: i64, ts_ns: i64) -> Bba<i64, DECIMALS> {
+    Bba {
+        bid: QuoteCurrency::new(bid, 0),
+        ask: QuoteCurrency::new(bid + 1, 0),
+        timestamp_exchange_ns: ts_ns.into(),
+    }
+}
+
 /// Constructs a mock exchange (for linear futures) for testing.
 /// The size is denoted in `BaseCurrency`
-/// and the margin currency is `QuoteCurency`
+/// and the margin currency is `QuoteCurency`.
+/// Its `AccountTracker` samples the equity of every market update with a distinct timestamp.
 pub fn mock_exchange_linear_with_account_tracker(
     starting_balance: QuoteCurrency<i64, DECIMALS>,
 ) -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
@@ -73,7 +84,11 @@ pub fn mock_exchange_linear_with_account_tracker(
         OrderRateLimits::default(),
     )
     .expect(EXPECT_CONFIG);
-    Exchange::new(config)
+    let mut exchange = Exchange::new(config);
+    exchange.set_account_tracker(
+        AccountTracker::new(1.into(), EquityDenomination::Margin).expect(EXPECT_ACCOUNT_TRACKER),
+    );
+    exchange
 }
 
 /// Constructs a mock exchange (for inverse futures) for testing.
@@ -0,0 +1,548 @@
+//! Several accounts trading against the same market data,
+//! with self-trade prevention between the accounts of a firm.
+
+use getset::{
+    CopyGetters,
+    Getters,
+    Setters,
+};
+use tracing::warn;
+
+use crate::{
+    EXPECT_CAPACITY,
+    exchange::{
+        Exchange,
+        MarketOrderSettlement,
+    },
+    market_update::MarketUpdate,
+    order_latency::{
+        ArrivalGate,
+        RequestId,
+    },
+    types::{
+        AccountId,
+        AmendLimitOrderError,
+        CancelBy,
+        CancelLimitOrderError,
+        Currency,
+        LimitOrder,
+        MarginCurrency,
+        MarketOrder,
+        Mon,
+        NewOrder,
+        OrderId,
+        Pending,
+        QuoteCurrency,
+        RiskError,
+        Side,
+        SubmitLimitOrderError,
+        SubmitMarketOrderError,
+        UpdateStateError,
+        UserOrderId,
+    },
+};
+
+/// Identifies the firm owning an account.
+/// Orders of different accounts of the same firm are subject to `SelfTradePrevention`.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    std::hash::Hash,
+    derive_more::Display,
+)]
+pub struct FirmId(u32);
+
+impl From<u32> for FirmId {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+/// What happens when an incoming order would trade against a resting limit order
+/// of another account of the same firm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfTradePrevention {
+    /// Reject the incoming order, the resting orders stay in the book.
+    #[default]
+    CancelNewest,
+    /// Cancel the resting orders and accept the incoming order.
+    CancelOldest,
+    /// Cancel the resting orders and reject the incoming order.
+    CancelBoth,
+}
+
+/// Runs several accounts, each with its own `Exchange` and thus its own `Balances`, `Position`,
+/// order rate limiter and events, against the same market data.
+///
+/// The accounts don't trade with each other, but an incoming order of one account which would trade
+/// against a resting limit order of another account of the same firm triggers the `SelfTradePrevention`.
+/// Orders sent through the `OrderPipeline` of an account are subject to it once they arrive at the venue.
+/// Each account fills against the full market data, so their fills don't compete for its liquidity.
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies.
+/// - `BaseOrQuote`: Either `BaseCurrency` or `QuoteCurrency` depending on the futures type.
+/// - `UserOrderIdT`: The type of user order id to use. Set to `()` if you don't need one.
+#[derive(Debug, Clone, Getters, CopyGetters, Setters)]
+pub struct MultiAccountExchange<I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// The `Exchange` of each account, indexed by its `AccountId`.
+    #[getset(get = "pub")]
+    exchanges: Vec<Exchange<I, D, BaseOrQuote, UserOrderIdT>>,
+
+    /// The firm of each account, indexed by its `AccountId`.
+    firms: Vec<FirmId>,
+
+    /// What happens when orders of different accounts of the same firm would trade against each other.
+    #[getset(get_copy = "pub", set = "pub")]
+    self_trade_prevention: SelfTradePrevention,
+
+    /// The resting limit orders cancelled by the `SelfTradePrevention` during the most recent order submission
+    /// or [`MultiAccountExchange::update_state`] call.
+    #[getset(get = "pub")]
+    self_trade_cancels: Vec<(
+        AccountId,
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    )>,
+
+    /// The accounts liquidated during the most recent [`MultiAccountExchange::update_state`] call.
+    #[getset(get = "pub")]
+    liquidations: Vec<AccountId>,
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT>
+    MultiAccountExchange<I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// Create a new `MultiAccountExchange` without accounts.
+    pub fn new(self_trade_prevention: SelfTradePrevention) -> Self {
+        Self {
+            exchanges: Vec::with_capacity(4),
+            firms: Vec::with_capacity(4),
+            self_trade_prevention,
+            self_trade_cancels: Vec::with_capacity(4),
+            liquidations: Vec::with_capacity(4),
+        }
+    }
+
+    /// Add an account of `firm`, trading through the given `exchange`.
+    /// The `exchange` may be configured beforehand, e.g. with a `FillModel` or `LatencyModel`.
+    pub fn add_account(
+        &mut self,
+        exchange: Exchange<I, D, BaseOrQuote, UserOrderIdT>,
+        firm: FirmId,
+    ) -> AccountId {
+        let account = AccountId::new(self.exchanges.len());
+        self.exchanges.reserve(1);
+        self.exchanges
+            .push_within_capacity(exchange)
+            .expect(EXPECT_CAPACITY);
+        self.firms.reserve(1);
+        self.firms
+            .push_within_capacity(firm)
+            .expect(EXPECT_CAPACITY);
+        // Each account gets liquidated at most once per market update.
+        self.liquidations
+            .reserve(self.exchanges.len().saturating_sub(self.liquidations.len()));
+        account
+    }
+
+    /// The `Exchange` of `account`, holding its `Account`, `MarketState` and events.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    #[inline]
+    pub fn exchange(&self, account: AccountId) -> &Exchange<I, D, BaseOrQuote, UserOrderIdT> {
+        &self.exchanges[account.idx()]
+    }
+
+    /// The firm owning `account`.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    #[inline]
+    pub fn firm(&self, account: AccountId) -> FirmId {
+        self.firms[account.idx()]
+    }
+
+    /// Update every account with the same `market_update`, see [`Exchange::update_state`].
+    /// The order actions arriving at the venue are subject to the `SelfTradePrevention`.
+    ///
+    /// # Returns:
+    /// If Ok, the accounts whose position got liquidated.
+    /// The limit order events and order action events of each account are available through its `Exchange`.
+    /// Else the first error of an account other than `RiskError::Liquidate`, e.g. because it rejected the
+    /// `market_update` in strict mode, in which case the accounts after it did not process the `market_update`.
+    pub fn update_state<U>(&mut self, market_update: &U) -> Result<&[AccountId], UpdateStateError>
+    where
+        U: MarketUpdate<I, D, BaseOrQuote>,
+    {
+        self.liquidations.clear();
+        self.self_trade_cancels.clear();
+        for idx in 0..self.exchanges.len() {
+            let account = AccountId::new(idx);
+            let (exchange, mut gate) = self.split_off(account);
+            let result = exchange
+                .update_state_with_gate(market_update, &mut gate)
+                .map(|_| ());
+            match result {
+                Ok(()) => {}
+                Err(UpdateStateError::Risk(RiskError::Liquidate)) => {
+                    warn!("account {account} got liquidated");
+                    self.liquidations
+                        .push_within_capacity(account)
+                        .expect(EXPECT_CAPACITY);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(&self.liquidations)
+    }
+
+    /// Submit a new `LimitOrder` of `account`, see [`Exchange::submit_limit_order`].
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn submit_limit_order(
+        &mut self,
+        account: AccountId,
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> Result<
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        SubmitLimitOrderError,
+    > {
+        self.with_self_trade_prevention(
+            account,
+            order.side(),
+            order.limit_price(),
+            SubmitLimitOrderError::SelfTradePrevented,
+            |exchange| exchange.submit_limit_order(order),
+        )
+    }
+
+    /// Submit a new `MarketOrder` of `account`, see [`Exchange::submit_market_order`].
+    /// It would trade against the resting orders at or through the best bid or ask it fills at.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn submit_market_order(
+        &mut self,
+        account: AccountId,
+        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> Result<MarketOrderSettlement<I, D, BaseOrQuote, UserOrderIdT>, SubmitMarketOrderError>
+    {
+        let market_state = self.exchanges[account.idx()].market_state();
+        let fill_price = match order.side() {
+            Side::Buy => market_state.ask(),
+            Side::Sell => market_state.bid(),
+        };
+        self.with_self_trade_prevention(
+            account,
+            order.side(),
+            fill_price,
+            SubmitMarketOrderError::SelfTradePrevented,
+            |exchange| exchange.submit_market_order(order),
+        )
+    }
+
+    /// Amend a resting limit order of `account`, see [`Exchange::amend_limit_order`].
+    /// The `new_order` is subject to the `SelfTradePrevention` like a newly submitted order.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn amend_limit_order(
+        &mut self,
+        account: AccountId,
+        existing_order_id: OrderId,
+        new_order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> Result<
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        AmendLimitOrderError,
+    > {
+        self.with_self_trade_prevention(
+            account,
+            new_order.side(),
+            new_order.limit_price(),
+            SubmitLimitOrderError::SelfTradePrevented.into(),
+            |exchange| exchange.amend_limit_order(existing_order_id, new_order),
+        )
+    }
+
+    /// Cancel a resting limit order of `account`, see [`Exchange::cancel_limit_order`].
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    #[allow(clippy::complexity, reason = "How is this hard to read?")]
+    pub fn cancel_limit_order(
+        &mut self,
+        account: AccountId,
+        cancel_by: CancelBy<UserOrderIdT>,
+    ) -> Result<
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+        CancelLimitOrderError<UserOrderIdT>,
+    > {
+        self.exchanges[account.idx()].cancel_limit_order(cancel_by)
+    }
+
+    /// Send a limit order of `account` to the venue, see [`Exchange::send_limit_order`].
+    /// It is subject to the `SelfTradePrevention` once it arrives.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn send_limit_order(
+        &mut self,
+        account: AccountId,
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> RequestId {
+        self.exchanges[account.idx()].send_limit_order(order)
+    }
+
+    /// Send a market order of `account` to the venue, see [`Exchange::send_market_order`].
+    /// It is subject to the `SelfTradePrevention` once it arrives.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn send_market_order(
+        &mut self,
+        account: AccountId,
+        order: MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> RequestId {
+        self.exchanges[account.idx()].send_market_order(order)
+    }
+
+    /// Send a cancel request of `account` to the venue, see [`Exchange::send_cancel_limit_order`].
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn send_cancel_limit_order(
+        &mut self,
+        account: AccountId,
+        cancel_by: CancelBy<UserOrderIdT>,
+    ) -> RequestId {
+        self.exchanges[account.idx()].send_cancel_limit_order(cancel_by)
+    }
+
+    /// Send an amend request of `account` to the venue, see [`Exchange::send_amend_limit_order`].
+    /// The `new_order` is subject to the `SelfTradePrevention` once it arrives.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    pub fn send_amend_limit_order(
+        &mut self,
+        account: AccountId,
+        existing_order_id: OrderId,
+        new_order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    ) -> RequestId {
+        self.exchanges[account.idx()].send_amend_limit_order(existing_order_id, new_order)
+    }
+
+    /// Run `submit` for an incoming order of `account` on `side` reaching up to `price`,
+    /// applying the `SelfTradePrevention` if it would trade against a resting order of another account of the same firm.
+    /// Resting orders are only cancelled if the incoming order is rejected by the `SelfTradePrevention`
+    /// or accepted by the `Exchange`.
+    fn with_self_trade_prevention<T, E>(
+        &mut self,
+        account: AccountId,
+        side: Side,
+        price: QuoteCurrency<I, D>,
+        prevented: E,
+        submit: impl FnOnce(&mut Exchange<I, D, BaseOrQuote, UserOrderIdT>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.self_trade_cancels.clear();
+        let (exchange, mut gate) = self.split_off(account);
+        if !gate.admit(side, price) {
+            return Err(prevented);
+        }
+        let accepted = submit(exchange)?;
+        gate.accepted(side, price);
+        Ok(accepted)
+    }
+
+    /// The `Exchange` of `account` and the `SelfTradeGate` guarding the other accounts of its firm.
+    ///
+    /// # Panics:
+    /// If the `account` was not added to this `MultiAccountExchange`.
+    #[allow(clippy::type_complexity, reason = "Destructured right away")]
+    fn split_off(
+        &mut self,
+        account: AccountId,
+    ) -> (
+        &mut Exchange<I, D, BaseOrQuote, UserOrderIdT>,
+        SelfTradeGate<'_, I, D, BaseOrQuote, UserOrderIdT>,
+    ) {
+        let (before, rest) = self.exchanges.split_at_mut(account.idx());
+        let (exchange, after) = rest
+            .split_first_mut()
+            .expect("The account was added to this `MultiAccountExchange`");
+        let gate = SelfTradeGate {
+            account,
+            before,
+            after,
+            firms: &self.firms,
+            self_trade_prevention: self.self_trade_prevention,
+            self_trade_cancels: &mut self.self_trade_cancels,
+        };
+        (exchange, gate)
+    }
+}
+
+/// Applies the `SelfTradePrevention` to the incoming orders of `account`,
+/// against the resting limit orders of the other accounts of its firm.
+struct SelfTradeGate<'a, I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    account: AccountId,
+    /// The exchanges of the accounts before `account`.
+    before: &'a mut [Exchange<I, D, BaseOrQuote, UserOrderIdT>],
+    /// The exchanges of the accounts after `account`.
+    after: &'a mut [Exchange<I, D, BaseOrQuote, UserOrderIdT>],
+    firms: &'a [FirmId],
+    self_trade_prevention: SelfTradePrevention,
+    self_trade_cancels: &'a mut Vec<(
+        AccountId,
+        LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    )>,
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT> SelfTradeGate<'_, I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// `true` if an order on `side`, reaching up to `price`, would trade against
+    /// a resting limit order of another account of the same firm.
+    fn would_self_trade(&self, side: Side, price: QuoteCurrency<I, D>) -> bool {
+        let firm = self.firms[self.account.idx()];
+        let offset = self.account.idx() + 1;
+        self.before
+            .iter()
+            .enumerate()
+            .chain(
+                self.after
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, exchange)| (idx + offset, exchange)),
+            )
+            .filter(|(idx, _)| self.firms[*idx] == firm)
+            .any(|(_, exchange)| {
+                let orders = exchange.account().active_limit_orders();
+                match side {
+                    Side::Buy => orders.best_ask(),
+                    Side::Sell => orders.best_bid(),
+                }
+                .is_some_and(|best| crosses(side, price, best.limit_price()))
+            })
+    }
+
+    /// Cancel the resting limit orders of the other accounts of the firm,
+    /// which an order on `side` reaching up to `price` would trade against.
+    fn cancel_self_trades(&mut self, side: Side, price: QuoteCurrency<I, D>) {
+        let firm = self.firms[self.account.idx()];
+        let offset = self.account.idx() + 1;
+        let others = self.before.iter_mut().enumerate().chain(
+            self.after
+                .iter_mut()
+                .enumerate()
+                .map(|(idx, exchange)| (idx + offset, exchange)),
+        );
+        for (idx, exchange) in others {
+            if self.firms[idx] != firm {
+                continue;
+            }
+            self.self_trade_cancels
+                .reserve(exchange.account().active_limit_orders().num_active());
+            loop {
+                // The best resting order is the first one the incoming order would trade against.
+                let orders = exchange.account().active_limit_orders();
+                let Some(order_id) = match side {
+                    Side::Buy => orders.best_ask(),
+                    Side::Sell => orders.best_bid(),
+                }
+                .filter(|best| crosses(side, price, best.limit_price()))
+                .map(|best| best.id()) else {
+                    break;
+                };
+                // A venue initiated cancellation, which does not count against the rate limit.
+                let cancelled = exchange
+                    .cancel_limit_order_no_rate_limit(CancelBy::OrderId(order_id))
+                    .expect("The order is active");
+                self.self_trade_cancels
+                    .push_within_capacity((AccountId::new(idx), cancelled))
+                    .expect(EXPECT_CAPACITY);
+            }
+        }
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT> ArrivalGate<I, D>
+    for SelfTradeGate<'_, I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    fn admit(&mut self, side: Side, price: QuoteCurrency<I, D>) -> bool {
+        if !self.would_self_trade(side, price) {
+            return true;
+        }
+        warn!(
+            "{side} order of account {} would trade against another account of firm {}, applying {:?}",
+            self.account,
+            self.firms[self.account.idx()],
+            self.self_trade_prevention
+        );
+        match self.self_trade_prevention {
+            SelfTradePrevention::CancelNewest => false,
+            SelfTradePrevention::CancelOldest => true,
+            SelfTradePrevention::CancelBoth => {
+                self.cancel_self_trades(side, price);
+                false
+            }
+        }
+    }
+
+    fn accepted(&mut self, side: Side, price: QuoteCurrency<I, D>) {
+        if self.self_trade_prevention == SelfTradePrevention::CancelOldest {
+            self.cancel_self_trades(side, price);
+        }
+    }
+}
+
+/// `true` if an incoming order on `side` reaching up to `price` trades against a resting order at `resting_price`.
+#[inline]
+fn crosses<I, const D: u8>(
+    side: Side,
+    price: QuoteCurrency<I, D>,
+    resting_price: QuoteCurrency<I, D>,
+) -> bool
+where
+    I: Mon<D>,
+{
+    match side {
+        Side::Buy => resting_price <= price,
+        Side::Sell => resting_price >= price,
+    }
+}
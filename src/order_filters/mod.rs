@@ -1,7 +1,9 @@
 //! Contains order filter related code
 
+mod notional_filter;
 mod price_filter;
 mod quantity_filter;
 
+pub use notional_filter::NotionalFilter;
 pub use price_filter::*;
 pub use quantity_filter::QuantityFilter;
@@ -0,0 +1,164 @@
+//! This module contains the order notional filter.
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::{
+    prelude::{
+        ConfigError,
+        Currency,
+        Mon,
+        QuoteCurrency,
+    },
+    types::ValidateOrderNotionalError,
+};
+
+/// The `NotionalFilter` defines the rules for the notional value of each order,
+/// e.g. a minimum order value of 5 USDT.
+/// The notional value is denoted in the margin currency.
+#[derive(Debug, Clone, Copy, CopyGetters)]
+pub struct NotionalFilter<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Defines the optional minimum notional value of any order.
+    #[getset(get_copy = "pub")]
+    min_notional: Option<BaseOrQuote::PairedCurrency>,
+
+    /// Defines the optional maximum notional value of any order.
+    #[getset(get_copy = "pub")]
+    max_notional: Option<BaseOrQuote::PairedCurrency>,
+}
+
+impl<I, const D: u8, BaseOrQuote> Default for NotionalFilter<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn default() -> Self {
+        Self {
+            min_notional: None,
+            max_notional: None,
+        }
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> NotionalFilter<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a new instance of the `NotionalFilter`.
+    /// Both bounds must be positive and `min_notional` must not exceed `max_notional`.
+    pub fn new(
+        min_notional: Option<BaseOrQuote::PairedCurrency>,
+        max_notional: Option<BaseOrQuote::PairedCurrency>,
+    ) -> Result<Self, ConfigError> {
+        if min_notional.is_some_and(|min| min <= Zero::zero())
+            || max_notional.is_some_and(|max| max <= Zero::zero())
+        {
+            return Err(ConfigError::InvalidNotionalFilter);
+        }
+        if let (Some(min), Some(max)) = (min_notional, max_notional)
+            && min > max
+        {
+            return Err(ConfigError::InvalidNotionalFilter);
+        }
+
+        Ok(Self {
+            min_notional,
+            max_notional,
+        })
+    }
+
+    /// Check the notional value of an order with `quantity` at `price`.
+    pub(crate) fn validate_order_notional(
+        &self,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+    ) -> Result<(), ValidateOrderNotionalError> {
+        let notional = BaseOrQuote::PairedCurrency::convert_from(quantity, price);
+
+        if let Some(min_notional) = self.min_notional
+            && notional < min_notional
+        {
+            return Err(ValidateOrderNotionalError::NotionalTooLow);
+        }
+        if let Some(max_notional) = self.max_notional
+            && notional > max_notional
+        {
+            return Err(ValidateOrderNotionalError::NotionalTooHigh);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn notional_filter_new() {
+        assert_eq!(
+            NotionalFilter::<i64, 5, BaseCurrency<i64, 5>>::new(Some(QuoteCurrency::zero()), None)
+                .unwrap_err(),
+            ConfigError::InvalidNotionalFilter
+        );
+        assert_eq!(
+            NotionalFilter::<i64, 5, BaseCurrency<i64, 5>>::new(
+                Some(QuoteCurrency::new(10, 0)),
+                Some(QuoteCurrency::new(5, 0))
+            )
+            .unwrap_err(),
+            ConfigError::InvalidNotionalFilter
+        );
+        NotionalFilter::<i64, 5, BaseCurrency<i64, 5>>::new(
+            Some(QuoteCurrency::new(5, 0)),
+            Some(QuoteCurrency::new(5, 0)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn notional_filter_linear() {
+        let filter = NotionalFilter::<i64, 5, BaseCurrency<i64, 5>>::new(
+            Some(QuoteCurrency::new(5, 0)),
+            Some(QuoteCurrency::new(1000, 0)),
+        )
+        .unwrap();
+        let price = QuoteCurrency::new(100, 0);
+        assert_eq!(
+            filter.validate_order_notional(BaseCurrency::new(4, 2), price),
+            Err(ValidateOrderNotionalError::NotionalTooLow)
+        );
+        filter
+            .validate_order_notional(BaseCurrency::new(5, 2), price)
+            .unwrap();
+        filter
+            .validate_order_notional(BaseCurrency::new(10, 0), price)
+            .unwrap();
+        assert_eq!(
+            filter.validate_order_notional(BaseCurrency::new(1001, 2), price),
+            Err(ValidateOrderNotionalError::NotionalTooHigh)
+        );
+    }
+
+    #[test]
+    fn notional_filter_inverse() {
+        let filter = NotionalFilter::<i64, 5, QuoteCurrency<i64, 5>>::new(
+            Some(BaseCurrency::new(1, 2)),
+            None,
+        )
+        .unwrap();
+        let price = QuoteCurrency::new(100, 0);
+        assert_eq!(
+            filter.validate_order_notional(QuoteCurrency::new(99, 2), price),
+            Err(ValidateOrderNotionalError::NotionalTooLow)
+        );
+        filter
+            .validate_order_notional(QuoteCurrency::new(1, 0), price)
+            .unwrap();
+    }
+}
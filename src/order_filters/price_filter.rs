@@ -1,5 +1,9 @@
 use const_decimal::Decimal;
-use getset::CopyGetters;
+use getset::{
+    CopyGetters,
+    Getters,
+    Setters,
+};
 use num_traits::{
     One,
     Zero,
@@ -12,8 +16,92 @@ use crate::prelude::{
     QuoteCurrency,
 };
 
+/// The price which anchors the price band of the `PriceFilter`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriceReference {
+    /// The mid price between the best bid and ask.
+    #[default]
+    MidPrice,
+    /// The price of the last observed `Trade`.
+    LastTradePrice,
+    /// The mark price of the venue, observed through `MarkPrice` updates.
+    MarkPrice,
+}
+
+/// The direction in which a price off the tick grid is moved onto it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickRounding {
+    /// Towards the next lower tick, e.g. for bids.
+    Down,
+    /// Towards the next higher tick, e.g. for asks.
+    Up,
+    /// Towards the closest tick, rounding half way prices up.
+    Nearest,
+}
+
+/// A tick size which depends on the price level,
+/// e.g. a tick size of 0.01 below a price of 10 and a tick size of 0.1 above.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickSizeTable<I, const D: u8>
+where
+    I: Mon<D>,
+{
+    /// The minimum price from which on a tick size applies, with strictly increasing prices.
+    levels: Vec<(QuoteCurrency<I, D>, QuoteCurrency<I, D>)>,
+}
+
+impl<I, const D: u8> Default for TickSizeTable<I, D>
+where
+    I: Mon<D>,
+{
+    fn default() -> Self {
+        Self {
+            levels: Vec::with_capacity(0),
+        }
+    }
+}
+
+impl<I, const D: u8> TickSizeTable<I, D>
+where
+    I: Mon<D>,
+{
+    /// Create a new table from `(min_price, tick_size)` levels.
+    /// The `min_price` of the levels must be strictly increasing and a multiple of its `tick_size`.
+    /// Below the first level, the `tick_size` of the `PriceFilter` applies.
+    pub fn new(
+        levels: Vec<(QuoteCurrency<I, D>, QuoteCurrency<I, D>)>,
+    ) -> Result<Self, ConfigError> {
+        if levels.iter().any(|(min_price, tick_size)| {
+            *min_price <= QuoteCurrency::zero()
+                || *tick_size <= QuoteCurrency::zero()
+                || (*min_price % *tick_size) != QuoteCurrency::zero()
+        }) {
+            return Err(ConfigError::InvalidTickSizeTable);
+        }
+        if levels.windows(2).any(|w| w[1].0 <= w[0].0) {
+            return Err(ConfigError::InvalidTickSizeTable);
+        }
+        Ok(Self { levels })
+    }
+
+    /// The `(min_price, tick_size)` levels of the table.
+    #[inline]
+    pub fn levels(&self) -> &[(QuoteCurrency<I, D>, QuoteCurrency<I, D>)] {
+        &self.levels
+    }
+
+    /// The tick size of the level containing `price`, if any.
+    #[inline]
+    fn tick_size(&self, price: QuoteCurrency<I, D>) -> Option<QuoteCurrency<I, D>> {
+        let idx = self
+            .levels
+            .partition_point(|(min_price, _)| *min_price <= price);
+        idx.checked_sub(1).map(|idx| self.levels[idx].1)
+    }
+}
+
 /// The `PriceFilter` defines the price rules for a symbol
-#[derive(Debug, Clone, CopyGetters)]
+#[derive(Debug, Clone, CopyGetters, Getters, Setters)]
 pub struct PriceFilter<I, const D: u8>
 where
     I: Mon<D>,
@@ -32,17 +120,26 @@ where
     #[getset(get_copy = "pub")]
     tick_size: QuoteCurrency<I, D>,
 
-    /// Defines valid ranges for the order price relative to the mark price
+    /// Price dependent tick sizes, overriding `tick_size` from the price of their first level on.
+    /// Empty by default.
+    #[getset(get = "pub", set = "pub")]
+    tick_size_table: TickSizeTable<I, D>,
+
+    /// Defines valid ranges for the order price relative to the reference price
     /// To pass this filter,
-    /// order.limit_price <= mark_price * multiplier_up
+    /// order.limit_price <= reference_price * multiplier_up
     #[getset(get_copy = "pub")]
     multiplier_up: Decimal<I, D>,
 
-    /// Defines valid ranges for the order price relative to the mark price
+    /// Defines valid ranges for the order price relative to the reference price
     /// To pass this filter,
-    /// order.limit_price >= mark_price * multiplier_down
+    /// order.limit_price >= reference_price * multiplier_down
     #[getset(get_copy = "pub")]
     multiplier_down: Decimal<I, D>,
+
+    /// The price anchoring the band of `multiplier_up` and `multiplier_down`.
+    #[getset(get_copy = "pub", set = "pub")]
+    reference_price: PriceReference,
 }
 
 impl<I, const D: u8> Default for PriceFilter<I, D>
@@ -54,8 +151,10 @@ where
             min_price: None,
             max_price: None,
             tick_size: QuoteCurrency::from(Decimal::one()),
+            tick_size_table: TickSizeTable::default(),
             multiplier_up: Decimal::TWO,
             multiplier_down: Decimal::zero(),
+            reference_price: PriceReference::default(),
         }
     }
 }
@@ -115,31 +214,96 @@ where
             min_price,
             max_price,
             tick_size,
+            tick_size_table: TickSizeTable::default(),
             multiplier_up,
             multiplier_down,
+            reference_price: PriceReference::default(),
         })
     }
 
+    /// The tick size at the `price` level, taking the `tick_size_table` into account.
+    #[inline]
+    pub fn tick_size_at(&self, price: QuoteCurrency<I, D>) -> QuoteCurrency<I, D> {
+        self.tick_size_table
+            .tick_size(price)
+            .unwrap_or(self.tick_size)
+    }
+
     /// check if an `Order` is valid
+    /// The `reference_price` must be the price selected by `Self::reference_price`.
     pub fn validate_limit_price(
         &self,
         limit_price: QuoteCurrency<I, D>,
-        mark_price: QuoteCurrency<I, D>,
+        reference_price: QuoteCurrency<I, D>,
     ) -> Result<(), PriceFilterError> {
         enforce_max_price(self.max_price, limit_price)?;
         enforce_min_price(self.min_price, limit_price)?;
-        enforce_step_size(self.tick_size, limit_price)?;
+        enforce_step_size(self.tick_size_at(limit_price), limit_price)?;
+        self.enforce_price_band(limit_price, reference_price)
+    }
 
-        if limit_price > mark_price * self.multiplier_up && self.multiplier_up != Decimal::zero() {
+    /// Check if a `price` of a market update lies within the band of `multiplier_up` and `multiplier_down`
+    /// around the `reference_price`, which must be the price selected by `Self::reference_price`.
+    /// As long as no market data was observed, the `reference_price` is zero and the band is not enforced.
+    pub fn validate_price_band(
+        &self,
+        price: QuoteCurrency<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        if reference_price.is_zero() {
+            return Ok(());
+        }
+        self.enforce_price_band(price, reference_price)
+    }
+
+    fn enforce_price_band(
+        &self,
+        price: QuoteCurrency<I, D>,
+        reference_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        if price > reference_price * self.multiplier_up && self.multiplier_up != Decimal::zero() {
             return Err(PriceFilterError::LimitPriceAboveMultiple);
         }
-        if limit_price < mark_price * self.multiplier_down
+        if price < reference_price * self.multiplier_down
             && self.multiplier_down != Decimal::zero()
         {
             return Err(PriceFilterError::LimitPriceBelowMultiple);
         }
         Ok(())
     }
+
+    /// Move a positive `price` onto the tick grid at its price level in the direction of `rounding`.
+    /// Prices already on the grid are returned as is.
+    pub fn snap_to_tick(
+        &self,
+        price: QuoteCurrency<I, D>,
+        rounding: TickRounding,
+    ) -> QuoteCurrency<I, D> {
+        let tick_size = self.tick_size_at(price);
+        let below = price.quantize_round_to_zero(tick_size);
+        let remainder = price - below;
+        if remainder.is_zero() {
+            return price;
+        }
+        let round_up = match rounding {
+            TickRounding::Down => false,
+            TickRounding::Up => true,
+            TickRounding::Nearest => remainder + remainder >= tick_size,
+        };
+        if round_up { below + tick_size } else { below }
+    }
+
+    /// Check if the price of a resting order is still valid, e.g. after the venue changed its tick size.
+    /// Unlike `validate_limit_price`, the price band around the mark price is not enforced,
+    /// as it only applies to order entry.
+    pub(crate) fn validate_resting_price(
+        &self,
+        limit_price: QuoteCurrency<I, D>,
+    ) -> Result<(), PriceFilterError> {
+        enforce_max_price(self.max_price, limit_price)?;
+        enforce_min_price(self.min_price, limit_price)?;
+        enforce_step_size(self.tick_size_at(limit_price), limit_price)
+    }
 }
 
 /// Errors if there is no bid-ask spread
@@ -322,8 +486,10 @@ mod tests {
             min_price: Some(QuoteCurrency::<i32, 2>::new(1, 1)),
             max_price: Some(QuoteCurrency::new(1000, 0)),
             tick_size: QuoteCurrency::new(1, 1),
+            tick_size_table: TickSizeTable::default(),
             multiplier_up: Decimal::try_from_scaled(12, 1).unwrap(),
             multiplier_down: Decimal::try_from_scaled(8, 1).unwrap(),
+            reference_price: PriceReference::MidPrice,
         };
         let mark_price = QuoteCurrency::new(100, 0);
 
@@ -376,7 +542,79 @@ mod tests {
 
     #[test]
     fn size_of_price_filter() {
-        assert_eq!(size_of::<PriceFilter<i64, 5>>(), 56);
+        assert_eq!(size_of::<PriceFilter<i64, 5>>(), 88);
+    }
+
+    #[test]
+    fn tick_size_table_new() {
+        assert_eq!(
+            TickSizeTable::<i64, 5>::new(vec![(QuoteCurrency::new(10, 0), QuoteCurrency::zero())]),
+            Err(ConfigError::InvalidTickSizeTable)
+        );
+        assert_eq!(
+            TickSizeTable::<i64, 5>::new(vec![(QuoteCurrency::new(105, 1), QuoteCurrency::one())]),
+            Err(ConfigError::InvalidTickSizeTable)
+        );
+        assert_eq!(
+            TickSizeTable::<i64, 5>::new(vec![
+                (QuoteCurrency::new(10, 0), QuoteCurrency::new(1, 1)),
+                (QuoteCurrency::new(10, 0), QuoteCurrency::one()),
+            ]),
+            Err(ConfigError::InvalidTickSizeTable)
+        );
+    }
+
+    #[test]
+    fn price_filter_tick_size_table() {
+        let mut filter = PriceFilter::<i64, 5>::new(
+            None,
+            None,
+            QuoteCurrency::new(1, 2),
+            Decimal::TWO,
+            Decimal::zero(),
+        )
+        .unwrap();
+        filter.set_tick_size_table(
+            TickSizeTable::new(vec![
+                (QuoteCurrency::new(10, 0), QuoteCurrency::new(1, 1)),
+                (QuoteCurrency::new(100, 0), QuoteCurrency::one()),
+            ])
+            .unwrap(),
+        );
+        assert_eq!(
+            filter.tick_size_at(QuoteCurrency::new(999, 2)),
+            QuoteCurrency::new(1, 2)
+        );
+        assert_eq!(
+            filter.tick_size_at(QuoteCurrency::new(10, 0)),
+            QuoteCurrency::new(1, 1)
+        );
+        assert_eq!(
+            filter.tick_size_at(QuoteCurrency::new(150, 0)),
+            QuoteCurrency::one()
+        );
+
+        let reference_price = QuoteCurrency::new(100, 0);
+        filter
+            .validate_limit_price(QuoteCurrency::new(999, 2), reference_price)
+            .unwrap();
+        filter
+            .validate_limit_price(QuoteCurrency::new(995, 1), reference_price)
+            .unwrap();
+        assert_eq!(
+            filter.validate_limit_price(QuoteCurrency::new(9995, 2), reference_price),
+            Err(PriceFilterError::PriceStepSize {
+                price: QuoteCurrency::<i64, 5>::new(9995, 2).to_string(),
+                step_size: QuoteCurrency::<i64, 5>::new(1, 1).to_string(),
+            })
+        );
+        assert_eq!(
+            filter.validate_limit_price(QuoteCurrency::new(1005, 1), reference_price),
+            Err(PriceFilterError::PriceStepSize {
+                price: QuoteCurrency::<i64, 5>::new(1005, 1).to_string(),
+                step_size: QuoteCurrency::<i64, 5>::one().to_string(),
+            })
+        );
     }
 
     #[test]
@@ -387,4 +625,84 @@ mod tests {
             "PriceFilter( min_price: None, max_price: None, tick_size: 1.0 Quote, multiplier_up: 2.0, multiplier_down: 0.0 )",
         );
     }
+
+    #[test]
+    fn price_filter_snap_to_tick() {
+        let filter = PriceFilter::<i64, 5>::new(
+            None,
+            None,
+            QuoteCurrency::new(5, 1),
+            Decimal::TWO,
+            Decimal::zero(),
+        )
+        .unwrap();
+        let price = QuoteCurrency::new(10025, 2);
+        assert_eq!(
+            filter.snap_to_tick(price, TickRounding::Down),
+            QuoteCurrency::new(100, 0)
+        );
+        assert_eq!(
+            filter.snap_to_tick(price, TickRounding::Up),
+            QuoteCurrency::new(1005, 1)
+        );
+        assert_eq!(
+            filter.snap_to_tick(price, TickRounding::Nearest),
+            QuoteCurrency::new(1005, 1)
+        );
+        assert_eq!(
+            filter.snap_to_tick(QuoteCurrency::new(10024, 2), TickRounding::Nearest),
+            QuoteCurrency::new(100, 0)
+        );
+        assert_eq!(
+            filter.snap_to_tick(QuoteCurrency::new(1005, 1), TickRounding::Up),
+            QuoteCurrency::new(1005, 1)
+        );
+    }
+
+    #[test]
+    fn price_filter_validate_price_band() {
+        let filter = PriceFilter::<i64, 5>::new(
+            None,
+            None,
+            QuoteCurrency::new(1, 0),
+            Decimal::try_from_scaled(12, 1).unwrap(),
+            Decimal::try_from_scaled(8, 1).unwrap(),
+        )
+        .unwrap();
+        let reference_price = QuoteCurrency::new(100, 0);
+
+        filter
+            .validate_price_band(QuoteCurrency::new(120, 0), reference_price)
+            .unwrap();
+        filter
+            .validate_price_band(QuoteCurrency::new(80, 0), reference_price)
+            .unwrap();
+        assert_eq!(
+            filter.validate_price_band(QuoteCurrency::new(121, 0), reference_price),
+            Err(PriceFilterError::LimitPriceAboveMultiple)
+        );
+        assert_eq!(
+            filter.validate_price_band(QuoteCurrency::new(79, 0), reference_price),
+            Err(PriceFilterError::LimitPriceBelowMultiple)
+        );
+
+        // Without an observed reference price the band is not enforced.
+        filter
+            .validate_price_band(QuoteCurrency::new(1000, 0), QuoteCurrency::zero())
+            .unwrap();
+
+        let bba = Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(121, 0),
+            timestamp_exchange_ns: 0.into(),
+        };
+        assert_eq!(
+            <Bba<i64, 5> as MarketUpdate<i64, 5, BaseCurrency<i64, 5>>>::validate_price_band(
+                &bba,
+                &filter,
+                reference_price
+            ),
+            Err(PriceFilterError::LimitPriceAboveMultiple)
+        );
+    }
 }
@@ -0,0 +1,565 @@
+//! Models the latency between a trading strategy and the venue,
+//! so order actions only take effect once they arrive at the venue
+//! and their outcomes are only observed once the response arrives back at the strategy.
+
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fmt::Display,
+};
+
+use getset::CopyGetters;
+
+use crate::{
+    exchange::MarketOrderSettlement,
+    rng::SplitMix64,
+    types::{
+        AmendLimitOrderError,
+        CancelBy,
+        CancelLimitOrderError,
+        ConfigError,
+        Currency,
+        LimitOrder,
+        MarginCurrency,
+        MarketOrder,
+        Mon,
+        NewOrder,
+        OrderId,
+        Pending,
+        QuoteCurrency,
+        Side,
+        SubmitLimitOrderError,
+        SubmitMarketOrderError,
+        TimestampNs,
+        UserOrderId,
+    },
+};
+
+/// The distribution of the one-way latency from the trading strategy to the venue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LatencyDistribution {
+    /// Every order action takes the same time to arrive.
+    Fixed(TimestampNs),
+    /// The latency is uniformly distributed within the inclusive range.
+    Uniform {
+        /// The lowest possible latency.
+        min: TimestampNs,
+        /// The highest possible latency.
+        max: TimestampNs,
+    },
+    /// The latency is log-normally distributed, which captures the heavy right tail of network latency.
+    LogNormal {
+        /// The median latency.
+        median: TimestampNs,
+        /// The standard deviation of the logarithm of the latency.
+        sigma: f64,
+    },
+}
+
+/// Samples the latency of the order actions sent through the `send_*` methods of the `Exchange`,
+/// or of their responses on the way back.
+/// Seeded, so a backtest is reproducible.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct LatencyModel {
+    /// The distribution the latency is sampled from.
+    #[getset(get_copy = "pub")]
+    distribution: LatencyDistribution,
+
+    rng: SplitMix64,
+}
+
+impl Default for LatencyModel {
+    /// Without latency, a sent order action arrives with the next `update_state` call,
+    /// which also reports its outcome.
+    fn default() -> Self {
+        Self {
+            distribution: LatencyDistribution::Fixed(0.into()),
+            rng: SplitMix64::new(0),
+        }
+    }
+}
+
+impl LatencyModel {
+    /// Create a new `LatencyModel`.
+    /// Latencies must not be negative, `min` must not exceed `max`
+    /// and the `sigma` of a log-normal distribution must be finite and not negative.
+    pub fn new(distribution: LatencyDistribution, seed: u64) -> Result<Self, ConfigError> {
+        let zero = TimestampNs::from(0);
+        let valid = match distribution {
+            LatencyDistribution::Fixed(latency) => latency >= zero,
+            LatencyDistribution::Uniform { min, max } => min >= zero && min <= max,
+            LatencyDistribution::LogNormal { median, sigma } => {
+                median > zero && sigma.is_finite() && sigma >= 0.0
+            }
+        };
+        if !valid {
+            return Err(ConfigError::InvalidLatencyModel);
+        }
+
+        Ok(Self {
+            distribution,
+            rng: SplitMix64::new(seed),
+        })
+    }
+
+    /// Sample the latency of the next order action.
+    pub(crate) fn sample(&mut self) -> TimestampNs {
+        match self.distribution {
+            LatencyDistribution::Fixed(latency) => latency,
+            LatencyDistribution::Uniform { min, max } => {
+                let span = (max - min).get();
+                // Scale to `span + 1` possible values, where the clamp guards against rounding at the upper end.
+                let offset = (self.rng.next_f64() * (span as f64 + 1.0)) as i64;
+                min + offset.min(span).into()
+            }
+            LatencyDistribution::LogNormal { median, sigma } => {
+                let z = self.rng.next_standard_normal();
+                let latency = ((median.get() as f64) * (sigma * z).exp()).round() as i64;
+                latency.into()
+            }
+        }
+    }
+}
+
+/// Identifies an order action sent through one of the `send_*` methods of the `Exchange`.
+#[derive(Debug, Default, Clone, Copy, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RequestId(u64);
+
+impl From<u64> for RequestId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The outcome of an order action at the venue,
+/// emitted by the `Exchange::update_state` call during which the response arrives back at the strategy.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OrderActionEvent<I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    /// The venue accepted the limit order.
+    LimitOrderAccepted {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The order with its `OrderId` assigned by the venue.
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    },
+    /// The venue rejected the limit order.
+    LimitOrderRejected {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The reason for the rejection.
+        error: SubmitLimitOrderError,
+    },
+    /// The market order was filled on arrival.
+    MarketOrderFilled {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The fill along with the side effects of settling it.
+        settlement: MarketOrderSettlement<I, D, BaseOrQuote, UserOrderIdT>,
+    },
+    /// The venue rejected the market order.
+    MarketOrderRejected {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The reason for the rejection.
+        error: SubmitMarketOrderError,
+    },
+    /// The limit order was cancelled.
+    Cancelled {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The cancelled order.
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    },
+    /// The order was filled or cancelled while the cancel request was in flight.
+    TooLateToCancel {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The order the cancel request referred to.
+        cancel_by: CancelBy<UserOrderIdT>,
+    },
+    /// The venue rejected the cancel request.
+    CancelRejected {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The reason for the rejection.
+        error: CancelLimitOrderError<UserOrderIdT>,
+    },
+    /// The limit order was amended.
+    Amended {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The amended order with its new `OrderId`.
+        order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
+    },
+    /// The venue rejected the amend request.
+    /// `AmendLimitOrderError::OrderNoLongerActive` means the order was filled or cancelled while the request was in flight.
+    AmendRejected {
+        /// Identifies the order action.
+        request_id: RequestId,
+        /// The reason for the rejection.
+        error: AmendLimitOrderError,
+    },
+}
+
+/// An order action on its way to the venue.
+#[derive(Debug, Clone)]
+pub(crate) enum OrderAction<I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    SubmitLimitOrder(LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>),
+    SubmitMarketOrder(MarketOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>),
+    CancelLimitOrder {
+        cancel_by: CancelBy<UserOrderIdT>,
+        /// Whether the referred order was known to the strategy when sending the request,
+        /// which distinguishes a cancel arriving too late from one for an unknown order.
+        order_was_known: bool,
+    },
+    AmendLimitOrder {
+        existing_order_id: OrderId,
+        new_order: LimitOrder<I, D, BaseOrQuote, UserOrderIdT, NewOrder>,
+    },
+}
+
+/// Decides on the incoming orders before the `Exchange` processes them,
+/// so the `MultiAccountExchange` can apply its `SelfTradePrevention` to orders arriving through the `OrderPipeline`.
+pub(crate) trait ArrivalGate<I, const D: u8>
+where
+    I: Mon<D>,
+{
+    /// `false` rejects an incoming order on `side` reaching up to `price`.
+    fn admit(&mut self, side: Side, price: QuoteCurrency<I, D>) -> bool;
+
+    /// Called once the `Exchange` accepted an admitted order on `side` reaching up to `price`.
+    fn accepted(&mut self, side: Side, price: QuoteCurrency<I, D>);
+}
+
+/// Admits every incoming order, as a single `Exchange` has no other accounts to trade against.
+pub(crate) struct AdmitAll;
+
+impl<I, const D: u8> ArrivalGate<I, D> for AdmitAll
+where
+    I: Mon<D>,
+{
+    #[inline(always)]
+    fn admit(&mut self, _side: Side, _price: QuoteCurrency<I, D>) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn accepted(&mut self, _side: Side, _price: QuoteCurrency<I, D>) {}
+}
+
+/// An entry of the `OrderPipeline` which arrives at `ts_ns`.
+/// Ordered such that a `BinaryHeap` pops the earliest arrival first,
+/// and equal arrivals in the order given by their `seq`.
+#[derive(Debug, Clone)]
+struct Scheduled<T> {
+    ts_ns: TimestampNs,
+    seq: u64,
+    item: T,
+}
+
+impl<T> PartialEq for Scheduled<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ts_ns == other.ts_ns && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Scheduled<T> {}
+
+impl<T> PartialOrd for Scheduled<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Scheduled<T> {
+    // Reversed, as the `BinaryHeap` is a max-heap.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .ts_ns
+            .cmp(&self.ts_ns)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// The order actions in flight to the venue, keyed by their arrival timestamp,
+/// and their responses in flight back to the strategy, keyed by the timestamp they arrive back.
+#[derive(Debug, Clone)]
+pub(crate) struct OrderPipeline<I, const D: u8, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    latency_model: LatencyModel,
+    response_latency_model: LatencyModel,
+    next_request_id: RequestId,
+    /// Keeps the responses arriving back at the same timestamp in the order they were sent.
+    next_response_seq: u64,
+    in_flight: BinaryHeap<Scheduled<(RequestId, OrderAction<I, D, BaseOrQuote, UserOrderIdT>)>>,
+    responses: BinaryHeap<Scheduled<OrderActionEvent<I, D, BaseOrQuote, UserOrderIdT>>>,
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT> Default
+    for OrderPipeline<I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    fn default() -> Self {
+        Self {
+            latency_model: LatencyModel::default(),
+            response_latency_model: LatencyModel::default(),
+            next_request_id: RequestId::default(),
+            next_response_seq: 0,
+            in_flight: BinaryHeap::with_capacity(16),
+            responses: BinaryHeap::with_capacity(16),
+        }
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote, UserOrderIdT> OrderPipeline<I, D, BaseOrQuote, UserOrderIdT>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+    UserOrderIdT: UserOrderId,
+{
+    #[inline]
+    pub(crate) fn latency_model(&self) -> &LatencyModel {
+        &self.latency_model
+    }
+
+    #[inline]
+    pub(crate) fn set_latency_model(&mut self, latency_model: LatencyModel) {
+        self.latency_model = latency_model;
+    }
+
+    #[inline]
+    pub(crate) fn response_latency_model(&self) -> &LatencyModel {
+        &self.response_latency_model
+    }
+
+    #[inline]
+    pub(crate) fn set_response_latency_model(&mut self, latency_model: LatencyModel) {
+        self.response_latency_model = latency_model;
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    #[inline]
+    pub(crate) fn num_responses(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Send an order action at `ts_ns`, which arrives after the sampled latency.
+    pub(crate) fn send(
+        &mut self,
+        ts_ns: TimestampNs,
+        action: OrderAction<I, D, BaseOrQuote, UserOrderIdT>,
+    ) -> RequestId {
+        let request_id = self.next_request_id;
+        self.next_request_id = RequestId(request_id.0 + 1);
+
+        // Sampled latencies can overtake earlier actions, but equal arrivals keep their sending order.
+        self.in_flight.push(Scheduled {
+            ts_ns: ts_ns + self.latency_model.sample(),
+            seq: request_id.0,
+            item: (request_id, action),
+        });
+        request_id
+    }
+
+    /// Whether an order action arrived up to and including `ts_ns`.
+    #[inline]
+    pub(crate) fn has_arrived(&self, ts_ns: TimestampNs) -> bool {
+        self.in_flight
+            .peek()
+            .is_some_and(|scheduled| scheduled.ts_ns <= ts_ns)
+    }
+
+    /// Take the next order action which arrived up to and including `ts_ns`.
+    #[inline]
+    pub(crate) fn pop_arrived(
+        &mut self,
+        ts_ns: TimestampNs,
+    ) -> Option<(
+        TimestampNs,
+        RequestId,
+        OrderAction<I, D, BaseOrQuote, UserOrderIdT>,
+    )> {
+        if self.in_flight.peek()?.ts_ns > ts_ns {
+            return None;
+        }
+        self.in_flight
+            .pop()
+            .map(|Scheduled { ts_ns, item, .. }| (ts_ns, item.0, item.1))
+    }
+
+    /// Send the response of an order action which arrived at the venue at `arrival_ts_ns`,
+    /// which arrives back at the strategy after the sampled response latency.
+    pub(crate) fn respond(
+        &mut self,
+        arrival_ts_ns: TimestampNs,
+        event: OrderActionEvent<I, D, BaseOrQuote, UserOrderIdT>,
+    ) {
+        let seq = self.next_response_seq;
+        self.next_response_seq += 1;
+        self.responses.push(Scheduled {
+            ts_ns: arrival_ts_ns + self.response_latency_model.sample(),
+            seq,
+            item: event,
+        });
+    }
+
+    /// Take the next response which arrived back at the strategy up to and including `ts_ns`.
+    #[inline]
+    pub(crate) fn pop_response(
+        &mut self,
+        ts_ns: TimestampNs,
+    ) -> Option<OrderActionEvent<I, D, BaseOrQuote, UserOrderIdT>> {
+        if self.responses.peek()?.ts_ns > ts_ns {
+            return None;
+        }
+        self.responses.pop().map(|scheduled| scheduled.item)
+    }
+
+    /// Whether an in-flight order action creates an order with `user_order_id`.
+    pub(crate) fn creates_order_with(&self, user_order_id: UserOrderIdT) -> bool {
+        self.in_flight.iter().any(|scheduled| match &scheduled.item.1 {
+            OrderAction::SubmitLimitOrder(order) => order.user_order_id() == user_order_id,
+            OrderAction::AmendLimitOrder { new_order, .. } => {
+                new_order.user_order_id() == user_order_id
+            }
+            OrderAction::SubmitMarketOrder(_) | OrderAction::CancelLimitOrder { .. } => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::{
+            BaseCurrency,
+            QuoteCurrency,
+            Side,
+        },
+        utils::NoUserOrderId,
+    };
+
+    #[test]
+    fn latency_model_new() {
+        assert_eq!(
+            LatencyModel::new(LatencyDistribution::Fixed((-1).into()), 0).unwrap_err(),
+            ConfigError::InvalidLatencyModel
+        );
+        assert_eq!(
+            LatencyModel::new(
+                LatencyDistribution::Uniform {
+                    min: 10.into(),
+                    max: 5.into()
+                },
+                0
+            )
+            .unwrap_err(),
+            ConfigError::InvalidLatencyModel
+        );
+        assert_eq!(
+            LatencyModel::new(
+                LatencyDistribution::LogNormal {
+                    median: 10.into(),
+                    sigma: f64::NAN
+                },
+                0
+            )
+            .unwrap_err(),
+            ConfigError::InvalidLatencyModel
+        );
+    }
+
+    #[test]
+    fn latency_model_sample() {
+        let mut model = LatencyModel::new(LatencyDistribution::Fixed(5.into()), 0).unwrap();
+        assert_eq!(model.sample(), 5.into());
+
+        let distribution = LatencyDistribution::Uniform {
+            min: 10.into(),
+            max: 20.into(),
+        };
+        let mut model = LatencyModel::new(distribution, 42).unwrap();
+        let mut same_seed = LatencyModel::new(distribution, 42).unwrap();
+        for _ in 0..1000 {
+            let latency = model.sample();
+            assert!(latency >= 10.into() && latency <= 20.into());
+            assert_eq!(latency, same_seed.sample());
+        }
+
+        let mut model = LatencyModel::new(
+            LatencyDistribution::LogNormal {
+                median: 1_000.into(),
+                sigma: 0.5,
+            },
+            0,
+        )
+        .unwrap();
+        let mut samples: Vec<i64> = (0..1001).map(|_| model.sample().get()).collect();
+        samples.sort_unstable();
+        assert!(samples.iter().all(|latency| *latency > 0));
+        assert!((900..1100).contains(&samples[500]));
+    }
+
+    #[test]
+    fn order_pipeline_arrival_order() {
+        let mut pipeline = OrderPipeline::<i64, 5, BaseCurrency<i64, 5>, NoUserOrderId>::default();
+        let order = LimitOrder::new(
+            Side::Buy,
+            QuoteCurrency::new(100, 0),
+            BaseCurrency::new(1, 0),
+        )
+        .unwrap();
+        pipeline.set_latency_model(
+            LatencyModel::new(LatencyDistribution::Fixed(10.into()), 0).unwrap(),
+        );
+        let first = pipeline.send(0.into(), OrderAction::SubmitLimitOrder(order.clone()));
+        pipeline
+            .set_latency_model(LatencyModel::new(LatencyDistribution::Fixed(5.into()), 0).unwrap());
+        let second = pipeline.send(1.into(), OrderAction::SubmitLimitOrder(order.clone()));
+        let third = pipeline.send(5.into(), OrderAction::SubmitLimitOrder(order));
+        assert_eq!(pipeline.len(), 3);
+
+        assert!(!pipeline.has_arrived(5.into()));
+        assert!(pipeline.has_arrived(6.into()));
+        let arrived: Vec<(TimestampNs, RequestId)> =
+            std::iter::from_fn(|| pipeline.pop_arrived(10.into()))
+                .map(|(ts, request_id, _)| (ts, request_id))
+                .collect();
+        assert_eq!(arrived, vec![
+            (TimestampNs::from(6), second),
+            (TimestampNs::from(10), first),
+            (TimestampNs::from(10), third)
+        ]);
+    }
+}
@@ -0,0 +1,337 @@
+//! Attributes the fills, fees and profit and loss of an account to the sub-strategies placing its orders.
+
+use getset::CopyGetters;
+use num_traits::Zero;
+
+use crate::{
+    EXPECT_CAPACITY,
+    account::Position,
+    types::{
+        Currency,
+        MarginCurrency,
+        Mon,
+        QuoteCurrency,
+        Side,
+        StrategyTag,
+    },
+};
+
+/// The sub-position, fees and realized profit and loss of a single `StrategyTag`.
+#[derive(Debug, Clone, Default, CopyGetters)]
+pub struct TagAttribution<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The net quantity of the fills of the tag, negative for a short sub-position.
+    #[getset(get_copy = "pub")]
+    quantity: BaseOrQuote,
+
+    /// The profit and loss the account realized with the fills of the tag, excluding fees.
+    #[getset(get_copy = "pub")]
+    realized_pnl: BaseOrQuote::PairedCurrency,
+
+    /// The fees paid by the fills of the tag, negative if more maker rebates were received than fees paid.
+    #[getset(get_copy = "pub")]
+    fees_paid: BaseOrQuote::PairedCurrency,
+
+    /// The number of fills of the tag, including the liquidations of its sub-position.
+    #[getset(get_copy = "pub")]
+    num_fills: u64,
+
+    _quote: std::marker::PhantomData<QuoteCurrency<I, D>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> TagAttribution<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Record a fill changing the sub-position by the signed `quantity`.
+    fn record(
+        &mut self,
+        quantity: BaseOrQuote,
+        realized_pnl: BaseOrQuote::PairedCurrency,
+        fee: BaseOrQuote::PairedCurrency,
+    ) {
+        self.quantity += quantity;
+        self.realized_pnl += realized_pnl;
+        self.fees_paid += fee;
+        self.num_fills += 1;
+    }
+}
+
+/// Attributes the fills, fees and realized profit and loss of an account to the `StrategyTag`s of its orders,
+/// for running several signals through one account.
+///
+/// Each order carries the tag of its sub-strategy, see `LimitOrder::set_strategy_tag` and `MarketOrder::set_strategy_tag`.
+/// A fill adds its quantity to the sub-position of its tag, along with its fee and the profit and loss
+/// which `Position::change` realized for it in the account,
+/// so the sub-positions, fees and realized profit and loss of all tags sum exactly to the account.
+/// As the account nets opposing sub-positions, a fill reducing the position of the account realizes
+/// against its average entry price, which may stem from the fills of other tags.
+/// A liquidation closes the sub-positions of all tags in proportion to their share of the position,
+/// which also splits its realized profit and loss, while its fee is split by the size of each sub-position.
+/// Set it with `Exchange::set_pnl_attribution`, which assigns a position already open at that time
+/// to the `StrategyTag::default()`.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct PnlAttribution<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    tags: Vec<(StrategyTag, TagAttribution<I, D, BaseOrQuote>)>,
+
+    /// The profit and loss realized by the account since the attribution was set, excluding fees.
+    #[getset(get_copy = "pub")]
+    account_realized_pnl: BaseOrQuote::PairedCurrency,
+
+    /// The fees paid by the account since the attribution was set.
+    #[getset(get_copy = "pub")]
+    account_fees_paid: BaseOrQuote::PairedCurrency,
+}
+
+impl<I, const D: u8, BaseOrQuote> Default for PnlAttribution<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> PnlAttribution<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// Create a new instance without any attributed fills.
+    pub fn new() -> Self {
+        Self {
+            tags: Vec::with_capacity(8),
+            account_realized_pnl: Zero::zero(),
+            account_fees_paid: Zero::zero(),
+        }
+    }
+
+    /// The attribution of every tag that had a fill, ordered by the first fill of each tag.
+    #[inline]
+    pub fn tags(&self) -> &[(StrategyTag, TagAttribution<I, D, BaseOrQuote>)] {
+        &self.tags
+    }
+
+    /// The attribution of `tag`, `None` if it had no fill yet.
+    pub fn tag(&self, tag: StrategyTag) -> Option<&TagAttribution<I, D, BaseOrQuote>> {
+        self.tags.iter().find(|(t, _)| *t == tag).map(|(_, a)| a)
+    }
+
+    /// Start attributing the fills of an account with `position`,
+    /// which becomes the sub-position of the `StrategyTag::default()` if it is open.
+    pub(crate) fn open(&mut self, position: &Position<I, D, BaseOrQuote>) {
+        if !position.quantity().is_zero() {
+            Self::tag_mut(&mut self.tags, StrategyTag::default()).quantity = position.quantity();
+        }
+    }
+
+    /// The attribution of `tag`, inserting it on its first fill.
+    fn tag_mut(
+        tags: &mut Vec<(StrategyTag, TagAttribution<I, D, BaseOrQuote>)>,
+        tag: StrategyTag,
+    ) -> &mut TagAttribution<I, D, BaseOrQuote> {
+        let idx = match tags.iter().position(|(t, _)| *t == tag) {
+            Some(idx) => idx,
+            None => {
+                // Grows with the number of tags, which is small.
+                tags.reserve(1);
+                tags.push_within_capacity((tag, TagAttribution::default()))
+                    .expect(EXPECT_CAPACITY);
+                tags.len() - 1
+            }
+        };
+        &mut tags[idx].1
+    }
+
+    /// Record a fill of `quantity` which realized `realized_pnl` in the account and paid `fee`,
+    /// of an order with `tag`. A fill without a tag is a liquidation, closing all sub-positions.
+    pub(crate) fn record_fill(
+        &mut self,
+        tag: Option<StrategyTag>,
+        side: Side,
+        quantity: BaseOrQuote,
+        realized_pnl: BaseOrQuote::PairedCurrency,
+        fee: BaseOrQuote::PairedCurrency,
+    ) {
+        self.account_realized_pnl += realized_pnl;
+        self.account_fees_paid += fee;
+        let quantity = match side {
+            Side::Buy => quantity,
+            Side::Sell => -quantity,
+        };
+        match tag {
+            Some(tag) => Self::tag_mut(&mut self.tags, tag).record(quantity, realized_pnl, fee),
+            None => self.liquidate(quantity, realized_pnl, fee),
+        }
+    }
+
+    /// Reduce the sub-positions of all tags by the signed `quantity` of a liquidation,
+    /// in proportion to their share of the position which also splits the `realized_pnl`.
+    /// The `fee` is split in proportion to the size of each sub-position.
+    fn liquidate(
+        &mut self,
+        quantity: BaseOrQuote,
+        realized_pnl: BaseOrQuote::PairedCurrency,
+        fee: BaseOrQuote::PairedCurrency,
+    ) {
+        let (position, gross_quantity) = self.tags.iter().fold(
+            (BaseOrQuote::zero(), BaseOrQuote::zero()),
+            |(net, gross), (_, a)| (net + a.quantity, gross + a.quantity.abs()),
+        );
+        if position.is_zero() {
+            Self::tag_mut(&mut self.tags, StrategyTag::default()).record(
+                quantity,
+                realized_pnl,
+                fee,
+            );
+            return;
+        }
+        let mut remaining_quantity = quantity;
+        let mut remaining_pnl = realized_pnl;
+        let mut remaining_fee = fee;
+        let mut remaining_gross_quantity = gross_quantity;
+        for (_, attribution) in self.tags.iter_mut() {
+            let sub_position = attribution.quantity;
+            if sub_position.is_zero() {
+                continue;
+            }
+            remaining_gross_quantity -= sub_position.abs();
+            // The last sub-position takes the remainders, so the shares sum up exactly.
+            let (quantity_share, pnl_share, fee_share) = if remaining_gross_quantity.is_zero() {
+                (remaining_quantity, remaining_pnl, remaining_fee)
+            } else {
+                let position_share = *sub_position.as_ref() / *position.as_ref();
+                (
+                    quantity * position_share,
+                    realized_pnl * position_share,
+                    fee * (*sub_position.abs().as_ref() / *gross_quantity.as_ref()),
+                )
+            };
+            remaining_quantity -= quantity_share;
+            remaining_pnl -= pnl_share;
+            remaining_fee -= fee_share;
+            attribution.record(quantity_share, pnl_share, fee_share);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        account::Balances,
+        types::BaseCurrency,
+    };
+
+    type Attribution = PnlAttribution<i64, 5, BaseCurrency<i64, 5>>;
+
+    /// Settle a fill into the `position` like the `Exchange` does and attribute it to `tag`.
+    fn fill(
+        attribution: &mut Attribution,
+        position: &mut Position<i64, 5, BaseCurrency<i64, 5>>,
+        tag: Option<u32>,
+        side: Side,
+        qty: i64,
+        price: i64,
+    ) {
+        let quantity = BaseCurrency::new(qty, 0);
+        let mut balances = Balances::new(QuoteCurrency::new(10_000, 0));
+        let realized_pnl =
+            position.change(quantity, QuoteCurrency::new(price, 0), side, &mut balances);
+        attribution.record_fill(
+            tag.map(StrategyTag::from),
+            side,
+            quantity,
+            realized_pnl,
+            QuoteCurrency::new(1, 0),
+        );
+    }
+
+    fn assert_sums_to_account(
+        attribution: &Attribution,
+        position: &Position<i64, 5, BaseCurrency<i64, 5>>,
+    ) {
+        let (quantity, realized_pnl, fees_paid) = attribution.tags().iter().fold(
+            (
+                BaseCurrency::zero(),
+                QuoteCurrency::zero(),
+                QuoteCurrency::zero(),
+            ),
+            |(q, r, f), (_, a)| (q + a.quantity(), r + a.realized_pnl(), f + a.fees_paid()),
+        );
+        assert_eq!(quantity, position.quantity());
+        assert_eq!(realized_pnl, attribution.account_realized_pnl());
+        assert_eq!(fees_paid, attribution.account_fees_paid());
+    }
+
+    #[test]
+    fn pnl_attribution_netting_tags() {
+        let mut attribution = Attribution::new();
+        let mut position = Position::default();
+        fill(&mut attribution, &mut position, Some(1), Side::Buy, 1, 100);
+        fill(&mut attribution, &mut position, Some(2), Side::Sell, 1, 110);
+        fill(&mut attribution, &mut position, Some(1), Side::Sell, 1, 105);
+
+        // The sell of tag 2 closed the long of tag 1 in the account, realizing 10.
+        assert_eq!(
+            attribution.account_realized_pnl(),
+            QuoteCurrency::new(10, 0)
+        );
+        let tag_1 = attribution.tag(1.into()).unwrap();
+        assert!(tag_1.quantity().is_zero());
+        assert!(tag_1.realized_pnl().is_zero());
+        assert_eq!(tag_1.fees_paid(), QuoteCurrency::new(2, 0));
+        assert_eq!(tag_1.num_fills(), 2);
+        let tag_2 = attribution.tag(2.into()).unwrap();
+        assert_eq!(tag_2.quantity(), BaseCurrency::new(-1, 0));
+        assert_eq!(tag_2.realized_pnl(), QuoteCurrency::new(10, 0));
+        assert_sums_to_account(&attribution, &position);
+    }
+
+    #[test]
+    fn pnl_attribution_liquidation() {
+        let mut attribution = Attribution::new();
+        let mut position = Position::default();
+        // A position opened before the attribution belongs to the default tag.
+        position.change(
+            BaseCurrency::new(1, 0),
+            QuoteCurrency::new(100, 0),
+            Side::Buy,
+            &mut Balances::new(QuoteCurrency::new(10_000, 0)),
+        );
+        attribution.open(&position);
+        fill(&mut attribution, &mut position, Some(2), Side::Buy, 2, 100);
+        fill(&mut attribution, &mut position, Some(3), Side::Sell, 1, 100);
+        fill(&mut attribution, &mut position, None, Side::Sell, 2, 90);
+
+        assert_eq!(
+            attribution.account_realized_pnl(),
+            QuoteCurrency::new(-20, 0)
+        );
+        let untagged = attribution.tag(StrategyTag::default()).unwrap();
+        assert_eq!(untagged.realized_pnl(), QuoteCurrency::new(-10, 0));
+        assert_eq!(untagged.fees_paid(), QuoteCurrency::new(25, 2));
+        let tag_2 = attribution.tag(2.into()).unwrap();
+        assert_eq!(tag_2.realized_pnl(), QuoteCurrency::new(-20, 0));
+        assert_eq!(tag_2.fees_paid(), QuoteCurrency::new(15, 1));
+        // The short of tag 3 gained from the price drop which liquidated the account.
+        let tag_3 = attribution.tag(3.into()).unwrap();
+        assert_eq!(tag_3.realized_pnl(), QuoteCurrency::new(10, 0));
+        for (_, a) in attribution.tags() {
+            assert!(a.quantity().is_zero());
+        }
+        assert_sums_to_account(&attribution, &position);
+    }
+}
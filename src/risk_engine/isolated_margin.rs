@@ -21,6 +21,7 @@ use crate::{
         MarketOrder,
         NotEnoughAvailableBalance,
         Pending,
+        PositionLimitExceeded,
         Side,
         UserOrderId,
     },
@@ -43,6 +44,15 @@ where
     pub(crate) fn new(contract_spec: ContractSpecification<I, D, BaseOrQuote>) -> Self {
         Self { contract_spec }
     }
+
+    /// Replace the contract specification, e.g. after the active fees changed.
+    #[inline]
+    pub(crate) fn set_contract_spec(
+        &mut self,
+        contract_spec: ContractSpecification<I, D, BaseOrQuote>,
+    ) {
+        self.contract_spec = contract_spec;
+    }
 }
 
 impl<I, const D: u8, BaseOrQuote, UserOrderIdT> RiskEngine<I, D, BaseOrQuote, UserOrderIdT>
@@ -84,6 +94,43 @@ where
         Ok(())
     }
 
+    fn check_position_limits(
+        &self,
+        account: &Account<I, D, BaseOrQuote, UserOrderIdT>,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+    ) -> Result<(), PositionLimitExceeded> {
+        let max_quantity = self.contract_spec.max_position_quantity();
+        let max_notional = self.contract_spec.max_position_notional();
+        if max_quantity.is_none() && max_notional.is_none() {
+            return Ok(());
+        }
+
+        // The worst-case position quantity in the direction of `side`,
+        // where an opposite position is netted against the fills.
+        let resting_quantity = account.active_limit_orders().remaining_quantity(side);
+        let position_quantity = account.position().quantity();
+        let worst_case_quantity = match side {
+            Side::Buy => position_quantity + resting_quantity + quantity,
+            Side::Sell => resting_quantity + quantity - position_quantity,
+        };
+        trace!("check_position_limits: worst_case_quantity: {worst_case_quantity}");
+
+        if let Some(max_quantity) = max_quantity
+            && worst_case_quantity > max_quantity
+        {
+            return Err(PositionLimitExceeded::Quantity);
+        }
+        if let Some(max_notional) = max_notional
+            && BaseOrQuote::PairedCurrency::convert_from(worst_case_quantity, price) > max_notional
+        {
+            return Err(PositionLimitExceeded::Notional);
+        }
+
+        Ok(())
+    }
+
     fn check_maintenance_margin(
         &self,
         market_state: &MarketState<I, D>,
@@ -14,6 +14,8 @@ use crate::{
         MarketOrder,
         NotEnoughAvailableBalance,
         Pending,
+        PositionLimitExceeded,
+        Side,
         UserOrderId,
     },
 };
@@ -53,6 +55,17 @@ where
         order: &LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>,
     ) -> Result<(), NotEnoughAvailableBalance>;
 
+    /// Checks that the position stays within the position limits of the contract
+    /// if the new order with `quantity` on `side` and all resting limit orders on that side get filled.
+    /// The resulting position notional is valued at `price`.
+    fn check_position_limits(
+        &self,
+        account: &Account<I, D, BaseOrQuote, UserOrderIdT>,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+    ) -> Result<(), PositionLimitExceeded>;
+
     /// Ensure the account has enough maintenance margin, to keep the position open.
     /// The maintenance margin is the minimum amount of funds that must be maintained in a trader's account
     /// to ensure that they can meet any losses that may occur due to adverse price movements in the futures contract.
@@ -0,0 +1,91 @@
+//! A small seedable pseudo random number generator,
+//! which keeps stochastic simulation components reproducible without an additional dependency.
+
+/// The `SplitMix64` generator of Sebastiano Vigna.
+/// It is not cryptographically secure, but fast and of sufficient statistical quality for simulations.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Create a new generator, where the same `seed` always yields the same sequence.
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next uniformly distributed `u64`.
+    #[inline]
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// The next uniformly distributed `f64` in the range [0, 1).
+    #[inline]
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        // The upper 53 bits fill the mantissa exactly.
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// The next sample of the standard normal distribution, using the Box-Muller transform.
+    #[inline]
+    pub(crate) fn next_standard_normal(&mut self) -> f64 {
+        // In the range (0, 1], so the logarithm stays finite.
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+
+    /// The next sample of the exponential distribution with a rate of 1.
+    #[inline]
+    pub(crate) fn next_standard_exponential(&mut self) -> f64 {
+        -(1.0 - self.next_f64()).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_mix_64_reproducible() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(SplitMix64::new(1).next_u64(), SplitMix64::new(2).next_u64());
+    }
+
+    #[test]
+    fn split_mix_64_distributions() {
+        let mut rng = SplitMix64::new(0);
+        let n = 10_000;
+        let mut sum_uniform = 0.0;
+        let mut sum_normal = 0.0;
+        let mut sum_normal_sq = 0.0;
+        let mut sum_exponential = 0.0;
+        for _ in 0..n {
+            let u = rng.next_f64();
+            assert!((0.0..1.0).contains(&u));
+            sum_uniform += u;
+
+            let z = rng.next_standard_normal();
+            sum_normal += z;
+            sum_normal_sq += z * z;
+
+            let e = rng.next_standard_exponential();
+            assert!(e >= 0.0);
+            sum_exponential += e;
+        }
+        let n = f64::from(n);
+        assert!((sum_uniform / n - 0.5).abs() < 0.02);
+        assert!((sum_normal / n).abs() < 0.05);
+        assert!((sum_normal_sq / n - 1.0).abs() < 0.05);
+        assert!((sum_exponential / n - 1.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,617 @@
+//! Generates the synthetic market data from the stochastic processes.
+
+use getset::{
+    CopyGetters,
+    Getters,
+};
+use num_traits::Zero;
+use typed_builder::TypedBuilder;
+
+use super::processes::{
+    HawkesArrivals,
+    JumpDiffusion,
+    SpreadDynamics,
+};
+use crate::{
+    EXPECT_CAPACITY,
+    EXPECT_DECIMAL,
+    market_update::{
+        Bba,
+        MarketUpdate,
+        SmartCandle,
+        Trade,
+    },
+    order_filters::{
+        PriceFilter,
+        QuantityFilter,
+    },
+    rng::SplitMix64,
+    types::{
+        ConfigError,
+        Currency,
+        Mon,
+        NANOS_PER_SECOND,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+    utils::{
+        decimal_from_f64,
+        max,
+    },
+};
+
+/// The configuration of a `SyntheticMarket`.
+#[derive(Debug, Clone, TypedBuilder, Getters, CopyGetters)]
+pub struct SyntheticMarketConfig<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The mid price at the start.
+    #[getset(get_copy = "pub")]
+    initial_price: QuoteCurrency<I, D>,
+
+    /// The mean quantity of a trade. The trade quantities are exponentially distributed.
+    #[getset(get_copy = "pub")]
+    mean_trade_quantity: BaseOrQuote,
+
+    /// The resting quantity at each price level as a multiple of the `mean_trade_quantity`.
+    /// A larger trade sweeps through the touch and prints at the last price level it reaches.
+    #[builder(default = 2.0)]
+    #[getset(get_copy = "pub")]
+    level_depth: f64,
+
+    /// The price rules all generated prices adhere to.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    price_filter: PriceFilter<I, D>,
+
+    /// The quantity rules all generated trade quantities adhere to.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    quantity_filter: QuantityFilter<I, D, BaseOrQuote>,
+
+    /// The process of the mid price.
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    price_process: JumpDiffusion,
+
+    /// The process of the trade arrivals.
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    trade_arrivals: HawkesArrivals,
+
+    /// The process of the bid-ask spread.
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    spread: SpreadDynamics,
+
+    /// The timestamp at the start.
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    start_ts_ns: TimestampNs,
+}
+
+/// A seeded generator of synthetic `Trade`, `Bba` and `SmartCandle` streams,
+/// allowing reproducible stress scenarios without recorded market data.
+///
+/// The mid price follows a `JumpDiffusion`, the spread around it `SpreadDynamics` and
+/// the taker trades arrive according to `HawkesArrivals`, buying at the ask and selling at the bid.
+/// A trade larger than the `level_depth` sweeps through the touch, so passive orders at the touch
+/// get traded through as well.
+/// All prices are multiples of the tick size and within the bounds of the `PriceFilter`,
+/// all trade quantities multiples of the step of the `QuantityFilter`.
+/// The same configuration and seed always yield the same streams.
+#[derive(Debug, Clone, CopyGetters)]
+pub struct SyntheticMarket<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    config: SyntheticMarketConfig<I, D, BaseOrQuote>,
+
+    rng: SplitMix64,
+
+    /// The unrounded mid price.
+    mid_price: f64,
+
+    /// The unrounded spread in ticks.
+    spread_ticks: f64,
+
+    /// The excitation of the trade arrivals right before `next_arrival_ns`.
+    excitation: f64,
+
+    next_arrival_ns: TimestampNs,
+
+    /// The smallest trade quantity allowed by the `QuantityFilter`.
+    min_trade_quantity: BaseOrQuote,
+
+    /// The trades of the `SmartCandle` being generated, reused across candles.
+    candle_trades: Vec<Trade<I, D, BaseOrQuote>>,
+
+    /// The timestamp of the latest generated market update.
+    #[getset(get_copy = "pub")]
+    current_ts_ns: TimestampNs,
+
+    /// The current best bid and ask.
+    #[getset(get_copy = "pub")]
+    bba: Bba<I, D>,
+}
+
+impl<I, const D: u8, BaseOrQuote> SyntheticMarket<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Create a new `SyntheticMarket`, where the same `seed` always yields the same market data.
+    /// The parameters of the processes must be finite and not negative, the trade arrivals stationary,
+    /// the `mean_trade_quantity` and `level_depth` positive and the `initial_price` allowed by the `price_filter`.
+    pub fn new(
+        config: SyntheticMarketConfig<I, D, BaseOrQuote>,
+        seed: u64,
+    ) -> Result<Self, ConfigError> {
+        if !config.price_process.is_valid()
+            || !config.trade_arrivals.is_valid()
+            || !config.spread.is_valid()
+            || config.mean_trade_quantity <= BaseOrQuote::zero()
+            || !(config.level_depth.is_finite() && config.level_depth > 0.0)
+            || config
+                .price_filter
+                .validate_resting_price(config.initial_price)
+                .is_err()
+        {
+            return Err(ConfigError::InvalidSyntheticMarket);
+        }
+
+        let min_trade_quantity = config
+            .quantity_filter
+            .min_quantity()
+            .unwrap_or(config.quantity_filter.tick_size());
+        let mut market = Self {
+            rng: SplitMix64::new(seed),
+            mid_price: config.initial_price.into(),
+            spread_ticks: config.spread.mean_ticks,
+            excitation: 0.0,
+            next_arrival_ns: config.start_ts_ns,
+            min_trade_quantity,
+            candle_trades: Vec::with_capacity(16),
+            current_ts_ns: config.start_ts_ns,
+            bba: Bba {
+                bid: config.initial_price,
+                ask: config.initial_price,
+                timestamp_exchange_ns: config.start_ts_ns,
+            },
+            config,
+        };
+        market.schedule_next_arrival();
+        market.bba = market.quote();
+        Ok(market)
+    }
+
+    /// The configuration of the market.
+    #[inline]
+    pub fn config(&self) -> &SyntheticMarketConfig<I, D, BaseOrQuote> {
+        &self.config
+    }
+
+    /// Generate the next taker trade. The `bba` is updated to the quote at the time of the trade.
+    pub fn next_trade(&mut self) -> Trade<I, D, BaseOrQuote> {
+        let ts_ns = self.next_arrival_ns;
+        self.arrive();
+
+        let side = if self.rng.next_f64() < self.config.trade_arrivals.buy_probability {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        let relative_quantity = self.rng.next_standard_exponential();
+        // Every full `level_depth` of the trade consumes a price level beyond the touch.
+        let levels_swept = (relative_quantity / self.config.level_depth).floor() as u32;
+        let price = match side {
+            Side::Buy => self.sweep_price(side, self.bba.ask, levels_swept),
+            Side::Sell => self.sweep_price(side, self.bba.bid, levels_swept),
+        };
+        let mean_quantity: f64 = self.config.mean_trade_quantity.into();
+        let quantity = BaseOrQuote::from(
+            decimal_from_f64(mean_quantity * relative_quantity).expect(EXPECT_DECIMAL),
+        );
+
+        Trade {
+            timestamp_exchange_ns: ts_ns,
+            price,
+            quantity: self.align_quantity(quantity),
+            side,
+        }
+    }
+
+    /// Generate the quote `interval_ns` after the current one.
+    /// Trades arriving in the meantime still move the process of the trade arrivals, but are not emitted.
+    pub fn next_bba(&mut self, interval_ns: TimestampNs) -> Bba<I, D> {
+        assert!(interval_ns > TimestampNs::from(0));
+        let ts_ns = self.current_ts_ns + interval_ns;
+        while self.next_arrival_ns <= ts_ns {
+            self.arrive();
+        }
+        self.evolve_to(ts_ns);
+        self.bba
+    }
+
+    /// Generate a `SmartCandle` from all trades in the next `interval_ns` and the quote at its end.
+    /// If no trade arrives within the interval, the candle extends up to and including the next trade.
+    pub fn next_smart_candle(
+        &mut self,
+        interval_ns: TimestampNs,
+    ) -> SmartCandle<I, D, BaseOrQuote> {
+        assert!(interval_ns > TimestampNs::from(0));
+        let end_ts_ns = self.current_ts_ns + interval_ns;
+        self.candle_trades.clear();
+        while self.next_arrival_ns <= end_ts_ns || self.candle_trades.is_empty() {
+            let trade = self.next_trade();
+            self.candle_trades.reserve(1);
+            self.candle_trades
+                .push_within_capacity(trade)
+                .expect(EXPECT_CAPACITY);
+        }
+        if self.current_ts_ns < end_ts_ns {
+            self.evolve_to(end_ts_ns);
+        }
+        SmartCandle::new(&self.candle_trades, self.bba, &self.config.price_filter)
+    }
+
+    /// An endless stream of taker trades.
+    pub fn trades(&mut self) -> impl Iterator<Item = Trade<I, D, BaseOrQuote>> + '_ {
+        std::iter::repeat_with(|| self.next_trade())
+    }
+
+    /// An endless stream of quotes, spaced `interval_ns` apart.
+    pub fn bbas(&mut self, interval_ns: TimestampNs) -> impl Iterator<Item = Bba<I, D>> + '_ {
+        std::iter::repeat_with(move || self.next_bba(interval_ns))
+    }
+
+    /// An endless stream of `SmartCandle`s, each spanning at least `interval_ns`.
+    pub fn smart_candles(
+        &mut self,
+        interval_ns: TimestampNs,
+    ) -> impl Iterator<Item = SmartCandle<I, D, BaseOrQuote>> + '_ {
+        std::iter::repeat_with(move || self.next_smart_candle(interval_ns))
+    }
+
+    /// Evolve the mid price and spread up to `ts_ns` and update the `bba`.
+    fn evolve_to(&mut self, ts_ns: TimestampNs) {
+        assert2::debug_assert!(ts_ns >= self.current_ts_ns);
+        let dt = i64::from(ts_ns - self.current_ts_ns) as f64 / NANOS_PER_SECOND as f64;
+        self.mid_price *= self
+            .config
+            .price_process
+            .log_return(&mut self.rng, dt)
+            .exp();
+        self.spread_ticks = self
+            .config
+            .spread
+            .step(&mut self.rng, self.spread_ticks, dt);
+
+        // Keep the mid price within the bounds of the `PriceFilter`, so it cannot get stuck outside.
+        let price_filter = &self.config.price_filter;
+        let lower = price_filter
+            .min_price()
+            .unwrap_or(price_filter.tick_size())
+            .into();
+        self.mid_price = self.mid_price.max(lower);
+        if let Some(max_price) = price_filter.max_price() {
+            self.mid_price = self.mid_price.min(max_price.into());
+        }
+
+        self.current_ts_ns = ts_ns;
+        self.bba = self.quote();
+    }
+
+    /// Advance to the arrival of the next trade, which excites the arrival of further trades.
+    fn arrive(&mut self) {
+        self.evolve_to(self.next_arrival_ns);
+        self.excitation += self.config.trade_arrivals.excitation;
+        self.schedule_next_arrival();
+    }
+
+    /// The price of a taker trade on `side` sweeping `levels` price levels through the `touch`,
+    /// stopping at the bounds of the `PriceFilter`.
+    fn sweep_price(
+        &self,
+        side: Side,
+        touch: QuoteCurrency<I, D>,
+        levels: u32,
+    ) -> QuoteCurrency<I, D> {
+        let price_filter = &self.config.price_filter;
+        let lower = price_filter.min_price().unwrap_or(price_filter.tick_size());
+        let mut price = touch;
+        for _ in 0..levels {
+            let next = match side {
+                Side::Buy => price + price_filter.tick_size_at(price),
+                Side::Sell => {
+                    let below = price - price_filter.tick_size_at(price);
+                    below.quantize_round_to_zero(price_filter.tick_size_at(below))
+                }
+            };
+            if next < lower
+                || price_filter
+                    .max_price()
+                    .is_some_and(|max_price| next > max_price)
+            {
+                break;
+            }
+            price = next;
+        }
+        price
+    }
+
+    /// Round the `quantity` down to a multiple of the step of the `QuantityFilter`,
+    /// but to at least its minimum quantity.
+    fn align_quantity(&self, quantity: BaseOrQuote) -> BaseOrQuote {
+        if quantity <= self.min_trade_quantity {
+            return self.min_trade_quantity;
+        }
+        let above_min = quantity - self.min_trade_quantity;
+        self.min_trade_quantity + above_min - above_min % self.config.quantity_filter.tick_size()
+    }
+
+    /// Draw the arrival time of the next trade, starting at the current timestamp.
+    fn schedule_next_arrival(&mut self) {
+        let wait = self
+            .config
+            .trade_arrivals
+            .next_arrival(&mut self.rng, &mut self.excitation);
+        let wait_ns = (wait * NANOS_PER_SECOND as f64).round() as i64;
+        self.next_arrival_ns = self.current_ts_ns + TimestampNs::from(wait_ns);
+    }
+
+    /// The quote around the current mid price, respecting the tick size and price bounds.
+    fn quote(&self) -> Bba<I, D> {
+        let price_filter = &self.config.price_filter;
+        let lower = price_filter.min_price().unwrap_or(price_filter.tick_size());
+        let to_price = |price: f64| -> QuoteCurrency<I, D> {
+            QuoteCurrency::from(decimal_from_f64(price).expect(EXPECT_DECIMAL))
+        };
+        let round_up = |price: QuoteCurrency<I, D>| {
+            let tick_size = price_filter.tick_size_at(price);
+            let rounded = price.quantize_round_to_zero(tick_size);
+            if rounded < price {
+                rounded + tick_size
+            } else {
+                rounded
+            }
+        };
+
+        let ticks = f64::from(SpreadDynamics::quoted_ticks(self.spread_ticks));
+        let tick_size: f64 = price_filter.tick_size_at(to_price(self.mid_price)).into();
+        let bid = to_price(self.mid_price - 0.5 * ticks * tick_size);
+        let mut bid = max(
+            bid.quantize_round_to_zero(price_filter.tick_size_at(bid)),
+            lower,
+        );
+        let bid_tick_size: f64 = price_filter.tick_size_at(bid).into();
+        let bid_f64: f64 = bid.into();
+        let mut ask = round_up(to_price(bid_f64 + ticks * bid_tick_size));
+
+        if let Some(max_price) = price_filter.max_price()
+            && ask > max_price
+        {
+            ask = max_price.quantize_round_to_zero(price_filter.tick_size_at(max_price));
+            let below = ask - price_filter.tick_size_at(ask);
+            bid = below.quantize_round_to_zero(price_filter.tick_size_at(below));
+        }
+
+        let bba = Bba {
+            bid,
+            ask,
+            timestamp_exchange_ns: self.current_ts_ns,
+        };
+        debug_assert!(
+            <Bba<I, D> as MarketUpdate<I, D, BaseOrQuote>>::validate_market_update(
+                &bba,
+                price_filter
+            )
+            .is_ok(),
+            "The synthetic quote respects the `PriceFilter`"
+        );
+        bba
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use const_decimal::Decimal;
+
+    use super::*;
+    use crate::{
+        DECIMALS,
+        types::BaseCurrency,
+    };
+
+    fn market(seed: u64) -> SyntheticMarket<i64, DECIMALS, BaseCurrency<i64, DECIMALS>> {
+        let config = SyntheticMarketConfig::builder()
+            .initial_price(QuoteCurrency::new(100, 0))
+            .mean_trade_quantity(BaseCurrency::<i64, DECIMALS>::new(1, 0))
+            .price_filter(
+                PriceFilter::new(
+                    None,
+                    None,
+                    QuoteCurrency::new(1, 1),
+                    Decimal::TWO,
+                    Decimal::zero(),
+                )
+                .unwrap(),
+            )
+            .quantity_filter(QuantityFilter::new(None, None, BaseCurrency::new(1, 1)).unwrap())
+            .price_process(JumpDiffusion {
+                volatility: 0.002,
+                jump_intensity: 0.1,
+                ..Default::default()
+            })
+            .build();
+        SyntheticMarket::new(config, seed).unwrap()
+    }
+
+    #[test]
+    fn synthetic_market_new() {
+        let config = |initial_price: i64, mean_trade_quantity: i64| {
+            SyntheticMarketConfig::builder()
+                .initial_price(QuoteCurrency::<i64, DECIMALS>::new(initial_price, 0))
+                .mean_trade_quantity(BaseCurrency::<i64, DECIMALS>::new(mean_trade_quantity, 0))
+                .build()
+        };
+        assert!(SyntheticMarket::new(config(100, 1), 0).is_ok());
+        let err = Err(ConfigError::InvalidSyntheticMarket);
+        assert_eq!(SyntheticMarket::new(config(0, 1), 0).map(|_| ()), err);
+        assert_eq!(SyntheticMarket::new(config(100, 0), 0).map(|_| ()), err);
+        assert_eq!(
+            SyntheticMarket::new(
+                SyntheticMarketConfig::builder()
+                    .initial_price(QuoteCurrency::<i64, DECIMALS>::new(100, 0))
+                    .mean_trade_quantity(BaseCurrency::<i64, DECIMALS>::new(1, 0))
+                    .level_depth(0.0)
+                    .build(),
+                0
+            )
+            .map(|_| ()),
+            err
+        );
+        assert_eq!(
+            SyntheticMarket::new(
+                SyntheticMarketConfig::builder()
+                    .initial_price(QuoteCurrency::<i64, DECIMALS>::new(100, 0))
+                    .mean_trade_quantity(BaseCurrency::<i64, DECIMALS>::new(1, 0))
+                    .trade_arrivals(HawkesArrivals {
+                        excitation: 2.0,
+                        ..Default::default()
+                    })
+                    .build(),
+                0
+            )
+            .map(|_| ()),
+            err
+        );
+    }
+
+    #[test]
+    fn synthetic_market_reproducible() {
+        let trades = |seed: u64| market(seed).trades().take(100).collect::<Vec<_>>();
+        assert_eq!(trades(0), trades(0));
+        assert_ne!(trades(0), trades(1));
+
+        let bbas = |seed: u64| {
+            market(seed)
+                .bbas(1_000_000_000.into())
+                .take(100)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bbas(0), bbas(0));
+    }
+
+    #[test]
+    fn synthetic_market_trades_respect_price_filter() {
+        let mut market = market(42);
+        let price_filter = market.config().price_filter().clone();
+        let quantity_filter = market.config().quantity_filter().clone();
+        let mut last_ts_ns = TimestampNs::from(0);
+        for trade in market.trades().take(1_000) {
+            assert!(trade.timestamp_exchange_ns >= last_ts_ns);
+            last_ts_ns = trade.timestamp_exchange_ns;
+            assert!(trade.quantity > BaseCurrency::zero());
+            assert!(
+                quantity_filter
+                    .validate_order_quantity(trade.quantity)
+                    .is_ok()
+            );
+            assert!(
+                <Trade<_, DECIMALS, _> as MarketUpdate<_, DECIMALS, _>>::validate_market_update(
+                    &trade,
+                    &price_filter
+                )
+                .is_ok()
+            );
+        }
+        let bba = market.bba();
+        assert!(bba.bid < bba.ask);
+    }
+
+    #[test]
+    fn synthetic_market_trades_sweep_through_touch() {
+        let mut market = market(5);
+        let mut swept = 0;
+        for _ in 0..1_000 {
+            let trade = market.next_trade();
+            let bba = market.bba();
+            let through = match trade.side {
+                Side::Buy => {
+                    assert!(trade.price >= bba.ask);
+                    trade.price > bba.ask
+                }
+                Side::Sell => {
+                    assert!(trade.price <= bba.bid);
+                    trade.price < bba.bid
+                }
+            };
+            swept += usize::from(through);
+        }
+        // With the default `level_depth` of 2, a trade sweeps through the touch with a probability of exp(-2).
+        assert!((80..200).contains(&swept), "swept: {swept}");
+    }
+
+    #[test]
+    fn synthetic_market_bounded_by_price_filter() {
+        let price_filter = PriceFilter::new(
+            Some(QuoteCurrency::new(99, 0)),
+            Some(QuoteCurrency::new(101, 0)),
+            QuoteCurrency::new(5, 1),
+            Decimal::TWO,
+            Decimal::zero(),
+        )
+        .unwrap();
+        let config = SyntheticMarketConfig::builder()
+            .initial_price(QuoteCurrency::<i64, DECIMALS>::new(100, 0))
+            .mean_trade_quantity(BaseCurrency::<i64, DECIMALS>::new(1, 0))
+            .price_filter(price_filter)
+            .price_process(JumpDiffusion {
+                volatility: 0.1,
+                ..Default::default()
+            })
+            .build();
+        let mut market = SyntheticMarket::new(config, 7).unwrap();
+        for bba in market.bbas(1_000_000_000.into()).take(1_000) {
+            assert!(bba.bid >= QuoteCurrency::new(99, 0));
+            assert!(bba.ask <= QuoteCurrency::new(101, 0));
+            assert!(bba.bid < bba.ask);
+        }
+    }
+
+    #[test]
+    fn synthetic_market_smart_candles() {
+        let mut market = market(3);
+        let interval_ns = TimestampNs::from(10_000_000_000);
+        let mut last_ts_ns = market.current_ts_ns();
+        for candle in market.smart_candles(interval_ns).take(50) {
+            assert!(candle.last_timestamp_exchange_ns() >= last_ts_ns);
+            last_ts_ns = candle.last_timestamp_exchange_ns();
+        }
+        assert!(market.current_ts_ns() >= TimestampNs::from(500_000_000_000));
+    }
+
+    #[test]
+    fn synthetic_market_buy_imbalance() {
+        let config = SyntheticMarketConfig::builder()
+            .initial_price(QuoteCurrency::<i64, DECIMALS>::new(100, 0))
+            .mean_trade_quantity(BaseCurrency::<i64, DECIMALS>::new(1, 0))
+            .trade_arrivals(HawkesArrivals {
+                buy_probability: 0.8,
+                ..Default::default()
+            })
+            .build();
+        let mut market = SyntheticMarket::new(config, 11).unwrap();
+        let buys = market
+            .trades()
+            .take(10_000)
+            .filter(|trade| trade.side == Side::Buy)
+            .count();
+        assert!((7_700..8_300).contains(&buys), "buys: {buys}");
+    }
+}
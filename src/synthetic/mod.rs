@@ -0,0 +1,14 @@
+//! Seeded generators of synthetic market data, for reproducible stress scenarios without recorded data.
+
+mod market;
+mod processes;
+
+pub use market::{
+    SyntheticMarket,
+    SyntheticMarketConfig,
+};
+pub use processes::{
+    HawkesArrivals,
+    JumpDiffusion,
+    SpreadDynamics,
+};
@@ -0,0 +1,261 @@
+//! The stochastic processes driving the `SyntheticMarket`.
+//! All rates and volatilities are expressed per second.
+
+use crate::rng::SplitMix64;
+
+/// A geometric Brownian motion of the mid price with normally distributed jumps of the log price,
+/// arriving as a Poisson process (Merton jump diffusion).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpDiffusion {
+    /// The drift of the log price per second.
+    pub drift: f64,
+    /// The volatility of the log price per square root of a second.
+    pub volatility: f64,
+    /// The expected number of jumps per second.
+    pub jump_intensity: f64,
+    /// The mean of a single jump of the log price.
+    pub jump_mean: f64,
+    /// The standard deviation of a single jump of the log price.
+    pub jump_volatility: f64,
+}
+
+impl Default for JumpDiffusion {
+    fn default() -> Self {
+        Self {
+            drift: 0.0,
+            volatility: 0.0002,
+            jump_intensity: 1.0 / 3_600.0,
+            jump_mean: 0.0,
+            jump_volatility: 0.005,
+        }
+    }
+}
+
+impl JumpDiffusion {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.drift.is_finite()
+            && self.jump_mean.is_finite()
+            && [self.volatility, self.jump_intensity, self.jump_volatility]
+                .iter()
+                .all(|v| v.is_finite() && *v >= 0.0)
+    }
+
+    /// Sample the change of the log price over `dt` seconds.
+    pub(crate) fn log_return(&self, rng: &mut SplitMix64, dt: f64) -> f64 {
+        assert2::debug_assert!(dt >= 0.0);
+        let mut log_return = (self.drift - 0.5 * self.volatility * self.volatility) * dt
+            + self.volatility * dt.sqrt() * rng.next_standard_normal();
+        for _ in 0..poisson(rng, self.jump_intensity * dt) {
+            log_return += self.jump_mean + self.jump_volatility * rng.next_standard_normal();
+        }
+        log_return
+    }
+}
+
+/// Trade arrivals following a self-exciting Hawkes process with an exponential kernel,
+/// which reproduces the clustering of trades observed in real markets.
+///
+/// The intensity is `baseline_intensity + sum(excitation * exp(-decay * (t - t_i)))` over all past trades `t_i`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HawkesArrivals {
+    /// The exogenous number of trades per second.
+    pub baseline_intensity: f64,
+    /// The increase of the intensity caused by every trade.
+    pub excitation: f64,
+    /// The rate per second at which the excitation of a trade decays.
+    /// Must be larger than `excitation`, otherwise the trade arrivals explode.
+    pub decay: f64,
+    /// The probability of a trade being a buy, where 0.5 is a balanced order flow.
+    pub buy_probability: f64,
+}
+
+impl Default for HawkesArrivals {
+    fn default() -> Self {
+        Self {
+            baseline_intensity: 1.0,
+            excitation: 0.5,
+            decay: 1.0,
+            buy_probability: 0.5,
+        }
+    }
+}
+
+impl HawkesArrivals {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.baseline_intensity.is_finite()
+            && self.baseline_intensity > 0.0
+            && self.excitation.is_finite()
+            && self.excitation >= 0.0
+            && self.decay.is_finite()
+            && self.excitation < self.decay
+            && (0.0..=1.0).contains(&self.buy_probability)
+    }
+
+    /// Sample the time in seconds until the next trade using Ogata's thinning algorithm.
+    ///
+    /// # Arguments:
+    /// `excitation`: The excitation of all past trades, which is decayed up to the next trade.
+    pub(crate) fn next_arrival(&self, rng: &mut SplitMix64, excitation: &mut f64) -> f64 {
+        let mut elapsed = 0.0;
+        loop {
+            // The intensity only decays until the next trade, so the current one is an upper bound.
+            let upper_bound = self.baseline_intensity + *excitation;
+            let wait = rng.next_standard_exponential() / upper_bound;
+            elapsed += wait;
+            *excitation *= (-self.decay * wait).exp();
+            if rng.next_f64() * upper_bound <= self.baseline_intensity + *excitation {
+                return elapsed;
+            }
+        }
+    }
+}
+
+/// The bid-ask spread in ticks following a mean reverting Ornstein-Uhlenbeck process.
+/// The quoted spread is rounded to whole ticks and never tighter than a single tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpreadDynamics {
+    /// The long term mean of the spread in ticks.
+    pub mean_ticks: f64,
+    /// The rate per second at which the spread reverts to its mean.
+    pub reversion: f64,
+    /// The volatility of the spread in ticks per square root of a second.
+    pub volatility: f64,
+}
+
+impl Default for SpreadDynamics {
+    fn default() -> Self {
+        Self {
+            mean_ticks: 1.5,
+            reversion: 1.0,
+            volatility: 1.0,
+        }
+    }
+}
+
+impl SpreadDynamics {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.mean_ticks.is_finite()
+            && self.mean_ticks >= 1.0
+            && [self.reversion, self.volatility]
+                .iter()
+                .all(|v| v.is_finite() && *v >= 0.0)
+    }
+
+    /// Evolve the unrounded `spread_ticks` over `dt` seconds, using the exact transition density.
+    pub(crate) fn step(&self, rng: &mut SplitMix64, spread_ticks: f64, dt: f64) -> f64 {
+        assert2::debug_assert!(dt >= 0.0);
+        let z = rng.next_standard_normal();
+        if self.reversion == 0.0 {
+            return spread_ticks + self.volatility * dt.sqrt() * z;
+        }
+        let decay = (-self.reversion * dt).exp();
+        let std_dev = self.volatility * ((1.0 - decay * decay) / (2.0 * self.reversion)).sqrt();
+        self.mean_ticks + (spread_ticks - self.mean_ticks) * decay + std_dev * z
+    }
+
+    /// The quoted spread in whole ticks.
+    #[inline]
+    pub(crate) fn quoted_ticks(spread_ticks: f64) -> u32 {
+        // Saturates for absurdly large spreads.
+        spread_ticks.round().max(1.0) as u32
+    }
+}
+
+/// Sample the Poisson distribution with the expected value `lambda` using Knuth's algorithm.
+fn poisson(rng: &mut SplitMix64, lambda: f64) -> u32 {
+    let threshold = (-lambda).exp();
+    let mut count = 0;
+    let mut product = rng.next_f64();
+    while product > threshold {
+        count += 1;
+        product *= rng.next_f64();
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthetic_processes_default_valid() {
+        assert!(JumpDiffusion::default().is_valid());
+        assert!(HawkesArrivals::default().is_valid());
+        assert!(SpreadDynamics::default().is_valid());
+
+        assert!(
+            !JumpDiffusion {
+                volatility: -0.1,
+                ..Default::default()
+            }
+            .is_valid()
+        );
+        // A branching ratio of one is not stationary.
+        assert!(
+            !HawkesArrivals {
+                excitation: 1.0,
+                decay: 1.0,
+                ..Default::default()
+            }
+            .is_valid()
+        );
+        assert!(
+            !HawkesArrivals {
+                buy_probability: 1.5,
+                ..Default::default()
+            }
+            .is_valid()
+        );
+        assert!(
+            !SpreadDynamics {
+                mean_ticks: 0.5,
+                ..Default::default()
+            }
+            .is_valid()
+        );
+    }
+
+    #[test]
+    fn synthetic_poisson_mean() {
+        let mut rng = SplitMix64::new(0);
+        let n = 10_000;
+        let sum: u32 = (0..n).map(|_| poisson(&mut rng, 2.0)).sum();
+        assert!((f64::from(sum) / f64::from(n) - 2.0).abs() < 0.05);
+        assert_eq!(poisson(&mut rng, 0.0), 0);
+    }
+
+    #[test]
+    fn synthetic_hawkes_mean_intensity() {
+        // The stationary intensity is `baseline_intensity / (1 - excitation / decay)`.
+        let arrivals = HawkesArrivals {
+            baseline_intensity: 1.0,
+            excitation: 0.5,
+            decay: 1.0,
+            buy_probability: 0.5,
+        };
+        let mut rng = SplitMix64::new(1);
+        let mut excitation = 0.0;
+        let n = 20_000;
+        let mut elapsed = 0.0;
+        for _ in 0..n {
+            elapsed += arrivals.next_arrival(&mut rng, &mut excitation);
+            excitation += arrivals.excitation;
+        }
+        let intensity = f64::from(n) / elapsed;
+        assert!((intensity - 2.0).abs() < 0.1, "intensity: {intensity}");
+    }
+
+    #[test]
+    fn synthetic_spread_mean_reversion() {
+        let spread = SpreadDynamics {
+            mean_ticks: 3.0,
+            reversion: 1.0,
+            volatility: 0.0,
+        };
+        let mut rng = SplitMix64::new(2);
+        let s = spread.step(&mut rng, 10.0, 100.0);
+        assert!((s - 3.0).abs() < 1e-9);
+        assert_eq!(SpreadDynamics::quoted_ticks(0.2), 1);
+        assert_eq!(SpreadDynamics::quoted_ticks(2.6), 3);
+    }
+}
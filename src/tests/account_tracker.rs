@@ -0,0 +1,67 @@
+use crate::{
+    mock_bba,
+    mock_exchange_inverse,
+    mock_exchange_linear_with_account_tracker,
+    prelude::*,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn account_tracker_linear_round_trip() {
+    let mut exchange = mock_exchange_linear_with_account_tracker(QuoteCurrency::new(1000, 0));
+    assert!(exchange.update_state(&mock_bba(99, 0)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(5, 0)).unwrap())
+        .unwrap();
+    assert!(exchange.update_state(&mock_bba(100, 1)).unwrap().is_empty());
+    assert!(exchange.update_state(&mock_bba(110, 2)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Sell, BaseCurrency::new(5, 0)).unwrap())
+        .unwrap();
+    assert!(exchange.update_state(&mock_bba(110, 3)).unwrap().is_empty());
+
+    let tracker = exchange.account_tracker().unwrap();
+    let equity: Vec<f64> = tracker.equity_curve().iter().map(|s| s.equity).collect();
+    assert_eq!(equity.len(), 4);
+    for (equity, expected) in equity.iter().zip([1000.0, 999.7, 1049.7, 1049.37]) {
+        assert!((equity - expected).abs() < 1e-9);
+    }
+
+    assert_eq!(tracker.num_fills(), 2);
+    assert_eq!(tracker.traded_notional(), 1050.0);
+    assert_eq!(tracker.realized_pnl(), 50.0);
+    assert!((tracker.fees_paid() - 0.63).abs() < 1e-9);
+    assert_eq!(tracker.win_rate(), Some(1.0));
+    assert_eq!(tracker.profit_factor(), None);
+    assert!((tracker.total_return().unwrap() - 0.04937).abs() < 1e-9);
+    assert!((tracker.max_drawdown() - 0.0003).abs() < 1e-9);
+    assert_eq!(tracker.max_drawdown_duration(), 1.into());
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn account_tracker_inverse_in_quote_terms() {
+    let mut exchange = mock_exchange_inverse(BaseCurrency::new(10, 0));
+    exchange
+        .set_account_tracker(AccountTracker::new(10.into(), EquityDenomination::Quote).unwrap());
+    assert!(exchange.update_state(&mock_bba(99, 0)).unwrap().is_empty());
+    // Only the first update of each interval is sampled.
+    assert!(exchange.update_state(&mock_bba(100, 5)).unwrap().is_empty());
+    assert!(
+        exchange
+            .update_state(&mock_bba(109, 10))
+            .unwrap()
+            .is_empty()
+    );
+
+    let tracker = exchange.account_tracker().unwrap();
+    let samples: Vec<(i64, f64)> = tracker
+        .equity_curve()
+        .iter()
+        .map(|s| (s.timestamp_ns.get(), s.equity))
+        .collect();
+    // Without a position, the quote value of the equity follows the mid price.
+    assert_eq!(samples, vec![(0, 995.0), (10, 1095.0)]);
+    assert!((tracker.total_return().unwrap() - 0.1005025125628).abs() < 1e-9);
+    assert_eq!(tracker.num_fills(), 0);
+}
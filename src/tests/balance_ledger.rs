@@ -0,0 +1,125 @@
+use std::num::NonZeroU16;
+
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    EXPECT_CONFIG,
+    EXPECT_CONTRACT_SPEC,
+    EXPECT_DECIMAL,
+    EXPECT_NON_ZERO,
+    EXPECT_QUANTITY_FILTER,
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+    utils::NoUserOrderId,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn balance_ledger_round_trip() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_balance_ledger(BalanceLedger::default());
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+
+    let settlement = exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(2, 0)).unwrap())
+        .unwrap();
+    let market_order_id = settlement.filled_order.state().meta().id();
+    let limit_order = exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Sell,
+                QuoteCurrency::new(105, 0),
+                BaseCurrency::new(2, 0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let events = exchange
+        .update_state(&Trade {
+            price: QuoteCurrency::new(106, 0),
+            quantity: BaseCurrency::new(2, 0),
+            side: Side::Buy,
+            timestamp_exchange_ns: 2.into(),
+        })
+        .unwrap();
+    assert_eq!(events.len(), 1);
+
+    let ledger = exchange.balance_ledger().unwrap();
+    assert_eq!(ledger.opening_equity(), QuoteCurrency::new(1000, 0));
+    assert_eq!(
+        ledger.entries(),
+        &[
+            LedgerEntry {
+                timestamp_ns: 0.into(),
+                order_id: Some(market_order_id),
+                cause: LedgerCause::TakerFee,
+                amount: QuoteCurrency::new(-1212, 4),
+            },
+            LedgerEntry {
+                timestamp_ns: 2.into(),
+                order_id: Some(limit_order.id()),
+                cause: LedgerCause::RealizedPnl,
+                amount: QuoteCurrency::new(8, 0),
+            },
+            LedgerEntry {
+                timestamp_ns: 2.into(),
+                order_id: Some(limit_order.id()),
+                cause: LedgerCause::MakerFee,
+                amount: QuoteCurrency::new(-42, 3),
+            },
+        ]
+    );
+    assert!(ledger.reconciles(exchange.account().balances()));
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn balance_ledger_liquidation_with_bad_debt() {
+    let contract_spec = ContractSpecification::new(
+        leverage!(5),
+        Decimal::try_from_scaled(5, 1).expect(EXPECT_DECIMAL),
+        PriceFilter::default(),
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).expect(EXPECT_QUANTITY_FILTER),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .expect(EXPECT_CONTRACT_SPEC);
+    let config = Config::new(
+        QuoteCurrency::new(1000, 0),
+        NonZeroU16::new(10).expect(EXPECT_NON_ZERO),
+        contract_spec,
+        OrderRateLimits::default(),
+    )
+    .expect(EXPECT_CONFIG);
+    let mut exchange =
+        Exchange::<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId>::new(config);
+    exchange.set_balance_ledger(BalanceLedger::default());
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(40, 0)).unwrap())
+        .unwrap();
+
+    // Closing 40 @ 70 realizes a loss exceeding the equity, the rest becomes bad debt.
+    assert!(matches!(
+        exchange.update_state(&mock_bba(70, 1)),
+        Err(UpdateStateError::Risk(RiskError::Liquidate))
+    ));
+
+    let ledger = exchange.balance_ledger().unwrap();
+    let [_taker_fee, pnl, fee, bad_debt] = ledger.entries() else {
+        panic!("Expected four entries");
+    };
+    assert_eq!(pnl.cause, LedgerCause::Liquidation);
+    assert_eq!(pnl.order_id, None);
+    assert_eq!(pnl.amount, QuoteCurrency::new(-1240, 0));
+    assert_eq!(fee.cause, LedgerCause::LiquidationFee);
+    assert_eq!(fee.amount, QuoteCurrency::new(-168, 2));
+    assert_eq!(bad_debt.cause, LedgerCause::BadDebt);
+    assert_eq!(bad_debt.amount, QuoteCurrency::new(24_410_400, 5));
+    assert!(ledger.equity().is_zero());
+    assert!(ledger.reconciles(exchange.account().balances()));
+}
@@ -0,0 +1,94 @@
+use crate::{
+    mock_bba,
+    mock_exchange_linear_with_account_tracker,
+    prelude::*,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn deposit_and_withdraw() {
+    let mut exchange = mock_exchange_linear_with_account_tracker(QuoteCurrency::new(1000, 0));
+    exchange.set_balance_ledger(BalanceLedger::default());
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+
+    assert_eq!(
+        exchange.deposit(QuoteCurrency::zero()),
+        Err(CashFlowError::AmountLTEZero)
+    );
+    exchange.deposit(QuoteCurrency::new(500, 0)).unwrap();
+    assert_eq!(
+        exchange.account().balances().equity(),
+        QuoteCurrency::new(1500, 0)
+    );
+    assert!(exchange.update_state(&mock_bba(100, 1)).unwrap().is_empty());
+
+    // Long 10 @ 101 requires 1010 of margin and pays a fee of 0.606.
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(10, 0)).unwrap())
+        .unwrap();
+    assert_eq!(
+        exchange.account().available_balance(),
+        QuoteCurrency::new(489_394, 3)
+    );
+    assert_eq!(
+        exchange.withdraw(QuoteCurrency::new(490, 0)),
+        Err(CashFlowError::NotEnoughAvailableBalance(
+            NotEnoughAvailableBalance
+        ))
+    );
+    assert_eq!(
+        exchange.withdraw(QuoteCurrency::new(-1, 0)),
+        Err(CashFlowError::AmountLTEZero)
+    );
+    exchange.withdraw(QuoteCurrency::new(400, 0)).unwrap();
+    assert_eq!(
+        exchange.account().balances().equity(),
+        QuoteCurrency::new(1_099_394, 3)
+    );
+
+    assert_eq!(
+        exchange.cash_flows(),
+        &[
+            CashFlow {
+                timestamp_ns: 0.into(),
+                amount: QuoteCurrency::new(500, 0),
+            },
+            CashFlow {
+                timestamp_ns: 1.into(),
+                amount: QuoteCurrency::new(-400, 0),
+            },
+        ]
+    );
+
+    let ledger = exchange.balance_ledger().unwrap();
+    assert_eq!(
+        ledger.total(LedgerCause::Deposit),
+        QuoteCurrency::new(500, 0)
+    );
+    assert_eq!(
+        ledger.total(LedgerCause::Withdrawal),
+        QuoteCurrency::new(-400, 0)
+    );
+    assert!(ledger.reconciles(exchange.account().balances()));
+
+    // The deposit is not a return.
+    let tracker = exchange.account_tracker().unwrap();
+    assert_eq!(tracker.net_cash_flow(), 100.0);
+    assert_eq!(tracker.equity_curve()[1].cash_flow, 500.0);
+    assert!(tracker.total_return().unwrap().abs() < 1e-12);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn deposit_before_first_quote() {
+    let mut exchange = mock_exchange_linear_with_account_tracker(QuoteCurrency::new(1000, 0));
+    exchange.deposit(QuoteCurrency::new(500, 0)).unwrap();
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+    assert!(exchange.update_state(&mock_bba(100, 1)).unwrap().is_empty());
+
+    // The deposit is part of the starting equity rather than a cash flow between samples.
+    let tracker = exchange.account_tracker().unwrap();
+    assert_eq!(tracker.net_cash_flow(), 0.0);
+    assert_eq!(tracker.equity_curve()[0].equity, 1500.0);
+    assert!(tracker.total_return().unwrap().abs() < 1e-12);
+}
@@ -0,0 +1,163 @@
+use const_decimal::Decimal;
+
+use crate::{
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn contract_spec_schedule_tick_size_change() {
+    let mut exchange = mock_exchange_linear();
+    let new_contract_spec = ContractSpecification::new(
+        leverage!(2),
+        Decimal::try_from_scaled(5, 1).unwrap(),
+        PriceFilter::new(
+            None,
+            None,
+            QuoteCurrency::new(5, 0),
+            Decimal::TWO,
+            Decimal::zero(),
+        )
+        .unwrap(),
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).unwrap(),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .unwrap();
+    exchange.set_contract_spec_schedule(
+        ContractSpecificationSchedule::new(vec![(10.into(), new_contract_spec)]).unwrap(),
+    );
+
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(99, 0),
+                ask: QuoteCurrency::new(100, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    let off_tick_order = exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(98, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+    let on_tick_order = exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(95, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        exchange.account().init_margin_req(),
+        Decimal::try_from_scaled(1, 0).unwrap()
+    );
+
+    // Not yet reached.
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(99, 0),
+                ask: QuoteCurrency::new(100, 0),
+                timestamp_exchange_ns: 9.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(exchange.account().active_limit_orders().num_active(), 2);
+
+    assert_eq!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(95, 0),
+                ask: QuoteCurrency::new(100, 0),
+                timestamp_exchange_ns: 10.into(),
+            })
+            .unwrap(),
+        &vec![LimitOrderEvent::ContractSpecCancel(off_tick_order)]
+    );
+    assert_eq!(exchange.account().active_limit_orders().num_active(), 1);
+    assert_eq!(
+        exchange
+            .account()
+            .active_limit_orders()
+            .get_by_id(on_tick_order.id(), Side::Buy),
+        Some(&on_tick_order)
+    );
+    assert_eq!(
+        exchange.config().contract_spec().price_filter().tick_size(),
+        QuoteCurrency::new(5, 0)
+    );
+    assert_eq!(
+        exchange.account().init_margin_req(),
+        Decimal::try_from_scaled(5, 1).unwrap()
+    );
+    assert_eq!(
+        exchange.account().order_margin(),
+        QuoteCurrency::new(475, 1)
+    );
+
+    // New orders must follow the new tick size.
+    assert!(
+        exchange
+            .submit_limit_order(
+                LimitOrder::new(Side::Buy, QuoteCurrency::new(94, 0), BaseCurrency::one()).unwrap(),
+            )
+            .is_err()
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn contract_spec_schedule_min_notional_change() {
+    let mut exchange = mock_exchange_linear();
+    let mut new_contract_spec = exchange.config().contract_spec().clone();
+    new_contract_spec
+        .set_notional_filter(NotionalFilter::new(Some(QuoteCurrency::new(100, 0)), None).unwrap());
+    exchange.set_contract_spec_schedule(
+        ContractSpecificationSchedule::new(vec![(10.into(), new_contract_spec)]).unwrap(),
+    );
+
+    assert!(exchange.update_state(&mock_bba(99, 0)).unwrap().is_empty());
+    let small_order = exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(95, 0),
+                BaseCurrency::new(5, 1),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(95, 0),
+                BaseCurrency::new(2, 0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    // Resting orders below the new minimum notional are cancelled.
+    assert_eq!(
+        exchange.update_state(&mock_bba(99, 10)).unwrap(),
+        &vec![LimitOrderEvent::ContractSpecCancel(small_order)]
+    );
+    assert_eq!(exchange.account().active_limit_orders().num_active(), 1);
+}
+
+#[test]
+fn contract_spec_schedule_new() {
+    let spec = ContractSpecification::<i64, 5, BaseCurrency<i64, 5>>::default();
+    assert_eq!(
+        ContractSpecificationSchedule::new(vec![(10.into(), spec.clone()), (10.into(), spec)])
+            .unwrap_err(),
+        ConfigError::InvalidContractSpecificationSchedule
+    );
+}
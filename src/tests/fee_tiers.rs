@@ -0,0 +1,128 @@
+use std::num::NonZeroU16;
+
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+    types::NANOS_PER_SECOND,
+};
+
+fn vip_fee_maker() -> Fee<i64, DECIMALS, Maker> {
+    Fee::from(Decimal::try_from_scaled(1, 4).unwrap())
+}
+
+fn vip_fee_taker() -> Fee<i64, DECIMALS, Taker> {
+    Fee::from(Decimal::try_from_scaled(4, 4).unwrap())
+}
+
+fn exchange_with_fee_schedule()
+-> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut contract_spec = ContractSpecification::new(
+        leverage!(1),
+        Decimal::try_from_scaled(5, 1).unwrap(),
+        PriceFilter::default(),
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).unwrap(),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .unwrap();
+    contract_spec.set_fee_schedule(Some(
+        FeeSchedule::new(
+            vec![
+                FeeTier::new(QuoteCurrency::new(0, 0), test_fee_maker(), test_fee_taker()),
+                FeeTier::new(QuoteCurrency::new(500, 0), vip_fee_maker(), vip_fee_taker()),
+            ],
+            (60 * NANOS_PER_SECOND).into(),
+        )
+        .unwrap(),
+    ));
+    let config = Config::new(
+        QuoteCurrency::new(1000, 0),
+        NonZeroU16::new(10).unwrap(),
+        contract_spec,
+        OrderRateLimits::default(),
+    )
+    .unwrap();
+    Exchange::new(config)
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fee_tier_upgrade_and_expiry() {
+    let mut exchange = exchange_with_fee_schedule();
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 0.into()
+            })
+            .unwrap()
+            .is_empty()
+    );
+    assert!(exchange.fee_tier_changes().is_empty());
+    assert_eq!(exchange.fee_tier_tracker().active_tier(), 0);
+
+    let qty = BaseCurrency::new(5, 0);
+    let order = MarketOrder::new(Side::Buy, qty).unwrap();
+    let settlement = exchange.submit_market_order(order).unwrap();
+
+    // The fill itself still pays the fee of the tier it was executed in.
+    let notional = QuoteCurrency::new(505, 0);
+    let fee = notional * *test_fee_taker().as_ref();
+    assert_eq!(exchange.account().balances().total_fees_paid(), fee);
+    assert_eq!(
+        settlement.fee_tier_change,
+        Some(FeeTierChange {
+            timestamp_ns: 0.into(),
+            previous_tier: 0,
+            new_tier: 1,
+            rolling_notional: notional,
+            fee_maker: vip_fee_maker(),
+            fee_taker: vip_fee_taker(),
+        })
+    );
+    assert_eq!(exchange.fee_tier_tracker().active_tier(), 1);
+    assert_eq!(
+        exchange.config().contract_spec().fee_maker(),
+        vip_fee_maker()
+    );
+    assert_eq!(
+        exchange.config().contract_spec().fee_taker(),
+        vip_fee_taker()
+    );
+
+    // Subsequent fills pay the fees of the new tier.
+    let order = MarketOrder::new(Side::Sell, qty).unwrap();
+    exchange.submit_market_order(order).unwrap();
+    let fee_2 = QuoteCurrency::new(500, 0) * *vip_fee_taker().as_ref();
+    assert_eq!(exchange.account().balances().total_fees_paid(), fee + fee_2);
+
+    // Once the traded notional rolled out of the window, the account falls back to the first tier.
+    let ts: TimestampNs = (60 * NANOS_PER_SECOND).into();
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: ts
+            })
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(exchange.fee_tier_changes(), &vec![FeeTierChange {
+        timestamp_ns: ts,
+        previous_tier: 1,
+        new_tier: 0,
+        rolling_notional: QuoteCurrency::zero(),
+        fee_maker: test_fee_maker(),
+        fee_taker: test_fee_taker(),
+    }]);
+    assert_eq!(
+        exchange.config().contract_spec().fee_taker(),
+        test_fee_taker()
+    );
+}
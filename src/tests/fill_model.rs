@@ -0,0 +1,186 @@
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+fn exchange_with_bid(
+    quantity: BaseCurrency<i64, DECIMALS>,
+) -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut exchange = mock_exchange_linear();
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(100, 0), quantity).unwrap(),
+        )
+        .unwrap();
+    exchange
+}
+
+fn filled_quantities(
+    events: &[LimitOrderEvent<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId>],
+) -> Vec<BaseCurrency<i64, DECIMALS>> {
+    events
+        .iter()
+        .map(|event| match event {
+            LimitOrderEvent::Fill(LimitOrderFill::PartiallyFilled {
+                filled_quantity, ..
+            })
+            | LimitOrderEvent::Fill(LimitOrderFill::FullyFilled {
+                filled_quantity, ..
+            }) => *filled_quantity,
+            event => panic!("Unexpected event {event:?}"),
+        })
+        .collect()
+}
+
+fn trade_at_bid(
+    quantity: BaseCurrency<i64, DECIMALS>,
+) -> Trade<i64, DECIMALS, BaseCurrency<i64, DECIMALS>> {
+    Trade {
+        timestamp_exchange_ns: 1.into(),
+        price: QuoteCurrency::new(100, 0),
+        quantity,
+        side: Side::Sell,
+    }
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fill_model_default_requires_trade_through() {
+    let mut exchange = exchange_with_bid(BaseCurrency::one());
+    assert!(exchange.fill_model().is_none());
+    assert!(
+        exchange
+            .update_state(&trade_at_bid(BaseCurrency::new(2, 0)))
+            .unwrap()
+            .is_empty()
+    );
+
+    exchange.set_fill_model(TradeThrough);
+    assert!(
+        exchange
+            .update_state(&trade_at_bid(BaseCurrency::new(2, 0)))
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fill_model_touch_fill_trade() {
+    let mut exchange = exchange_with_bid(BaseCurrency::new(3, 0));
+    exchange.set_fill_model(TouchFill);
+    let events = exchange
+        .update_state(&trade_at_bid(BaseCurrency::new(2, 0)))
+        .unwrap();
+    assert_eq!(filled_quantities(events), vec![BaseCurrency::new(2, 0)]);
+    assert_eq!(
+        exchange.account().position().quantity(),
+        BaseCurrency::new(2, 0)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fill_model_touch_fill_candle() {
+    let mut exchange = exchange_with_bid(BaseCurrency::new(3, 0));
+    exchange.set_fill_model(TouchFill);
+    let candle = Candle::builder()
+        .bid(QuoteCurrency::new(101, 0))
+        .ask(QuoteCurrency::new(102, 0))
+        .low(QuoteCurrency::new(100, 0))
+        .high(QuoteCurrency::new(103, 0))
+        .timestamp_exchange_ns(1.into())
+        .build();
+    let events = exchange.update_state(&candle).unwrap();
+    // Without volume information the remaining quantity is filled.
+    assert_eq!(filled_quantities(events), vec![BaseCurrency::new(3, 0)]);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fill_model_touch_fill_consumes_volume() {
+    let mut exchange = exchange_with_bid(BaseCurrency::new(2, 0));
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(99, 0),
+                BaseCurrency::new(2, 0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    exchange.set_fill_model(TouchFill);
+    let events = exchange
+        .update_state(&Trade {
+            timestamp_exchange_ns: 1.into(),
+            price: QuoteCurrency::new(99, 0),
+            quantity: BaseCurrency::new(3, 0),
+            side: Side::Sell,
+        })
+        .unwrap();
+    // The better priced order consumes most of the traded volume.
+    assert_eq!(filled_quantities(events), vec![
+        BaseCurrency::new(2, 0),
+        BaseCurrency::new(1, 0)
+    ]);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fill_model_volume_capped() {
+    let mut exchange = exchange_with_bid(BaseCurrency::new(3, 0));
+    exchange
+        .set_fill_model(VolumeCappedFill::new(Decimal::try_from_scaled(5, 1).unwrap()).unwrap());
+    let events = exchange
+        .update_state(&Trade {
+            timestamp_exchange_ns: 1.into(),
+            price: QuoteCurrency::new(99, 0),
+            quantity: BaseCurrency::new(4, 0),
+            side: Side::Sell,
+        })
+        .unwrap();
+    assert_eq!(filled_quantities(events), vec![BaseCurrency::new(2, 0)]);
+    assert_eq!(
+        exchange
+            .account()
+            .active_limit_orders()
+            .best_bid()
+            .unwrap()
+            .remaining_quantity(),
+        BaseCurrency::new(1, 0)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn fill_model_probabilistic_touch_fill() {
+    let mut never = exchange_with_bid(BaseCurrency::one());
+    never.set_fill_model(ProbabilisticTouchFill::new(0.0, 0).unwrap());
+    assert!(
+        never
+            .update_state(&trade_at_bid(BaseCurrency::new(2, 0)))
+            .unwrap()
+            .is_empty()
+    );
+
+    let mut always = exchange_with_bid(BaseCurrency::one());
+    always.set_fill_model(ProbabilisticTouchFill::new(1.0, 0).unwrap());
+    let events = always
+        .update_state(&trade_at_bid(BaseCurrency::new(2, 0)))
+        .unwrap();
+    assert_eq!(filled_quantities(events), vec![BaseCurrency::one()]);
+}
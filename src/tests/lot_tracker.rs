@@ -0,0 +1,85 @@
+use crate::{
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn lot_tracker_fifo() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_lot_tracker(LotTracker::new(CostBasisMethod::Fifo));
+    for (bid, ts_ns, side) in [
+        (100, 0, Side::Buy),
+        (110, 1, Side::Buy),
+        (120, 2, Side::Sell),
+    ] {
+        assert!(
+            exchange
+                .update_state(&mock_bba(bid, ts_ns))
+                .unwrap()
+                .is_empty()
+        );
+        exchange
+            .submit_market_order(MarketOrder::new(side, BaseCurrency::new(1, 0)).unwrap())
+            .unwrap();
+    }
+
+    let lots = exchange.lot_tracker().unwrap();
+    assert_eq!(
+        lots.closed_lots(),
+        &[ClosedLot {
+            entry_ts_ns: 0.into(),
+            exit_ts_ns: 2.into(),
+            quantity: BaseCurrency::new(1, 0),
+            entry_price: QuoteCurrency::new(101, 0),
+            exit_price: QuoteCurrency::new(120, 0),
+            realized_pnl: QuoteCurrency::new(19, 0),
+        }]
+    );
+    assert_eq!(
+        lots.open_lots().iter().copied().collect::<Vec<_>>(),
+        vec![Lot {
+            entry_ts_ns: 1.into(),
+            quantity: BaseCurrency::new(1, 0),
+            entry_price: QuoteCurrency::new(111, 0),
+        }]
+    );
+    assert_eq!(
+        lots.open_quantity(),
+        exchange.account().position().quantity()
+    );
+
+    // The margin accounting stays on the average cost basis.
+    assert_eq!(
+        exchange.account().position().entry_price(),
+        QuoteCurrency::new(106, 0)
+    );
+    let fees = exchange.account().balances().total_fees_paid();
+    assert_eq!(
+        exchange.account().balances().equity(),
+        QuoteCurrency::new(1014, 0) - fees
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn lot_tracker_opens_existing_position() {
+    let mut exchange = mock_exchange_linear();
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Sell, BaseCurrency::new(2, 0)).unwrap())
+        .unwrap();
+    assert!(exchange.update_state(&mock_bba(90, 1)).unwrap().is_empty());
+    exchange.set_lot_tracker(LotTracker::new(CostBasisMethod::Lifo));
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(2, 0)).unwrap())
+        .unwrap();
+
+    let lots = exchange.lot_tracker().unwrap();
+    assert!(lots.open_lots().is_empty());
+    assert_eq!(lots.closed_lots().len(), 1);
+    assert_eq!(lots.closed_lots()[0].entry_ts_ns, 1.into());
+    assert_eq!(lots.closed_lots()[0].quantity, BaseCurrency::new(-2, 0));
+    assert_eq!(lots.realized_pnl(), QuoteCurrency::new(18, 0));
+}
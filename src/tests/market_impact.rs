@@ -0,0 +1,76 @@
+use crate::{
+    DECIMALS,
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+fn exchange_with_impact(
+    temporary_coefficient: f64,
+    permanent_coefficient: f64,
+) -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut exchange = mock_exchange_linear();
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+    exchange.set_market_impact_model(
+        MarketImpactModel::new(
+            temporary_coefficient,
+            permanent_coefficient,
+            0.5,
+            BaseCurrency::new(4, 0),
+            1_000.into(),
+        )
+        .unwrap(),
+    );
+    exchange
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn market_impact_temporary() {
+    let mut exchange = exchange_with_impact(0.02, 0.0);
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(101, 0));
+
+    let settlement = exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap())
+        .unwrap();
+    assert_eq!(
+        settlement.filled_order.state().avg_fill_price(),
+        QuoteCurrency::new(101, 0)
+    );
+    assert_eq!(exchange.market_state().bid(), QuoteCurrency::new(101, 0));
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(102, 0));
+    assert_eq!(
+        exchange.market_state().recorded_ask(),
+        QuoteCurrency::new(101, 0)
+    );
+
+    // The next market order fills at the shifted ask.
+    let settlement = exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap())
+        .unwrap();
+    assert_eq!(
+        settlement.filled_order.state().avg_fill_price(),
+        QuoteCurrency::new(102, 0)
+    );
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(103, 0));
+
+    // The temporary impact has decayed after many half lives.
+    exchange.update_state(&mock_bba(100, 10_000)).unwrap();
+    assert_eq!(exchange.market_state().bid(), QuoteCurrency::new(100, 0));
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(101, 0));
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn market_impact_permanent() {
+    let mut exchange = exchange_with_impact(0.0, 0.02);
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Sell, BaseCurrency::one()).unwrap())
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), QuoteCurrency::new(99, 0));
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(100, 0));
+
+    exchange.update_state(&mock_bba(100, 1_000_000)).unwrap();
+    assert_eq!(exchange.market_state().bid(), QuoteCurrency::new(99, 0));
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(100, 0));
+}
@@ -0,0 +1,57 @@
+use crate::{
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn markout_analytics_limit_fills() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_markout_analytics(
+        MarkoutAnalytics::new(
+            vec![5.into(), 10.into()],
+            vec![2.into()],
+            Vec::with_capacity(0),
+        )
+        .unwrap(),
+    );
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(100, 0),
+                BaseCurrency::new(1, 0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let events = exchange
+        .update_state(&Trade {
+            price: QuoteCurrency::new(99, 0),
+            quantity: BaseCurrency::new(1, 0),
+            side: Side::Sell,
+            timestamp_exchange_ns: 3.into(),
+        })
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(exchange.markout_analytics().unwrap().num_pending_fills(), 1);
+
+    assert!(exchange.update_state(&mock_bba(102, 8)).unwrap().is_empty());
+    let markouts = exchange.markout_analytics().unwrap();
+    let stats = markouts.stats_by_side(Side::Buy, 0);
+    assert_eq!(stats.count, 1);
+    assert_eq!(stats.average(), Some(2.5));
+    assert_eq!(stats.average_bps(), Some(250.0));
+    // The order rested for 3ns before it was filled.
+    assert_eq!(markouts.stats_by_age(1, 0).count, 1);
+    assert_eq!(markouts.stats_by_age(0, 0).count, 0);
+    assert_eq!(markouts.stats_by_side(Side::Buy, 1).count, 0);
+
+    assert!(exchange.update_state(&mock_bba(98, 13)).unwrap().is_empty());
+    let markouts = exchange.markout_analytics().unwrap();
+    assert_eq!(markouts.stats_by_side(Side::Buy, 1).average(), Some(-1.5));
+    assert_eq!(markouts.stats_by_side(Side::Sell, 1).count, 0);
+    assert_eq!(markouts.num_pending_fills(), 0);
+}
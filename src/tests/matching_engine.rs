@@ -0,0 +1,359 @@
+use crate::{
+    DECIMALS,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+type Engine = MatchingEngine<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId>;
+
+fn engine_with_participants(n: usize) -> (Engine, Vec<AccountId>) {
+    let mut engine = MatchingEngine::new(Bba {
+        bid: QuoteCurrency::new(100, 0),
+        ask: QuoteCurrency::new(101, 0),
+        timestamp_exchange_ns: 0.into(),
+    })
+    .unwrap();
+    let config = mock_exchange_linear().config().clone();
+    let participants = (0..n)
+        .map(|_| engine.add_participant(config.clone()))
+        .collect();
+    (engine, participants)
+}
+
+fn limit_order(
+    side: Side,
+    price: i64,
+    quantity: BaseCurrency<i64, DECIMALS>,
+) -> LimitOrder<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId, NewOrder> {
+    LimitOrder::new(side, QuoteCurrency::new(price, 0), quantity).unwrap()
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn matching_engine_publishes_quote() {
+    let (mut engine, participants) = engine_with_participants(2);
+    assert_eq!(engine.participants().len(), 2);
+    engine
+        .submit_limit_order(
+            participants[0],
+            limit_order(Side::Buy, 99, BaseCurrency::one()),
+        )
+        .unwrap();
+    assert_eq!(
+        engine.order_book().best_bid(),
+        Some(QuoteCurrency::new(99, 0))
+    );
+    assert_eq!(engine.bid(), QuoteCurrency::new(99, 0));
+    // Without resting sell orders the ask keeps its last price.
+    assert_eq!(engine.ask(), QuoteCurrency::new(101, 0));
+    let market_state = engine.participant(participants[0]).market_state();
+    assert_eq!(market_state.bid(), QuoteCurrency::new(99, 0));
+    assert_eq!(market_state.ask(), QuoteCurrency::new(101, 0));
+    // Participants without a position observe the quote once they act.
+    assert_eq!(
+        engine.participant(participants[1]).market_state().bid(),
+        QuoteCurrency::new(100, 0)
+    );
+
+    // Orders crossing the published quote are rejected.
+    assert!(matches!(
+        engine.submit_limit_order(
+            participants[1],
+            limit_order(Side::Sell, 99, BaseCurrency::one())
+        ),
+        Err(SubmitLimitOrderError::GoodTillCrossingRejectedOrder { .. })
+    ));
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn matching_engine_market_order_price_time_priority() {
+    let (mut engine, participants) = engine_with_participants(3);
+    let (maker_a, maker_b, taker) = (participants[0], participants[1], participants[2]);
+    engine
+        .submit_limit_order(maker_a, limit_order(Side::Sell, 102, BaseCurrency::one()))
+        .unwrap();
+    engine
+        .submit_limit_order(maker_b, limit_order(Side::Sell, 101, BaseCurrency::one()))
+        .unwrap();
+    engine
+        .submit_limit_order(maker_a, limit_order(Side::Sell, 101, BaseCurrency::one()))
+        .unwrap();
+    assert_eq!(
+        engine
+            .order_book()
+            .quantity_at(Side::Sell, QuoteCurrency::new(101, 0)),
+        BaseCurrency::new(2, 0)
+    );
+
+    let MatchedMarketOrder::Filled(settlement) = engine
+        .submit_market_order(
+            taker,
+            MarketOrder::new(Side::Buy, BaseCurrency::new(25, 1)).unwrap(),
+        )
+        .unwrap()
+    else {
+        panic!("The resting orders fill the whole order");
+    };
+    assert_eq!(settlement.solvency, Solvency::Solvent);
+    assert_eq!(
+        settlement.filled_order.state().avg_fill_price(),
+        QuoteCurrency::new(1012, 1)
+    );
+    assert_eq!(
+        settlement.filled_order.state().filled_qty(),
+        BaseCurrency::new(25, 1)
+    );
+
+    let trades: Vec<_> = engine
+        .trades()
+        .iter()
+        .map(|trade| (trade.price, trade.quantity, trade.side))
+        .collect();
+    assert_eq!(trades, vec![
+        (QuoteCurrency::new(101, 0), BaseCurrency::one(), Side::Buy),
+        (QuoteCurrency::new(101, 0), BaseCurrency::one(), Side::Buy),
+        (
+            QuoteCurrency::new(102, 0),
+            BaseCurrency::new(5, 1),
+            Side::Buy
+        ),
+    ]);
+    let makers: Vec<_> = engine
+        .limit_order_events()
+        .iter()
+        .map(|(participant, _)| *participant)
+        .collect();
+    assert_eq!(makers, vec![maker_b, maker_a, maker_a]);
+
+    assert_eq!(engine.last_trade_price(), Some(QuoteCurrency::new(102, 0)));
+    assert_eq!(engine.ask(), QuoteCurrency::new(102, 0));
+    assert_eq!(
+        engine
+            .order_book()
+            .quantity_at(Side::Sell, QuoteCurrency::new(102, 0)),
+        BaseCurrency::new(5, 1)
+    );
+    assert_eq!(
+        engine.participant(taker).account().position().quantity(),
+        BaseCurrency::new(25, 1)
+    );
+    assert_eq!(
+        engine.participant(maker_a).account().position().quantity(),
+        BaseCurrency::new(-15, 1)
+    );
+    assert_eq!(
+        engine.participant(maker_b).account().position().quantity(),
+        BaseCurrency::new(-1, 0)
+    );
+    assert_eq!(
+        engine
+            .participant(maker_b)
+            .market_state()
+            .last_trade_price(),
+        QuoteCurrency::new(102, 0)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn matching_engine_insufficient_liquidity() {
+    let (mut engine, participants) = engine_with_participants(2);
+    engine
+        .submit_limit_order(
+            participants[0],
+            limit_order(Side::Sell, 101, BaseCurrency::one()),
+        )
+        .unwrap();
+    // Resting orders of the participant itself are skipped.
+    assert_eq!(
+        engine.submit_market_order(
+            participants[0],
+            MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap()
+        ),
+        Err(SubmitMarketOrderError::InsufficientLiquidity)
+    );
+    assert!(engine.trades().is_empty());
+
+    let MatchedMarketOrder::RemainderCancelled {
+        settlement,
+        cancelled_quantity,
+        reason,
+    } = engine
+        .submit_market_order(
+            participants[1],
+            MarketOrder::new(Side::Buy, BaseCurrency::new(2, 0)).unwrap(),
+        )
+        .unwrap()
+    else {
+        panic!("The resting orders fill half of the order");
+    };
+    assert_eq!(reason, RemainderCancelReason::InsufficientLiquidity);
+    assert_eq!(cancelled_quantity, BaseCurrency::one());
+    assert_eq!(
+        settlement.filled_order.state().filled_qty(),
+        BaseCurrency::one()
+    );
+    assert!(engine.order_book().is_empty());
+    assert_eq!(engine.trades().len(), 1);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn matching_engine_market_order_margin_checked_per_fill() {
+    let (mut engine, participants) = engine_with_participants(3);
+    let (maker_a, maker_b, taker) = (participants[0], participants[1], participants[2]);
+    engine
+        .submit_limit_order(
+            maker_a,
+            limit_order(Side::Sell, 101, BaseCurrency::new(9, 0)),
+        )
+        .unwrap();
+    engine
+        .submit_limit_order(
+            maker_b,
+            limit_order(Side::Sell, 102, BaseCurrency::new(9, 0)),
+        )
+        .unwrap();
+
+    // The taker can afford the fill at 101 but not the one at 102.
+    let MatchedMarketOrder::RemainderCancelled {
+        settlement,
+        cancelled_quantity,
+        reason,
+    } = engine
+        .submit_market_order(
+            taker,
+            MarketOrder::new(Side::Buy, BaseCurrency::new(12, 0)).unwrap(),
+        )
+        .unwrap()
+    else {
+        panic!("The taker can only afford the first fill");
+    };
+    assert_eq!(reason, RemainderCancelReason::NotEnoughAvailableBalance);
+    assert_eq!(cancelled_quantity, BaseCurrency::new(3, 0));
+    assert_eq!(
+        settlement.filled_order.state().filled_qty(),
+        BaseCurrency::new(9, 0)
+    );
+    assert_eq!(
+        settlement.filled_order.state().avg_fill_price(),
+        QuoteCurrency::new(101, 0)
+    );
+    assert_eq!(
+        engine.participant(taker).account().position().quantity(),
+        BaseCurrency::new(9, 0)
+    );
+
+    // Without any affordable fill the order is rejected.
+    assert_eq!(
+        engine.submit_market_order(
+            taker,
+            MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap()
+        ),
+        Err(SubmitMarketOrderError::NotEnoughAvailableBalance(
+            NotEnoughAvailableBalance
+        ))
+    );
+    assert!(engine.trades().is_empty());
+    assert_eq!(
+        engine
+            .order_book()
+            .quantity_at(Side::Sell, QuoteCurrency::new(102, 0)),
+        BaseCurrency::new(9, 0)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn matching_engine_liquidation_trades_against_the_book() {
+    let (mut engine, participants) = engine_with_participants(3);
+    let (victim, maker_a, maker_b) = (participants[0], participants[1], participants[2]);
+    engine
+        .submit_limit_order(
+            maker_a,
+            limit_order(Side::Buy, 100, BaseCurrency::new(5, 0)),
+        )
+        .unwrap();
+    assert!(matches!(
+        engine
+            .submit_market_order(
+                victim,
+                MarketOrder::new(Side::Sell, BaseCurrency::new(5, 0)).unwrap()
+            )
+            .unwrap(),
+        MatchedMarketOrder::Filled(_)
+    ));
+    assert!(engine.liquidations().is_empty());
+
+    // The new ask exceeds the liquidation price of the short position,
+    // which then buys the resting order quoting it.
+    engine
+        .submit_limit_order(
+            maker_b,
+            limit_order(Side::Sell, 160, BaseCurrency::new(5, 0)),
+        )
+        .unwrap();
+    assert_eq!(engine.liquidations(), &vec![victim]);
+    let trades: Vec<_> = engine
+        .trades()
+        .iter()
+        .map(|trade| (trade.price, trade.quantity, trade.side))
+        .collect();
+    assert_eq!(trades, vec![(
+        QuoteCurrency::new(160, 0),
+        BaseCurrency::new(5, 0),
+        Side::Buy
+    )]);
+    assert!(engine.order_book().is_empty());
+    assert_eq!(engine.last_trade_price(), Some(QuoteCurrency::new(160, 0)));
+
+    let positions: Vec<_> = participants
+        .iter()
+        .map(|participant| {
+            engine
+                .participant(*participant)
+                .account()
+                .position()
+                .quantity()
+        })
+        .collect();
+    assert_eq!(positions, vec![
+        BaseCurrency::zero(),
+        BaseCurrency::new(5, 0),
+        BaseCurrency::new(-5, 0)
+    ]);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn matching_engine_cancel_limit_order() {
+    let (mut engine, participants) = engine_with_participants(1);
+    let order = engine
+        .submit_limit_order(
+            participants[0],
+            limit_order(Side::Buy, 99, BaseCurrency::one()),
+        )
+        .unwrap();
+    engine
+        .cancel_limit_order(participants[0], CancelBy::OrderId(order.id()))
+        .unwrap();
+    assert!(engine.order_book().is_empty());
+    assert_eq!(engine.bid(), QuoteCurrency::new(99, 0));
+    assert!(
+        engine
+            .participant(participants[0])
+            .account()
+            .active_limit_orders()
+            .is_empty()
+    );
+
+    engine.update_timestamp(1_000.into());
+    assert_eq!(
+        engine
+            .participant(participants[0])
+            .market_state()
+            .current_ts_ns(),
+        1_000.into()
+    );
+}
@@ -1,11 +1,30 @@
+mod account_tracker;
 mod amend;
+mod balance_ledger;
 mod cancel_limit_order;
+mod cash_flows;
+mod contract_spec_schedule;
+mod fee_tiers;
+mod fill_model;
+mod lot_tracker;
+mod market_impact;
+mod markout;
+mod matching_engine;
+mod multi_account;
+mod notional_filter;
+mod ohlcv_candle;
+mod order_latency;
 mod partial_order_fill;
+mod pnl_attribution;
+mod position_limits;
+mod price_band;
 mod reduce_position_order_margin;
+mod strict_market_data;
 mod submit_limit_buy_order;
 mod submit_limit_sell_order;
 mod submit_market_buy_order;
 mod submit_market_sell_order;
+mod trade_journal;
 
 #[allow(unused, reason = "Used in benchmarks")]
 use criterion::*;
@@ -0,0 +1,239 @@
+use crate::{
+    DECIMALS,
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+type Multi = MultiAccountExchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId>;
+
+/// Two accounts with a resting sell order of the first one inside the spread.
+fn multi_with_resting_ask(
+    self_trade_prevention: SelfTradePrevention,
+    second_firm: FirmId,
+) -> (Multi, AccountId, AccountId) {
+    let mut multi = MultiAccountExchange::new(self_trade_prevention);
+    let maker = multi.add_account(mock_exchange_linear(), FirmId::from(0));
+    let taker = multi.add_account(mock_exchange_linear(), second_firm);
+    assert!(
+        multi
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(102, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    multi
+        .submit_limit_order(maker, buy_or_sell(Side::Sell, 101))
+        .unwrap();
+    (multi, maker, taker)
+}
+
+fn buy_or_sell(
+    side: Side,
+    price: i64,
+) -> LimitOrder<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId, NewOrder> {
+    LimitOrder::new(side, QuoteCurrency::new(price, 0), BaseCurrency::one()).unwrap()
+}
+
+fn num_active(multi: &Multi, account: AccountId) -> usize {
+    multi
+        .exchange(account)
+        .account()
+        .active_limit_orders()
+        .num_active()
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn multi_account_update_state() {
+    let (mut multi, maker, taker) =
+        multi_with_resting_ask(SelfTradePrevention::CancelNewest, FirmId::from(1));
+    assert_eq!(multi.exchanges().len(), 2);
+    assert_eq!(multi.firm(taker), FirmId::from(1));
+    multi
+        .submit_limit_order(taker, buy_or_sell(Side::Sell, 101))
+        .unwrap();
+
+    // Each account fills against the full market data.
+    assert!(
+        multi
+            .update_state(&Trade {
+                timestamp_exchange_ns: 1.into(),
+                price: QuoteCurrency::new(102, 0),
+                quantity: BaseCurrency::one(),
+                side: Side::Buy,
+            })
+            .unwrap()
+            .is_empty()
+    );
+    for account in [maker, taker] {
+        let exchange = multi.exchange(account);
+        assert_eq!(exchange.limit_order_events().len(), 1);
+        assert_eq!(
+            exchange.account().position().quantity(),
+            BaseCurrency::new(-1, 0)
+        );
+        assert_eq!(exchange.market_state().current_ts_ns(), 1.into());
+    }
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn multi_account_different_firms_trade_freely() {
+    let (mut multi, maker, taker) =
+        multi_with_resting_ask(SelfTradePrevention::CancelBoth, FirmId::from(1));
+    multi
+        .submit_limit_order(taker, buy_or_sell(Side::Buy, 101))
+        .unwrap();
+    assert!(multi.self_trade_cancels().is_empty());
+    assert_eq!(num_active(&multi, maker), 1);
+    assert_eq!(num_active(&multi, taker), 1);
+}
+
+#[test_case::test_matrix([
+    SelfTradePrevention::CancelNewest,
+    SelfTradePrevention::CancelOldest,
+    SelfTradePrevention::CancelBoth
+])]
+#[tracing_test::traced_test]
+fn multi_account_self_trade_prevention_limit_order(self_trade_prevention: SelfTradePrevention) {
+    let (mut multi, maker, taker) = multi_with_resting_ask(self_trade_prevention, FirmId::from(0));
+    // Not crossing the resting order of the same firm.
+    multi
+        .submit_limit_order(taker, buy_or_sell(Side::Buy, 100))
+        .unwrap();
+
+    let result = multi.submit_limit_order(taker, buy_or_sell(Side::Buy, 101));
+    let cancelled: Vec<_> = multi
+        .self_trade_cancels()
+        .iter()
+        .map(|(account, order)| (*account, order.limit_price()))
+        .collect();
+    match self_trade_prevention {
+        SelfTradePrevention::CancelNewest => {
+            assert_eq!(result, Err(SubmitLimitOrderError::SelfTradePrevented));
+            assert!(cancelled.is_empty());
+            assert_eq!(num_active(&multi, maker), 1);
+            assert_eq!(num_active(&multi, taker), 1);
+        }
+        SelfTradePrevention::CancelOldest => {
+            assert!(result.is_ok());
+            assert_eq!(cancelled, vec![(maker, QuoteCurrency::new(101, 0))]);
+            assert_eq!(num_active(&multi, maker), 0);
+            assert_eq!(num_active(&multi, taker), 2);
+        }
+        SelfTradePrevention::CancelBoth => {
+            assert_eq!(result, Err(SubmitLimitOrderError::SelfTradePrevented));
+            assert_eq!(cancelled, vec![(maker, QuoteCurrency::new(101, 0))]);
+            assert_eq!(num_active(&multi, maker), 0);
+            assert_eq!(num_active(&multi, taker), 1);
+        }
+    }
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn multi_account_self_trade_prevention_market_order() {
+    let (mut multi, maker, taker) =
+        multi_with_resting_ask(SelfTradePrevention::CancelNewest, FirmId::from(0));
+    // The market buy fills at the ask of 102, trading through the resting sell order at 101.
+    assert_eq!(
+        multi.submit_market_order(
+            taker,
+            MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap()
+        ),
+        Err(SubmitMarketOrderError::SelfTradePrevented)
+    );
+    // A market sell does not interact with it.
+    multi
+        .submit_market_order(
+            taker,
+            MarketOrder::new(Side::Sell, BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+
+    multi.set_self_trade_prevention(SelfTradePrevention::CancelOldest);
+    multi
+        .submit_market_order(
+            taker,
+            MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(multi.self_trade_cancels().len(), 1);
+    assert_eq!(num_active(&multi, maker), 0);
+    assert_eq!(
+        multi.exchange(taker).account().position().quantity(),
+        BaseCurrency::zero()
+    );
+}
+
+#[test_case::test_matrix([
+    SelfTradePrevention::CancelNewest,
+    SelfTradePrevention::CancelOldest
+])]
+#[tracing_test::traced_test]
+fn multi_account_self_trade_prevention_on_arrival(self_trade_prevention: SelfTradePrevention) {
+    let (mut multi, maker, taker) = multi_with_resting_ask(self_trade_prevention, FirmId::from(0));
+    let request_id = multi.send_limit_order(taker, buy_or_sell(Side::Buy, 101));
+    // The resting order is only checked once the sent order arrives at the venue.
+    assert_eq!(num_active(&multi, maker), 1);
+
+    assert!(
+        multi
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(102, 0),
+                timestamp_exchange_ns: 1.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    let events = multi.exchange(taker).order_action_events();
+    assert_eq!(events.len(), 1);
+    if self_trade_prevention == SelfTradePrevention::CancelNewest {
+        assert_eq!(events[0], OrderActionEvent::LimitOrderRejected {
+            request_id,
+            error: SubmitLimitOrderError::SelfTradePrevented
+        });
+        assert!(multi.self_trade_cancels().is_empty());
+        assert_eq!(num_active(&multi, maker), 1);
+        assert_eq!(num_active(&multi, taker), 0);
+    } else {
+        assert!(matches!(
+            events[0],
+            OrderActionEvent::LimitOrderAccepted { request_id: id, .. } if id == request_id
+        ));
+        assert_eq!(multi.self_trade_cancels().len(), 1);
+        assert_eq!(num_active(&multi, maker), 0);
+        assert_eq!(num_active(&multi, taker), 1);
+    }
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn multi_account_update_state_propagates_errors() {
+    let mut strict = mock_exchange_linear();
+    strict.set_strict_market_data(true);
+    let mut multi: Multi = MultiAccountExchange::new(SelfTradePrevention::CancelNewest);
+    let lenient = multi.add_account(mock_exchange_linear(), FirmId::from(0));
+    let strict = multi.add_account(strict, FirmId::from(0));
+    assert!(multi.update_state(&mock_bba(100, 1)).unwrap().is_empty());
+    // An update older than the previous one is rejected by the account in strict mode.
+    assert!(matches!(
+        multi.update_state(&mock_bba(100, 0)),
+        Err(UpdateStateError::MarketUpdate(
+            MarketUpdateError::OutOfOrder { .. }
+        ))
+    ));
+    assert_eq!(
+        multi.exchange(lenient).market_state().current_ts_ns(),
+        0.into()
+    );
+    assert_eq!(
+        multi.exchange(strict).market_state().current_ts_ns(),
+        1.into()
+    );
+}
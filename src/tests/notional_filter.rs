@@ -0,0 +1,106 @@
+use std::num::NonZeroU16;
+
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+};
+
+fn exchange_with_min_notional()
+-> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut contract_spec = ContractSpecification::new(
+        leverage!(1),
+        Decimal::try_from_scaled(5, 1).unwrap(),
+        PriceFilter::default(),
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).unwrap(),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .unwrap();
+    contract_spec
+        .set_notional_filter(NotionalFilter::new(Some(QuoteCurrency::new(5, 0)), None).unwrap());
+    let config = Config::new(
+        QuoteCurrency::new(1000, 0),
+        NonZeroU16::new(10).unwrap(),
+        contract_spec,
+        OrderRateLimits::default(),
+    )
+    .unwrap();
+    let mut exchange = Exchange::new(config);
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(99, 0),
+                ask: QuoteCurrency::new(100, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    exchange
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn notional_filter_market_order() {
+    let mut exchange = exchange_with_min_notional();
+    assert_eq!(
+        exchange.submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(4, 2)).unwrap()),
+        Err(SubmitMarketOrderError::ValidateOrderNotional(
+            ValidateOrderNotionalError::NotionalTooLow
+        ))
+    );
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(5, 2)).unwrap())
+        .unwrap();
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn notional_filter_limit_order_and_amend() {
+    let mut exchange = exchange_with_min_notional();
+    assert_eq!(
+        exchange.submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(98, 0),
+                BaseCurrency::new(5, 2)
+            )
+            .unwrap()
+        ),
+        Err(SubmitLimitOrderError::ValidateOrderNotional(
+            ValidateOrderNotionalError::NotionalTooLow
+        ))
+    );
+    let order = exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(98, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+
+    // Shrinking the order below the minimum notional is rejected and the order keeps resting.
+    let new_order = LimitOrder::new(
+        Side::Buy,
+        QuoteCurrency::new(98, 0),
+        BaseCurrency::new(5, 2),
+    )
+    .unwrap();
+    assert_eq!(
+        exchange.amend_limit_order(order.id(), new_order),
+        Err(AmendLimitOrderError::SubmitLimitOrder(
+            SubmitLimitOrderError::ValidateOrderNotional(
+                ValidateOrderNotionalError::NotionalTooLow
+            )
+        ))
+    );
+    assert_eq!(
+        exchange
+            .account()
+            .active_limit_orders()
+            .get_by_id(order.id(), Side::Buy),
+        Some(&order)
+    );
+}
@@ -0,0 +1,119 @@
+use crate::{
+    DECIMALS,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+fn exchange_with_bracket() -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut exchange = mock_exchange_linear();
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(96, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Sell, QuoteCurrency::new(104, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+    exchange
+}
+
+fn candle(
+    intrabar_path: IntrabarPath,
+    volume: BaseCurrency<i64, DECIMALS>,
+) -> OhlcvCandle<i64, DECIMALS, BaseCurrency<i64, DECIMALS>> {
+    OhlcvCandle::builder()
+        .bid(QuoteCurrency::new(100, 0))
+        .ask(QuoteCurrency::new(101, 0))
+        .open(QuoteCurrency::new(100, 0))
+        .high(QuoteCurrency::new(105, 0))
+        .low(QuoteCurrency::new(95, 0))
+        .close(QuoteCurrency::new(100, 0))
+        .volume(volume)
+        .intrabar_path(intrabar_path)
+        .timestamp_exchange_ns(1.into())
+        .build()
+}
+
+fn filled_sides(
+    events: &[LimitOrderEvent<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId>],
+) -> Vec<(Side, BaseCurrency<i64, DECIMALS>)> {
+    events
+        .iter()
+        .map(|event| match event {
+            LimitOrderEvent::Fill(LimitOrderFill::FullyFilled {
+                filled_quantity,
+                order_after_fill,
+                ..
+            }) => (order_after_fill.side(), *filled_quantity),
+            LimitOrderEvent::Fill(LimitOrderFill::PartiallyFilled {
+                filled_quantity,
+                order_after_fill,
+                ..
+            }) => (order_after_fill.side(), *filled_quantity),
+            event => panic!("Unexpected event {event:?}"),
+        })
+        .collect()
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn ohlcv_candle_open_high_low_close() {
+    let mut exchange = exchange_with_bracket();
+    let events = exchange
+        .update_state(&candle(
+            IntrabarPath::OpenHighLowClose,
+            BaseCurrency::new(10, 0),
+        ))
+        .unwrap();
+    assert_eq!(filled_sides(events), vec![
+        (Side::Sell, BaseCurrency::one()),
+        (Side::Buy, BaseCurrency::one())
+    ]);
+    assert_eq!(
+        exchange.market_state().last_trade_price(),
+        QuoteCurrency::new(100, 0)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn ohlcv_candle_open_low_high_close() {
+    let mut exchange = exchange_with_bracket();
+    let events = exchange
+        .update_state(&candle(
+            IntrabarPath::OpenLowHighClose,
+            BaseCurrency::new(10, 0),
+        ))
+        .unwrap();
+    assert_eq!(filled_sides(events), vec![
+        (Side::Buy, BaseCurrency::one()),
+        (Side::Sell, BaseCurrency::one())
+    ]);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn ohlcv_candle_fills_capped_by_volume() {
+    let mut exchange = exchange_with_bracket();
+    let events = exchange
+        .update_state(&candle(
+            IntrabarPath::OpenLowHighClose,
+            BaseCurrency::new(4, 1),
+        ))
+        .unwrap();
+    // The bid is reached first and uses up the whole volume, as its split is unknown.
+    assert_eq!(filled_sides(events), vec![(Side::Buy, BaseCurrency::new(4, 1))]);
+    assert_eq!(exchange.account().active_limit_orders().num_active(), 2);
+}
@@ -0,0 +1,226 @@
+use crate::{
+    DECIMALS,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+fn exchange_with_latency(
+    latency_ns: i64,
+) -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_latency_model(
+        LatencyModel::new(LatencyDistribution::Fixed(latency_ns.into()), 0).unwrap(),
+    );
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    exchange
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn order_latency_limit_order_acknowledged_on_arrival() {
+    let mut exchange = exchange_with_latency(10);
+    let request_id = exchange.send_limit_order(
+        LimitOrder::new(Side::Buy, QuoteCurrency::new(100, 0), BaseCurrency::one()).unwrap(),
+    );
+    assert_eq!(exchange.num_in_flight_order_actions(), 1);
+    assert!(exchange.account().active_limit_orders().is_empty());
+
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 9.into(),
+        })
+        .unwrap();
+    assert!(exchange.order_action_events().is_empty());
+    assert!(exchange.account().active_limit_orders().is_empty());
+
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 10.into(),
+        })
+        .unwrap();
+    assert_eq!(exchange.num_in_flight_order_actions(), 0);
+    assert_eq!(exchange.order_action_events().len(), 1);
+    match &exchange.order_action_events()[0] {
+        OrderActionEvent::LimitOrderAccepted {
+            request_id: id,
+            order,
+        } => {
+            assert_eq!(*id, request_id);
+            assert_eq!(order.state().meta().ts_exchange_received(), 10.into());
+        }
+        event => panic!("Unexpected event {event:?}"),
+    }
+    assert_eq!(exchange.account().active_limit_orders().num_active(), 1);
+
+    // Events are only reported by the update during which the action arrived.
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 11.into(),
+        })
+        .unwrap();
+    assert!(exchange.order_action_events().is_empty());
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn order_latency_too_late_to_cancel() {
+    let mut exchange = exchange_with_latency(10);
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(100, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+    let request_id = exchange.send_cancel_limit_order(CancelBy::OrderId(OrderId::from(0)));
+
+    // The order fills while the cancel request is in flight.
+    let events = exchange
+        .update_state(&Trade {
+            timestamp_exchange_ns: 5.into(),
+            price: QuoteCurrency::new(99, 0),
+            quantity: BaseCurrency::new(2, 0),
+            side: Side::Sell,
+        })
+        .unwrap();
+    assert!(matches!(
+        events[0],
+        LimitOrderEvent::Fill(LimitOrderFill::FullyFilled { .. })
+    ));
+    assert!(exchange.order_action_events().is_empty());
+
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(99, 0),
+            ask: QuoteCurrency::new(100, 0),
+            timestamp_exchange_ns: 10.into(),
+        })
+        .unwrap();
+    assert_eq!(exchange.order_action_events(), &vec![
+        OrderActionEvent::TooLateToCancel {
+            request_id,
+            cancel_by: CancelBy::OrderId(OrderId::from(0)),
+        }
+    ]);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn order_latency_cancel_unknown_order_rejected() {
+    let mut exchange = exchange_with_latency(10);
+    let request_id = exchange.send_cancel_limit_order(CancelBy::OrderId(OrderId::from(5)));
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 10.into(),
+        })
+        .unwrap();
+    assert_eq!(exchange.order_action_events(), &vec![
+        OrderActionEvent::CancelRejected {
+            request_id,
+            error: CancelLimitOrderError::OrderIdNotFound(OrderIdNotFound::OrderId(OrderId::from(
+                5
+            ))),
+        }
+    ]);
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn order_latency_market_order_fills_at_arrival_price() {
+    let mut exchange = exchange_with_latency(10);
+    let request_id =
+        exchange.send_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(1, 0)).unwrap());
+    // The ask moves before the order arrives at the venue.
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(101, 0),
+            ask: QuoteCurrency::new(102, 0),
+            timestamp_exchange_ns: 5.into(),
+        })
+        .unwrap();
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(103, 0),
+            ask: QuoteCurrency::new(104, 0),
+            timestamp_exchange_ns: 20.into(),
+        })
+        .unwrap();
+    match &exchange.order_action_events()[0] {
+        OrderActionEvent::MarketOrderFilled {
+            request_id: id,
+            settlement,
+        } => {
+            assert_eq!(*id, request_id);
+            assert_eq!(
+                settlement.filled_order.state().avg_fill_price(),
+                QuoteCurrency::new(102, 0)
+            );
+        }
+        event => panic!("Unexpected event {event:?}"),
+    }
+    assert_eq!(
+        exchange.account().position().quantity(),
+        BaseCurrency::new(1, 0)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn order_latency_response_arrives_after_response_latency() {
+    let mut exchange = exchange_with_latency(10);
+    exchange.set_response_latency_model(
+        LatencyModel::new(LatencyDistribution::Fixed(5.into()), 0).unwrap(),
+    );
+    let request_id = exchange.send_limit_order(
+        LimitOrder::new(Side::Buy, QuoteCurrency::new(100, 0), BaseCurrency::one()).unwrap(),
+    );
+
+    // The venue accepts the order on arrival, but the acknowledgement is still on its way back.
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 10.into(),
+        })
+        .unwrap();
+    assert_eq!(exchange.num_in_flight_order_actions(), 0);
+    assert_eq!(exchange.num_in_flight_order_responses(), 1);
+    assert_eq!(exchange.account().active_limit_orders().num_active(), 1);
+    assert!(exchange.order_action_events().is_empty());
+
+    exchange
+        .update_state(&Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 15.into(),
+        })
+        .unwrap();
+    assert_eq!(exchange.num_in_flight_order_responses(), 0);
+    match &exchange.order_action_events()[..] {
+        [
+            OrderActionEvent::LimitOrderAccepted {
+                request_id: id,
+                order,
+            },
+        ] => {
+            assert_eq!(*id, request_id);
+            assert_eq!(order.state().meta().ts_exchange_received(), 10.into());
+        }
+        events => panic!("Unexpected events {events:?}"),
+    }
+}
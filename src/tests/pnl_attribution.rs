@@ -0,0 +1,100 @@
+use crate::{
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn pnl_attribution_two_sub_strategies() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_pnl_attribution(PnlAttribution::new());
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+
+    // Sub-strategy 1 buys 2 at the ask, sub-strategy 2 quotes 1 at 105.
+    let mut order = MarketOrder::new(Side::Buy, BaseCurrency::new(2, 0)).unwrap();
+    order.set_strategy_tag(1.into());
+    exchange.submit_market_order(order).unwrap();
+    let mut order = LimitOrder::new(
+        Side::Sell,
+        QuoteCurrency::new(105, 0),
+        BaseCurrency::new(1, 0),
+    )
+    .unwrap();
+    order.set_strategy_tag(2.into());
+    exchange.submit_limit_order(order).unwrap();
+    let events = exchange
+        .update_state(&Trade {
+            price: QuoteCurrency::new(106, 0),
+            quantity: BaseCurrency::new(1, 0),
+            side: Side::Buy,
+            timestamp_exchange_ns: 1.into(),
+        })
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        exchange.account().position().quantity(),
+        BaseCurrency::new(1, 0)
+    );
+
+    let attribution = exchange.pnl_attribution().unwrap();
+    assert_eq!(
+        attribution
+            .tags()
+            .iter()
+            .map(|(tag, _)| *tag)
+            .collect::<Vec<_>>(),
+        vec![StrategyTag::from(1), StrategyTag::from(2)]
+    );
+    // The quote of sub-strategy 2 reduced the long of the account, realizing 4.
+    assert_eq!(attribution.account_realized_pnl(), QuoteCurrency::new(4, 0));
+    let tag_1 = attribution.tag(1.into()).unwrap();
+    assert_eq!(tag_1.quantity(), BaseCurrency::new(2, 0));
+    assert!(tag_1.realized_pnl().is_zero());
+    let tag_2 = attribution.tag(2.into()).unwrap();
+    assert_eq!(tag_2.quantity(), BaseCurrency::new(-1, 0));
+    assert_eq!(tag_2.realized_pnl(), QuoteCurrency::new(4, 0));
+    assert_eq!(tag_2.num_fills(), 1);
+
+    assert_eq!(
+        tag_1.quantity() + tag_2.quantity(),
+        exchange.account().position().quantity()
+    );
+    assert_eq!(
+        attribution.account_fees_paid(),
+        exchange.account().balances().total_fees_paid()
+    );
+    assert_eq!(
+        tag_1.fees_paid() + tag_2.fees_paid(),
+        attribution.account_fees_paid()
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn pnl_attribution_opens_existing_position() {
+    let mut exchange = mock_exchange_linear();
+    assert!(exchange.update_state(&mock_bba(100, 0)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(2, 0)).unwrap())
+        .unwrap();
+    exchange.set_pnl_attribution(PnlAttribution::new());
+
+    // Sub-strategy 1 sells 1 below the entry price of the untagged long position.
+    let mut order = MarketOrder::new(Side::Sell, BaseCurrency::new(1, 0)).unwrap();
+    order.set_strategy_tag(1.into());
+    exchange.submit_market_order(order).unwrap();
+
+    let attribution = exchange.pnl_attribution().unwrap();
+    assert_eq!(
+        attribution.tag(StrategyTag::default()).unwrap().quantity(),
+        BaseCurrency::new(2, 0)
+    );
+    let tag_1 = attribution.tag(1.into()).unwrap();
+    assert_eq!(tag_1.quantity(), BaseCurrency::new(-1, 0));
+    assert_eq!(tag_1.realized_pnl(), QuoteCurrency::new(-1, 0));
+    assert_eq!(
+        attribution.account_realized_pnl(),
+        QuoteCurrency::new(-1, 0)
+    );
+}
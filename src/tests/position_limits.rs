@@ -0,0 +1,190 @@
+use std::num::NonZeroU16;
+
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+};
+
+fn exchange_with_position_limits(
+    max_position_quantity: Option<BaseCurrency<i64, DECIMALS>>,
+    max_position_notional: Option<QuoteCurrency<i64, DECIMALS>>,
+) -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut contract_spec = ContractSpecification::new(
+        leverage!(1),
+        Decimal::try_from_scaled(5, 1).unwrap(),
+        PriceFilter::default(),
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).unwrap(),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .unwrap();
+    contract_spec
+        .set_position_limits(max_position_quantity, max_position_notional)
+        .unwrap();
+    let config = Config::new(
+        QuoteCurrency::new(1000, 0),
+        NonZeroU16::new(10).unwrap(),
+        contract_spec,
+        OrderRateLimits::default(),
+    )
+    .unwrap();
+    let mut exchange = Exchange::new(config);
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(99, 0),
+                ask: QuoteCurrency::new(100, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    exchange
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn position_limit_quantity() {
+    let mut exchange = exchange_with_position_limits(Some(BaseCurrency::new(2, 0)), None);
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap())
+        .unwrap();
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(98, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+
+    // The resting buy order counts as if it was filled.
+    assert_eq!(
+        exchange.submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(97, 0),
+                BaseCurrency::new(5, 1)
+            )
+            .unwrap()
+        ),
+        Err(SubmitLimitOrderError::PositionLimitExceeded(
+            PositionLimitExceeded::Quantity
+        ))
+    );
+    assert_eq!(
+        exchange.submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(1, 2)).unwrap()),
+        Err(SubmitMarketOrderError::PositionLimitExceeded(
+            PositionLimitExceeded::Quantity
+        ))
+    );
+
+    // The long position nets against sell orders.
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Sell,
+                QuoteCurrency::new(101, 0),
+                BaseCurrency::new(3, 0),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        exchange.submit_limit_order(
+            LimitOrder::new(
+                Side::Sell,
+                QuoteCurrency::new(102, 0),
+                BaseCurrency::new(1, 2)
+            )
+            .unwrap()
+        ),
+        Err(SubmitLimitOrderError::PositionLimitExceeded(
+            PositionLimitExceeded::Quantity
+        ))
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn position_limit_amend_keeps_existing_order() {
+    let mut exchange = exchange_with_position_limits(Some(BaseCurrency::new(2, 0)), None);
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap())
+        .unwrap();
+    let order = exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(98, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        exchange.amend_limit_order(
+            order.id(),
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(98, 0),
+                BaseCurrency::new(2, 0)
+            )
+            .unwrap()
+        ),
+        Err(AmendLimitOrderError::SubmitLimitOrder(
+            SubmitLimitOrderError::PositionLimitExceeded(PositionLimitExceeded::Quantity)
+        ))
+    );
+    assert_eq!(
+        exchange
+            .account()
+            .active_limit_orders()
+            .get_by_id(order.id(), Side::Buy),
+        Some(&order)
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn position_limit_notional() {
+    let mut exchange = exchange_with_position_limits(None, Some(QuoteCurrency::new(150, 0)));
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::one()).unwrap())
+        .unwrap();
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(90, 0),
+                BaseCurrency::new(6, 1),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        exchange.submit_limit_order(
+            LimitOrder::new(
+                Side::Buy,
+                QuoteCurrency::new(98, 0),
+                BaseCurrency::new(1, 1)
+            )
+            .unwrap()
+        ),
+        Err(SubmitLimitOrderError::PositionLimitExceeded(
+            PositionLimitExceeded::Notional
+        ))
+    );
+}
+
+#[test]
+fn position_limits_must_be_positive() {
+    let mut contract_spec =
+        ContractSpecification::<i64, DECIMALS, BaseCurrency<i64, DECIMALS>>::default();
+    assert_eq!(
+        contract_spec.set_position_limits(Some(BaseCurrency::zero()), None),
+        Err(ConfigError::InvalidPositionLimits)
+    );
+    assert_eq!(
+        contract_spec.set_position_limits(None, Some(QuoteCurrency::new(-1, 0))),
+        Err(ConfigError::InvalidPositionLimits)
+    );
+    assert_eq!(contract_spec.max_position_quantity(), None);
+}
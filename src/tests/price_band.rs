@@ -0,0 +1,92 @@
+use std::num::NonZeroU16;
+
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+};
+
+fn exchange_with_price_reference(
+    reference_price: PriceReference,
+) -> Exchange<i64, DECIMALS, BaseCurrency<i64, DECIMALS>, NoUserOrderId> {
+    let mut price_filter = PriceFilter::new(
+        None,
+        None,
+        QuoteCurrency::new(1, 1),
+        Decimal::try_from_scaled(11, 1).unwrap(),
+        Decimal::try_from_scaled(9, 1).unwrap(),
+    )
+    .unwrap();
+    price_filter.set_reference_price(reference_price);
+    let contract_spec = ContractSpecification::new(
+        leverage!(1),
+        Decimal::try_from_scaled(5, 1).unwrap(),
+        price_filter,
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).unwrap(),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .unwrap();
+    let config = Config::new(
+        QuoteCurrency::new(1000, 0),
+        NonZeroU16::new(10).unwrap(),
+        contract_spec,
+        OrderRateLimits::default(),
+    )
+    .unwrap();
+    let mut exchange = Exchange::new(config);
+    assert!(
+        exchange
+            .update_state(&Bba {
+                bid: QuoteCurrency::new(100, 0),
+                ask: QuoteCurrency::new(101, 0),
+                timestamp_exchange_ns: 0.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        exchange
+            .update_state(&MarkPrice {
+                price: QuoteCurrency::new(90, 0),
+                timestamp_exchange_ns: 1.into(),
+            })
+            .unwrap()
+            .is_empty()
+    );
+    exchange
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn price_band_anchored_on_mid_price() {
+    let mut exchange = exchange_with_price_reference(PriceReference::MidPrice);
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(95, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn price_band_anchored_on_mark_price() {
+    let mut exchange = exchange_with_price_reference(PriceReference::MarkPrice);
+    // Within the band around the mid price, but above the band around the mark price of 90.
+    assert_eq!(
+        exchange.submit_limit_order(
+            LimitOrder::new(Side::Sell, QuoteCurrency::new(102, 0), BaseCurrency::one()).unwrap(),
+        ),
+        Err(SubmitLimitOrderError::PriceFilter(
+            PriceFilterError::LimitPriceAboveMultiple
+        ))
+    );
+    exchange
+        .submit_limit_order(
+            LimitOrder::new(Side::Buy, QuoteCurrency::new(85, 0), BaseCurrency::one()).unwrap(),
+        )
+        .unwrap();
+}
@@ -117,7 +117,10 @@ fn liquidation_force_cancels_resting_orders() {
         ask: QuoteCurrency::new(51, 0),
         timestamp_exchange_ns: 1.into(),
     });
-    assert!(matches!(result, Err(RiskError::Liquidate)));
+    assert!(matches!(
+        result,
+        Err(UpdateStateError::Risk(RiskError::Liquidate))
+    ));
 
     // The forced cancellation is observable through the event stream.
     assert_eq!(exchange.limit_order_events().len(), 1);
@@ -316,7 +319,10 @@ fn gapped_liquidation_records_bad_debt_without_panicking() {
         ask: QuoteCurrency::new(71, 0),
         timestamp_exchange_ns: 1.into(),
     });
-    assert!(matches!(result, Err(RiskError::Liquidate)));
+    assert!(matches!(
+        result,
+        Err(UpdateStateError::Risk(RiskError::Liquidate))
+    ));
 
     assert!(exchange.account().position().quantity().is_zero());
     assert!(exchange.account().balances().equity().is_zero());
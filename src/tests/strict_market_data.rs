@@ -0,0 +1,143 @@
+use const_decimal::Decimal;
+
+use crate::{
+    DECIMALS,
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+    test_fee_maker,
+    test_fee_taker,
+};
+
+#[test]
+fn strict_market_data_rejects_invalid_updates() {
+    let mut exchange = mock_exchange_linear();
+    assert!(!exchange.strict_market_data());
+    exchange.set_strict_market_data(true);
+    assert!(exchange.update_state(&mock_bba(100, 1)).unwrap().is_empty());
+
+    assert_eq!(
+        exchange.update_state(&Bba {
+            bid: QuoteCurrency::new(101, 0),
+            ask: QuoteCurrency::new(100, 0),
+            timestamp_exchange_ns: 2.into(),
+        }),
+        Err(UpdateStateError::MarketUpdate(
+            MarketUpdateError::InvalidPrice(PriceFilterError::InvalidBidAskSpread)
+        ))
+    );
+    assert_eq!(
+        exchange.update_state(&Bba {
+            bid: QuoteCurrency::new(0, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 3.into(),
+        }),
+        Err(UpdateStateError::MarketUpdate(
+            MarketUpdateError::InvalidPrice(PriceFilterError::PriceTooLow)
+        ))
+    );
+    assert_eq!(
+        exchange.update_state(&Bba {
+            bid: QuoteCurrency::new(1005, 1),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 4.into(),
+        }),
+        Err(UpdateStateError::MarketUpdate(
+            MarketUpdateError::InvalidPrice(PriceFilterError::PriceStepSize {
+                price: QuoteCurrency::<i64, DECIMALS>::new(1005, 1).to_string(),
+                step_size: QuoteCurrency::<i64, DECIMALS>::new(1, 0).to_string(),
+            })
+        ))
+    );
+    // Rejected updates leave the market state untouched.
+    assert_eq!(exchange.market_state().bid(), QuoteCurrency::new(100, 0));
+    assert_eq!(exchange.market_state().current_ts_ns(), 1.into());
+
+    assert!(exchange.update_state(&mock_bba(99, 5)).is_ok());
+    assert_eq!(
+        exchange.update_state(&mock_bba(99, 4)),
+        Err(UpdateStateError::MarketUpdate(
+            MarketUpdateError::OutOfOrder {
+                current_ts_ns: 5.into(),
+                update_ts_ns: 4.into(),
+            }
+        ))
+    );
+    // Updates with the same timestamp are fine.
+    assert!(exchange.update_state(&mock_bba(98, 5)).is_ok());
+}
+
+#[test]
+fn strict_market_data_at_tick_size_change() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_strict_market_data(true);
+    let new_contract_spec = ContractSpecification::new(
+        leverage!(1),
+        Decimal::try_from_scaled(5, 1).unwrap(),
+        PriceFilter::new(
+            None,
+            None,
+            QuoteCurrency::new(5, 1),
+            Decimal::TWO,
+            Decimal::zero(),
+        )
+        .unwrap(),
+        QuantityFilter::new(None, None, BaseCurrency::new(1, 2)).unwrap(),
+        test_fee_maker(),
+        test_fee_taker(),
+    )
+    .unwrap();
+    exchange.set_contract_spec_schedule(
+        ContractSpecificationSchedule::new(vec![(10.into(), new_contract_spec)]).unwrap(),
+    );
+    assert!(exchange.update_state(&mock_bba(100, 1)).unwrap().is_empty());
+
+    let on_new_tick = Bba {
+        bid: QuoteCurrency::new(1005, 1),
+        ask: QuoteCurrency::new(101, 0),
+        timestamp_exchange_ns: 10.into(),
+    };
+    assert!(exchange.update_state(&on_new_tick).unwrap().is_empty());
+    assert_eq!(exchange.market_state().bid(), QuoteCurrency::new(1005, 1));
+}
+
+#[test]
+fn validated_market_data_into_strict_exchange() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_strict_market_data(true);
+    let bbas = [
+        mock_bba(100, 1),
+        Bba {
+            bid: QuoteCurrency::new(101, 0),
+            ask: QuoteCurrency::new(100, 0),
+            timestamp_exchange_ns: 2.into(),
+        },
+        Bba {
+            bid: QuoteCurrency::new(100, 0),
+            ask: QuoteCurrency::new(102, 0),
+            timestamp_exchange_ns: 4.into(),
+        },
+        mock_bba(99, 3),
+        Bba {
+            bid: QuoteCurrency::new(0, 0),
+            ask: QuoteCurrency::new(101, 0),
+            timestamp_exchange_ns: 5.into(),
+        },
+    ];
+    let mut validated = MarketDataValidation::builder()
+        .price_filter(exchange.config().contract_spec().price_filter().clone())
+        .out_of_order(IssueAction::Repair)
+        .reorder_window_ns(5.into())
+        .build()
+        .validate::<_, _, BaseCurrency<i64, DECIMALS>>(bbas);
+    for bba in validated.by_ref() {
+        exchange.update_state(&bba).unwrap();
+    }
+    assert_eq!(exchange.market_state().current_ts_ns(), 4.into());
+    assert_eq!(exchange.market_state().ask(), QuoteCurrency::new(102, 0));
+    let report = validated.report();
+    assert_eq!(report.emitted(), 3);
+    assert_eq!(report.crossed_book().dropped(), 1);
+    assert_eq!(report.invalid_price().dropped(), 1);
+    assert_eq!(report.out_of_order().repaired(), 1);
+}
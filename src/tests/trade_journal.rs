@@ -0,0 +1,57 @@
+use crate::{
+    mock_bba,
+    mock_exchange_linear,
+    prelude::*,
+};
+
+#[test]
+#[tracing_test::traced_test]
+fn trade_journal_reversal_round_trips() {
+    let mut exchange = mock_exchange_linear();
+    exchange.set_trade_journal(TradeJournal::default());
+    assert!(exchange.update_state(&mock_bba(99, 0)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(2, 0)).unwrap())
+        .unwrap();
+    assert!(exchange.update_state(&mock_bba(95, 1)).unwrap().is_empty());
+    assert!(exchange.update_state(&mock_bba(105, 2)).unwrap().is_empty());
+    // Reverse into a short position.
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Sell, BaseCurrency::new(3, 0)).unwrap())
+        .unwrap();
+    assert!(exchange.update_state(&mock_bba(103, 3)).unwrap().is_empty());
+    exchange
+        .submit_market_order(MarketOrder::new(Side::Buy, BaseCurrency::new(1, 0)).unwrap())
+        .unwrap();
+
+    let journal = exchange.trade_journal().unwrap();
+    assert!(journal.open_round_trip().is_none());
+    let [long, short] = journal.round_trips() else {
+        panic!("Expected two round trips");
+    };
+
+    assert_eq!(long.side, PositionSide::Long);
+    assert_eq!(long.entry_ts_ns, 0.into());
+    assert_eq!(long.exit_ts_ns, 2.into());
+    assert_eq!(long.quantity, BaseCurrency::new(2, 0));
+    assert_eq!(long.entry_vwap, QuoteCurrency::new(100, 0));
+    assert_eq!(long.exit_vwap, QuoteCurrency::new(105, 0));
+    assert_eq!(long.realized_pnl, QuoteCurrency::new(10, 0));
+    assert_eq!(long.max_adverse_excursion, QuoteCurrency::new(10, 0));
+    assert_eq!(long.max_favourable_excursion, QuoteCurrency::new(10, 0));
+
+    assert_eq!(short.side, PositionSide::Short);
+    assert_eq!(short.entry_ts_ns, 2.into());
+    assert_eq!(short.exit_ts_ns, 3.into());
+    assert_eq!(short.quantity, BaseCurrency::new(1, 0));
+    assert_eq!(short.entry_vwap, QuoteCurrency::new(105, 0));
+    assert_eq!(short.exit_vwap, QuoteCurrency::new(104, 0));
+    assert_eq!(short.realized_pnl, QuoteCurrency::new(1, 0));
+    assert_eq!(short.max_favourable_excursion, QuoteCurrency::new(1, 0));
+
+    // The round trips account for every fee paid.
+    assert_eq!(
+        long.fees + short.fees,
+        exchange.account().balances().total_fees_paid()
+    );
+}
@@ -0,0 +1,212 @@
+//! The optional trackers which observe the account of an `Exchange`.
+
+use num_traits::Zero;
+
+use crate::{
+    account::{
+        Account,
+        Balances,
+        Position,
+    },
+    account_tracker::AccountTracker,
+    balance_ledger::{
+        BalanceLedger,
+        FillKind,
+    },
+    lot_tracker::LotTracker,
+    market_state::MarketState,
+    markout::MarkoutAnalytics,
+    pnl_attribution::PnlAttribution,
+    prelude::{
+        Currency,
+        Mon,
+        QuoteCurrency,
+    },
+    trade_journal::TradeJournal,
+    types::{
+        MarginCurrency,
+        OrderId,
+        Side,
+        StrategyTag,
+        TimestampNs,
+        UserOrderId,
+    },
+};
+
+/// A fill of the account as observed by the `Trackers`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TrackedFill<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    pub(crate) kind: FillKind,
+    /// The id and `StrategyTag` of the filled order, `None` for a liquidation.
+    pub(crate) order: Option<(OrderId, StrategyTag)>,
+    pub(crate) side: Side,
+    pub(crate) quantity: BaseOrQuote,
+    pub(crate) price: QuoteCurrency<I, D>,
+    pub(crate) fee: BaseOrQuote::PairedCurrency,
+    /// How long the filled limit order rested in the book, `None` unless it is a maker fill.
+    pub(crate) order_age: Option<TimestampNs>,
+    pub(crate) ts_ns: TimestampNs,
+}
+
+/// The trackers observing the account of an `Exchange`, each `None` if it is not used.
+/// The `Exchange` feeds them its market updates, fills and cash flows.
+#[derive(Debug, Clone)]
+pub(crate) struct Trackers<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// Records the equity curve and fills of the account.
+    pub(crate) account_tracker: Option<AccountTracker<I, D, BaseOrQuote>>,
+
+    /// Groups the fills of the account into round trips.
+    pub(crate) trade_journal: Option<TradeJournal<I, D, BaseOrQuote>>,
+
+    /// Samples the markouts after our limit order fills.
+    pub(crate) markout_analytics: Option<MarkoutAnalytics<I, D, BaseOrQuote>>,
+
+    /// Records every change of the balances.
+    pub(crate) balance_ledger: Option<BalanceLedger<I, D, BaseOrQuote::PairedCurrency>>,
+
+    /// Attributes the fills to the sub-strategies placing the orders.
+    pub(crate) pnl_attribution: Option<PnlAttribution<I, D, BaseOrQuote>>,
+
+    /// Realizes the profit and loss of the fills lot by lot.
+    pub(crate) lot_tracker: Option<LotTracker<I, D, BaseOrQuote>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> Default for Trackers<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    fn default() -> Self {
+        Self {
+            account_tracker: None,
+            trade_journal: None,
+            markout_analytics: None,
+            balance_ledger: None,
+            pnl_attribution: None,
+            lot_tracker: None,
+        }
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> Trackers<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// Sample the equity of the `account`, including the unrealized profit and loss,
+    /// observe the excursion of the open round trip and sample the markouts.
+    /// Does nothing until the `market_state` has a valid quote.
+    pub(crate) fn market_update<UserOrderIdT>(
+        &mut self,
+        account: &Account<I, D, BaseOrQuote, UserOrderIdT>,
+        market_state: &MarketState<I, D>,
+    ) where
+        UserOrderIdT: UserOrderId,
+    {
+        let bid = market_state.bid();
+        let ask = market_state.ask();
+        if bid <= QuoteCurrency::zero() || ask <= QuoteCurrency::zero() {
+            return;
+        }
+        if let Some(tracker) = self.account_tracker.as_mut() {
+            let equity = account.balances().equity() + account.position().unrealized_pnl(bid, ask);
+            tracker.sample_equity(
+                market_state.current_ts_ns(),
+                equity,
+                market_state.mid_price(),
+            );
+        }
+        if let Some(journal) = self.trade_journal.as_mut() {
+            journal.update_excursion(account.position(), bid, ask);
+        }
+        if let Some(markouts) = self.markout_analytics.as_mut() {
+            markouts.update(market_state.current_ts_ns(), market_state.mid_price());
+        }
+    }
+
+    /// Record a `fill` before it is settled into the `position`.
+    #[inline]
+    pub(crate) fn fill(
+        &mut self,
+        fill: TrackedFill<I, D, BaseOrQuote>,
+        position: &Position<I, D, BaseOrQuote>,
+    ) {
+        let TrackedFill {
+            side,
+            quantity,
+            price,
+            fee,
+            order_age,
+            ts_ns,
+            ..
+        } = fill;
+        if let Some(lots) = self.lot_tracker.as_mut() {
+            lots.record_fill(side, quantity, price, ts_ns);
+        }
+        if let Some(tracker) = self.account_tracker.as_mut() {
+            tracker.record_fill(position, side, quantity, price, fee);
+        }
+        if let Some(journal) = self.trade_journal.as_mut() {
+            journal.record_fill(position, side, quantity, price, fee, ts_ns);
+        }
+        if let Some(markouts) = self.markout_analytics.as_mut()
+            && let Some(order_age) = order_age
+        {
+            markouts.record_fill(side, quantity, price, order_age, ts_ns);
+        }
+    }
+
+    /// Record the settlement of a `fill` which realized `realized_pnl` and left the `balances`.
+    #[inline]
+    pub(crate) fn settlement(
+        &mut self,
+        fill: TrackedFill<I, D, BaseOrQuote>,
+        realized_pnl: BaseOrQuote::PairedCurrency,
+        balances: &Balances<I, D, BaseOrQuote::PairedCurrency>,
+    ) {
+        let order_id = fill.order.map(|(order_id, _)| order_id);
+        if let Some(ledger) = self.balance_ledger.as_mut() {
+            ledger.record_fill(fill.kind, realized_pnl, fill.fee, order_id, fill.ts_ns);
+            ledger.record_bad_debt(balances.bad_debt(), order_id, fill.ts_ns);
+        }
+        if let Some(attribution) = self.pnl_attribution.as_mut() {
+            attribution.record_fill(
+                fill.order.map(|(_, tag)| tag),
+                fill.side,
+                fill.quantity,
+                realized_pnl,
+                fill.fee,
+            );
+        }
+    }
+
+    /// Record a deposit (positive `amount`) or withdrawal (negative `amount`).
+    /// Before the first valid quote the `AccountTracker` sampled no equity yet,
+    /// so the cash flow becomes part of its starting equity.
+    pub(crate) fn cash_flow(
+        &mut self,
+        amount: BaseOrQuote::PairedCurrency,
+        ts_ns: TimestampNs,
+        mid_price: QuoteCurrency<I, D>,
+    ) {
+        if let Some(ledger) = self.balance_ledger.as_mut() {
+            ledger.record_cash_flow(amount, ts_ns);
+        }
+        if mid_price > QuoteCurrency::zero()
+            && let Some(tracker) = self.account_tracker.as_mut()
+        {
+            tracker.record_cash_flow(amount, mid_price);
+        }
+    }
+}
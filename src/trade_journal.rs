@@ -0,0 +1,490 @@
+//! Groups the fills of an account into round trips of the position.
+
+use std::io::Write;
+
+use num_traits::Zero;
+
+use crate::{
+    EXPECT_CAPACITY,
+    account::{
+        Position,
+        PositionSide,
+    },
+    types::{
+        Currency,
+        MarginCurrency,
+        Mon,
+        QuoteCurrency,
+        Side,
+        TimestampNs,
+    },
+    utils::min,
+};
+
+/// The columns of the csv export of a `TradeJournal`.
+const CSV_HEADER: [&str; 11] = [
+    "side",
+    "entry_ts_ns",
+    "exit_ts_ns",
+    "holding_time_ns",
+    "quantity",
+    "entry_vwap",
+    "exit_vwap",
+    "max_adverse_excursion",
+    "max_favourable_excursion",
+    "fees",
+    "realized_pnl",
+];
+
+/// A round trip of the position from flat to flat.
+/// A fill reversing the position closes the round trip and opens the next one with the remaining quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTrip<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// Either `PositionSide::Long` or `PositionSide::Short`.
+    pub side: PositionSide,
+    /// The timestamp of the fill opening the position.
+    pub entry_ts_ns: TimestampNs,
+    /// The timestamp of the fill closing the position.
+    pub exit_ts_ns: TimestampNs,
+    /// The total quantity of the fills opening or increasing the position.
+    pub quantity: BaseOrQuote,
+    /// The volume weighted average price of the fills opening or increasing the position.
+    pub entry_vwap: QuoteCurrency<I, D>,
+    /// The volume weighted average price of the fills reducing or closing the position.
+    pub exit_vwap: QuoteCurrency<I, D>,
+    /// The largest loss of the round trip at any observed market update,
+    /// including the realized and unrealized profit and loss but excluding fees.
+    pub max_adverse_excursion: BaseOrQuote::PairedCurrency,
+    /// The largest profit of the round trip at any observed market update,
+    /// including the realized and unrealized profit and loss but excluding fees.
+    pub max_favourable_excursion: BaseOrQuote::PairedCurrency,
+    /// The fees paid by all fills of the round trip.
+    pub fees: BaseOrQuote::PairedCurrency,
+    /// The realized profit and loss, excluding fees.
+    pub realized_pnl: BaseOrQuote::PairedCurrency,
+}
+
+impl<I, const D: u8, BaseOrQuote> RoundTrip<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    /// The time the position was held.
+    #[inline]
+    pub fn holding_time(&self) -> TimestampNs {
+        self.exit_ts_ns - self.entry_ts_ns
+    }
+
+    /// The realized profit and loss after fees.
+    #[inline]
+    pub fn net_pnl(&self) -> BaseOrQuote::PairedCurrency {
+        self.realized_pnl - self.fees
+    }
+
+    fn side_str(&self) -> &'static str {
+        match self.side {
+            PositionSide::Long => "long",
+            PositionSide::Short => "short",
+            PositionSide::Neutral => "neutral",
+        }
+    }
+}
+
+/// The round trip which has not been closed yet.
+#[derive(Debug, Clone)]
+struct OpenRoundTrip<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    trip: RoundTrip<I, D, BaseOrQuote>,
+    /// The quantity of the fills reducing the position so far.
+    exit_quantity: BaseOrQuote,
+}
+
+impl<I, const D: u8, BaseOrQuote> OpenRoundTrip<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    fn new(
+        side: PositionSide,
+        ts_ns: TimestampNs,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+        fee: BaseOrQuote::PairedCurrency,
+    ) -> Self {
+        Self {
+            trip: RoundTrip {
+                side,
+                entry_ts_ns: ts_ns,
+                exit_ts_ns: ts_ns,
+                quantity,
+                entry_vwap: price,
+                exit_vwap: QuoteCurrency::zero(),
+                max_adverse_excursion: Zero::zero(),
+                max_favourable_excursion: Zero::zero(),
+                fees: fee,
+                realized_pnl: Zero::zero(),
+            },
+            exit_quantity: BaseOrQuote::zero(),
+        }
+    }
+
+    fn update_excursion(&mut self, pnl: BaseOrQuote::PairedCurrency) {
+        let trip = &mut self.trip;
+        if pnl > trip.max_favourable_excursion {
+            trip.max_favourable_excursion = pnl;
+        }
+        if -pnl > trip.max_adverse_excursion {
+            trip.max_adverse_excursion = -pnl;
+        }
+    }
+}
+
+/// Follows the `Position` of an account from flat to flat and records each `RoundTrip`,
+/// with csv and json export for reviewing a strategy.
+/// Set it with `Exchange::set_trade_journal`, after which every settled fill is recorded
+/// and the excursions are observed by each `update_state`.
+///
+/// A position which is already open when the journal is set is recorded
+/// as entered at its entry price by its first reducing fill.
+#[derive(Debug, Clone)]
+pub struct TradeJournal<I, const D: u8, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    round_trips: Vec<RoundTrip<I, D, BaseOrQuote>>,
+    open: Option<OpenRoundTrip<I, D, BaseOrQuote>>,
+}
+
+impl<I, const D: u8, BaseOrQuote> Default for TradeJournal<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+{
+    fn default() -> Self {
+        Self {
+            round_trips: Vec::with_capacity(64),
+            open: None,
+        }
+    }
+}
+
+impl<I, const D: u8, BaseOrQuote> TradeJournal<I, D, BaseOrQuote>
+where
+    I: Mon<D>,
+    BaseOrQuote: Currency<I, D>,
+    BaseOrQuote::PairedCurrency: MarginCurrency<I, D>,
+{
+    /// The closed round trips in the order they were closed.
+    #[inline]
+    pub fn round_trips(&self) -> &[RoundTrip<I, D, BaseOrQuote>] {
+        &self.round_trips
+    }
+
+    /// The round trip of the currently open position, whose exit fields reflect the reducing fills so far.
+    #[inline]
+    pub fn open_round_trip(&self) -> Option<&RoundTrip<I, D, BaseOrQuote>> {
+        self.open.as_ref().map(|open| &open.trip)
+    }
+
+    /// Record a fill of `quantity` at `price` paying `fee`,
+    /// where `position` is the position before the fill is settled.
+    pub(crate) fn record_fill(
+        &mut self,
+        position: &Position<I, D, BaseOrQuote>,
+        side: Side,
+        quantity: BaseOrQuote,
+        price: QuoteCurrency<I, D>,
+        fee: BaseOrQuote::PairedCurrency,
+        ts_ns: TimestampNs,
+    ) {
+        assert2::debug_assert!(quantity > BaseOrQuote::zero());
+        let position_qty = position.quantity();
+        let reduces_position = match side {
+            Side::Buy => position_qty < BaseOrQuote::zero(),
+            Side::Sell => position_qty > BaseOrQuote::zero(),
+        };
+        if !reduces_position {
+            match self.open.as_mut() {
+                Some(open) => {
+                    let trip = &mut open.trip;
+                    trip.entry_vwap = QuoteCurrency::new_weighted_price(
+                        trip.entry_vwap,
+                        *trip.quantity.as_ref(),
+                        price,
+                        *quantity.as_ref(),
+                    );
+                    trip.quantity += quantity;
+                    trip.fees += fee;
+                }
+                None => {
+                    let side = match side {
+                        Side::Buy => PositionSide::Long,
+                        Side::Sell => PositionSide::Short,
+                    };
+                    self.open = Some(OpenRoundTrip::new(side, ts_ns, quantity, price, fee));
+                }
+            }
+            return;
+        }
+
+        let open = self.open.get_or_insert_with(|| {
+            OpenRoundTrip::new(
+                position.side(),
+                ts_ns,
+                position_qty.abs(),
+                position.entry_price(),
+                Zero::zero(),
+            )
+        });
+        let closed_qty = min(quantity, position_qty.abs());
+        let closed_fee = if closed_qty == quantity {
+            fee
+        } else {
+            fee * (*closed_qty.as_ref() / *quantity.as_ref())
+        };
+        let signed_closed_qty = if position_qty < BaseOrQuote::zero() {
+            -closed_qty
+        } else {
+            closed_qty
+        };
+        let trip = &mut open.trip;
+        trip.exit_vwap = if open.exit_quantity.is_zero() {
+            price
+        } else {
+            QuoteCurrency::new_weighted_price(
+                trip.exit_vwap,
+                *open.exit_quantity.as_ref(),
+                price,
+                *closed_qty.as_ref(),
+            )
+        };
+        open.exit_quantity += closed_qty;
+        trip.fees += closed_fee;
+        trip.realized_pnl +=
+            BaseOrQuote::PairedCurrency::pnl(position.entry_price(), price, signed_closed_qty);
+        if closed_qty < position_qty.abs() {
+            return;
+        }
+
+        let realized_pnl = open.trip.realized_pnl;
+        open.update_excursion(realized_pnl);
+        open.trip.exit_ts_ns = ts_ns;
+        // The journal grows with the number of round trips.
+        self.round_trips.reserve(1);
+        self.round_trips
+            .push_within_capacity(open.trip)
+            .expect(EXPECT_CAPACITY);
+        self.open = None;
+
+        if closed_qty < quantity {
+            let side = match side {
+                Side::Buy => PositionSide::Long,
+                Side::Sell => PositionSide::Short,
+            };
+            self.open = Some(OpenRoundTrip::new(
+                side,
+                ts_ns,
+                quantity - closed_qty,
+                price,
+                fee - closed_fee,
+            ));
+        }
+    }
+
+    /// Observe the excursion of the open round trip at the current `bid` and `ask`,
+    /// where `position` is the current position.
+    pub(crate) fn update_excursion(
+        &mut self,
+        position: &Position<I, D, BaseOrQuote>,
+        bid: QuoteCurrency<I, D>,
+        ask: QuoteCurrency<I, D>,
+    ) {
+        let Some(open) = self.open.as_mut() else {
+            return;
+        };
+        let pnl = open.trip.realized_pnl + position.unrealized_pnl(bid, ask);
+        open.update_excursion(pnl);
+    }
+
+    /// Write the closed round trips as csv with a header row.
+    /// Currencies are written as plain decimal numbers, the side as `long` or `short`.
+    pub fn write_csv<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(CSV_HEADER)?;
+        for trip in &self.round_trips {
+            writer.write_record([
+                trip.side_str().to_string(),
+                trip.entry_ts_ns.to_string(),
+                trip.exit_ts_ns.to_string(),
+                trip.holding_time().to_string(),
+                trip.quantity.as_ref().to_string(),
+                trip.entry_vwap.as_ref().to_string(),
+                trip.exit_vwap.as_ref().to_string(),
+                trip.max_adverse_excursion.as_ref().to_string(),
+                trip.max_favourable_excursion.as_ref().to_string(),
+                trip.fees.as_ref().to_string(),
+                trip.realized_pnl.as_ref().to_string(),
+            ])?;
+        }
+        writer.flush()
+    }
+
+    /// Write the closed round trips as a json array of objects with the same fields as the csv export.
+    pub fn write_json<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(writer, "[")?;
+        for (i, trip) in self.round_trips.iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            write!(
+                writer,
+                "{{\"side\":\"{}\",\"entry_ts_ns\":{},\"exit_ts_ns\":{},\"holding_time_ns\":{},\"quantity\":{},\"entry_vwap\":{},\"exit_vwap\":{},\"max_adverse_excursion\":{},\"max_favourable_excursion\":{},\"fees\":{},\"realized_pnl\":{}}}",
+                trip.side_str(),
+                trip.entry_ts_ns,
+                trip.exit_ts_ns,
+                trip.holding_time(),
+                trip.quantity.as_ref(),
+                trip.entry_vwap.as_ref(),
+                trip.exit_vwap.as_ref(),
+                trip.max_adverse_excursion.as_ref(),
+                trip.max_favourable_excursion.as_ref(),
+                trip.fees.as_ref(),
+                trip.realized_pnl.as_ref(),
+            )?;
+        }
+        write!(writer, "]")?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BaseCurrency;
+
+    type Journal = TradeJournal<i64, 5, BaseCurrency<i64, 5>>;
+
+    /// Record a fill into the journal and settle it into the position like the `Exchange` does.
+    fn fill(
+        journal: &mut Journal,
+        position: &mut Position<i64, 5, BaseCurrency<i64, 5>>,
+        side: Side,
+        qty: i64,
+        price: i64,
+        ts: i64,
+    ) {
+        let quantity = BaseCurrency::new(qty, 0);
+        let price = QuoteCurrency::new(price, 0);
+        let fee = QuoteCurrency::new(1, 0);
+        journal.record_fill(position, side, quantity, price, fee, ts.into());
+        let mut balances = crate::account::Balances::new(QuoteCurrency::new(1_000_000, 0));
+        position.change(quantity, price, side, &mut balances);
+    }
+
+    #[test]
+    fn trade_journal_round_trip() {
+        let mut journal = Journal::default();
+        let mut position = Position::default();
+        fill(&mut journal, &mut position, Side::Buy, 1, 100, 0);
+        fill(&mut journal, &mut position, Side::Buy, 3, 104, 1);
+        journal.update_excursion(
+            &position,
+            QuoteCurrency::new(99, 0),
+            QuoteCurrency::new(100, 0),
+        );
+        journal.update_excursion(
+            &position,
+            QuoteCurrency::new(110, 0),
+            QuoteCurrency::new(111, 0),
+        );
+        fill(&mut journal, &mut position, Side::Sell, 2, 108, 5);
+        assert!(journal.round_trips().is_empty());
+        assert_eq!(
+            journal.open_round_trip().unwrap().exit_vwap,
+            QuoteCurrency::new(108, 0)
+        );
+        fill(&mut journal, &mut position, Side::Sell, 2, 106, 7);
+
+        assert!(journal.open_round_trip().is_none());
+        assert_eq!(
+            journal.round_trips(),
+            &[RoundTrip {
+                side: PositionSide::Long,
+                entry_ts_ns: 0.into(),
+                exit_ts_ns: 7.into(),
+                quantity: BaseCurrency::new(4, 0),
+                entry_vwap: QuoteCurrency::new(103, 0),
+                exit_vwap: QuoteCurrency::new(107, 0),
+                max_adverse_excursion: QuoteCurrency::new(16, 0),
+                max_favourable_excursion: QuoteCurrency::new(28, 0),
+                fees: QuoteCurrency::new(4, 0),
+                realized_pnl: QuoteCurrency::new(16, 0),
+            }]
+        );
+        assert_eq!(journal.round_trips()[0].holding_time(), 7.into());
+        assert_eq!(
+            journal.round_trips()[0].net_pnl(),
+            QuoteCurrency::new(12, 0)
+        );
+    }
+
+    #[test]
+    fn trade_journal_reversal() {
+        let mut journal = Journal::default();
+        let mut position = Position::default();
+        fill(&mut journal, &mut position, Side::Sell, 1, 100, 0);
+        fill(&mut journal, &mut position, Side::Buy, 3, 90, 2);
+
+        assert_eq!(journal.round_trips().len(), 1);
+        let short = journal.round_trips()[0];
+        assert_eq!(short.side, PositionSide::Short);
+        assert_eq!(short.realized_pnl, QuoteCurrency::new(10, 0));
+        // One third of the fee of the reversing fill belongs to the short round trip.
+        assert_eq!(
+            short.fees,
+            QuoteCurrency::new(1, 0) + QuoteCurrency::new(33333, 5)
+        );
+
+        let long = journal.open_round_trip().unwrap();
+        assert_eq!(long.side, PositionSide::Long);
+        assert_eq!(long.entry_ts_ns, 2.into());
+        assert_eq!(long.quantity, BaseCurrency::new(2, 0));
+        assert_eq!(long.entry_vwap, QuoteCurrency::new(90, 0));
+        assert_eq!(long.fees, QuoteCurrency::new(66667, 5));
+    }
+
+    #[test]
+    fn trade_journal_export() {
+        let mut journal = Journal::default();
+        let mut position = Position::default();
+        fill(&mut journal, &mut position, Side::Buy, 1, 100, 0);
+        fill(&mut journal, &mut position, Side::Sell, 1, 110, 10);
+
+        let mut csv = Vec::with_capacity(256);
+        journal.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER.join(",").as_str()));
+        assert_eq!(
+            lines.next(),
+            Some("long,0,10,10,1.00000,100.00000,110.00000,0.00000,10.00000,2.00000,10.00000")
+        );
+        assert_eq!(lines.next(), None);
+
+        let mut json = Vec::with_capacity(256);
+        journal.write_json(&mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "[{\"side\":\"long\",\"entry_ts_ns\":0,\"exit_ts_ns\":10,\"holding_time_ns\":10,\"quantity\":1.00000,\"entry_vwap\":100.00000,\"exit_vwap\":110.00000,\"max_adverse_excursion\":0.00000,\"max_favourable_excursion\":10.00000,\"fees\":2.00000,\"realized_pnl\":10.00000}]"
+        );
+    }
+}
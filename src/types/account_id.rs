@@ -0,0 +1,18 @@
+/// Identifies one of several accounts trading in the same market,
+/// handed out by `MatchingEngine::add_participant` and `MultiAccountExchange::add_account`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, std::hash::Hash, derive_more::Display,
+)]
+pub struct AccountId(usize);
+
+impl AccountId {
+    /// Create a new `AccountId` from the index of the account.
+    pub(crate) fn new(idx: usize) -> Self {
+        Self(idx)
+    }
+
+    /// The index of the account.
+    pub(crate) fn idx(&self) -> usize {
+        self.0
+    }
+}
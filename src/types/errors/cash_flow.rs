@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+use super::NotEnoughAvailableBalance;
+
+/// The error when depositing or withdrawing collateral.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum CashFlowError {
+    #[error("The amount must be greater than zero")]
+    AmountLTEZero,
+
+    #[error(transparent)]
+    NotEnoughAvailableBalance(#[from] NotEnoughAvailableBalance),
+}
@@ -30,4 +30,56 @@ pub enum ConfigError {
 
     #[error("Invalid order limits")]
     InvalidOrderLimits,
+
+    #[error(
+        "The fee schedule must have a first tier starting at zero, strictly increasing tiers and a positive rolling window"
+    )]
+    InvalidFeeSchedule,
+
+    #[error(
+        "The changes of a contract specification schedule must have strictly increasing timestamps"
+    )]
+    InvalidContractSpecificationSchedule,
+
+    #[error(
+        "The `min_notional` and `max_notional` must be > 0 and `min_notional` <= `max_notional`"
+    )]
+    InvalidNotionalFilter,
+
+    #[error("The `max_position_quantity` and `max_position_notional` must be > 0")]
+    InvalidPositionLimits,
+
+    #[error(
+        "The levels of a tick size table must have strictly increasing positive prices, each being a multiple of its positive tick size"
+    )]
+    InvalidTickSizeTable,
+
+    #[error(
+        "The latency model must not have negative latencies, `min` <= `max` and a finite, non-negative `sigma`"
+    )]
+    InvalidLatencyModel,
+
+    #[error("The fill probability must be in [0, 1] and the participation rate in (0, 1]")]
+    InvalidFillModel,
+
+    #[error(
+        "The market impact model must have finite, non-negative coefficients, a positive exponent, reference quantity and half life"
+    )]
+    InvalidMarketImpactModel,
+
+    #[error(
+        "The synthetic market must have finite, non-negative process parameters, a stationary trade arrival process, a positive level depth and an initial price allowed by the price filter"
+    )]
+    InvalidSyntheticMarket,
+
+    #[error("The threshold of a bar rule must be positive")]
+    InvalidBarRule,
+
+    #[error("The sample interval of the account tracker must be positive")]
+    InvalidAccountTracker,
+
+    #[error(
+        "The markout horizons must not be empty and the horizons and bucket bounds must be positive and strictly increasing"
+    )]
+    InvalidMarkoutAnalytics,
 }
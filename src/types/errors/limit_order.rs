@@ -8,7 +8,9 @@ use crate::{
         OrderId,
         OrderIdNotFound,
         OrderQuantityLTEZero,
+        PositionLimitExceeded,
         PriceFilterError,
+        ValidateOrderNotionalError,
         ValidateOrderQuantityError,
     },
 };
@@ -39,6 +41,15 @@ pub enum SubmitLimitOrderError {
 
     #[error(transparent)]
     ValidateOrderQuantity(#[from] ValidateOrderQuantityError),
+
+    #[error(transparent)]
+    ValidateOrderNotional(#[from] ValidateOrderNotionalError),
+
+    #[error(transparent)]
+    PositionLimitExceeded(#[from] PositionLimitExceeded),
+
+    #[error("The order would trade against a resting order of another account of the same firm")]
+    SelfTradePrevented,
 }
 
 #[derive(Error, Debug, Clone, Eq, PartialEq)]
@@ -0,0 +1,59 @@
+/// An error while loading market data.
+/// Errors of a single record carry the line number, so the record can be skipped or fixed.
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum MarketDataError {
+    #[error("Could not read the market data: {0}")]
+    Io(String),
+
+    #[error("Reading gzip compressed market data requires the `gzip` feature")]
+    GzipUnsupported,
+
+    #[error("The header does not contain the column `{0}`")]
+    MissingColumn(String),
+
+    #[error("Line {line}: could not read the record: {message}")]
+    InvalidRecord { line: u64, message: String },
+
+    #[error("Line {line}: the record has no field at column index {column}")]
+    MissingField { line: u64, column: usize },
+
+    #[error("Line {line}: could not parse the {field} `{value}`")]
+    InvalidField {
+        line: u64,
+        field: &'static str,
+        value: String,
+    },
+
+    #[error("Line {line}: the trade quantity must not be zero")]
+    ZeroQuantity { line: u64 },
+
+    #[error("The data does not start with a binary market data header")]
+    NotBinaryMarketData,
+
+    #[error("Version {0} of the binary market data format is not supported")]
+    UnsupportedBinaryVersion(u8),
+
+    #[error("The binary market data has a {field} of {found}, but {expected} is required")]
+    BinaryHeaderMismatch {
+        field: &'static str,
+        expected: u8,
+        found: u8,
+    },
+
+    #[error("The binary market data ends with a truncated record of {bytes} bytes")]
+    TruncatedBinaryRecord { bytes: usize },
+
+    #[error("Binary record {record}: invalid {field} of {found}")]
+    InvalidBinaryRecord {
+        record: u64,
+        field: &'static str,
+        found: u8,
+    },
+}
+
+impl From<std::io::Error> for MarketDataError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+use super::RiskError;
+use crate::types::{
+    PriceFilterError,
+    TimestampNs,
+};
+
+/// A market update `Exchange::update_state` rejects in strict mode, see `Exchange::set_strict_market_data`.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum MarketUpdateError {
+    #[error("The market update violates the `PriceFilter`: {0}")]
+    InvalidPrice(PriceFilterError),
+
+    #[error("The market update at {update_ts_ns} is older than the current time {current_ts_ns}")]
+    OutOfOrder {
+        current_ts_ns: TimestampNs,
+        update_ts_ns: TimestampNs,
+    },
+}
+
+/// The error of `Exchange::update_state`.
+/// A rejected market update leaves the state of the `Exchange` unchanged, a `RiskError` does not.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum UpdateStateError {
+    #[error(transparent)]
+    Risk(#[from] RiskError),
+
+    #[error(transparent)]
+    MarketUpdate(#[from] MarketUpdateError),
+}
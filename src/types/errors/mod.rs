@@ -1,11 +1,20 @@
+mod cash_flow;
 mod config;
 mod filter;
 mod limit_order;
+mod market_data;
+mod market_update;
 mod order;
 mod risk;
 
+pub use cash_flow::CashFlowError;
 pub use config::ConfigError;
 pub use filter::*;
 pub use limit_order::*;
+pub use market_data::MarketDataError;
+pub use market_update::{
+    MarketUpdateError,
+    UpdateStateError,
+};
 pub use order::*;
 pub use risk::*;
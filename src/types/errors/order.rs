@@ -9,6 +9,7 @@ use crate::{
     types::{
         NotEnoughAvailableBalance,
         OrderId,
+        PositionLimitExceeded,
     },
 };
 
@@ -38,6 +39,16 @@ pub enum ValidateOrderQuantityError {
     InvalidQuantityStepSize,
 }
 
+#[derive(Debug, Clone, Error, Eq, PartialEq)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum ValidateOrderNotionalError {
+    #[error("The order notional value is too low")]
+    NotionalTooLow,
+
+    #[error("The order notional value is too high")]
+    NotionalTooHigh,
+}
+
 #[derive(Debug, Clone, Error, Eq, PartialEq)]
 #[allow(missing_docs, reason = "Self documenting")]
 pub enum SubmitMarketOrderError {
@@ -49,6 +60,18 @@ pub enum SubmitMarketOrderError {
 
     #[error(transparent)]
     ValidateOrderQuantity(#[from] ValidateOrderQuantityError),
+
+    #[error(transparent)]
+    ValidateOrderNotional(#[from] ValidateOrderNotionalError),
+
+    #[error(transparent)]
+    PositionLimitExceeded(#[from] PositionLimitExceeded),
+
+    #[error("There are no resting orders of other participants to fill the market order")]
+    InsufficientLiquidity,
+
+    #[error("The order would trade against a resting order of another account of the same firm")]
+    SelfTradePrevented,
 }
 
 #[derive(Error, Debug, Clone, Eq, PartialEq, derive_more::Display)]
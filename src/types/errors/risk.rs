@@ -14,3 +14,15 @@ pub enum RiskError {
 #[derive(Error, Debug, Clone, Eq, PartialEq, derive_more::Display)]
 #[allow(missing_docs, reason = "Self documenting")]
 pub struct NotEnoughAvailableBalance;
+
+/// The order would allow the position to exceed a position limit of the `ContractSpecification`,
+/// assuming all resting limit orders on the same side get filled.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+#[allow(missing_docs, reason = "Self documenting")]
+pub enum PositionLimitExceeded {
+    #[error("The position quantity would exceed `max_position_quantity`")]
+    Quantity,
+
+    #[error("The position notional would exceed `max_position_notional`")]
+    Notional,
+}
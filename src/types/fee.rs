@@ -58,11 +58,11 @@ where
 }
 
 /// The fee limit orders pay.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Maker;
 
 /// The fee market orders pay.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Taker;
 
 #[cfg(test)]
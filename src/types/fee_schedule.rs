@@ -0,0 +1,213 @@
+use getset::{
+    CopyGetters,
+    Getters,
+};
+
+use super::{
+    ConfigError,
+    Currency,
+    Fee,
+    Maker,
+    Mon,
+    Taker,
+    TimestampNs,
+};
+
+/// A single tier of a [`FeeSchedule`].
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies.
+/// - `NotionalT`: The currency in which the traded notional is measured, which is the margin currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CopyGetters)]
+pub struct FeeTier<I, const D: u8, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// The rolling traded notional from which on this tier applies (inclusive).
+    #[getset(get_copy = "pub")]
+    min_rolling_notional: NotionalT,
+
+    /// The maker fee of this tier.
+    #[getset(get_copy = "pub")]
+    fee_maker: Fee<I, D, Maker>,
+
+    /// The taker fee of this tier.
+    #[getset(get_copy = "pub")]
+    fee_taker: Fee<I, D, Taker>,
+}
+
+impl<I, const D: u8, NotionalT> FeeTier<I, D, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// Create a new fee tier which applies once the rolling traded notional reaches `min_rolling_notional`.
+    pub fn new(
+        min_rolling_notional: NotionalT,
+        fee_maker: Fee<I, D, Maker>,
+        fee_taker: Fee<I, D, Taker>,
+    ) -> Self {
+        Self {
+            min_rolling_notional,
+            fee_maker,
+            fee_taker,
+        }
+    }
+}
+
+/// A volume-tiered fee schedule, like the VIP tiers of real venues.
+/// The active tier is selected by the traded notional over a rolling window (e.g. 30 days).
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies.
+/// - `NotionalT`: The currency in which the traded notional is measured, which is the margin currency.
+#[derive(Debug, Clone, PartialEq, Eq, Getters, CopyGetters)]
+pub struct FeeSchedule<I, const D: u8, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// The tiers, sorted by ascending `min_rolling_notional`.
+    #[getset(get = "pub")]
+    tiers: Vec<FeeTier<I, D, NotionalT>>,
+
+    /// The length of the rolling window over which the traded notional is summed.
+    #[getset(get_copy = "pub")]
+    rolling_window_ns: TimestampNs,
+}
+
+impl<I, const D: u8, NotionalT> FeeSchedule<I, D, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// Create a new fee schedule.
+    ///
+    /// # Arguments:
+    /// `tiers`: The fee tiers. The first one must start at a rolling notional of zero
+    /// and their `min_rolling_notional` must be strictly increasing.
+    /// `rolling_window_ns`: The length of the rolling window, must be > 0.
+    pub fn new(
+        tiers: Vec<FeeTier<I, D, NotionalT>>,
+        rolling_window_ns: TimestampNs,
+    ) -> Result<Self, ConfigError> {
+        let Some(first) = tiers.first() else {
+            return Err(ConfigError::InvalidFeeSchedule);
+        };
+        if first.min_rolling_notional != NotionalT::zero() {
+            return Err(ConfigError::InvalidFeeSchedule);
+        }
+        if tiers
+            .windows(2)
+            .any(|w| w[1].min_rolling_notional <= w[0].min_rolling_notional)
+        {
+            return Err(ConfigError::InvalidFeeSchedule);
+        }
+        if rolling_window_ns <= TimestampNs::from(0) {
+            return Err(ConfigError::InvalidFeeSchedule);
+        }
+
+        Ok(Self {
+            tiers,
+            rolling_window_ns,
+        })
+    }
+
+    /// The index of the tier which applies to the given rolling traded notional.
+    pub fn tier_index(&self, rolling_notional: NotionalT) -> usize {
+        // The first tier starts at zero, so there is always at least one applicable tier.
+        self.tiers
+            .partition_point(|tier| tier.min_rolling_notional <= rolling_notional)
+            .saturating_sub(1)
+    }
+
+    /// The tier at `index`.
+    ///
+    /// # Panics:
+    /// If `index` is out of bounds.
+    #[inline]
+    pub fn tier(&self, index: usize) -> &FeeTier<I, D, NotionalT> {
+        &self.tiers[index]
+    }
+}
+
+/// Reports that the active tier of a [`FeeSchedule`] changed.
+///
+/// Generics:
+/// - `I`: The numeric data type of currencies.
+/// - `D`: The constant decimal precision of the currencies.
+/// - `NotionalT`: The currency in which the traded notional is measured, which is the margin currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeTierChange<I, const D: u8, NotionalT>
+where
+    I: Mon<D>,
+    NotionalT: Currency<I, D>,
+{
+    /// The timestamp at which the change took effect.
+    pub timestamp_ns: TimestampNs,
+    /// The index of the previously active tier.
+    pub previous_tier: usize,
+    /// The index of the now active tier.
+    pub new_tier: usize,
+    /// The rolling traded notional which caused the change.
+    pub rolling_notional: NotionalT,
+    /// The now active maker fee.
+    pub fee_maker: Fee<I, D, Maker>,
+    /// The now active taker fee.
+    pub fee_taker: Fee<I, D, Taker>,
+}
+
+#[cfg(test)]
+mod tests {
+    use const_decimal::Decimal;
+
+    use super::*;
+    use crate::types::QuoteCurrency;
+
+    fn tier(min: i64, maker_bps: i64, taker_bps: i64) -> FeeTier<i64, 5, QuoteCurrency<i64, 5>> {
+        FeeTier::new(
+            QuoteCurrency::new(min, 0),
+            Fee::from(Decimal::try_from_scaled(maker_bps, 4).unwrap()),
+            Fee::from(Decimal::try_from_scaled(taker_bps, 4).unwrap()),
+        )
+    }
+
+    #[test]
+    fn fee_schedule_new() {
+        assert_eq!(
+            FeeSchedule::<i64, 5, QuoteCurrency<i64, 5>>::new(Vec::with_capacity(0), 1.into()),
+            Err(ConfigError::InvalidFeeSchedule)
+        );
+        assert_eq!(
+            FeeSchedule::new(vec![tier(1, 2, 6)], 1.into()),
+            Err(ConfigError::InvalidFeeSchedule)
+        );
+        assert_eq!(
+            FeeSchedule::new(vec![tier(0, 2, 6), tier(0, 1, 5)], 1.into()),
+            Err(ConfigError::InvalidFeeSchedule)
+        );
+        assert_eq!(
+            FeeSchedule::new(vec![tier(0, 2, 6)], 0.into()),
+            Err(ConfigError::InvalidFeeSchedule)
+        );
+        assert!(FeeSchedule::new(vec![tier(0, 2, 6), tier(1000, 1, 5)], 1.into()).is_ok());
+    }
+
+    #[test]
+    fn fee_schedule_tier_index() {
+        let schedule = FeeSchedule::new(
+            vec![tier(0, 2, 6), tier(1000, 1, 5), tier(5000, 0, 4)],
+            1.into(),
+        )
+        .unwrap();
+        assert_eq!(schedule.tier_index(QuoteCurrency::new(0, 0)), 0);
+        assert_eq!(schedule.tier_index(QuoteCurrency::new(999, 0)), 0);
+        assert_eq!(schedule.tier_index(QuoteCurrency::new(1000, 0)), 1);
+        assert_eq!(schedule.tier_index(QuoteCurrency::new(4999, 0)), 1);
+        assert_eq!(schedule.tier_index(QuoteCurrency::new(5000, 0)), 2);
+        assert_eq!(schedule.tier_index(QuoteCurrency::new(1_000_000, 0)), 2);
+    }
+}
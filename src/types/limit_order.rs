@@ -17,6 +17,7 @@ use super::{
     Pending,
     QuoteCurrency,
     RePricing,
+    StrategyTag,
     UserOrderId,
     order_meta::ExchangeOrderMeta,
     order_status::NewOrder,
@@ -66,6 +67,10 @@ where
     #[getset(get_copy = "pub", set = "pub")]
     re_pricing: RePricing,
 
+    /// The sub-strategy the order belongs to, see `PnlAttribution`.
+    #[getset(get_copy = "pub", set = "pub")]
+    strategy_tag: StrategyTag,
+
     /// Depending on the status, different information is available.
     #[getset(get = "pub")]
     state: OrderStatus,
@@ -158,6 +163,7 @@ where
             remaining_quantity: quantity,
             side,
             re_pricing: RePricing::default(),
+            strategy_tag: StrategyTag::default(),
         })
     }
 }
@@ -199,6 +205,7 @@ where
             remaining_quantity: quantity,
             side,
             re_pricing: RePricing::default(),
+            strategy_tag: StrategyTag::default(),
         })
     }
 
@@ -226,6 +233,7 @@ where
             remaining_quantity: self.remaining_quantity,
             state: Pending::new(meta),
             re_pricing: RePricing::default(),
+            strategy_tag: self.strategy_tag,
         }
     }
 
@@ -310,6 +318,7 @@ where
             remaining_quantity: BaseOrQuote::zero(),
             side: self.side,
             re_pricing: self.re_pricing,
+            strategy_tag: self.strategy_tag,
         }
     }
 
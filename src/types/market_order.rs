@@ -1,6 +1,7 @@
 use getset::{
     CopyGetters,
     Getters,
+    Setters,
 };
 
 use super::{
@@ -11,6 +12,7 @@ use super::{
     Pending,
     QuoteCurrency,
     Side,
+    StrategyTag,
     TimestampNs,
     UserOrderId,
     order_status::NewOrder,
@@ -24,7 +26,7 @@ use crate::types::OrderQuantityLTEZero;
 /// - `BaseOrQuote`: Either `BaseCurrency` or `QuoteCurrency` depending on the futures type.
 /// - `UserOrderId`: The type of user order id to use. Set to `()` if you don't need one.
 /// - `OrderStatus`: The status of the order for each stage, contains different information based on the stage.
-#[derive(Debug, Clone, PartialEq, Eq, Getters, CopyGetters)]
+#[derive(Debug, Clone, PartialEq, Eq, Getters, CopyGetters, Setters)]
 pub struct MarketOrder<I, const D: u8, BaseOrQuote, UserOrderIdT, OrderStatus>
 where
     I: Mon<D>,
@@ -44,6 +46,10 @@ where
     #[getset(get_copy = "pub")]
     quantity: BaseOrQuote,
 
+    /// The sub-strategy the order belongs to, see `PnlAttribution`.
+    #[getset(get_copy = "pub", set = "pub")]
+    strategy_tag: StrategyTag,
+
     /// Depending on the status, different information is available.
     #[getset(get = "pub")]
     state: OrderStatus,
@@ -92,6 +98,7 @@ where
             state: NewOrder,
             side,
             quantity,
+            strategy_tag: StrategyTag::default(),
             _quote: std::marker::PhantomData,
         })
     }
@@ -118,6 +125,7 @@ where
             state: NewOrder,
             quantity,
             side,
+            strategy_tag: StrategyTag::default(),
             _quote: std::marker::PhantomData,
         })
     }
@@ -132,6 +140,7 @@ where
             side: self.side,
             quantity: self.quantity,
             state: Pending::new(meta),
+            strategy_tag: self.strategy_tag,
             _quote: std::marker::PhantomData,
         }
     }
@@ -149,6 +158,32 @@ where
         self,
         fill_price: QuoteCurrency<I, D>,
         ts_ns_executed: TimestampNs,
+    ) -> MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Filled<I, D, BaseOrQuote>> {
+        let quantity = self.quantity;
+        self.into_filled_state(fill_price, quantity, ts_ns_executed)
+    }
+
+    /// Mark the order as partially filled with `filled_quantity` at an `avg_fill_price`,
+    /// after the `MatchingEngine` cancelled its remainder.
+    ///
+    /// # Panics:
+    /// If the order is not filled partially.
+    pub(crate) fn into_partially_filled(
+        self,
+        avg_fill_price: QuoteCurrency<I, D>,
+        filled_quantity: BaseOrQuote,
+        ts_ns_executed: TimestampNs,
+    ) -> MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Filled<I, D, BaseOrQuote>> {
+        assert2::assert!(filled_quantity > BaseOrQuote::zero());
+        assert2::assert!(filled_quantity < self.quantity);
+        self.into_filled_state(avg_fill_price, filled_quantity, ts_ns_executed)
+    }
+
+    fn into_filled_state(
+        self,
+        fill_price: QuoteCurrency<I, D>,
+        filled_quantity: BaseOrQuote,
+        ts_ns_executed: TimestampNs,
     ) -> MarketOrder<I, D, BaseOrQuote, UserOrderIdT, Filled<I, D, BaseOrQuote>> {
         MarketOrder {
             user_order_id: self.user_order_id,
@@ -156,11 +191,11 @@ where
                 self.state.meta().clone(),
                 ts_ns_executed,
                 fill_price,
-                // Market orders are always fully filled currently.
-                self.quantity,
+                filled_quantity,
             ),
             quantity: self.quantity,
             side: self.side,
+            strategy_tag: self.strategy_tag,
             _quote: std::marker::PhantomData,
         }
     }
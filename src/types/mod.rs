@@ -1,5 +1,7 @@
+mod account_id;
 mod errors;
 mod fee;
+mod fee_schedule;
 mod leverage;
 mod limit_order;
 mod limits;
@@ -12,14 +14,21 @@ mod re_pricing;
 mod side;
 mod smol_currency;
 mod solvency;
+mod strategy_tag;
 mod timestamp_ns;
 
+pub use account_id::AccountId;
 pub use errors::*;
 pub use fee::{
     Fee,
     Maker,
     Taker,
 };
+pub use fee_schedule::{
+    FeeSchedule,
+    FeeTier,
+    FeeTierChange,
+};
 pub use leverage::Leverage;
 pub use limit_order::LimitOrder;
 pub use limits::OrderRateLimits;
@@ -46,6 +55,7 @@ pub use smol_currency::{
     QuoteCurrency,
 };
 pub use solvency::Solvency;
+pub use strategy_tag::StrategyTag;
 pub(crate) use timestamp_ns::NANOS_PER_SECOND;
 pub use timestamp_ns::TimestampNs;
 
@@ -66,7 +76,7 @@ impl<T> UserOrderId for T where
 
 /// Whether to cancel a limit order by its `OrderId` or the `UserOrderId`.
 #[allow(missing_docs, reason = "Self documenting")]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CancelBy<UserOrderIdT: UserOrderId> {
     OrderId(OrderId),
     UserOrderId(UserOrderIdT),
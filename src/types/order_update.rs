@@ -22,6 +22,9 @@ where
     /// The venue force-cancelled the resting order to keep the account's required
     /// collateral covered by its equity after a fill or liquidation (margin call).
     ForcedCancel(LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>),
+    /// The venue cancelled the resting order because it violates the `PriceFilter` or `QuantityFilter`
+    /// of a newly applied `ContractSpecification`, e.g. after a tick size change.
+    ContractSpecCancel(LimitOrder<I, D, BaseOrQuote, UserOrderIdT, Pending<I, D, BaseOrQuote>>),
 }
 
 /// Contains the possible updates to limit orders.
@@ -126,6 +126,11 @@ where
         BaseCurrency::convert_from(quantity, entry_price)
             - BaseCurrency::convert_from(quantity, exit_price)
     }
+
+    #[inline]
+    fn quote_value(self, price: QuoteCurrency<I, D>) -> QuoteCurrency<I, D> {
+        QuoteCurrency::convert_from(self, price)
+    }
 }
 
 impl<I, const D: u8> Zero for BaseCurrency<I, D>
@@ -33,4 +33,8 @@ where
         exit_price: QuoteCurrency<I, D>,
         quantity: Self::PairedCurrency,
     ) -> Self;
+
+    /// The value of this margin amount in `QuoteCurrency` at `price`,
+    /// which is the amount itself for linear futures.
+    fn quote_value(self, price: QuoteCurrency<I, D>) -> QuoteCurrency<I, D>;
 }
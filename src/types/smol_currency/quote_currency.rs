@@ -153,6 +153,11 @@ where
         QuoteCurrency::convert_from(quantity, exit_price)
             - QuoteCurrency::convert_from(quantity, entry_price)
     }
+
+    #[inline]
+    fn quote_value(self, _price: QuoteCurrency<I, D>) -> QuoteCurrency<I, D> {
+        self
+    }
 }
 
 impl<I, const D: u8> Zero for QuoteCurrency<I, D>
@@ -0,0 +1,26 @@
+use std::fmt::Display;
+
+/// Identifies the sub-strategy (signal) an order belongs to, see `PnlAttribution`.
+/// Orders which are not tagged belong to the `StrategyTag::default()`.
+#[derive(Debug, Default, Clone, Copy, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StrategyTag(u32);
+
+impl From<u32> for StrategyTag {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl StrategyTag {
+    /// Get the inner value
+    #[inline(always)]
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Display for StrategyTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
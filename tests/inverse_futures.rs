@@ -378,7 +378,7 @@ fn inv_short_market_loss_full() {
             ask,
             timestamp_exchange_ns: 2.into()
         }),
-        Err(RiskError::Liquidate)
+        Err(RiskError::Liquidate.into())
     );
     let liq_fee = BaseCurrency::convert_from(qty, ask) * *test_fee_taker().as_ref();
 
@@ -808,7 +808,7 @@ fn inv_short_market_loss_partial() {
             ask,
             timestamp_exchange_ns: 2.into()
         }),
-        Err(RiskError::Liquidate)
+        Err(RiskError::Liquidate.into())
     );
     let liq_fee = BaseCurrency::convert_from(qty, ask) * *test_fee_taker().as_ref();
     assert_eq!(exchange.account().position(), &Position::default());